@@ -0,0 +1,127 @@
+//! Benchmarks for the hot path of a simulation, run with `cargo bench --features bench`.
+
+use blackjack_sim::game::prelude::{BlackjackGameSim, BlackjackTableSim, PlayerSim, TableRules};
+use blackjack_sim::game::DeckSim;
+use blackjack_sim::money::Money;
+use blackjack_sim::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::sync::Arc;
+
+/// Helper function that returns the cards of a single shuffled shoe.
+fn shuffled_shoe(num_decks: usize) -> Vec<Arc<Card>> {
+    let mut deck = DeckSim::new(num_decks, Some(1));
+    deck.shuffle(1);
+    let mut cards = Vec::with_capacity(num_decks * 52);
+    while let Some(card) = deck.get_next_card() {
+        cards.push(card);
+    }
+    cards
+}
+
+/// Benchmarks a full game of 10,000 hands using HiLo and basic strategy.
+fn bench_game_run(c: &mut Criterion) {
+    const NUM_HANDS: u32 = 10_000;
+    const MIN_BET: u32 = 5;
+    const STARTING_BALANCE: Money = Money::from_dollars(1_000_000);
+    let strategy = PlayerStrategy::new(
+        HiLo::new(6),
+        BasicStrategy::new(),
+        MarginBettingStrategy::new(3.0, MIN_BET),
+    );
+    let player = PlayerSim::new(STARTING_BALANCE, strategy, TableRules::default());
+    let table = BlackjackTableSim::new(None, TableRules::default(), Some(1));
+    let mut game = BlackjackGameSim::new(table, player, NUM_HANDS, MIN_BET);
+
+    c.bench_function("game run 10k hands", |b| {
+        b.iter(|| {
+            game.run().unwrap();
+            game.reset(None, STARTING_BALANCE);
+        })
+    });
+}
+
+/// Benchmarks counting a full shoe with several counting strategies.
+fn bench_counting_update(c: &mut Criterion) {
+    let cards = shuffled_shoe(6);
+    let mut group = c.benchmark_group("counting strategy update");
+    let mut counting_strategies: Vec<Box<dyn CountingStrategy>> = vec![
+        Box::new(HiLo::new(6)),
+        Box::new(KO::new(6)),
+        Box::new(WongHalves::new(6)),
+        Box::new(RedSeven::new(6)),
+        Box::new(ZenCount::new(6)),
+    ];
+
+    for counting_strategy in counting_strategies.iter_mut() {
+        group.bench_function(counting_strategy.name(), |b| {
+            b.iter(|| {
+                for card in cards.iter() {
+                    counting_strategy.update(Arc::clone(card));
+                }
+                counting_strategy.reset();
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Benchmarks `decide_option` on a handful of representative table states.
+fn bench_decide_option(c: &mut Criterion) {
+    let card = |rank: &'static str| Arc::new(Card::new("H", rank));
+    // (player's hand, dealer's up card, true count)
+    let scenarios = [
+        ("hard 16 vs 10", vec![card("10"), card("6")], card("K"), 1.0),
+        ("hard 11 vs 6", vec![card("5"), card("6")], card("6"), 0.0),
+        ("soft 18 vs 9", vec![card("A"), card("7")], card("9"), -1.0),
+        (
+            "pair of 8s vs 10",
+            vec![card("8"), card("8")],
+            card("Q"),
+            2.0,
+        ),
+        (
+            "pair of 10s vs 5",
+            vec![card("10"), card("J")],
+            card("5"),
+            5.0,
+        ),
+    ];
+    let options = Options::all();
+
+    let decision_strategies: Vec<(&str, Box<dyn DecisionStrategy>)> = vec![
+        ("Basic Strategy", Box::new(BasicStrategy::new())),
+        ("S17 Deviations", Box::new(S17DeviationStrategy::new())),
+        ("H17 Deviations", Box::new(H17DeviationStrategy::new())),
+    ];
+
+    let mut group = c.benchmark_group("decide option");
+    for (name, decision_strategy) in decision_strategies.iter() {
+        for (scenario, hand, dealers_up_card, true_count) in scenarios.iter() {
+            let mut hand_value = vec![hand.iter().map(|c| c.val).sum::<u8>()];
+            if hand_value[0] <= 11 && hand.iter().any(|c| c.rank == "A") {
+                hand_value.push(hand_value[0] + 10);
+            }
+            group.bench_function(format!("{}: {}", name, scenario), |b| {
+                b.iter(|| {
+                    let state = TableState::new(
+                        hand,
+                        &hand_value,
+                        5,
+                        Arc::clone(dealers_up_card),
+                        BetState::new(500.0, true_count * 6.0, *true_count, 6),
+                    );
+                    black_box(decision_strategy.decide_option(state, options))
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_game_run,
+    bench_counting_update,
+    bench_decide_option
+);
+criterion_main!(benches);
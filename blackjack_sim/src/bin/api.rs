@@ -1,15 +1,29 @@
 use actix_web::{
-    body::{self, BoxBody},
-    error, get,
-    http::{header::ContentType, StatusCode},
-    post, web, App, HttpRequest, HttpResponse, HttpServer, Responder,
+    body::{self, BodySize, BoxBody, MessageBody},
+    delete, error, get,
+    http::{
+        header::{self, ContentType},
+        StatusCode,
+    },
+    post, rt, web, App, HttpRequest, HttpResponse, HttpServer, Responder,
 };
 use blackjack_sim::prelude::*;
+use blackjack_sim::registry;
+use blackjack_sim::write::{self, CollectEvent};
+use clap::Parser;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
-use std::io::{BufWriter, Write};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::future::Future;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc::Receiver;
-use std::sync::{Mutex, MutexGuard};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// A struct for handling the configurations of the game. Meant to be deserialized from JSON.
 #[derive(Debug, Deserialize)]
@@ -23,13 +37,18 @@ struct GameConfig {
     surrender: bool,
     soft_seventeen: Option<bool>,
     insurance: Option<bool>,
+    split_by_value: Option<bool>,
+    double_after_split: Option<bool>,
+    penetration: Option<f32>,
+    seed: Option<u64>,
 }
 
 impl From<GameConfig> for BlackjackSimulatorConfig {
+    /// Converts the configuration, a seed is generated if none was given so every run can be reproduced.
     fn from(value: GameConfig) -> Self {
-        BlackjackSimulatorConfig::new()
+        let mut builder = BlackjackSimulatorConfig::new();
+        builder
             .player_starting_balance(value.player_starting_balance)
-            .table_starting_balance(value.table_starting_balance.unwrap_or(f32::MAX))
             .num_simulations(value.num_simulations)
             .num_decks(value.num_decks)
             .hands_per_simulation(value.hands_per_simulation)
@@ -37,17 +56,141 @@ impl From<GameConfig> for BlackjackSimulatorConfig {
             .surrender(value.surrender)
             .soft_seventeen(value.soft_seventeen.unwrap_or(false))
             .insurance(value.insurance.unwrap_or(false))
-            .build()
+            .split_by_value(value.split_by_value.unwrap_or(true))
+            .double_after_split(value.double_after_split.unwrap_or(true))
+            .penetration(value.penetration.unwrap_or(0.8))
+            .seed(value.seed.unwrap_or_else(rand::random));
+        if let Some(balance) = value.table_starting_balance {
+            builder.table_starting_balance(balance);
+        }
+        builder.build()
     }
 }
 
 /// A struct for deserializing the strategy configuration from json.
-#[derive(Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 struct SimConfig {
     counting_strategy: String,
     decision_strategy: String,
     betting_strategy: String,
     betting_margin: f32,
+    compile_decision_strategy: Option<bool>,
+    ramp: Option<Vec<RampStepJson>>,
+    unit: Option<u32>,
+    deviations: Option<Vec<DeviationJson>>,
+}
+
+/// A struct for deserializing a single playing deviation from json, `comparison` is either ">=" or "<=" and `action` the name of an option.
+#[derive(Clone, Serialize, Deserialize)]
+struct DeviationJson {
+    hand_total: u8,
+    #[serde(default)]
+    soft: bool,
+    dealer_up_card: u8,
+    comparison: String,
+    threshold: f32,
+    action: String,
+}
+
+/// A struct for deserializing a single step of a bet ramp from json.
+#[derive(Clone, Serialize, Deserialize)]
+struct RampStepJson {
+    true_count: f32,
+    units: u32,
+}
+
+/// The name of the betting strategy that bets according to the `ramp` of a `SimConfig`.
+const RAMP_BETTING_STRATEGY: &str = "Ramp";
+
+impl SimConfig {
+    /// Returns whether the betting strategy of the simulation uses the betting margin.
+    fn needs_margin(&self) -> bool {
+        registry::find_betting_strategy(&self.betting_strategy)
+            .is_some_and(|descriptor| descriptor.needs_margin)
+    }
+
+    /// Returns whether the betting strategy of the simulation is `Ramp`, under any of the names the registry accepts for it.
+    fn is_ramp(&self) -> bool {
+        registry::find_betting_strategy(&self.betting_strategy)
+            .is_some_and(|descriptor| descriptor.name == RAMP_BETTING_STRATEGY)
+    }
+
+    /// Helper method that creates the bet ramp of the simulation if its betting strategy is `Ramp`, the unit defaults to `min_bet`.
+    /// Returns an error if the ramp is missing or invalid, or the unit is less than `min_bet`.
+    fn ramp_betting_strategy(
+        &self,
+        min_bet: u32,
+    ) -> Result<Option<RampBettingStrategy>, UserError> {
+        if !self.is_ramp() {
+            return Ok(None);
+        }
+        let Some(ref ramp) = self.ramp else {
+            return Err(UserError::InvalidConfig(vec![ConfigError::new(
+                "ramp",
+                "is required by the Ramp betting strategy",
+            )]));
+        };
+        let unit = self.unit.unwrap_or(min_bet);
+        if unit < min_bet {
+            return Err(UserError::InvalidConfig(vec![ConfigError::new(
+                "unit",
+                "must not be less than min_bet",
+            )]));
+        }
+        let ramp = ramp
+            .iter()
+            .map(|step| RampStep {
+                true_count: step.true_count,
+                units: step.units,
+            })
+            .collect();
+        RampBettingStrategy::new(ramp, unit)
+            .map(Some)
+            .map_err(|err| UserError::InvalidConfig(vec![err]))
+    }
+
+    /// Helper method that parses and validates the custom deviations of the simulation, each error names the field of the deviation it was found in.
+    fn deviations(&self) -> Result<Option<Vec<Deviation>>, UserError> {
+        let Some(ref deviations) = self.deviations else {
+            return Ok(None);
+        };
+        let mut errors = vec![];
+        let mut parsed = vec![];
+        for (i, deviation) in deviations.iter().enumerate() {
+            let comparison = Comparison::from_symbol(&deviation.comparison).unwrap_or_else(|| {
+                errors.push(ConfigError::new(
+                    format!("deviations[{}].comparison", i),
+                    "must be one of: >=, <=",
+                ));
+                Comparison::AtLeast
+            });
+            let action = deviation.action.parse::<Action>().unwrap_or_else(|_| {
+                errors.push(ConfigError::new(
+                    format!("deviations[{}].action", i),
+                    "must be one of: hit, stand, split, double down, surrender",
+                ));
+                Action::Hit
+            });
+            // Unrecognized fields are replaced, so the remaining fields of the deviation are still checked
+            parsed.push(Deviation {
+                hand_total: deviation.hand_total,
+                soft: deviation.soft,
+                dealer_up_card: deviation.dealer_up_card,
+                comparison,
+                threshold: deviation.threshold,
+                action,
+            });
+        }
+        if let Err(deviation_errors) = validate_deviations(&parsed) {
+            errors.extend(deviation_errors);
+        }
+
+        if errors.is_empty() {
+            Ok(Some(parsed))
+        } else {
+            Err(UserError::InvalidConfig(errors))
+        }
+    }
 }
 
 /// An enum that will handle user facing errors
@@ -57,6 +200,20 @@ enum UserError {
     SimulationCreationError(String),
     SimulatorNotCreated,
     BadInput(String),
+    JobNotFound(JobId),
+    JobNotFinished(JobId),
+    JobNotRunning(JobId),
+    SimulationNotFound(usize),
+    NotAcceptable(String),
+    InvalidConfig(Vec<ConfigError>),
+    SavedConfigExists(String),
+    SavedConfigNotFound(String),
+    StorageError(String),
+    TooManySimulations(usize),
+    TooManyJobs(usize),
+    RunInProgress,
+    SimulationFailed(JobFailure),
+    NoResults,
 }
 
 impl std::fmt::Display for UserError {
@@ -70,14 +227,83 @@ impl std::fmt::Display for UserError {
                 "unable to add simulation, a simulator has not been created"
             ),
             UserError::BadInput(s) => write!(f, "{}", s),
+            UserError::JobNotFound(id) => write!(f, "job {} does not exist", id),
+            UserError::SimulationNotFound(idx) => write!(f, "simulation {} does not exist", idx),
+            UserError::JobNotFinished(id) => {
+                write!(f, "job {} has not finished successfully", id)
+            }
+            UserError::JobNotRunning(id) => write!(f, "job {} is no longer running", id),
+            UserError::SavedConfigExists(ref name) => {
+                write!(f, "a configuration named {} already exists", name)
+            }
+            UserError::SavedConfigNotFound(ref name) => {
+                write!(f, "no configuration named {} has been saved", name)
+            }
+            UserError::StorageError(ref s) => write!(f, "{}", s),
+            UserError::TooManySimulations(limit) => write!(
+                f,
+                "unable to add simulation, at most {} simulations may be configured",
+                limit
+            ),
+            UserError::TooManyJobs(limit) => write!(
+                f,
+                "unable to start a run, at most {} runs may be queued or running at once",
+                limit
+            ),
+            UserError::RunInProgress => write!(
+                f,
+                "unable to reset while a simulation is running, use ?force=true to cancel it"
+            ),
+            UserError::SimulationFailed(ref failure) => write!(f, "{}", failure.error),
+            UserError::NoResults => write!(f, "no simulation run has finished yet"),
+            UserError::InvalidConfig(ref errors) => {
+                let errors = errors
+                    .iter()
+                    .map(|err| err.to_string())
+                    .collect::<Vec<String>>();
+                write!(f, "invalid configuration: {}", errors.join(", "))
+            }
+            UserError::NotAcceptable(ref format) => write!(
+                f,
+                "format {} is not supported, supported formats are: {}",
+                format,
+                SUPPORTED_RESULT_FORMATS.join(", ")
+            ),
         }
     }
 }
 
 impl std::error::Error for UserError {}
 
+impl From<SimulationError> for UserError {
+    fn from(value: SimulationError) -> Self {
+        match value {
+            SimulationError::TooManySimulations(limit) => UserError::TooManySimulations(limit),
+            _ => UserError::InternalError,
+        }
+    }
+}
+
 impl error::ResponseError for UserError {
     fn error_response(&self) -> HttpResponse<BoxBody> {
+        // Invalid configurations list each field, so clients can report them individually
+        if let UserError::InvalidConfig(ref errors) = *self {
+            let errors = errors
+                .iter()
+                .map(|err| serde_json::json!({ "field": err.field, "constraint": err.constraint }))
+                .collect::<Vec<serde_json::Value>>();
+            return HttpResponse::build(self.status_code())
+                .content_type(ContentType::json())
+                .body(serde_json::json!({ "errors": errors }).to_string());
+        }
+        // Failed simulations name the strategy that failed, and the kind of error it failed with
+        if let UserError::SimulationFailed(ref failure) = *self {
+            if let Ok(body) = serde_json::to_string(failure) {
+                return HttpResponse::build(self.status_code())
+                    .content_type(ContentType::json())
+                    .body(body);
+            }
+        }
         HttpResponse::build(self.status_code())
             .content_type(ContentType::html())
             .body(self.to_string())
@@ -89,115 +315,84 @@ impl error::ResponseError for UserError {
             UserError::SimulationCreationError(_) => StatusCode::BAD_REQUEST,
             UserError::SimulatorNotCreated => StatusCode::BAD_REQUEST,
             UserError::BadInput(_) => StatusCode::BAD_REQUEST,
+            UserError::JobNotFound(_) => StatusCode::NOT_FOUND,
+            UserError::SimulationNotFound(_) => StatusCode::NOT_FOUND,
+            UserError::JobNotFinished(_) => StatusCode::CONFLICT,
+            UserError::JobNotRunning(_) => StatusCode::CONFLICT,
+            UserError::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+            UserError::InvalidConfig(_) => StatusCode::BAD_REQUEST,
+            UserError::SavedConfigExists(_) => StatusCode::CONFLICT,
+            UserError::SavedConfigNotFound(_) => StatusCode::NOT_FOUND,
+            UserError::StorageError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            UserError::TooManySimulations(_) => StatusCode::CONFLICT,
+            UserError::TooManyJobs(_) => StatusCode::TOO_MANY_REQUESTS,
+            UserError::RunInProgress => StatusCode::CONFLICT,
+            UserError::SimulationFailed(ref failure) => failure.status_code(),
+            UserError::NoResults => StatusCode::NOT_FOUND,
         }
     }
 }
 
-/// A struct for collecting simulation `SimulationSummary` data into something that can deserialize into JSON
-#[derive(Serialize)]
-struct SimulationSummaryJson {
-    pub counting_strategy: String,
-    pub wins: i32,
-    pub pushes: i32,
-    pub losses: i32,
-    pub early_endings: i32,
-    pub winnings: f32,
-    pub num_hands: u32,
-    pub player_blackjacks: i32,
-    pub total_hands_played: u32,
-    pub win_pct: f32,
-    pub push_pct: f32,
-    pub lose_pct: f32,
-    pub avg_winnings_per_hand: f32,
-}
-
-impl SimulationSummaryJson {
-    fn new(counting_strategy: String) -> Self {
-        SimulationSummaryJson {
-            counting_strategy,
-            wins: 0,
-            pushes: 0,
-            losses: 0,
-            early_endings: 0,
-            winnings: 0.0,
-            num_hands: 0,
-            player_blackjacks: 0,
-            total_hands_played: 0,
-            win_pct: 0.0,
-            push_pct: 0.0,
-            lose_pct: 0.0,
-            avg_winnings_per_hand: 0.0,
-        }
-    }
-}
-
-unsafe impl Send for SimulationSummaryJson {}
-
-/// A struct for collecting all of the simulation summaries into a format that can be
-#[derive(Serialize)]
-struct SimulationSummaryMap {
-    summaries: HashMap<usize, SimulationSummaryJson>,
-}
-
-impl SimulationSummaryMap {
-    fn new() -> Self {
-        SimulationSummaryMap {
-            summaries: HashMap::new(),
-        }
-    }
+/// An event emitted while the summaries of a run are being collected.
+enum RunEvent<'a> {
+    /// A single simulation of the strategy with the given id has finished
+    Progress {
+        id: usize,
+        counting_strategy: &'a str,
+        simulations_completed: u32,
+    },
+    /// Every simulation of the strategy with the given id has finished
+    Summary {
+        id: usize,
+        summary: &'a SimulationSummary,
+    },
+    /// Every simulation of every strategy has finished
+    Finished {
+        summaries: &'a HashMap<usize, SimulationSummary>,
+    },
+    /// The run stopped before every simulation finished, holds the summaries of the simulations completed so far
+    Stopped {
+        summaries: &'a HashMap<usize, SimulationSummary>,
+    },
 }
 
-unsafe impl Send for SimulationSummaryMap {}
-
-/// A function for writing data that can be passed as a write function to the `MulStrategyBlackjackSimulator` run method.
-fn write_simulation_summary_as_json(
+/// Helper function that collects the summaries sent by a run into JSON, calling `on_event` whenever a simulation or a strategy finishes.
+fn collect_simulation_summaries(
     receiver: Receiver<(Option<SimulationSummary>, usize)>,
-    mut ids: HashSet<usize>,
+    ids: HashSet<usize>,
+    mut on_event: impl FnMut(RunEvent),
 ) -> Result<String, Box<dyn std::error::Error + Send + 'static>> {
-    let mut summaries_map = SimulationSummaryMap::new();
-
-    'outer: loop {
-        match receiver.recv().unwrap() {
-            (Some(cur_summary), id) => {
-                let summary = summaries_map
-                    .summaries
-                    .entry(id)
-                    .or_insert(SimulationSummaryJson::new(cur_summary.label));
-                summary.wins += cur_summary.wins;
-                summary.pushes += cur_summary.pushes;
-                summary.losses += cur_summary.losses;
-                summary.winnings += cur_summary.winnings;
-                summary.player_blackjacks += cur_summary.player_blackjacks;
-                summary.early_endings += cur_summary.early_endings;
-            }
-            (None, id) => {
-                // Remove from ids
-                ids.remove(&id);
-                // Check if we are done processing simulations
-                if ids.is_empty() {
-                    break 'outer;
-                }
+    let mut simulations_completed: HashMap<usize, u32> = HashMap::new();
+    let (summaries, unfinished) = write::collect_summaries(receiver, ids, |event| match event {
+        CollectEvent::Simulation { id, summary } => {
+            let completed = simulations_completed.entry(id).or_insert(0);
+            *completed += 1;
+            on_event(RunEvent::Progress {
+                id,
+                counting_strategy: &summary.label,
+                simulations_completed: *completed,
+            });
+        }
+        CollectEvent::Finished { id, summary } => {
+            if let Some(summary) = summary {
+                on_event(RunEvent::Summary { id, summary });
             }
         }
-    }
+    });
 
-    // Compute final statistics
-    for (_, v) in &mut summaries_map.summaries {
-        let total_hands_played = v.wins + v.pushes + v.losses;
-        let win_pct = (v.wins as f32) / (total_hands_played as f32);
-        let push_pct = (v.pushes as f32) / (total_hands_played as f32);
-        let lose_pct = (v.losses as f32) / (total_hands_played as f32);
-        let avg_winnings_per_hand = (v.winnings as f32) / (total_hands_played as f32);
-        v.win_pct = win_pct;
-        v.push_pct = push_pct;
-        v.lose_pct = lose_pct;
-        v.avg_winnings_per_hand = avg_winnings_per_hand;
+    // Every sender hung up before all simulations finished, i.e. the run was cancelled
+    if !unfinished.is_empty() {
+        on_event(RunEvent::Stopped {
+            summaries: &summaries,
+        });
+        return Err(Box::new(SimulationError::Cancelled));
     }
 
-    match serde_json::to_string(&summaries_map) {
-        Ok(res) => Ok(res),
-        Err(_) => Err(Box::new(UserError::InternalError)),
-    }
+    on_event(RunEvent::Finished {
+        summaries: &summaries,
+    });
+
+    Ok(write::format_summaries_as_json(&summaries))
 }
 
 /// Helper function to create a counting strategy i.e. a `CountingStrategy` trait object at runtime.
@@ -205,55 +400,59 @@ fn create_counting_strategy<S: AsRef<str>>(
     name: S,
     num_decks: u32,
 ) -> Result<Box<dyn CountingStrategy + Send + 'static>, &'static str> {
-    let counting_strategy: Box<dyn CountingStrategy + Send + 'static> = match name.as_ref() {
-        "HiLo" => Box::new(HiLo::new(num_decks)),
-        "Wong Halves" => Box::new(WongHalves::new(num_decks)),
-        "KO" => Box::new(KO::new(num_decks)),
-        "HiOptI" => Box::new(HiOptI::new(num_decks)),
-        "HiOptII" => Box::new(HiOptII::new(num_decks)),
-        "Red Seven" => Box::new(RedSeven::new(num_decks)),
-        "OmegaII" => Box::new(OmegaII::new(num_decks)),
-        "AceFive" => Box::new(AceFive::new(num_decks)),
-        "Zen Count" => Box::new(ZenCount::new(num_decks)),
-        "Halves" => Box::new(Halves::new(num_decks)),
-        "KISS" => Box::new(KISS::new(num_decks)),
-        "KISSII" => Box::new(KISSII::new(num_decks)),
-        "KISSIII" => Box::new(KISSIII::new(num_decks)),
-        "JNoir" => Box::new(JNoir::new(num_decks)),
-        "Silver Fox" => Box::new(SilverFox::new(num_decks)),
-        "Unbalanced Zen 2" => Box::new(UnbalancedZen2::new(num_decks)),
-        _ => return Err("counting strategy not recognized"),
-    };
-
-    Ok(counting_strategy)
+    match registry::find_counting_strategy(name.as_ref()) {
+        Some(descriptor) => Ok((descriptor.constructor)(num_decks)),
+        None => Err("counting strategy not recognized"),
+    }
 }
 
 /// Helper function to create a decsion strategy i.e. a `DecisionStrategy` trait object at runtime.
+/// If `deviations` are given they are layered over the decision strategy with a `DeviationStrategy`.
+/// If `compile` is true the decision strategy is wrapped in a `CompiledStrategy`, precomputing its decisions for true counts from -10 to 10.
 fn create_decision_strategy<S: AsRef<str>>(
     name: S,
+    compile: bool,
+    deviations: Option<Vec<Deviation>>,
 ) -> Result<Box<dyn DecisionStrategy + Send + 'static>, &'static str> {
-    let decision_strategy: Box<dyn DecisionStrategy + Send + 'static> = match name.as_ref() {
-        "Basic Strategy" => Box::new(BasicStrategy::new()),
-        "S17 Deviations" => Box::new(S17DeviationStrategy::new()),
-        "H17 Deviations" => Box::new(H17DeviationStrategy::new()),
-        _ => return Err("decision strategy not recognized"),
+    let decision_strategy = match registry::find_decision_strategy(name.as_ref()) {
+        Some(descriptor) => (descriptor.constructor)(),
+        None => return Err("decision strategy not recognized"),
+    };
+
+    let decision_strategy: Box<dyn DecisionStrategy + Send + 'static> = match deviations {
+        Some(deviations) => match DeviationStrategy::new(decision_strategy, deviations) {
+            Ok(deviation_strategy) => Box::new(deviation_strategy),
+            Err(_) => return Err("deviations are not valid"),
+        },
+        None => decision_strategy,
     };
 
+    if compile {
+        return Ok(Box::new(CompiledStrategy::new(decision_strategy, -10, 10)));
+    }
+
     Ok(decision_strategy)
 }
 
 /// Helper function to create a betting strategy at runtime i.e. a `BettingStrategy` trait object.
+/// The `Ramp` betting strategy is built beforehand from the `SimConfig`, and passed as `ramp`.
 fn create_betting_strategy<S: AsRef<str>>(
     name: S,
     margin: f32,
     min_bet: u32,
+    ramp: Option<RampBettingStrategy>,
 ) -> Result<Box<dyn BettingStrategy + Send + 'static>, &'static str> {
-    let betting_strategy: Box<dyn BettingStrategy + Send + 'static> = match name.as_ref() {
-        "Margin" => Box::new(MarginBettingStrategy::new(margin, min_bet)),
-        _ => return Err("betting startegy not recognized"),
+    let Some(descriptor) = registry::find_betting_strategy(name.as_ref()) else {
+        return Err("betting startegy not recognized");
     };
-
-    Ok(betting_strategy)
+    if descriptor.name == RAMP_BETTING_STRATEGY {
+        return match ramp {
+            Some(ramp) => Ok(Box::new(ramp)),
+            None => Err("betting strategy Ramp requires a ramp"),
+        };
+    }
+    (descriptor.constructor)(&registry::BettingParameters::new(margin, min_bet))
+        .map_err(|_| "betting strategy parameters are invalid")
 }
 
 /// Helper function to create a `Strategy` trait object at runtime
@@ -264,33 +463,127 @@ fn create_strategy<S: AsRef<str>>(
     num_decks: u32,
     min_bet: u32,
     margin: f32,
-) -> Result<PlayerStrategyDyn, &'static str> {
+    ramp: Option<RampBettingStrategy>,
+    deviations: Option<Vec<Deviation>>,
+    compile_decision_strategy: bool,
+) -> Result<PlayerStrategyDyn, String> {
     let counting_strategy = create_counting_strategy(counting_strategy, num_decks)?;
-    let decision_strategy = create_decision_strategy(decision_strategy)?;
-    let betting_strategy = create_betting_strategy(betting_strategy, margin, min_bet)?;
-    Ok(PlayerStrategyDyn::new()
+    let decision_strategy =
+        create_decision_strategy(decision_strategy, compile_decision_strategy, deviations)?;
+    let betting_strategy = create_betting_strategy(betting_strategy, margin, min_bet, ramp)?;
+    PlayerStrategyDyn::new()
         .counting_strategy(counting_strategy)
         .decision_strategy(decision_strategy)
         .betting_strategy(betting_strategy)
-        .build())
+        .build()
+        .map_err(|e| e.to_string())
 }
 
-/// A handler that will configure, and build a new `MulStrategyBlackjackSimulator` using the given parameters the body of the request
+/// A handler that will configure, and build a new `MulStrategyBlackjackSimulator` using the given parameters the body of the request.
+/// Responds with the seed the simulator uses, which is generated if the request did not contain one.
 #[post("/config-game-params")]
 async fn configure_simulation_parameters(
     params: web::Json<GameConfig>,
     app_sim: web::Data<Mutex<Option<MulStrategyBlackjackSimulator>>>,
+    sim_configs: web::Data<SimConfigs>,
+    limits: web::Data<ServerLimits>,
 ) -> Result<HttpResponse, UserError> {
     // let config = params.into_inner();
     let config = BlackjackSimulatorConfig::from(params.into_inner());
+    config.validate().map_err(UserError::InvalidConfig)?;
     let mut guard = if let Ok(g) = app_sim.lock() {
         g
     } else {
         return Err(UserError::InternalError);
     };
 
-    *guard = Some(MulStrategyBlackjackSimulator::new(config).build());
-    Ok(HttpResponse::Ok().body("simulator created successfully"))
+    *guard = Some(new_simulator(config, &limits));
+    sim_configs
+        .lock()
+        .map_err(|_| UserError::InternalError)?
+        .clear();
+    let body = serde_json::json!({
+        "message": "simulator created successfully",
+        "seed": config.seed,
+    });
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(body.to_string()))
+}
+
+/// The default maximum number of simulations a simulator may hold, so a client can not make a run spawn an unbounded number of threads.
+const DEFAULT_MAX_SIMULATIONS: usize = 64;
+
+/// The default maximum number of runs that may be queued or running at once, each run spawns a thread per simulation.
+const DEFAULT_MAX_RUNNING_JOBS: usize = 4;
+
+/// The default number of finished jobs whose results are kept, the oldest are evicted as new runs start.
+const DEFAULT_MAX_FINISHED_JOBS: usize = 64;
+
+/// The limits the server enforces on every simulator it creates, and on the jobs it keeps.
+struct ServerLimits {
+    max_simulations: usize,
+    max_running_jobs: usize,
+    max_finished_jobs: usize,
+}
+
+impl Default for ServerLimits {
+    fn default() -> Self {
+        ServerLimits {
+            max_simulations: DEFAULT_MAX_SIMULATIONS,
+            max_running_jobs: DEFAULT_MAX_RUNNING_JOBS,
+            max_finished_jobs: DEFAULT_MAX_FINISHED_JOBS,
+        }
+    }
+}
+
+/// Helper function that creates a simulator without any simulations, limited to the number of simulations allowed by the server.
+fn new_simulator(
+    config: BlackjackSimulatorConfig,
+    limits: &ServerLimits,
+) -> MulStrategyBlackjackSimulator {
+    MulStrategyBlackjackSimulator::new(config)
+        .max_simulations(limits.max_simulations)
+        .build()
+}
+
+/// The `SimConfig` of every simulation added to the simulator, in the order they were added. Kept so the setup can be saved with `POST /configs`.
+/// Always locked after the simulator, so the two stay in step.
+type SimConfigs = Mutex<Vec<SimConfig>>;
+
+/// Helper function that creates the strategy described by `sim_params`, and adds it to `simulator`.
+fn add_simulation_from_config(
+    simulator: &mut MulStrategyBlackjackSimulator,
+    sim_params: &SimConfig,
+) -> Result<(), UserError> {
+    if sim_params.needs_margin() {
+        blackjack_sim::validate_betting_margin(sim_params.betting_margin)
+            .map_err(|err| UserError::InvalidConfig(vec![err]))?;
+    }
+    let (num_decks, min_bet) = (simulator.config.num_decks, simulator.config.min_bet);
+    let ramp = sim_params.ramp_betting_strategy(min_bet)?;
+    let deviations = sim_params.deviations()?;
+    let (counting_strategy, decision_strategy, betting_strategy, margin) = (
+        sim_params.counting_strategy.as_str(),
+        sim_params.decision_strategy.as_str(),
+        sim_params.betting_strategy.as_str(),
+        sim_params.betting_margin,
+    );
+
+    match create_strategy(
+        counting_strategy,
+        decision_strategy,
+        betting_strategy,
+        num_decks as u32,
+        min_bet,
+        margin,
+        ramp,
+        deviations,
+        sim_params.compile_decision_strategy.unwrap_or(false),
+    ) {
+        Ok(s) => Ok(simulator.add_simulation(s)?),
+        Err(msg) => Err(UserError::SimulationCreationError(msg)),
+    }
 }
 
 /// A handler that will add a simulation to the simulator.
@@ -298,6 +591,7 @@ async fn configure_simulation_parameters(
 async fn add_simulation(
     sim_params: web::Json<SimConfig>,
     app_sim: web::Data<Mutex<Option<MulStrategyBlackjackSimulator>>>,
+    sim_configs: web::Data<SimConfigs>,
 ) -> Result<HttpResponse, UserError> {
     let mut guard = if let Ok(g) = app_sim.lock() {
         g
@@ -306,79 +600,2734 @@ async fn add_simulation(
     };
 
     if let Some(simulator) = guard.as_mut() {
-        let (num_decks, min_bet) = (simulator.config.num_decks, simulator.config.min_bet);
-        let (counting_strategy, decision_strategy, betting_strategy, margin) = (
-            sim_params.counting_strategy.as_str(),
-            sim_params.decision_strategy.as_str(),
-            sim_params.betting_strategy.as_str(),
-            sim_params.betting_margin,
-        );
+        add_simulation_from_config(simulator, &sim_params)?;
+        sim_configs
+            .lock()
+            .map_err(|_| UserError::InternalError)?
+            .push(sim_params.into_inner());
+        return Ok(HttpResponse::Ok().body("simulation added successfully"));
+    }
 
-        match create_strategy(
-            counting_strategy,
-            decision_strategy,
-            betting_strategy,
-            num_decks as u32,
-            min_bet,
+    return Err(UserError::SimulatorNotCreated);
+}
+
+/// The parameters that can be swept by `POST /sweep`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum SweepParameter {
+    BettingMargin,
+    NumDecks,
+    Penetration,
+}
+
+impl std::fmt::Display for SweepParameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SweepParameter::BettingMargin => write!(f, "betting_margin"),
+            SweepParameter::NumDecks => write!(f, "num_decks"),
+            SweepParameter::Penetration => write!(f, "penetration"),
+        }
+    }
+}
+
+/// A struct for deserializing the parameter to sweep, and the values it takes, from JSON.
+#[derive(Deserialize)]
+struct SweepSpec {
+    parameter: SweepParameter,
+    values: Vec<f32>,
+}
+
+/// A struct for deserializing a parameter sweep from JSON, the simulation described by `sim_config` is run once for each value of the swept parameter.
+#[derive(Deserialize)]
+struct SweepConfig {
+    game_config: GameConfig,
+    sim_config: SimConfig,
+    sweep: SweepSpec,
+}
+
+/// A struct for serializing the results of a parameter sweep into JSON, the summaries are keyed by the value of the swept parameter.
+#[derive(Serialize)]
+struct SweepResultJson {
+    parameter: SweepParameter,
+    summaries: HashMap<String, serde_json::Value>,
+}
+
+/// Helper function that serializes the summaries of a sweep into JSON, the simulation with id `i` was run with `values[i - 1]`.
+fn format_sweep_as_json(
+    parameter: SweepParameter,
+    values: &[f32],
+    summaries: &HashMap<usize, SimulationSummary>,
+) -> Result<String, UserError> {
+    let sweep_result = SweepResultJson {
+        parameter,
+        summaries: summaries
+            .iter()
+            .map(|(&id, summary)| {
+                (
+                    values[id - 1].to_string(),
+                    write::format_summary_as_json(id, summary),
+                )
+            })
+            .collect(),
+    };
+
+    serde_json::to_string(&sweep_result).map_err(|_| UserError::InternalError)
+}
+
+/// A handler that runs the configured simulation once for each value of the swept parameter, and responds with the summary of each value.
+/// Every value is added to its own simulator as a separate simulation, labelled with the value so the summaries can be told apart.
+#[post("/sweep")]
+async fn sweep(
+    params: web::Json<SweepConfig>,
+    limits: web::Data<ServerLimits>,
+) -> Result<HttpResponse, UserError> {
+    let SweepConfig {
+        game_config,
+        sim_config,
+        sweep,
+    } = params.into_inner();
+    let config = BlackjackSimulatorConfig::from(game_config);
+    config.validate().map_err(UserError::InvalidConfig)?;
+
+    if sweep.values.is_empty() {
+        return Err(UserError::BadInput(String::from(
+            "at least one sweep value is required",
+        )));
+    }
+    let distinct_values = sweep
+        .values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<HashSet<String>>();
+    if distinct_values.len() != sweep.values.len() {
+        return Err(UserError::BadInput(String::from(
+            "sweep values must be unique",
+        )));
+    }
+    if sweep.parameter != SweepParameter::BettingMargin && sim_config.needs_margin() {
+        blackjack_sim::validate_betting_margin(sim_config.betting_margin)
+            .map_err(|err| UserError::InvalidConfig(vec![err]))?;
+    }
+    let ramp = sim_config.ramp_betting_strategy(config.min_bet)?;
+    let deviations = sim_config.deviations()?;
+
+    let mut simulator = new_simulator(config, &limits);
+    for &value in &sweep.values {
+        let (config, margin) = match sweep.parameter {
+            SweepParameter::BettingMargin => {
+                blackjack_sim::validate_betting_margin(value)
+                    .map_err(|err| UserError::InvalidConfig(vec![err]))?;
+                (config, value)
+            }
+            SweepParameter::NumDecks => {
+                if value < 1.0 || value.fract() != 0.0 {
+                    return Err(UserError::BadInput(format!(
+                        "num_decks values must be positive whole numbers, got {}",
+                        value
+                    )));
+                }
+                let num_decks = value as usize;
+                (
+                    config.with_override(ConfigOverride::NumDecks(num_decks)),
+                    sim_config.betting_margin,
+                )
+            }
+            SweepParameter::Penetration => {
+                let config = config.with_override(ConfigOverride::Penetration(value));
+                config.validate().map_err(UserError::InvalidConfig)?;
+                (config, sim_config.betting_margin)
+            }
+        };
+
+        let strategy_error = |msg: &str| UserError::SimulationCreationError(msg.to_owned());
+        let counting_strategy =
+            create_counting_strategy(&sim_config.counting_strategy, config.num_decks as u32)
+                .map_err(strategy_error)?;
+        let label = format!(
+            "{} ({} = {})",
+            counting_strategy.name(),
+            sweep.parameter,
+            value
+        );
+        let decision_strategy = create_decision_strategy(
+            &sim_config.decision_strategy,
+            sim_config.compile_decision_strategy.unwrap_or(false),
+            deviations.clone(),
+        )
+        .map_err(strategy_error)?;
+        let betting_strategy = create_betting_strategy(
+            &sim_config.betting_strategy,
             margin,
-        ) {
-            Ok(s) => {
-                simulator.add_simulation(s);
-                return Ok(HttpResponse::Ok().body("simulation added successfully"));
+            config.min_bet,
+            ramp.clone(),
+        )
+        .map_err(strategy_error)?;
+        let strategy = PlayerStrategyDyn::new()
+            .counting_strategy(counting_strategy)
+            .decision_strategy(decision_strategy)
+            .betting_strategy(betting_strategy)
+            .label(&label)
+            .build()
+            .map_err(|e| UserError::SimulationCreationError(e.to_string()))?;
+        simulator.add_simulation_with_config(strategy, config)?;
+    }
+
+    let SweepSpec { parameter, values } = sweep;
+    let write_fn = move |receiver, ids| {
+        let mut result = None;
+        collect_simulation_summaries(receiver, ids, |event| {
+            if let RunEvent::Finished { summaries } = event {
+                result = Some(format_sweep_as_json(parameter, &values, summaries));
             }
-            Err(msg) => return Err(UserError::SimulationCreationError(msg.to_owned())),
+        })?;
+        result
+            .unwrap_or(Err(UserError::InternalError))
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send>)
+    };
+    let res_as_json = web::block(move || {
+        simulator
+            .run_return_out(write_fn)
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send>)
+            .and_then(|result| result)
+            .map_err(|e| JobFailure::new(&*e))
+    })
+    .await
+    .map_err(|_| UserError::InternalError)?
+    .map_err(UserError::SimulationFailed)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .body(res_as_json))
+}
+
+/// A struct for serializing the configuration shared by every simulation into JSON.
+#[derive(Serialize)]
+struct GameConfigJson {
+    player_starting_balance: f32,
+    table_starting_balance: Option<f32>,
+    num_simulations: u32,
+    num_decks: usize,
+    hands_per_simulation: u32,
+    min_bet: u32,
+    surrender: bool,
+    soft_seventeen: bool,
+    insurance: bool,
+    split_by_value: bool,
+    double_after_split: bool,
+    penetration: f32,
+    seed: Option<u64>,
+}
+
+impl From<&BlackjackSimulatorConfig> for GameConfigJson {
+    fn from(value: &BlackjackSimulatorConfig) -> Self {
+        GameConfigJson {
+            player_starting_balance: value.player_starting_balance,
+            table_starting_balance: value.table_starting_balance,
+            num_simulations: value.num_simulations,
+            num_decks: value.num_decks,
+            hands_per_simulation: value.hands_per_simulation,
+            min_bet: value.min_bet,
+            surrender: value.surrender,
+            soft_seventeen: value.soft_seventeen,
+            insurance: value.insurance,
+            split_by_value: value.split_by_value,
+            double_after_split: value.double_after_split,
+            penetration: value.penetration,
+            seed: value.seed,
         }
     }
+}
 
-    return Err(UserError::SimulatorNotCreated);
+/// A struct for serializing the description of a configured simulation into JSON.
+#[derive(Serialize)]
+struct SimulationJson<'a> {
+    index: usize,
+    counting_strategy: &'a str,
+    decision_strategy: &'a str,
+    betting_strategy: &'a str,
 }
 
-/// A handler that will run the simulation given the configurations.
-/// Will return an error resposne if the game has not been configured and/or no simulations have been added.
-#[get("/run-sim")]
-async fn run_simulation(
+/// A struct for serializing the configured simulations and their shared configuration into JSON.
+#[derive(Serialize)]
+struct SimulationsJson<'a> {
+    config: GameConfigJson,
+    simulations: Vec<SimulationJson<'a>>,
+}
+
+/// A handler that lists the simulations that have been added to the simulator, together with the configuration they share.
+#[get("/simulations")]
+async fn list_simulations(
     app_sim: web::Data<Mutex<Option<MulStrategyBlackjackSimulator>>>,
 ) -> Result<HttpResponse, UserError> {
-    // Attempt to lock the mutex
-    if let Ok(mut guard) = app_sim.lock() {
-        // Check if we have a valid simulator
-        if let Some(simulator) = guard.as_mut() {
-            if simulator.simulations().is_empty() {
-                return Err(UserError::BadInput(String::from(
-                    "no simulations have been added, unable to run.",
-                )));
+    let guard = app_sim.lock().map_err(|_| UserError::InternalError)?;
+    let Some(simulator) = guard.as_ref() else {
+        return Err(UserError::SimulatorNotCreated);
+    };
+    let simulations = SimulationsJson {
+        config: GameConfigJson::from(&simulator.config),
+        simulations: simulator
+            .descriptors()
+            .iter()
+            .enumerate()
+            .map(|(index, descriptor)| SimulationJson {
+                index,
+                counting_strategy: &descriptor.counting_strategy,
+                decision_strategy: &descriptor.decision_strategy,
+                betting_strategy: &descriptor.betting_strategy,
+            })
+            .collect(),
+    };
+
+    match serde_json::to_string(&simulations) {
+        Ok(res) => Ok(HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .body(res)),
+        Err(_) => Err(UserError::InternalError),
+    }
+}
+
+/// A struct for serializing the effective configuration of the simulator into JSON, i.e. including the fields filled with their defaults.
+#[derive(Serialize)]
+struct EffectiveConfigJson<'a> {
+    config: &'a BlackjackSimulatorConfig,
+    simulations_added: usize,
+}
+
+/// A handler that responds with the full configuration the simulator was built with, and the number of simulations added to it.
+/// Responds with 404 if no simulator has been configured yet.
+#[get("/config")]
+async fn get_config(
+    app_sim: web::Data<Mutex<Option<MulStrategyBlackjackSimulator>>>,
+) -> Result<HttpResponse, UserError> {
+    let guard = app_sim.lock().map_err(|_| UserError::InternalError)?;
+    let Some(simulator) = guard.as_ref() else {
+        return Ok(HttpResponse::NotFound().body(UserError::SimulatorNotCreated.to_string()));
+    };
+    let config = EffectiveConfigJson {
+        config: &simulator.config,
+        simulations_added: simulator.simulations().len(),
+    };
+
+    match serde_json::to_string(&config) {
+        Ok(res) => Ok(HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .body(res)),
+        Err(_) => Err(UserError::InternalError),
+    }
+}
+
+/// A handler that removes the simulation at `index` from the simulator, responding with the label of the removed simulation.
+/// Indices start at zero and follow the order the simulations were added in, the simulations after `index` move down by one.
+#[delete("/simulations/{index}")]
+async fn remove_simulation(
+    path: web::Path<usize>,
+    app_sim: web::Data<Mutex<Option<MulStrategyBlackjackSimulator>>>,
+    sim_configs: web::Data<SimConfigs>,
+) -> Result<HttpResponse, UserError> {
+    let idx = path.into_inner();
+    let mut guard = app_sim.lock().map_err(|_| UserError::InternalError)?;
+    let Some(simulator) = guard.as_mut() else {
+        return Err(UserError::SimulatorNotCreated);
+    };
+    let Some(label) = simulator
+        .simulations()
+        .get(idx)
+        .map(|simulation| simulation.label())
+    else {
+        return Err(UserError::SimulationNotFound(idx));
+    };
+    simulator.remove_simulation(idx);
+    let mut sim_configs = sim_configs.lock().map_err(|_| UserError::InternalError)?;
+    if idx < sim_configs.len() {
+        sim_configs.remove(idx);
+    }
+
+    Ok(HttpResponse::Ok().body(format!("removed simulation {} ({})", idx, label)))
+}
+
+/// The id of a simulation run started with `POST /run-sim`.
+type JobId = u64;
+
+/// The id that will be given to the next job.
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The status of a simulation run.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum JobStatus {
+    Queued,
+    Running,
+    Finished,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Returns whether the job has stopped running, i.e. its status will not change anymore.
+    fn is_done(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Finished | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+/// The cause of a failed simulation run, `strategy_id` and `strategy` name the failing strategy if the error came from one of the simulations.
+/// `error` is the full error message, `message` the message of the underlying error.
+#[derive(Debug, Clone, Serialize)]
+struct JobFailure {
+    error: String,
+    kind: &'static str,
+    strategy_id: Option<usize>,
+    strategy: Option<Arc<str>>,
+    message: String,
+}
+
+impl JobFailure {
+    /// Associated method for describing the error a run failed with.
+    fn new(error: &(dyn std::error::Error + 'static)) -> Self {
+        let (kind, strategy_id, strategy, message) = match error.downcast_ref::<SimulationError>() {
+            // The strategy is already named, so a failed game reports only the error the game returned
+            Some(
+                e @ SimulationError::StrategyError {
+                    strategy_id,
+                    label,
+                    source,
+                    ..
+                },
+            ) => (
+                failure_kind(e),
+                Some(*strategy_id),
+                Some(Arc::clone(label)),
+                source.to_string(),
+            ),
+            Some(
+                e @ SimulationError::ChannelClosed {
+                    strategy_id, label, ..
+                },
+            ) => (
+                failure_kind(e),
+                Some(*strategy_id),
+                Some(Arc::clone(label)),
+                e.to_string(),
+            ),
+            Some(e) => (failure_kind(e), None, None, e.to_string()),
+            // Any other error is returned by the function writing the results
+            None => ("write_error", None, None, error.to_string()),
+        };
+        JobFailure {
+            error: error.to_string(),
+            kind,
+            strategy_id,
+            strategy,
+            message,
+        }
+    }
+
+    /// Returns the status the failure is reported with, a strategy that can not be played is the client's error.
+    fn status_code(&self) -> StatusCode {
+        match self.kind {
+            "game_error" => StatusCode::UNPROCESSABLE_ENTITY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Helper function that names the kind of a `SimulationError` for clients.
+fn failure_kind(error: &SimulationError) -> &'static str {
+    match error {
+        SimulationError::StrategyError { .. } => "game_error",
+        SimulationError::ChannelClosed { .. } => "sending_error",
+        SimulationError::CollectorPanicked | SimulationError::Io(_) => "write_error",
+        SimulationError::Cancelled => "cancelled",
+        SimulationError::TooManySimulations(_) => "too_many_simulations",
+    }
+}
+
+/// The state of a simulation run, the result is the JSON summary of the run once it has finished, or of the simulations completed before it was cancelled.
+/// `events` holds the summary and terminal server-sent events emitted by the job so far, already formatted, so late subscribers can replay them.
+/// Progress is not kept as events, `progress` only holds the latest progress of each strategy by its id.
+struct JobState {
+    status: JobStatus,
+    cancel_flag: Arc<AtomicBool>,
+    result: Option<String>,
+    csv_result: Option<String>,
+    failure: Option<JobFailure>,
+    simulations_completed: u64,
+    simulations_total: u64,
+    progress: BTreeMap<usize, StrategyProgress>,
+    events: Vec<String>,
+}
+
+/// The latest progress of a strategy of a job, `simulations_completed` counts the finished simulations of the strategy.
+struct StrategyProgress {
+    counting_strategy: String,
+    simulations_completed: u32,
+}
+
+impl StrategyProgress {
+    /// Method that formats the progress as a `progress` server-sent event of the strategy with id `id`.
+    fn format_event(&self, id: usize) -> String {
+        let data = serde_json::json!({
+            "id": id,
+            "counting_strategy": self.counting_strategy,
+            "simulations_completed": self.simulations_completed,
+        });
+        format_event("progress", &data.to_string())
+    }
+}
+
+/// The jobs started by `POST /run-sim`, shared between the handlers and the threads running the simulations.
+type Jobs = Mutex<HashMap<JobId, JobState>>;
+
+/// A struct for serializing the status of a job into JSON.
+#[derive(Serialize)]
+struct JobStatusJson<'a> {
+    id: JobId,
+    status: JobStatus,
+    error: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failure: Option<&'a JobFailure>,
+    simulations_completed: u64,
+    simulations_total: u64,
+}
+
+/// Helper function that formats a server-sent event, `data` must not contain any newlines other than a trailing one.
+fn format_event(event: &str, data: &str) -> String {
+    format!("event: {}\ndata: {}\n\n", event, data.trim_end())
+}
+
+/// Helper function to update the state of job `id`, does nothing if the job no longer exists.
+/// Finishing, failing or cancelling the job emits the terminal `result`, `error` or `cancelled` event.
+/// A cancelled job keeps the partial result recorded when its run stopped.
+fn update_job(
+    jobs: &Jobs,
+    id: JobId,
+    status: JobStatus,
+    result: Option<String>,
+    failure: Option<JobFailure>,
+) {
+    if let Ok(mut guard) = jobs.lock() {
+        if let Some(job) = guard.get_mut(&id) {
+            if let Some(ref res) = result {
+                job.events.push(format_event("result", res));
             }
-            match simulator.run_return_out(Box::new(write_simulation_summary_as_json)) {
-                Ok(res_as_json) => {
-                    return Ok(HttpResponse::Ok()
-                        .content_type(ContentType::json())
-                        .body(res_as_json));
-                }
-                Err(_e) => return Err(UserError::InternalError),
+            if status == JobStatus::Cancelled {
+                let data = job.result.as_deref().unwrap_or("[]");
+                job.events.push(format_event("cancelled", data));
+            } else if let Some(ref failure) = failure {
+                let data = serde_json::to_string(failure)
+                    .unwrap_or_else(|_| serde_json::json!({ "error": failure.error }).to_string());
+                job.events.push(format_event("error", &data));
+            }
+            job.status = status;
+            if result.is_some() {
+                job.result = result;
             }
+            job.failure = failure;
         }
     }
+}
+
+/// Helper function that records a `RunEvent` of job `id`, updating its progress and emitting the matching server-sent event.
+fn record_run_event(jobs: &Jobs, id: JobId, event: RunEvent) {
+    let Ok(mut guard) = jobs.lock() else {
+        return;
+    };
+    let Some(job) = guard.get_mut(&id) else {
+        return;
+    };
+    let (name, data) = match event {
+        RunEvent::Progress {
+            id,
+            counting_strategy,
+            simulations_completed,
+        } => {
+            // Only the latest progress of the strategy is kept, subscribers are sent it the next time they poll
+            job.simulations_completed += 1;
+            job.progress
+                .entry(id)
+                .and_modify(|progress| progress.simulations_completed = simulations_completed)
+                .or_insert_with(|| StrategyProgress {
+                    counting_strategy: counting_strategy.to_string(),
+                    simulations_completed,
+                });
+            return;
+        }
+        RunEvent::Summary { id, summary } => (
+            "summary",
+            serde_json::json!({ "id": id, "summary": write::format_summary_as_json(id, summary) }),
+        ),
+        RunEvent::Finished { summaries } => {
+            // Keep the results as csv as well, so they can be returned in either format
+            job.csv_result = Some(write::format_summaries_as_csv(summaries));
+            return;
+        }
+        RunEvent::Stopped { summaries } => {
+            // Only returned if the job was cancelled, a failed job has no result
+            job.result = Some(write::format_summaries_as_json(summaries));
+            job.csv_result = Some(write::format_summaries_as_csv(summaries));
+            return;
+        }
+    };
+    job.events.push(format_event(name, &data.to_string()));
+}
 
-    Err(UserError::InternalError)
+/// The result of the last run that finished, `completed_at` is the time it finished in seconds since the unix epoch.
+struct LatestResult {
+    job_id: JobId,
+    completed_at: u64,
+    result: String,
 }
 
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
-    let address = "127.0.0.1";
-    let port = 8080;
-    println!("Listenting at {}:{}...", address, port);
+/// The cache behind `GET /results/latest`, it keeps the result of the last finished run even after its job has been evicted from the
+/// finished jobs, which are capped by `ServerLimits::max_finished_jobs`.
+#[derive(Default)]
+struct ResultCache {
+    latest: Option<LatestResult>,
+    runs_completed: u64,
+}
 
-    let app_sim: web::Data<Mutex<Option<MulStrategyBlackjackSimulator>>> =
-        web::Data::new(Mutex::new(None));
+/// Helper function that caches `result` as the latest result, unless job `id` has been removed by a reset while it was running.
+/// `jobs` is locked before `cache`, the same order `POST /reset` locks them in.
+fn cache_result(jobs: &Jobs, cache: &Mutex<ResultCache>, id: JobId, result: &str) {
+    let Ok(jobs_guard) = jobs.lock() else {
+        return;
+    };
+    if !jobs_guard.contains_key(&id) {
+        return;
+    }
+    if let Ok(mut cache_guard) = cache.lock() {
+        let completed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        cache_guard.latest = Some(LatestResult {
+            job_id: id,
+            completed_at,
+            result: result.to_string(),
+        });
+        cache_guard.runs_completed += 1;
+    }
+}
 
-    HttpServer::new(move || {
-        App::new()
-            .app_data(app_sim.clone())
-            .service(configure_simulation_parameters)
-            .service(add_simulation)
-            .service(run_simulation)
-    })
-    .bind((address, port))?
-    .run()
-    .await
+/// Helper function that evicts the oldest finished jobs, so at most `max_finished_jobs` of them remain. Job ids increase with every run,
+/// so the oldest jobs have the smallest ids.
+fn evict_finished_jobs(jobs: &mut HashMap<JobId, JobState>, max_finished_jobs: usize) {
+    let mut finished = jobs
+        .iter()
+        .filter(|(_, job)| job.status.is_done())
+        .map(|(&id, _)| id)
+        .collect::<Vec<JobId>>();
+    if finished.len() <= max_finished_jobs {
+        return;
+    }
+    finished.sort_unstable();
+    for id in &finished[..finished.len() - max_finished_jobs] {
+        jobs.remove(id);
+    }
+}
+
+/// A handler that will start running the simulation given the configurations on a background thread, and respond with the id of the job immediately.
+/// The configured simulations are moved into the job, so new simulations can be added to the simulator while it runs.
+/// Will return an error resposne if the game has not been configured and/or no simulations have been added, or if `max_running_jobs` runs
+/// are already queued or running. Starting a run evicts the oldest finished jobs beyond `max_finished_jobs`.
+#[post("/run-sim")]
+async fn run_simulation(
+    app_sim: web::Data<Mutex<Option<MulStrategyBlackjackSimulator>>>,
+    sim_configs: web::Data<SimConfigs>,
+    jobs: web::Data<Jobs>,
+    cache: web::Data<Mutex<ResultCache>>,
+    limits: web::Data<ServerLimits>,
+) -> Result<HttpResponse, UserError> {
+    // The simulator, the simulation configs and the jobs stay locked until the job is recorded, so concurrent runs can not exceed the limit
+    let (id, mut simulator) = {
+        let mut guard = app_sim.lock().map_err(|_| UserError::InternalError)?;
+        // Check if we have a valid simulator
+        let Some(simulator) = guard.as_mut() else {
+            return Err(UserError::InternalError);
+        };
+        if simulator.simulations().is_empty() {
+            return Err(UserError::BadInput(String::from(
+                "no simulations have been added, unable to run.",
+            )));
+        }
+        let mut configs_guard = sim_configs.lock().map_err(|_| UserError::InternalError)?;
+        let mut jobs_guard = jobs.lock().map_err(|_| UserError::InternalError)?;
+        let running = jobs_guard
+            .values()
+            .filter(|job| !job.status.is_done())
+            .count();
+        if running >= limits.max_running_jobs {
+            return Err(UserError::TooManyJobs(limits.max_running_jobs));
+        }
+        let simulations_total = u64::try_from(simulator.simulations().len())
+            .ok()
+            .and_then(|len| u64::from(simulator.config.num_simulations).checked_mul(len))
+            .ok_or_else(|| {
+                UserError::BadInput(String::from(
+                    "too many simulations are configured to count, unable to run.",
+                ))
+            })?;
+
+        // Leave a simulator with the same configuration in place of the one being run
+        configs_guard.clear();
+        let mut replacement = MulStrategyBlackjackSimulator::new(simulator.config);
+        if let Some(limit) = simulator.max_simulations() {
+            replacement.max_simulations(limit);
+        }
+        let simulator = std::mem::replace(simulator, replacement.build());
+
+        evict_finished_jobs(&mut jobs_guard, limits.max_finished_jobs);
+        let id = NEXT_JOB_ID.fetch_add(1, Ordering::Relaxed);
+        jobs_guard.insert(
+            id,
+            JobState {
+                status: JobStatus::Queued,
+                cancel_flag: simulator.cancel_flag(),
+                result: None,
+                csv_result: None,
+                failure: None,
+                simulations_completed: 0,
+                simulations_total,
+                progress: BTreeMap::new(),
+                events: vec![],
+            },
+        );
+        (id, simulator)
+    };
+
+    let jobs = jobs.into_inner();
+    let cache = cache.into_inner();
+    thread::spawn(move || {
+        update_job(&jobs, id, JobStatus::Running, None, None);
+        let event_jobs = Arc::clone(&jobs);
+        let write_fn = move |receiver, ids| {
+            collect_simulation_summaries(receiver, ids, |event| {
+                record_run_event(&event_jobs, id, event)
+            })
+        };
+        let result = simulator
+            .run_return_out(write_fn)
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send>)
+            .and_then(|result| result);
+        match result {
+            Ok(res_as_json) => {
+                cache_result(&jobs, &cache, id, &res_as_json);
+                update_job(&jobs, id, JobStatus::Finished, Some(res_as_json), None)
+            }
+            Err(e) => {
+                let status = match e.downcast_ref::<SimulationError>() {
+                    Some(SimulationError::Cancelled) => JobStatus::Cancelled,
+                    _ => JobStatus::Failed,
+                };
+                update_job(&jobs, id, status, None, Some(JobFailure::new(&*e)))
+            }
+        }
+    });
+
+    Ok(HttpResponse::Accepted()
+        .content_type(ContentType::json())
+        .body(format!("{{\"job_id\":{}}}", id)))
+}
+
+/// A handler that reports the status of a job, and the error if the job failed.
+#[get("/jobs/{id}")]
+async fn job_status(
+    path: web::Path<JobId>,
+    jobs: web::Data<Jobs>,
+) -> Result<HttpResponse, UserError> {
+    let id = path.into_inner();
+    let guard = jobs.lock().map_err(|_| UserError::InternalError)?;
+    let job = guard.get(&id).ok_or(UserError::JobNotFound(id))?;
+    let status = JobStatusJson {
+        id,
+        status: job.status,
+        error: job.failure.as_ref().map(|failure| failure.error.as_str()),
+        failure: job
+            .failure
+            .as_ref()
+            .filter(|_| job.status == JobStatus::Failed),
+        simulations_completed: job.simulations_completed,
+        simulations_total: job.simulations_total,
+    };
+
+    match serde_json::to_string(&status) {
+        Ok(res) => Ok(HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .body(res)),
+        Err(_) => Err(UserError::InternalError),
+    }
+}
+
+/// A handler that cancels a queued or running job, its simulation threads stop between hands and the job ends up `cancelled`.
+/// The summaries of the simulations completed before the cancellation remain available from `GET /jobs/{id}/result`.
+/// Will return an error response if the job does not exist or is no longer running.
+#[post("/jobs/{id}/cancel")]
+async fn cancel_job(
+    path: web::Path<JobId>,
+    jobs: web::Data<Jobs>,
+) -> Result<HttpResponse, UserError> {
+    let id = path.into_inner();
+    let guard = jobs.lock().map_err(|_| UserError::InternalError)?;
+    let job = guard.get(&id).ok_or(UserError::JobNotFound(id))?;
+    if job.status.is_done() {
+        return Err(UserError::JobNotRunning(id));
+    }
+    job.cancel_flag.store(true, Ordering::Relaxed);
+    Ok(HttpResponse::Accepted().body("cancellation requested"))
+}
+
+/// A handler that responds with the JSON result of the last run that finished, so it can be fetched again without rerunning the simulation.
+/// The `ETag` header identifies the run and `X-Completed-At` holds the time it finished in seconds since the unix epoch,
+/// a request whose `If-None-Match` header matches the current run gets an empty 304 response.
+#[get("/results/latest")]
+async fn latest_result(
+    req: HttpRequest,
+    cache: web::Data<Mutex<ResultCache>>,
+) -> Result<HttpResponse, UserError> {
+    let guard = cache.lock().map_err(|_| UserError::InternalError)?;
+    let latest = guard.latest.as_ref().ok_or(UserError::NoResults)?;
+    let etag = format!("\"job-{}\"", latest.job_id);
+    let not_modified = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value
+                .split(',')
+                .any(|tag| tag.trim() == etag || tag.trim() == "*")
+        });
+    if not_modified {
+        return Ok(HttpResponse::NotModified()
+            .insert_header((header::ETAG, etag))
+            .finish());
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::json())
+        .insert_header((header::ETAG, etag))
+        .insert_header(("X-Completed-At", latest.completed_at.to_string()))
+        .body(latest.result.clone()))
+}
+
+/// The query parameters accepted by `POST /reset`.
+#[derive(Deserialize)]
+struct ResetQuery {
+    force: Option<bool>,
+}
+
+/// A handler that drops the configured simulator, its simulations, the cached latest result and every job together with its results, so a client can start over.
+/// Will return an error response if a job is still queued or running, unless `force=true` is given in which case those jobs are cancelled first.
+#[post("/reset")]
+async fn reset(
+    query: web::Query<ResetQuery>,
+    app_sim: web::Data<Mutex<Option<MulStrategyBlackjackSimulator>>>,
+    sim_configs: web::Data<SimConfigs>,
+    jobs: web::Data<Jobs>,
+    cache: web::Data<Mutex<ResultCache>>,
+) -> Result<HttpResponse, UserError> {
+    let mut sim_guard = app_sim.lock().map_err(|_| UserError::InternalError)?;
+    let mut configs_guard = sim_configs.lock().map_err(|_| UserError::InternalError)?;
+    let mut jobs_guard = jobs.lock().map_err(|_| UserError::InternalError)?;
+    let mut cache_guard = cache.lock().map_err(|_| UserError::InternalError)?;
+
+    let mut running = jobs_guard.values().filter(|job| !job.status.is_done());
+    if !query.force.unwrap_or(false) {
+        if running.next().is_some() {
+            return Err(UserError::RunInProgress);
+        }
+    } else {
+        // The threads of a cancelled job stop between hands, their updates are ignored once the job has been removed
+        for job in running {
+            job.cancel_flag.store(true, Ordering::Relaxed);
+        }
+    }
+
+    *sim_guard = None;
+    configs_guard.clear();
+    jobs_guard.clear();
+    cache_guard.latest = None;
+    Ok(HttpResponse::Ok().body("simulator reset successfully"))
+}
+
+/// The media types a job result can be returned as.
+const SUPPORTED_RESULT_FORMATS: [&str; 2] = ["application/json", "text/csv"];
+
+/// The formats a job result can be returned in.
+#[derive(Debug, PartialEq)]
+enum ResultFormat {
+    Json,
+    Csv,
+}
+
+/// The query parameters accepted by `GET /jobs/{id}/result`.
+#[derive(Deserialize)]
+struct ResultQuery {
+    format: Option<String>,
+}
+
+/// Helper function that picks the format of a job result, the `format` query parameter takes precedence over the `Accept` header.
+/// Returns `UserError::NotAcceptable` if neither names a supported format.
+fn negotiate_result_format(
+    req: &HttpRequest,
+    format: Option<&str>,
+) -> Result<ResultFormat, UserError> {
+    if let Some(format) = format {
+        return match format.to_lowercase().as_str() {
+            "json" => Ok(ResultFormat::Json),
+            "csv" => Ok(ResultFormat::Csv),
+            _ => Err(UserError::NotAcceptable(format.to_string())),
+        };
+    }
+
+    let Some(accept) = req.headers().get(header::ACCEPT) else {
+        return Ok(ResultFormat::Json);
+    };
+    let accept = accept
+        .to_str()
+        .map_err(|_| UserError::BadInput("invalid Accept header".to_string()))?;
+    // Media types are tried in the order given, ignoring their parameters
+    for media_type in accept.split(',') {
+        let media_type = media_type.split(';').next().unwrap_or("").trim();
+        match media_type {
+            "application/json" | "application/*" | "*/*" => return Ok(ResultFormat::Json),
+            "text/csv" | "text/*" => return Ok(ResultFormat::Csv),
+            _ => {}
+        }
+    }
+    Err(UserError::NotAcceptable(accept.to_string()))
+}
+
+/// A handler that returns the summary of a finished job, as JSON or as CSV depending on the `Accept` header or the `format` query parameter.
+/// Will return an error response if the job does not exist, has not finished successfully or the requested format is not supported.
+#[get("/jobs/{id}/result")]
+async fn job_result(
+    req: HttpRequest,
+    path: web::Path<JobId>,
+    query: web::Query<ResultQuery>,
+    jobs: web::Data<Jobs>,
+) -> Result<HttpResponse, UserError> {
+    let id = path.into_inner();
+    let format = negotiate_result_format(&req, query.format.as_deref())?;
+    let guard = jobs.lock().map_err(|_| UserError::InternalError)?;
+    let job = guard.get(&id).ok_or(UserError::JobNotFound(id))?;
+    if let (JobStatus::Failed, Some(failure)) = (job.status, &job.failure) {
+        return Err(UserError::SimulationFailed(failure.clone()));
+    }
+    if !matches!(job.status, JobStatus::Finished | JobStatus::Cancelled) {
+        return Err(UserError::JobNotFinished(id));
+    }
+    match (format, &job.result, &job.csv_result) {
+        (ResultFormat::Json, Some(res_as_json), _) => Ok(HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .body(res_as_json.clone())),
+        (ResultFormat::Csv, _, Some(res_as_csv)) => Ok(HttpResponse::Ok()
+            .content_type("text/csv")
+            .body(res_as_csv.clone())),
+        _ => Err(UserError::JobNotFinished(id)),
+    }
+}
+
+/// The body of a `GET /jobs/{id}/events` response, streams the events of a job as they are recorded until the job has finished.
+/// The events are polled from the job's state, so the simulation threads never wait on a slow client.
+/// `progress_sent` holds the progress of each strategy last sent to the client.
+struct JobEventStream {
+    jobs: Arc<Jobs>,
+    id: JobId,
+    next_event: usize,
+    progress_sent: HashMap<usize, u32>,
+    delay: Option<Pin<Box<rt::time::Sleep>>>,
+}
+
+impl MessageBody for JobEventStream {
+    type Error = std::convert::Infallible;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<web::Bytes, Self::Error>>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(delay) = this.delay.as_mut() {
+                if delay.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                this.delay = None;
+            }
+
+            let jobs = Arc::clone(&this.jobs);
+            let Ok(guard) = jobs.lock() else {
+                return Poll::Ready(None);
+            };
+            let Some(job) = guard.get(&this.id) else {
+                return Poll::Ready(None);
+            };
+            // Send the progress of every strategy that advanced, then every event recorded since the last poll at once.
+            // A strategy's progress is recorded before its summary, so the progress is always sent first
+            let mut events = String::new();
+            for (&id, progress) in job.progress.iter() {
+                let sent = this.progress_sent.entry(id).or_insert(0);
+                if progress.simulations_completed > *sent {
+                    *sent = progress.simulations_completed;
+                    events.push_str(&progress.format_event(id));
+                }
+            }
+            if this.next_event < job.events.len() {
+                events.push_str(&job.events[this.next_event..].concat());
+                this.next_event = job.events.len();
+            }
+            if !events.is_empty() {
+                return Poll::Ready(Some(Ok(web::Bytes::from(events))));
+            }
+            if job.status.is_done() {
+                return Poll::Ready(None);
+            }
+            drop(guard);
+            this.delay = Some(Box::pin(rt::time::sleep(Duration::from_millis(50))));
+        }
+    }
+}
+
+/// A handler that streams the progress of a job as server-sent events. A `progress` event is sent when simulations of a strategy have finished
+/// since the last poll, carrying the latest count, a `summary` event whenever every simulation of a strategy has finished, and the stream ends
+/// with either a `result` event carrying the complete JSON summary, a `cancelled` event carrying the partial JSON summary or an `error` event.
+/// Summary events emitted before the client connected are replayed, progress is only sent as the latest count of each strategy.
+#[get("/jobs/{id}/events")]
+async fn job_events(
+    path: web::Path<JobId>,
+    jobs: web::Data<Jobs>,
+) -> Result<HttpResponse, UserError> {
+    let id = path.into_inner();
+    if !jobs
+        .lock()
+        .map_err(|_| UserError::InternalError)?
+        .contains_key(&id)
+    {
+        return Err(UserError::JobNotFound(id));
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .body(JobEventStream {
+            jobs: jobs.into_inner(),
+            id,
+            next_event: 0,
+            progress_sent: HashMap::new(),
+            delay: None,
+        }))
+}
+
+/// A struct for serializing the description of a counting strategy into JSON.
+#[derive(Serialize)]
+struct CountingStrategyJson {
+    name: &'static str,
+    aliases: &'static [&'static str],
+    description: &'static str,
+    balanced: bool,
+}
+
+/// A struct for serializing the description of a decision strategy into JSON.
+#[derive(Serialize)]
+struct DecisionStrategyJson {
+    name: &'static str,
+    aliases: &'static [&'static str],
+    description: &'static str,
+}
+
+/// A struct for serializing the description of a betting strategy into JSON.
+#[derive(Serialize)]
+struct BettingStrategyJson {
+    name: &'static str,
+    aliases: &'static [&'static str],
+    description: &'static str,
+    needs_margin: bool,
+}
+
+/// A struct for serializing every strategy that can be used in a `SimConfig` into JSON.
+#[derive(Serialize)]
+struct StrategiesJson {
+    counting_strategies: Vec<CountingStrategyJson>,
+    decision_strategies: Vec<DecisionStrategyJson>,
+    betting_strategies: Vec<BettingStrategyJson>,
+}
+
+/// A handler that lists the names of every counting, decision and betting strategy that can be added to the simulator.
+#[get("/strategies")]
+async fn list_strategies() -> Result<HttpResponse, UserError> {
+    let strategies = StrategiesJson {
+        counting_strategies: registry::COUNTING_STRATEGIES
+            .iter()
+            .map(|descriptor| CountingStrategyJson {
+                name: descriptor.name,
+                aliases: descriptor.aliases,
+                description: descriptor.description,
+                balanced: descriptor.balanced,
+            })
+            .collect(),
+        decision_strategies: registry::DECISION_STRATEGIES
+            .iter()
+            .map(|descriptor| DecisionStrategyJson {
+                name: descriptor.name,
+                aliases: descriptor.aliases,
+                description: descriptor.description,
+            })
+            .collect(),
+        betting_strategies: registry::BETTING_STRATEGIES
+            .iter()
+            .map(|descriptor| BettingStrategyJson {
+                name: descriptor.name,
+                aliases: descriptor.aliases,
+                description: descriptor.description,
+                needs_margin: descriptor.needs_margin,
+            })
+            .collect(),
+    };
+
+    match serde_json::to_string(&strategies) {
+        Ok(res) => Ok(HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .body(res)),
+        Err(_) => Err(UserError::InternalError),
+    }
+}
+
+/// The directory simulator setups are saved to by `POST /configs`, one JSON file per setup.
+struct ConfigStore {
+    dir: PathBuf,
+}
+
+impl ConfigStore {
+    fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        ConfigStore { dir: dir.into() }
+    }
+
+    /// Returns the path of the setup saved under `name`, names are restricted to letters, digits, '-' and '_' so they can not escape `self.dir`.
+    fn path(&self, name: &str) -> Result<PathBuf, UserError> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(UserError::BadInput(String::from(
+                "configuration names may only contain letters, digits, '-' and '_'",
+            )));
+        }
+        Ok(self.dir.join(format!("{}.json", name)))
+    }
+}
+
+/// A simulator setup as it is saved to disk, the game configuration along with every simulation added to the simulator.
+#[derive(Serialize, Deserialize)]
+struct SavedConfig {
+    config: BlackjackSimulatorConfig,
+    simulations: Vec<SimConfig>,
+}
+
+/// A struct for deserializing the name to save the current setup under from JSON.
+#[derive(Deserialize)]
+struct SaveConfigRequest {
+    name: String,
+}
+
+/// Helper function that maps an I/O error into a `UserError::StorageError` describing what was being done.
+fn storage_error(action: &str, err: io::Error) -> UserError {
+    UserError::StorageError(format!("unable to {}: {}", action, err))
+}
+
+/// A handler that saves the current simulator setup under the given name.
+/// Will return an error response if no simulator has been configured, or a setup with the same name has already been saved.
+#[post("/configs")]
+async fn save_config(
+    params: web::Json<SaveConfigRequest>,
+    app_sim: web::Data<Mutex<Option<MulStrategyBlackjackSimulator>>>,
+    sim_configs: web::Data<SimConfigs>,
+    store: web::Data<ConfigStore>,
+) -> Result<HttpResponse, UserError> {
+    let name = params.into_inner().name;
+    let path = store.path(&name)?;
+    let saved_config = {
+        let guard = app_sim.lock().map_err(|_| UserError::InternalError)?;
+        let Some(simulator) = guard.as_ref() else {
+            return Err(UserError::SimulatorNotCreated);
+        };
+        SavedConfig {
+            config: simulator.config,
+            simulations: sim_configs
+                .lock()
+                .map_err(|_| UserError::InternalError)?
+                .clone(),
+        }
+    };
+    let contents = serde_json::to_string(&saved_config).map_err(|_| UserError::InternalError)?;
+
+    fs::create_dir_all(&store.dir)
+        .map_err(|e| storage_error("create the configuration directory", e))?;
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            return Err(UserError::SavedConfigExists(name))
+        }
+        Err(e) => return Err(storage_error("create the configuration file", e)),
+    };
+    file.write_all(contents.as_bytes())
+        .map_err(|e| storage_error("write the configuration file", e))?;
+
+    Ok(HttpResponse::Ok().body(format!("configuration {} saved successfully", name)))
+}
+
+/// A struct for serializing the names of the saved setups into JSON.
+#[derive(Serialize)]
+struct SavedConfigsJson {
+    configs: Vec<String>,
+}
+
+/// A handler that lists the names of every saved setup in alphabetical order.
+#[get("/configs")]
+async fn list_configs(store: web::Data<ConfigStore>) -> Result<HttpResponse, UserError> {
+    let mut configs = vec![];
+    match fs::read_dir(&store.dir) {
+        Ok(entries) => {
+            for entry in entries {
+                let path = entry
+                    .map_err(|e| storage_error("read the configuration directory", e))?
+                    .path();
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                        configs.push(name.to_string());
+                    }
+                }
+            }
+        }
+        // Nothing has been saved yet
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(storage_error("read the configuration directory", e)),
+    }
+    configs.sort();
+
+    match serde_json::to_string(&SavedConfigsJson { configs }) {
+        Ok(res) => Ok(HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .body(res)),
+        Err(_) => Err(UserError::InternalError),
+    }
+}
+
+/// A handler that replaces the current simulator with the setup saved under the given name.
+/// Will return an error response if no setup has been saved under the name, or the saved setup is no longer valid.
+#[post("/configs/{name}/load")]
+async fn load_config(
+    path: web::Path<String>,
+    app_sim: web::Data<Mutex<Option<MulStrategyBlackjackSimulator>>>,
+    sim_configs: web::Data<SimConfigs>,
+    store: web::Data<ConfigStore>,
+    limits: web::Data<ServerLimits>,
+) -> Result<HttpResponse, UserError> {
+    let name = path.into_inner();
+    let file = match File::open(store.path(&name)?) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(UserError::SavedConfigNotFound(name))
+        }
+        Err(e) => return Err(storage_error("open the configuration file", e)),
+    };
+    let saved_config: SavedConfig =
+        serde_json::from_reader(io::BufReader::new(file)).map_err(|e| {
+            UserError::StorageError(format!("unable to parse configuration {}: {}", name, e))
+        })?;
+
+    // Rebuild the simulator before replacing the current one, so a setup that is no longer valid leaves the current one untouched
+    saved_config
+        .config
+        .validate()
+        .map_err(UserError::InvalidConfig)?;
+    let mut simulator = new_simulator(saved_config.config, &limits);
+    for sim_params in &saved_config.simulations {
+        add_simulation_from_config(&mut simulator, sim_params)?;
+    }
+
+    let mut guard = app_sim.lock().map_err(|_| UserError::InternalError)?;
+    *guard = Some(simulator);
+    *sim_configs.lock().map_err(|_| UserError::InternalError)? = saved_config.simulations;
+
+    Ok(HttpResponse::Ok().body(format!("configuration {} loaded successfully", name)))
+}
+
+/// The options the server is started with.
+#[derive(Parser)]
+#[command(name = "Card Counting Simulator API")]
+#[command(about = "Serves the card counting simulator over HTTP")]
+struct ServerArgs {
+    /// The maximum number of simulations a simulator may be configured with
+    #[arg(long, value_name = "SIMULATIONS", default_value_t = DEFAULT_MAX_SIMULATIONS)]
+    max_simulations: usize,
+    /// The maximum number of runs that may be queued or running at once
+    #[arg(long, value_name = "JOBS", default_value_t = DEFAULT_MAX_RUNNING_JOBS)]
+    max_running_jobs: usize,
+    /// The number of finished jobs whose status and results are kept
+    #[arg(long, value_name = "JOBS", default_value_t = DEFAULT_MAX_FINISHED_JOBS)]
+    max_finished_jobs: usize,
+}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    let args = ServerArgs::parse();
+    let address = "127.0.0.1";
+    let port = 8080;
+    println!("Listenting at {}:{}...", address, port);
+
+    let app_sim: web::Data<Mutex<Option<MulStrategyBlackjackSimulator>>> =
+        web::Data::new(Mutex::new(None));
+    let jobs: web::Data<Jobs> = web::Data::new(Mutex::new(HashMap::new()));
+    let cache = web::Data::new(Mutex::new(ResultCache::default()));
+    let sim_configs: web::Data<SimConfigs> = web::Data::new(Mutex::new(vec![]));
+    let store = web::Data::new(ConfigStore::new("saved_configs"));
+    let limits = web::Data::new(ServerLimits {
+        max_simulations: args.max_simulations,
+        max_running_jobs: args.max_running_jobs,
+        max_finished_jobs: args.max_finished_jobs,
+    });
+
+    HttpServer::new(move || {
+        App::new()
+            .app_data(app_sim.clone())
+            .app_data(jobs.clone())
+            .app_data(cache.clone())
+            .app_data(sim_configs.clone())
+            .app_data(store.clone())
+            .app_data(limits.clone())
+            .service(configure_simulation_parameters)
+            .service(add_simulation)
+            .service(list_simulations)
+            .service(get_config)
+            .service(remove_simulation)
+            .service(run_simulation)
+            .service(job_status)
+            .service(job_result)
+            .service(cancel_job)
+            .service(reset)
+            .service(latest_result)
+            .service(job_events)
+            .service(sweep)
+            .service(save_config)
+            .service(list_configs)
+            .service(load_config)
+            .service(list_strategies)
+    })
+    .bind((address, port))?
+    .run()
+    .await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use actix_web::test;
+    use blackjack_sim::money::Money;
+
+    /// Configures a seeded simulator with a single HiLo simulation using the given betting margin, starts a run and evaluates to the job id.
+    macro_rules! start_run {
+        ($app:expr, $betting_margin:expr) => {{
+            let req = test::TestRequest::post()
+                .uri("/config-game-params")
+                .set_json(serde_json::json!({
+                    "player_starting_balance": 500.0,
+                    "num_simulations": 2,
+                    "num_decks": 6,
+                    "hands_per_simulation": 200,
+                    "min_bet": 5,
+                    "surrender": true,
+                    "seed": 7
+                }))
+                .to_request();
+            assert!(test::call_service(&$app, req).await.status().is_success());
+
+            let req = test::TestRequest::post()
+                .uri("/add-sim")
+                .set_json(serde_json::json!({
+                    "counting_strategy": "HiLo",
+                    "decision_strategy": "Basic Strategy",
+                    "betting_strategy": "Margin",
+                    "betting_margin": $betting_margin
+                }))
+                .to_request();
+            assert!(test::call_service(&$app, req).await.status().is_success());
+
+            let req = test::TestRequest::post().uri("/run-sim").to_request();
+            let resp = test::call_service(&$app, req).await;
+            assert_eq!(resp.status(), StatusCode::ACCEPTED);
+            let body: serde_json::Value = test::read_body_json(resp).await;
+            body["job_id"].as_u64().unwrap()
+        }};
+    }
+
+    /// Polls the status of a job until it is no longer queued or running, evaluates to the final status.
+    macro_rules! wait_for_job {
+        ($app:expr, $id:expr) => {{
+            let mut status = serde_json::Value::Null;
+            for _ in 0..1000 {
+                let req = test::TestRequest::get()
+                    .uri(&format!("/jobs/{}", $id))
+                    .to_request();
+                status = test::call_and_read_body_json(&$app, req).await;
+                if status["status"] != "queued" && status["status"] != "running" {
+                    break;
+                }
+                thread::sleep(std::time::Duration::from_millis(10));
+            }
+            status
+        }};
+    }
+
+    macro_rules! init_app {
+        (limits: $limits:expr) => {
+            init_app!(
+                @app std::env::temp_dir().join("blackjack_sim_unused_configs"),
+                $limits,
+                web::Data::new(Mutex::new(ResultCache::default()))
+            )
+        };
+        () => {
+            init_app!(std::env::temp_dir().join("blackjack_sim_unused_configs"))
+        };
+        ($config_dir:expr) => {
+            init_app!($config_dir, DEFAULT_MAX_SIMULATIONS)
+        };
+        ($config_dir:expr, $max_simulations:expr) => {
+            init_app!(
+                $config_dir,
+                $max_simulations,
+                web::Data::new(Mutex::new(ResultCache::default()))
+            )
+        };
+        ($config_dir:expr, $max_simulations:expr, $cache:expr) => {
+            init_app!(
+                @app $config_dir,
+                ServerLimits {
+                    max_simulations: $max_simulations,
+                    ..ServerLimits::default()
+                },
+                $cache
+            )
+        };
+        (@app $config_dir:expr, $limits:expr, $cache:expr) => {
+            test::init_service(
+                App::new()
+                    .app_data(web::Data::new(Mutex::new(
+                        None::<MulStrategyBlackjackSimulator>,
+                    )))
+                    .app_data(web::Data::new(Jobs::new(HashMap::new())))
+                    .app_data($cache.clone())
+                    .app_data(web::Data::new(SimConfigs::new(vec![])))
+                    .app_data(web::Data::new(ConfigStore::new($config_dir)))
+                    .app_data(web::Data::new($limits))
+                    .service(configure_simulation_parameters)
+                    .service(add_simulation)
+                    .service(list_simulations)
+                    .service(get_config)
+                    .service(remove_simulation)
+                    .service(run_simulation)
+                    .service(job_status)
+                    .service(job_result)
+                    .service(cancel_job)
+                    .service(reset)
+                    .service(latest_result)
+                    .service(job_events)
+                    .service(sweep)
+                    .service(save_config)
+                    .service(list_configs)
+                    .service(load_config),
+            )
+            .await
+        };
+    }
+
+    macro_rules! configure {
+        ($app:expr, $config:expr) => {{
+            let req = test::TestRequest::post()
+                .uri("/config-game-params")
+                .set_json($config)
+                .to_request();
+            test::call_service(&$app, req).await
+        }};
+    }
+
+    #[actix_web::test]
+    async fn valid_config_is_accepted() {
+        let app = init_app!();
+        let resp = configure!(
+            app,
+            serde_json::json!({
+                "player_starting_balance": 500.0,
+                "num_simulations": 2,
+                "num_decks": 6,
+                "hands_per_simulation": 200,
+                "min_bet": 5,
+                "surrender": true
+            })
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn invalid_config_fields_are_rejected() {
+        let app = init_app!();
+        for (field, value, constraint) in [
+            (
+                "player_starting_balance",
+                serde_json::json!(-5.0),
+                "must be a positive number",
+            ),
+            (
+                "table_starting_balance",
+                serde_json::json!(0.0),
+                "must be a positive number",
+            ),
+            (
+                "num_simulations",
+                serde_json::json!(0),
+                "must be at least 1",
+            ),
+            ("num_decks", serde_json::json!(0), "must be at least 1"),
+            (
+                "hands_per_simulation",
+                serde_json::json!(0),
+                "must be at least 1",
+            ),
+            (
+                "min_bet",
+                serde_json::json!(1000),
+                "must not be greater than player_starting_balance",
+            ),
+        ] {
+            let mut config = serde_json::json!({
+                "player_starting_balance": 500.0,
+                "num_simulations": 2,
+                "num_decks": 6,
+                "hands_per_simulation": 200,
+                "min_bet": 5,
+                "surrender": true
+            });
+            config[field] = value;
+            let resp = configure!(app, config);
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", field);
+            let body: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(
+                body["errors"],
+                serde_json::json!([{ "field": field, "constraint": constraint }])
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn invalid_betting_margin_is_rejected() {
+        let app = init_app!();
+        let resp = configure!(
+            app,
+            serde_json::json!({
+                "player_starting_balance": 500.0,
+                "num_simulations": 2,
+                "num_decks": 6,
+                "hands_per_simulation": 200,
+                "min_bet": 5,
+                "surrender": true
+            })
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = test::TestRequest::post()
+            .uri("/add-sim")
+            .set_json(serde_json::json!({
+                "counting_strategy": "HiLo",
+                "decision_strategy": "Basic Strategy",
+                "betting_strategy": "Margin",
+                "betting_margin": -3.0
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["errors"][0]["field"], "betting_margin");
+    }
+
+    #[actix_web::test]
+    async fn run_job_can_be_polled_to_completion() {
+        let app = init_app!();
+        let id = start_run!(app, 2.0);
+        let status = wait_for_job!(app, id);
+        assert_eq!(status["status"], "finished");
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/jobs/{}/result", id))
+            .to_request();
+        let result: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(result[0]["id"], 1);
+        assert_eq!(result[0]["strategy"], "HiLo");
+    }
+
+    #[actix_web::test]
+    async fn job_result_is_returned_as_csv_when_requested() {
+        let app = init_app!();
+        let id = start_run!(app, 2.0);
+        wait_for_job!(app, id);
+
+        for req in [
+            test::TestRequest::get()
+                .uri(&format!("/jobs/{}/result", id))
+                .insert_header(("Accept", "text/csv"))
+                .to_request(),
+            test::TestRequest::get()
+                .uri(&format!("/jobs/{}/result?format=csv", id))
+                .to_request(),
+        ] {
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(resp.headers().get("content-type").unwrap(), "text/csv");
+            let body = test::read_body(resp).await;
+            let body = std::str::from_utf8(&body).unwrap();
+            let mut lines = body.lines();
+            assert_eq!(lines.next(), Some(write::SUMMARY_CSV_HEADER.trim_end()));
+            assert!(lines.next().unwrap().starts_with("1,\"HiLo"));
+            assert_eq!(lines.next(), None);
+        }
+    }
+
+    #[actix_web::test]
+    async fn job_result_rejects_unsupported_formats() {
+        let app = init_app!();
+        let id = start_run!(app, 2.0);
+        wait_for_job!(app, id);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/jobs/{}/result", id))
+            .insert_header(("Accept", "application/xml"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
+        let body = test::read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains("application/json, text/csv"));
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/jobs/{}/result?format=xml", id))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[actix_web::test]
+    async fn job_events_stream_progress_then_result() {
+        let app = init_app!();
+        let id = start_run!(app, 2.0);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/jobs/{}/events", id))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            "text/event-stream"
+        );
+        let body = test::read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+
+        let events = body
+            .split("\n\n")
+            .filter(|event| !event.is_empty())
+            .map(|event| {
+                let mut lines = event.lines();
+                let name = lines.next().unwrap().strip_prefix("event: ").unwrap();
+                let data = lines.next().unwrap().strip_prefix("data: ").unwrap();
+                (name, serde_json::from_str(data).unwrap())
+            })
+            .collect::<Vec<(&str, serde_json::Value)>>();
+
+        // The progress of the two simulations of a single strategy, its summary, then the result. Progress recorded between two polls
+        // is sent once with the latest count, so there may be one or two progress events
+        let names = events.iter().map(|(name, _)| *name).collect::<Vec<&str>>();
+        let num_progress = names.len() - 2;
+        assert!((1..=2).contains(&num_progress), "{:?}", names);
+        assert!(names[..num_progress].iter().all(|&name| name == "progress"));
+        assert_eq!(names[num_progress..], ["summary", "result"]);
+        assert_eq!(events[num_progress - 1].1["simulations_completed"], 2);
+        assert_eq!(events[num_progress].1["summary"]["strategy"], "HiLo");
+        assert_eq!(events[num_progress + 1].1[0]["strategy"], "HiLo");
+    }
+
+    #[actix_web::test]
+    async fn failed_run_job_reports_the_error() {
+        let app = init_app!();
+        // A margin of one half bets below the table minimum as soon as the true count is positive
+        let id = start_run!(app, 0.5);
+        let status = wait_for_job!(app, id);
+        assert_eq!(status["status"], "failed");
+        assert!(status["error"].as_str().unwrap().contains("table minimum"));
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/jobs/{}/result", id))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        // The strategy could not be played, which is reported as the client's error along with its cause
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["kind"], "game_error");
+
+        let req = test::TestRequest::get().uri("/jobs/0").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn cancelled_job_reports_partial_results() {
+        let app = init_app!();
+        let resp = configure!(
+            app,
+            serde_json::json!({
+                "player_starting_balance": 500.0,
+                "num_simulations": 1_000_000,
+                "num_decks": 6,
+                "hands_per_simulation": 200,
+                "min_bet": 5,
+                "surrender": true
+            })
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+        let req = test::TestRequest::post()
+            .uri("/add-sim")
+            .set_json(serde_json::json!({
+                "counting_strategy": "HiLo",
+                "decision_strategy": "Basic Strategy",
+                "betting_strategy": "Margin",
+                "betting_margin": 2.0
+            }))
+            .to_request();
+        assert!(test::call_service(&app, req).await.status().is_success());
+        let req = test::TestRequest::post().uri("/run-sim").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let id = body["job_id"].as_u64().unwrap();
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/jobs/{}/cancel", id))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::ACCEPTED
+        );
+        let status = wait_for_job!(app, id);
+        assert_eq!(status["status"], "cancelled");
+
+        // The partial result is available, and the job can not be cancelled twice
+        let req = test::TestRequest::get()
+            .uri(&format!("/jobs/{}/result", id))
+            .to_request();
+        let result: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert!(result.is_array());
+        let req = test::TestRequest::post()
+            .uri(&format!("/jobs/{}/cancel", id))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::CONFLICT
+        );
+
+        // The server keeps serving runs afterwards
+        let id = start_run!(app, 2.0);
+        let status = wait_for_job!(app, id);
+        assert_eq!(status["status"], "finished");
+    }
+
+    #[actix_web::test]
+    async fn runs_are_limited_while_others_are_running() {
+        let app = init_app!(limits: ServerLimits {
+            max_running_jobs: 1,
+            ..ServerLimits::default()
+        });
+        let resp = configure!(
+            app,
+            serde_json::json!({
+                "player_starting_balance": 500.0,
+                "num_simulations": 1_000_000,
+                "num_decks": 6,
+                "hands_per_simulation": 200,
+                "min_bet": 5,
+                "surrender": true
+            })
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+        let add_sim_request = || {
+            test::TestRequest::post()
+                .uri("/add-sim")
+                .set_json(serde_json::json!({
+                    "counting_strategy": "HiLo",
+                    "decision_strategy": "Basic Strategy",
+                    "betting_strategy": "Margin",
+                    "betting_margin": 2.0
+                }))
+                .to_request()
+        };
+        assert!(test::call_service(&app, add_sim_request())
+            .await
+            .status()
+            .is_success());
+        let req = test::TestRequest::post().uri("/run-sim").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let id = body["job_id"].as_u64().unwrap();
+
+        // The simulations of a rejected run stay configured
+        assert!(test::call_service(&app, add_sim_request())
+            .await
+            .status()
+            .is_success());
+        let req = test::TestRequest::post().uri("/run-sim").to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        let req = test::TestRequest::get().uri("/simulations").to_request();
+        let simulations: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(simulations["simulations"].as_array().unwrap().len(), 1);
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/jobs/{}/cancel", id))
+            .to_request();
+        test::call_service(&app, req).await;
+        assert_eq!(wait_for_job!(app, id)["status"], "cancelled");
+        let req = test::TestRequest::post().uri("/run-sim").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let id = body["job_id"].as_u64().unwrap();
+        let req = test::TestRequest::post()
+            .uri(&format!("/jobs/{}/cancel", id))
+            .to_request();
+        test::call_service(&app, req).await;
+        assert_eq!(wait_for_job!(app, id)["status"], "cancelled");
+    }
+
+    #[actix_web::test]
+    async fn the_oldest_finished_jobs_are_evicted() {
+        let app = init_app!(limits: ServerLimits {
+            max_finished_jobs: 1,
+            ..ServerLimits::default()
+        });
+        let mut ids = vec![];
+        for _ in 0..3 {
+            let id = start_run!(app, 2.0);
+            assert_eq!(wait_for_job!(app, id)["status"], "finished");
+            ids.push(id);
+        }
+
+        // Starting the third run evicted the first, the last two runs are kept until the next run starts
+        let status = |id: u64| {
+            test::TestRequest::get()
+                .uri(&format!("/jobs/{}", id))
+                .to_request()
+        };
+        assert_eq!(
+            test::call_service(&app, status(ids[0])).await.status(),
+            StatusCode::NOT_FOUND
+        );
+        for &id in &ids[1..] {
+            assert_eq!(
+                test::call_service(&app, status(id)).await.status(),
+                StatusCode::OK
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn sweep_returns_a_labelled_summary_per_value() {
+        let app = init_app!();
+        let req = test::TestRequest::post()
+            .uri("/sweep")
+            .set_json(serde_json::json!({
+                "game_config": {
+                    "player_starting_balance": 500.0,
+                    "num_simulations": 2,
+                    "num_decks": 6,
+                    "hands_per_simulation": 100,
+                    "min_bet": 5,
+                    "surrender": true,
+                    "seed": 7
+                },
+                "sim_config": {
+                    "counting_strategy": "HiLo",
+                    "decision_strategy": "Basic Strategy",
+                    "betting_strategy": "Margin",
+                    "betting_margin": 2.0
+                },
+                "sweep": { "parameter": "betting_margin", "values": [1.0, 2.0, 3.0] }
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["parameter"], "betting_margin");
+        let summaries = body["summaries"].as_object().unwrap();
+        assert_eq!(summaries.len(), 3);
+        for value in ["1", "2", "3"] {
+            assert_eq!(
+                summaries[value]["strategy"],
+                format!("HiLo (betting_margin = {})", value)
+            );
+        }
+    }
+
+    macro_rules! add_ramp_simulation {
+        ($app:expr, $ramp:expr) => {{
+            let resp = configure!(
+                $app,
+                serde_json::json!({
+                    "player_starting_balance": 500.0,
+                    "num_simulations": 2,
+                    "num_decks": 6,
+                    "hands_per_simulation": 100,
+                    "min_bet": 5,
+                    "surrender": true,
+                    "seed": 7
+                })
+            );
+            assert_eq!(resp.status(), StatusCode::OK);
+            let req = test::TestRequest::post()
+                .uri("/add-sim")
+                .set_json(serde_json::json!({
+                    "counting_strategy": "HiLo",
+                    "decision_strategy": "Basic Strategy",
+                    "betting_strategy": "Ramp",
+                    "betting_margin": 0.0,
+                    "unit": 10,
+                    "ramp": $ramp
+                }))
+                .to_request();
+            test::call_service(&$app, req).await
+        }};
+    }
+
+    #[actix_web::test]
+    async fn ramp_simulation_runs() {
+        let app = init_app!();
+        let resp = add_ramp_simulation!(
+            app,
+            serde_json::json!([
+                { "true_count": 1.0, "units": 2 },
+                { "true_count": 2.0, "units": 4 },
+                { "true_count": 4.0, "units": 8 }
+            ])
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = test::TestRequest::post().uri("/run-sim").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let status = wait_for_job!(app, body["job_id"]);
+        assert_eq!(status["status"], "finished");
+    }
+
+    #[actix_web::test]
+    async fn invalid_ramps_are_rejected() {
+        let app = init_app!();
+        for (ramp, constraint) in [
+            (serde_json::json!([]), "must not be empty"),
+            (
+                serde_json::json!([
+                    { "true_count": 3.0, "units": 4 },
+                    { "true_count": 1.0, "units": 2 }
+                ]),
+                "must be sorted by strictly increasing true count",
+            ),
+        ] {
+            let resp = add_ramp_simulation!(app, ramp);
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            let body: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(
+                body["errors"],
+                serde_json::json!([{ "field": "ramp", "constraint": constraint }])
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn sweep_runs_each_penetration() {
+        let app = init_app!();
+        let sweep_request = |values: serde_json::Value| {
+            test::TestRequest::post()
+                .uri("/sweep")
+                .set_json(serde_json::json!({
+                    "game_config": {
+                        "player_starting_balance": 500.0,
+                        "num_simulations": 2,
+                        "num_decks": 1,
+                        "hands_per_simulation": 100,
+                        "min_bet": 5,
+                        "surrender": true,
+                        "seed": 7
+                    },
+                    "sim_config": {
+                        "counting_strategy": "HiLo",
+                        "decision_strategy": "Basic Strategy",
+                        "betting_strategy": "Margin",
+                        "betting_margin": 2.0
+                    },
+                    "sweep": { "parameter": "penetration", "values": values }
+                }))
+                .to_request()
+        };
+        let resp =
+            test::call_service(&app, sweep_request(serde_json::json!([0.5, 0.65, 0.8]))).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let summaries = body["summaries"].as_object().unwrap();
+        assert_eq!(summaries.len(), 3);
+        assert_eq!(summaries["0.65"]["strategy"], "HiLo (penetration = 0.65)");
+
+        let resp = test::call_service(&app, sweep_request(serde_json::json!([0.5, 1.0]))).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn custom_deviations_change_the_results() {
+        let mut winnings = vec![];
+        for deviations in [
+            serde_json::Value::Null,
+            serde_json::json!([{
+                "hand_total": 16,
+                "dealer_up_card": 10,
+                "comparison": ">=",
+                "threshold": -100.0,
+                "action": "stand"
+            }]),
+        ] {
+            let app = init_app!();
+            let resp = configure!(
+                app,
+                serde_json::json!({
+                    "player_starting_balance": 500.0,
+                    "num_simulations": 2,
+                    "num_decks": 6,
+                    "hands_per_simulation": 500,
+                    "min_bet": 5,
+                    "surrender": false,
+                    "seed": 11
+                })
+            );
+            assert_eq!(resp.status(), StatusCode::OK);
+            let req = test::TestRequest::post()
+                .uri("/add-sim")
+                .set_json(serde_json::json!({
+                    "counting_strategy": "HiLo",
+                    "decision_strategy": "Basic Strategy",
+                    "betting_strategy": "Margin",
+                    "betting_margin": 2.0,
+                    "deviations": deviations
+                }))
+                .to_request();
+            assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+            let req = test::TestRequest::post().uri("/run-sim").to_request();
+            let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+            let status = wait_for_job!(app, body["job_id"]);
+            assert_eq!(status["status"], "finished");
+            let req = test::TestRequest::get()
+                .uri(&format!("/jobs/{}/result", body["job_id"]))
+                .to_request();
+            let result: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+            winnings.push(result[0].clone());
+        }
+        // Standing on every 16 against a 10 plays different hands from the same shoes
+        assert_ne!(winnings[0], winnings[1]);
+    }
+
+    #[actix_web::test]
+    async fn invalid_deviations_are_rejected() {
+        let app = init_app!();
+        let resp = configure!(
+            app,
+            serde_json::json!({
+                "player_starting_balance": 500.0,
+                "num_simulations": 2,
+                "num_decks": 6,
+                "hands_per_simulation": 100,
+                "min_bet": 5,
+                "surrender": true
+            })
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+        let req = test::TestRequest::post()
+            .uri("/add-sim")
+            .set_json(serde_json::json!({
+                "counting_strategy": "HiLo",
+                "decision_strategy": "Basic Strategy",
+                "betting_strategy": "Margin",
+                "betting_margin": 2.0,
+                "deviations": [{
+                    "hand_total": 30,
+                    "dealer_up_card": 10,
+                    "comparison": ">=",
+                    "threshold": 0.0,
+                    "action": "fold"
+                }]
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let fields = body["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|err| err["field"].as_str().unwrap())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            fields,
+            vec!["deviations[0].action", "deviations[0].hand_total"]
+        );
+    }
+
+    #[actix_web::test]
+    async fn delete_removes_the_simulation() {
+        let app = init_app!();
+        let req = test::TestRequest::post()
+            .uri("/config-game-params")
+            .set_json(serde_json::json!({
+                "player_starting_balance": 500.0,
+                "num_simulations": 2,
+                "num_decks": 6,
+                "hands_per_simulation": 200,
+                "min_bet": 5,
+                "surrender": true
+            }))
+            .to_request();
+        assert!(test::call_service(&app, req).await.status().is_success());
+        for counting_strategy in ["HiLo", "KO", "Zen Count"] {
+            let req = test::TestRequest::post()
+                .uri("/add-sim")
+                .set_json(serde_json::json!({
+                    "counting_strategy": counting_strategy,
+                    "decision_strategy": "Basic Strategy",
+                    "betting_strategy": "Margin",
+                    "betting_margin": 2.0
+                }))
+                .to_request();
+            assert!(test::call_service(&app, req).await.status().is_success());
+        }
+
+        let req = test::TestRequest::delete()
+            .uri("/simulations/1")
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "removed simulation 1 (KO)");
+
+        // Only two simulations are left, so index 2 is out of range
+        let req = test::TestRequest::delete()
+            .uri("/simulations/2")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let req = test::TestRequest::get().uri("/simulations").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["simulations"].as_array().unwrap().len(), 2);
+        assert_eq!(body["simulations"][1]["counting_strategy"], "Zen Count");
+    }
+
+    #[actix_web::test]
+    async fn simulations_lists_configured_simulations() {
+        let app = init_app!();
+        let req = test::TestRequest::post()
+            .uri("/config-game-params")
+            .set_json(serde_json::json!({
+                "player_starting_balance": 500.0,
+                "num_simulations": 2,
+                "num_decks": 6,
+                "hands_per_simulation": 200,
+                "min_bet": 5,
+                "surrender": true,
+                "seed": 3
+            }))
+            .to_request();
+        assert!(test::call_service(&app, req).await.status().is_success());
+        let req = test::TestRequest::post()
+            .uri("/add-sim")
+            .set_json(serde_json::json!({
+                "counting_strategy": "Wong Halves",
+                "decision_strategy": "S17 Deviations",
+                "betting_strategy": "Margin",
+                "betting_margin": 2.0
+            }))
+            .to_request();
+        assert!(test::call_service(&app, req).await.status().is_success());
+
+        let req = test::TestRequest::get().uri("/simulations").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(
+            body["simulations"],
+            serde_json::json!([{
+                "index": 0,
+                "counting_strategy": "Wong Halves",
+                "decision_strategy": "S17 Deviations",
+                "betting_strategy": "Margin"
+            }])
+        );
+        assert_eq!(body["config"]["num_decks"], 6);
+        assert_eq!(body["config"]["seed"], 3);
+    }
+
+    #[actix_web::test]
+    async fn saved_config_can_be_loaded_and_run() {
+        let config_dir =
+            std::env::temp_dir().join(format!("blackjack_sim_configs_{}", std::process::id()));
+        let app = init_app!(config_dir.clone());
+        let resp = configure!(
+            app,
+            serde_json::json!({
+                "player_starting_balance": 500.0,
+                "num_simulations": 2,
+                "num_decks": 6,
+                "hands_per_simulation": 100,
+                "min_bet": 5,
+                "surrender": true,
+                "seed": 5
+            })
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+        let req = test::TestRequest::post()
+            .uri("/add-sim")
+            .set_json(serde_json::json!({
+                "counting_strategy": "KO",
+                "decision_strategy": "Basic Strategy",
+                "betting_strategy": "Margin",
+                "betting_margin": 2.0
+            }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+        let save = || {
+            test::TestRequest::post()
+                .uri("/configs")
+                .set_json(serde_json::json!({ "name": "ko-setup" }))
+                .to_request()
+        };
+        assert_eq!(
+            test::call_service(&app, save()).await.status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            test::call_service(&app, save()).await.status(),
+            StatusCode::CONFLICT
+        );
+        let req = test::TestRequest::get().uri("/configs").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["configs"], serde_json::json!(["ko-setup"]));
+
+        // Replace the setup, then restore the saved one
+        let resp = configure!(
+            app,
+            serde_json::json!({
+                "player_starting_balance": 100.0,
+                "num_simulations": 1,
+                "num_decks": 2,
+                "hands_per_simulation": 10,
+                "min_bet": 5,
+                "surrender": false
+            })
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+        let req = test::TestRequest::post()
+            .uri("/configs/ko-setup/load")
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+        let req = test::TestRequest::post()
+            .uri("/configs/missing/load")
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let req = test::TestRequest::get().uri("/simulations").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["config"]["num_decks"], 6);
+        assert_eq!(body["simulations"][0]["counting_strategy"], "KO");
+
+        let req = test::TestRequest::post().uri("/run-sim").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let status = wait_for_job!(app, body["job_id"]);
+        assert_eq!(status["status"], "finished");
+
+        fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn add_simulation_is_limited() {
+        let app = init_app!(std::env::temp_dir().join("blackjack_sim_unused_configs"), 3);
+        let resp = configure!(
+            app,
+            serde_json::json!({
+                "player_starting_balance": 500.0,
+                "num_simulations": 2,
+                "num_decks": 6,
+                "hands_per_simulation": 100,
+                "min_bet": 5,
+                "surrender": true
+            })
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let add = || {
+            test::TestRequest::post()
+                .uri("/add-sim")
+                .set_json(serde_json::json!({
+                    "counting_strategy": "HiLo",
+                    "decision_strategy": "Basic Strategy",
+                    "betting_strategy": "Margin",
+                    "betting_margin": 2.0
+                }))
+                .to_request()
+        };
+        for _ in 0..3 {
+            assert_eq!(
+                test::call_service(&app, add()).await.status(),
+                StatusCode::OK
+            );
+        }
+        let resp = test::call_service(&app, add()).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+        let body = test::read_body(resp).await;
+        assert!(std::str::from_utf8(&body).unwrap().contains("at most 3"));
+    }
+
+    /// Helper function that builds the summary of playing `rounds`, given as the amount bet and won each round.
+    fn summary_of_rounds(rounds: &[(f32, f32)]) -> SimulationSummary {
+        let winnings = rounds.iter().map(|&(_, won)| won).sum::<f32>();
+        let mean = winnings as f64 / rounds.len() as f64;
+        SimulationSummary {
+            wins: rounds.iter().filter(|&&(_, won)| won > 0.0).count() as i32,
+            pushes: rounds.iter().filter(|&&(_, won)| won == 0.0).count() as i32,
+            losses: rounds.iter().filter(|&&(_, won)| won < 0.0).count() as i32,
+            early_endings: 0,
+            winnings: Money::from_f32(winnings),
+            num_hands: rounds.len() as u32,
+            player_blackjacks: 0,
+            label: Arc::from("HiLo"),
+            strategy: Arc::from("HiLo"),
+            common_random_numbers: false,
+            rounds: rounds.len() as u32,
+            winnings_m2: rounds
+                .iter()
+                .map(|&(_, won)| (won as f64 - mean).powi(2))
+                .sum(),
+            total_wagered: rounds.iter().map(|&(bet, _)| Money::from_f32(bet)).sum(),
+            max_drawdown: rounds
+                .iter()
+                .map(|&(bet, _)| Money::from_f32(bet))
+                .fold(Money::ZERO, Money::max),
+            insurance_bets: 0,
+            insurance_winnings: Money::ZERO,
+            min_rounds_to_ruin: None,
+            max_rounds_to_ruin: None,
+            total_rounds_to_ruin: 0,
+        }
+    }
+
+    #[actix_web::test]
+    async fn summary_json_statistics_match_a_single_threaded_reference() {
+        let rounds = (0..120)
+            .map(|i| {
+                let bet = (10 * (1 + i % 3)) as f32;
+                let won = match i % 5 {
+                    0 => 1.5 * bet,
+                    1 | 4 => -bet,
+                    2 => bet,
+                    _ => -2.0 * bet,
+                };
+                (bet, won)
+            })
+            .collect::<Vec<(f32, f32)>>();
+
+        // The reference is computed in a single pass over every round
+        let n = rounds.len() as f64;
+        let winnings = rounds.iter().map(|&(_, won)| won as f64).sum::<f64>();
+        let wagered = rounds.iter().map(|&(bet, _)| bet as f64).sum::<f64>();
+        let mean = winnings / n;
+        let variance = rounds
+            .iter()
+            .map(|&(_, won)| (won as f64 - mean).powi(2))
+            .sum::<f64>()
+            / (n - 1.0);
+
+        // The summaries are accumulated chunk by chunk, like the summaries of a run are
+        let mut chunks = rounds.chunks(7);
+        let mut accumulated = summary_of_rounds(chunks.next().unwrap());
+        for chunk in chunks {
+            accumulated += &summary_of_rounds(chunk);
+        }
+        let json = write::format_summary_as_json(1, &accumulated);
+
+        let field = |name: &str| {
+            json.get(name)
+                .and_then(|value| value.as_f64())
+                .unwrap_or_else(|| panic!("summary is missing {}", name))
+        };
+        let close = |actual: f64, expected: f64| {
+            (actual - expected).abs() <= 1e-4 * expected.abs().max(1.0)
+        };
+        assert!(close(field("total_wagered"), wagered));
+        assert!(close(field("edge"), winnings / wagered));
+        assert!(close(field("std_dev_per_round"), variance.sqrt()));
+        assert!(close(field("max_drawdown"), 30.0));
+        assert!(close(field("n0"), variance / (mean * mean)));
+        assert!(close(field("avg_winnings_per_hand"), mean));
+    }
+
+    #[actix_web::test]
+    async fn summary_json_leaves_undefined_statistics_null() {
+        let json = write::format_summary_as_json(1, &summary_of_rounds(&[(10.0, 10.0)]));
+        assert!(json["std_dev_per_round"].is_null());
+        assert!(json["n0"].is_null());
+        assert_eq!(json["edge"], serde_json::json!(1.0));
+    }
+
+    #[actix_web::test]
+    async fn reset_while_idle_clears_the_simulator_and_jobs() {
+        let app = init_app!();
+        let id = start_run!(app, 2.0);
+        assert_eq!(wait_for_job!(app, id)["status"], "finished");
+
+        let req = test::TestRequest::post().uri("/reset").to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/jobs/{}", id))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+        let req = test::TestRequest::get().uri("/simulations").to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[actix_web::test]
+    async fn reset_while_running_requires_force() {
+        let app = init_app!();
+        let resp = configure!(
+            app,
+            serde_json::json!({
+                "player_starting_balance": 500.0,
+                "num_simulations": 1_000_000,
+                "num_decks": 6,
+                "hands_per_simulation": 200,
+                "min_bet": 5,
+                "surrender": true
+            })
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+        let req = test::TestRequest::post()
+            .uri("/add-sim")
+            .set_json(serde_json::json!({
+                "counting_strategy": "HiLo",
+                "decision_strategy": "Basic Strategy",
+                "betting_strategy": "Margin",
+                "betting_margin": 2.0
+            }))
+            .to_request();
+        assert!(test::call_service(&app, req).await.status().is_success());
+        let req = test::TestRequest::post().uri("/run-sim").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let id = body["job_id"].as_u64().unwrap();
+
+        let req = test::TestRequest::post().uri("/reset").to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::CONFLICT
+        );
+        let req = test::TestRequest::get()
+            .uri(&format!("/jobs/{}", id))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = test::TestRequest::post()
+            .uri("/reset?force=true")
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+        let req = test::TestRequest::get()
+            .uri(&format!("/jobs/{}", id))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[actix_web::test]
+    async fn config_is_not_found_before_configuring() {
+        let app = init_app!();
+        let req = test::TestRequest::get().uri("/config").to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[actix_web::test]
+    async fn config_includes_defaulted_fields() {
+        let app = init_app!();
+        let resp = configure!(
+            app,
+            serde_json::json!({
+                "player_starting_balance": 500.0,
+                "num_simulations": 2,
+                "num_decks": 6,
+                "hands_per_simulation": 100,
+                "min_bet": 5,
+                "surrender": true
+            })
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+        let req = test::TestRequest::post()
+            .uri("/add-sim")
+            .set_json(serde_json::json!({
+                "counting_strategy": "HiLo",
+                "decision_strategy": "Basic Strategy",
+                "betting_strategy": "Margin",
+                "betting_margin": 2.0
+            }))
+            .to_request();
+        assert!(test::call_service(&app, req).await.status().is_success());
+
+        let req = test::TestRequest::get().uri("/config").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["simulations_added"], 1);
+        assert_eq!(body["config"]["num_decks"], 6);
+        assert_eq!(body["config"]["min_bet"], 5);
+        assert_eq!(body["config"]["surrender"], true);
+        assert_eq!(body["config"]["soft_seventeen"], false);
+        assert_eq!(body["config"]["insurance"], false);
+        assert!(body["config"]["table_starting_balance"].is_null());
+        assert!(body["config"].get("hand_log_buffer_size").is_some());
+    }
+
+    #[actix_web::test]
+    async fn failed_job_names_the_failing_strategy() {
+        let config = BlackjackSimulatorConfig::new()
+            .num_simulations(2)
+            .min_bet(5)
+            .build();
+        let mut simulator = MulStrategyBlackjackSimulator::new(config).build();
+        // Bets a single unit of 1 while the count is neutral, below the table minimum
+        simulator
+            .add_simulation(PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 1),
+            ))
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(Mutex::new(Some(simulator))))
+                .app_data(web::Data::new(Jobs::new(HashMap::new())))
+                .app_data(web::Data::new(Mutex::new(ResultCache::default())))
+                .app_data(web::Data::new(SimConfigs::new(vec![])))
+                .app_data(web::Data::new(ServerLimits::default()))
+                .service(run_simulation)
+                .service(job_status)
+                .service(job_result),
+        )
+        .await;
+
+        let req = test::TestRequest::post().uri("/run-sim").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let id = body["job_id"].as_u64().unwrap();
+        let status = wait_for_job!(app, id);
+        assert_eq!(status["status"], "failed");
+        assert_eq!(status["failure"]["strategy"], "HiLo");
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/jobs/{}/result", id))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["kind"], "game_error");
+        assert_eq!(body["strategy_id"], 1);
+        assert_eq!(body["strategy"], "HiLo");
+        assert_eq!(
+            body["message"],
+            "player tried to bet less than table minimum"
+        );
+    }
+
+    #[actix_web::test]
+    async fn latest_result_is_served_from_the_cache() {
+        let cache = web::Data::new(Mutex::new(ResultCache::default()));
+        let app = init_app!(
+            std::env::temp_dir().join("blackjack_sim_unused_configs"),
+            DEFAULT_MAX_SIMULATIONS,
+            cache
+        );
+        let req = test::TestRequest::get().uri("/results/latest").to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let id = start_run!(app, 2.0);
+        assert_eq!(wait_for_job!(app, id)["status"], "finished");
+
+        let mut responses = vec![];
+        for _ in 0..2 {
+            let req = test::TestRequest::get().uri("/results/latest").to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            let etag = resp.headers().get(header::ETAG).unwrap().clone();
+            assert!(resp.headers().contains_key("X-Completed-At"));
+            responses.push((etag, test::read_body(resp).await));
+        }
+        assert_eq!(responses[0], responses[1]);
+        assert_eq!(cache.lock().unwrap().runs_completed, 1);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/jobs/{}/result", id))
+            .to_request();
+        let result = test::call_and_read_body(&app, req).await;
+        assert_eq!(responses[0].1, result);
+
+        let req = test::TestRequest::get()
+            .uri("/results/latest")
+            .insert_header((header::IF_NONE_MATCH, responses[0].0.clone()))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_MODIFIED
+        );
+    }
+
+    #[actix_web::test]
+    async fn configure_reports_the_generated_seed() {
+        let app = init_app!();
+        let resp = configure!(
+            app,
+            serde_json::json!({
+                "player_starting_balance": 500.0,
+                "num_simulations": 2,
+                "num_decks": 6,
+                "hands_per_simulation": 100,
+                "min_bet": 5,
+                "surrender": true
+            })
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let seed = body["seed"].as_u64().expect("a seed is generated");
+
+        let req = test::TestRequest::get().uri("/config").to_request();
+        let config: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(config["config"]["seed"], seed);
+    }
+
+    #[actix_web::test]
+    async fn seeded_runs_produce_identical_results() {
+        let mut results = vec![];
+        for _ in 0..2 {
+            let app = init_app!();
+            let resp = configure!(
+                app,
+                serde_json::json!({
+                    "player_starting_balance": 500.0,
+                    "num_simulations": 3,
+                    "num_decks": 6,
+                    "hands_per_simulation": 200,
+                    "min_bet": 5,
+                    "surrender": true,
+                    "seed": 42
+                })
+            );
+            let body: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(body["seed"], 42);
+            for counting_strategy in ["HiLo", "KO"] {
+                let req = test::TestRequest::post()
+                    .uri("/add-sim")
+                    .set_json(serde_json::json!({
+                        "counting_strategy": counting_strategy,
+                        "decision_strategy": "Basic Strategy",
+                        "betting_strategy": "Margin",
+                        "betting_margin": 2.0
+                    }))
+                    .to_request();
+                assert!(test::call_service(&app, req).await.status().is_success());
+            }
+            let req = test::TestRequest::post().uri("/run-sim").to_request();
+            let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+            let id = body["job_id"].as_u64().unwrap();
+            assert_eq!(wait_for_job!(app, id)["status"], "finished");
+
+            let req = test::TestRequest::get()
+                .uri(&format!("/jobs/{}/result", id))
+                .to_request();
+            results.push(test::call_and_read_body(&app, req).await);
+        }
+        assert_eq!(results[0], results[1]);
+    }
+
+    #[actix_web::test]
+    async fn strategies_lists_known_names() {
+        let app = test::init_service(App::new().service(list_strategies)).await;
+        let req = test::TestRequest::get().uri("/strategies").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        let names = |key: &str| {
+            body[key]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|strategy| strategy["name"].as_str().unwrap().to_string())
+                .collect::<Vec<String>>()
+        };
+        let counting_strategies = names("counting_strategies");
+        assert!(counting_strategies.contains(&"HiLo".to_string()));
+        assert!(counting_strategies.contains(&"Wong Halves".to_string()));
+        assert!(names("decision_strategies").contains(&"S17 Deviations".to_string()));
+        assert!(names("betting_strategies").contains(&"Margin".to_string()));
+        assert_eq!(body["counting_strategies"][0]["balanced"], true);
+        assert_eq!(body["betting_strategies"][0]["needs_margin"], true);
+    }
+
+    #[actix_web::test]
+    async fn strategy_factories_accept_every_registered_name() {
+        for descriptor in registry::COUNTING_STRATEGIES {
+            let label = (descriptor.constructor)(6).name();
+            for name in [descriptor.name, label.as_str()]
+                .into_iter()
+                .chain(descriptor.aliases.iter().copied())
+            {
+                let strategy =
+                    create_counting_strategy(name, 6).unwrap_or_else(|e| panic!("{}: {}", name, e));
+                assert_eq!(strategy.name(), label);
+            }
+        }
+        for descriptor in registry::DECISION_STRATEGIES {
+            for &name in std::iter::once(&descriptor.name).chain(descriptor.aliases) {
+                let strategy = create_decision_strategy(name, false, None).unwrap();
+                assert_eq!(strategy.name(), descriptor.name);
+            }
+        }
+
+        // The command line spellings are accepted as well
+        assert!(create_counting_strategy("AceFive", 6).is_ok());
+        assert!(create_counting_strategy("wong halves", 6).is_ok());
+        assert_eq!(
+            create_betting_strategy("flat", 2.0, 5, None)
+                .unwrap()
+                .name(),
+            "Flat"
+        );
+        assert_eq!(
+            create_betting_strategy("ramp", 2.0, 5, None).err(),
+            Some("betting strategy Ramp requires a ramp")
+        );
+    }
 }
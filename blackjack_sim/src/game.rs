@@ -5,19 +5,23 @@ pub mod player;
 pub mod strategy;
 pub mod table;
 pub mod prelude {
-    pub use super::BlackjackGameSim;
+    pub use super::{BlackjackGameSim, HandHistory};
     pub use crate::game::player::PlayerSim;
     pub use crate::game::strategy;
-    pub use crate::game::table::BlackjackTableSim;
+    pub use crate::game::table::{BlackjackTableSim, Recipient, TableRules, TraceEvent};
+    pub use crate::money::Money;
     pub use blackjack_lib::{BlackjackGameError, BlackjackTable, Card, Player, RANKS, SUITS};
     pub use std::io::{self, Write};
     // pub use BlackjackGameSim;
 }
 
+use crate::game::player::HandResult;
+use crate::write::{HandLogger, HandRecord};
 pub use prelude::*;
-use rand::{self, Rng};
+use rand::{self, rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use strategy::Strategy;
+use strategy::{Action, Strategy};
 
 use self::strategy::{BettingStrategy, CountingStrategy, DecisionStrategy};
 
@@ -28,6 +32,7 @@ pub struct DeckSim {
     deck_pos: usize,
     shuffle_flag_pos: usize,
     pub shuffle_flag: bool,
+    rng: StdRng,
 }
 
 /// A struct to represent a deck of cards, is basically a collection of card structs that implements some specific logic related to a game of blackjack
@@ -45,12 +50,16 @@ impl DeckSim {
         cards
     }
 
-    /// Creates and returns a new Deck struct
-    pub fn new(n_decks: usize) -> DeckSim {
+    /// Creates and returns a new Deck struct. If `seed` is given the deck is shuffled with a random number generator seeded from it,
+    /// so the same seed always produces the same sequence of shuffles.
+    pub fn new(n_decks: usize, seed: Option<u64>) -> DeckSim {
         assert!(n_decks > 0, "Cannot have a deck with zero cards");
         let cards = Self::build_card_deck(n_decks);
-        let n_cards = cards.len();
-        let shuffle_flag_pos = f32::floor(((n_cards - 1) as f32) * 0.8) as usize;
+        let shuffle_flag_pos = Self::cut_card_pos(cards.len(), 0.8);
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
 
         DeckSim {
             cards,
@@ -58,23 +67,47 @@ impl DeckSim {
             deck_pos: 0,
             shuffle_flag_pos,
             shuffle_flag: true,
+            rng,
         }
     }
 
-    /// Shuffles the deck of cards to simulate the random behavior of a shuffled deck of cards
-    pub fn shuffle(&mut self, n_shuffles: u32) {
-        assert!(n_shuffles > 0);
-        let mut rng = rand::thread_rng();
-        for _i in 0..n_shuffles {
-            for j in 0..self.cards.len() {
-                let random_idx = rng.gen_range(0..self.cards.len());
-                self.cards.swap(j, random_idx);
-            }
-        }
+    /// Helper function that returns the position of the cut card of a shoe of `n_cards` cards, i.e. how many cards are dealt
+    /// before the shoe is reshuffled. At least one card is dealt, and enough cards are left behind the cut card to finish a round.
+    fn cut_card_pos(n_cards: usize, penetration: f32) -> usize {
+        const MIN_CARDS_BEHIND_CUT: usize = 10;
+        let pos = f32::floor(((n_cards - 1) as f32) * penetration) as usize;
+        pos.min(n_cards - MIN_CARDS_BEHIND_CUT).max(1)
+    }
+
+    /// Sets the fraction of the shoe that is dealt before it is reshuffled, `penetration` must be between 0 and 1.
+    pub fn set_penetration(&mut self, penetration: f32) {
+        self.shuffle_flag_pos = Self::cut_card_pos(self.cards.len(), penetration);
+    }
+
+    /// Shuffles the deck of cards to simulate the random behavior of a shuffled deck of cards.
+    /// A single Fisher-Yates pass already produces a uniformly random ordering, so `n_shuffles` is ignored
+    /// and is only kept for compatibility.
+    pub fn shuffle(&mut self, _n_shuffles: u32) {
+        self.cards.shuffle(&mut self.rng);
         self.deck_pos = 0;
         self.shuffle_flag = false;
     }
 
+    /// Reseeds the random number generator used for shuffling and marks the deck to be shuffled before the next hand,
+    /// so the shoes dealt from this point on depend only on `seed`. Shuffling permutes the cards in whatever order they are in,
+    /// so the cards are put back in their original order as well, otherwise the next shoe would depend on every earlier shuffle.
+    pub fn reseed(&mut self, seed: u64) {
+        self.cards = Self::build_card_deck(self.n_decks);
+        self.deck_pos = 0;
+        self.rng = StdRng::seed_from_u64(seed);
+        self.shuffle_flag = true;
+    }
+
+    /// Returns the number of cards left to deal before the deck runs out.
+    pub fn cards_remaining(&self) -> u32 {
+        (self.cards.len() - self.deck_pos) as u32
+    }
+
     /// Returns the next card, i.e. the card that is at the top of the deck of cards
     pub fn get_next_card(&mut self) -> Option<Arc<Card>> {
         if self.deck_pos < self.cards.len() {
@@ -90,6 +123,37 @@ impl DeckSim {
     }
 }
 
+/// Records when the player of a simulation was ruined, i.e. could no longer afford the minimum bet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ruin {
+    /// The index of the round the player could not bet on, i.e. the number of rounds played before the player was ruined
+    pub round: u32,
+    /// The player's balance when they were ruined
+    pub balance: Money,
+}
+
+/// The history of a single round, handed to the observer set with `BlackjackGameSim::set_hand_observer()` once the round is resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandHistory {
+    /// The two cards the player was dealt
+    pub initial_cards: Vec<Arc<Card>>,
+    /// The dealer's face up card
+    pub dealer_up: Arc<Card>,
+    /// The actions the player played, in order across every hand of a split
+    pub actions: Vec<Action>,
+    /// The best value of each of the player's hands, in the order they were played
+    pub final_values: Vec<u8>,
+    /// The initial bet of the round
+    pub bet: u32,
+    /// The result of each of the player's hands
+    pub results: Vec<HandResult>,
+    /// The player's true count when the bet was placed
+    pub true_count_at_bet: f32,
+}
+
+/// A callback that is handed the history of every round played, see `BlackjackGameSim::set_hand_observer()`.
+pub type HandObserver = Box<dyn FnMut(&HandHistory) + Send>;
+
 /// Struct that provides the functionality to simulate a game of blackjack using a specific counting strategy.
 /// This struct saves all of the necessary data for reporting/logging the stats of the simulation as well.
 pub struct BlackjackGameSim<S: Strategy> {
@@ -100,9 +164,30 @@ pub struct BlackjackGameSim<S: Strategy> {
     pub total_wins: i32,
     pub total_pushes: i32,
     pub total_losses: i32,
-    pub total_winnings: f32,
+    pub total_winnings: Money,
     pub num_player_blackjacks: i32,
+    /// The number of insurance bets taken
+    pub insurance_bets: i32,
+    /// The net winnings of the insurance bets taken, included in `total_winnings`
+    pub insurance_winnings: Money,
     pub ended_early: bool,
+    /// When the player was ruined, `None` unless the simulation ended early
+    pub ruin: Option<Ruin>,
+    /// The number of rounds played, i.e. the number of initial bets placed
+    pub rounds: u32,
+    /// The number of hands played, i.e. the hands that had a bet resolved. Every hand of a split counts separately
+    pub hands_played: u32,
+    /// The sum of squared deviations of the winnings of each round from their mean
+    pub winnings_m2: f64,
+    /// The total amount bet, including doubles and splits
+    pub total_wagered: Money,
+    /// The largest drop of the player's balance from its highest point
+    pub max_drawdown: Money,
+    cancel_flag: Option<Arc<AtomicBool>>,
+    hands_counter: Option<Arc<AtomicU64>>,
+    hand_logger: Option<(HandLogger, usize)>,
+    hand_observer: Option<HandObserver>,
+    games_played: u64,
 }
 
 impl<S: Strategy> BlackjackGameSim<S> {
@@ -126,23 +211,84 @@ impl<S: Strategy> BlackjackGameSim<S> {
             total_wins: 0,
             total_pushes: 0,
             total_losses: 0,
-            total_winnings: 0.0,
+            total_winnings: Money::ZERO,
             num_player_blackjacks: 0,
+            insurance_bets: 0,
+            insurance_winnings: Money::ZERO,
             ended_early: false,
+            ruin: None,
+            rounds: 0,
+            hands_played: 0,
+            winnings_m2: 0.0,
+            total_wagered: Money::ZERO,
+            max_drawdown: Money::ZERO,
+            cancel_flag: None,
+            hands_counter: None,
+            hand_logger: None,
+            hand_observer: None,
+            games_played: 0,
         }
     }
 
+    /// Sets a `HandLogger` that every hand played is streamed to, tagged with `strategy_id`.
+    /// Logging blocks the game loop whenever the logger's buffer is full.
+    pub fn set_hand_logger(&mut self, hand_logger: HandLogger, strategy_id: usize) {
+        self.hand_logger = Some((hand_logger, strategy_id));
+    }
+
+    /// Sets an observer that is handed the `HandHistory` of every round once it is resolved. The history is only built while an observer is set.
+    pub fn set_hand_observer(&mut self, hand_observer: HandObserver) {
+        self.hand_observer = Some(hand_observer);
+    }
+
+    /// Sets a shared cancellation flag, once the flag is set `self.run()` stops before dealing the next hand.
+    pub fn set_cancel_flag(&mut self, cancel_flag: Arc<AtomicBool>) {
+        self.cancel_flag = Some(cancel_flag);
+    }
+
+    /// Sets a shared counter that is incremented after every round played, so the progress of `self.run()` can be read from another thread.
+    pub fn set_hands_counter(&mut self, hands_counter: Arc<AtomicU64>) {
+        self.hands_counter = Some(hands_counter);
+    }
+
+    /// Helper method that ends the simulation early in round `round`, recording that the player was ruined.
+    fn end_early(&mut self, round: u32) {
+        self.ended_early = true;
+        self.ruin = Some(Ruin {
+            round,
+            balance: self.player.balance(),
+        });
+    }
+
+    /// Returns true if a cancellation flag has been set and cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+
     /// Method that runs the blackjack simulation the number of times specified during object creation.
     pub fn run(&mut self) -> Result<(), BlackjackGameError> {
-        for _i in 0..self.num_hands {
-            // Check if player can continue
+        let simulation = self.games_played;
+        self.games_played += 1;
+        let mut peak_balance = self.player.balance();
+        for i in 0..self.num_hands {
+            // Stop early if the simulation was cancelled, the caller is responsible for discarding the partial results
+            if self.is_cancelled() {
+                break;
+            }
+            // Check if player can continue, going broke ends the simulation early but is not an error
             if !self.player.continue_play(self.min_bet) {
-                self.ended_early = true;
+                self.end_early(i);
                 break;
             }
+            // The count is only needed for the history handed to the observer
+            let true_count_at_bet = self.hand_observer.as_ref().map(|_| self.player.counts().1);
             // Get bet from player
             let bet = match self.player.bet() {
                 Ok(b) if b >= self.min_bet => b,
+                // Betting strategies clamp their bets to the player's balance, and the player can afford the minimum here,
+                // so a bet below the minimum is an error
                 Ok(_) => {
                     // eprintln!("error: player cannot bet less than the minimum of {}", self.min_bet);
                     return Err(BlackjackGameError::new(
@@ -155,19 +301,37 @@ impl<S: Strategy> BlackjackGameSim<S> {
                 }
             };
 
-            // Have player place bet
-            self.player.place_bet(bet as f32);
+            // Have player place bet, the table refuses bets it could not pay out
+            self.table.place_bet(&mut self.player, bet as f32)?;
+            if self.table.is_tracing() {
+                let event = TraceEvent::Bet {
+                    round: i + 1,
+                    bet,
+                    balance: self.player.balance(),
+                };
+                self.table.record(event);
+            }
 
             // Deal hand
             self.table.deal_hand(&mut self.player);
+            // The first hand is complete even if the round was already resolved by a natural
+            let initial_cards = self
+                .hand_observer
+                .as_ref()
+                .map(|_| self.player.hands()[0].clone());
 
             // Let player decide options until they are no longer able to
             while !self.player.turn_is_over() {
                 // Get the chosen option from the player, return if it is an error
                 // let options = self.player.get_playing_options();
-                let decision = self
-                    .player
-                    .decide_option(self.table.dealers_face_up_card())?;
+                let decision = if self.table.is_tracing() {
+                    self.traced_decision()?
+                } else {
+                    self.player.decide_option(
+                        self.table.dealers_face_up_card(),
+                        self.table.cards_remaining(),
+                    )?
+                };
                 // Play the given option, return an error if it fails
                 self.table.play_option(&mut self.player, decision)?;
             }
@@ -175,24 +339,110 @@ impl<S: Strategy> BlackjackGameSim<S> {
             // Finish the hand
             self.table.finish_hand(&mut self.player);
 
-            // Log the data from the game
-            if let Some((wins, pushes, losses, winnings)) = self.table.hand_log {
+            if let (Some(hand_observer), Some(initial_cards), Some(true_count_at_bet)) = (
+                self.hand_observer.as_mut(),
+                initial_cards,
+                true_count_at_bet,
+            ) {
+                let history = HandHistory {
+                    initial_cards,
+                    dealer_up: self.table.dealers_face_up_card(),
+                    actions: self.player.actions().to_vec(),
+                    final_values: self.player.final_values(),
+                    bet,
+                    results: self.player.bets_log.clone(),
+                    true_count_at_bet,
+                };
+                hand_observer(&history);
+            }
+
+            // Log the data from the game, the log is taken so a round without results is never logged as the previous round
+            if let Some((wins, pushes, losses, winnings)) = self.table.hand_log.take() {
+                // Update the running variance of the winnings per round before the winnings are added to the total
+                let mean = if self.rounds == 0 {
+                    0.0
+                } else {
+                    self.total_winnings.to_f64() / self.rounds as f64
+                };
+                self.rounds += 1;
+                let delta = winnings.to_f64() - mean;
+                self.winnings_m2 +=
+                    delta * (winnings.to_f64() - (mean + delta / self.rounds as f64));
+
+                self.hands_played += (wins + pushes + losses) as u32;
                 self.total_wins += wins;
                 self.total_pushes += pushes;
                 self.total_losses += losses;
                 self.total_winnings += winnings;
+
+                // Stream the hand to the hand log if one is set
+                if let Some((ref hand_logger, strategy_id)) = self.hand_logger {
+                    let record = HandRecord {
+                        strategy_id,
+                        simulation,
+                        hand: i,
+                        bet,
+                        wins,
+                        pushes,
+                        losses,
+                        winnings,
+                        balance: self.player.balance(),
+                    };
+                    if let Err(e) = hand_logger.log(record) {
+                        return Err(BlackjackGameError::new(e.to_string()));
+                    }
+                }
             }
 
             self.num_player_blackjacks += self.table.num_player_blackjacks;
+            if let Some(insurance_winnings) = self.table.insurance_winnings {
+                self.insurance_bets += 1;
+                self.insurance_winnings += insurance_winnings;
+            }
+            self.total_wagered += self.player.wagered();
+            peak_balance = peak_balance.max(self.player.balance());
+            self.max_drawdown = self.max_drawdown.max(peak_balance - self.player.balance());
 
             // Reset both player and table for another hand
             self.player.reset();
             self.table.reset();
+            if let Some(ref hands_counter) = self.hands_counter {
+                hands_counter.fetch_add(1, Ordering::Relaxed);
+            }
         }
 
         Ok(())
     }
 
+    /// Helper method that decides the player's option and records the decision along with the reason the strategy gives for it.
+    fn traced_decision(&mut self) -> Result<Action, BlackjackGameError> {
+        let dealers_up_card = self.table.dealers_face_up_card();
+        let options = self
+            .player
+            .get_playing_options(Arc::clone(&dealers_up_card));
+        let (option, reason) = self
+            .player
+            .decide_option_with_reason(dealers_up_card, self.table.cards_remaining())?;
+        let event = TraceEvent::Decision {
+            hand: self.player.current_hand().to_vec(),
+            options,
+            option,
+            reason,
+        };
+        self.table.record(event);
+        Ok(option)
+    }
+
+    /// Starts tracing the game, see `BlackjackTableSim::enable_trace()`.
+    pub fn enable_trace(&mut self) {
+        self.table.enable_trace();
+    }
+
+    /// Returns the events traced since tracing was enabled or the trace was last taken.
+    pub fn take_trace(&mut self) -> Vec<TraceEvent> {
+        self.table.take_trace()
+    }
+
     /// Writes the stats the stats currently recorded to the given writer.
     // TODO: allow an arbitrary writer to be passed in
     pub fn display_stats(&self) {
@@ -215,11 +465,11 @@ impl<S: Strategy> BlackjackGameSim<S> {
             "total losses:", self.total_losses
         );
         println!(
-            "{:<text_width$}{:>numeric_width$.2}",
+            "{:<text_width$}{:>numeric_width$}",
             "total winnings:", self.total_winnings
         );
         println!(
-            "{:<text_width$}{:>numeric_width$.2}",
+            "{:<text_width$}{:>numeric_width$}",
             "players final balance:",
             self.player.balance()
         );
@@ -234,21 +484,45 @@ impl<S: Strategy> BlackjackGameSim<S> {
         println!("{}", "-".repeat(width));
     }
 
-    pub fn reset(&mut self, new_table_balance: f32, new_player_balance: f32) {
+    /// Resets the game for the next simulation. A simulation is a single independent session: the player and the table start from
+    /// `new_player_balance` and `new_table_balance`, the first hand is dealt from a freshly shuffled shoe with the player's count reset,
+    /// and at most `num_hands` rounds are played.
+    /// A `new_table_balance` of `None` is a table that never runs out of money.
+    pub fn reset(&mut self, new_table_balance: Option<Money>, new_player_balance: Money) {
         self.table.balance = new_table_balance;
         self.player.balance = new_player_balance;
+        self.table.new_shoe();
+        self.player.reset();
+        self.player.reset_strategy();
         self.num_player_blackjacks = 0;
         self.table.num_player_blackjacks = 0;
+        self.insurance_bets = 0;
+        self.insurance_winnings = Money::ZERO;
         self.total_wins = 0;
         self.total_pushes = 0;
         self.total_losses = 0;
-        self.total_winnings = 0.0;
+        self.total_winnings = Money::ZERO;
         self.ended_early = false;
+        self.ruin = None;
+        self.rounds = 0;
+        self.hands_played = 0;
+        self.winnings_m2 = 0.0;
+        self.total_wagered = Money::ZERO;
+        self.max_drawdown = Money::ZERO;
     }
 
-    pub fn label(&self) -> String {
+    pub fn label(&self) -> Arc<str> {
         self.player.label()
     }
+
+    pub fn full_label(&self) -> String {
+        self.player.full_label()
+    }
+
+    /// Reseeds the deck used by the game, the next hand is dealt from a freshly shuffled shoe.
+    pub fn reseed(&mut self, seed: u64) {
+        self.table.reseed(seed);
+    }
 }
 
 #[cfg(test)]
@@ -267,11 +541,11 @@ mod test {
         let decision_strategy = BasicStrategy::new();
         let betting_strategy = MarginBettingStrategy::new(3.0, MIN_BET);
         let strategy = PlayerStrategy::new(counting_strategy, decision_strategy, betting_strategy);
-        let player = PlayerSim::new(500.0, strategy, true);
+        let player = PlayerSim::new(Money::from_dollars(500), strategy, TableRules::default());
         // let table = <BlackjackTableSim as BlackjackTable<
         //     PlayerSim<PlayerStrategy<HiLo, BasicStrategy, MarginBettingStrategy>>,
         // >>::new(f32::MAX, 6, 7);
-        let table = BlackjackTableSim::new(f32::MAX, 6, 7, false, false);
+        let table = BlackjackTableSim::new(None, TableRules::default(), None);
         let mut game = BlackjackGameSim::new(table, player, NUM_HANDS, MIN_BET);
 
         if let Err(e) = game.run() {
@@ -282,4 +556,259 @@ mod test {
 
         assert!(true);
     }
+
+    #[test]
+    fn traced_game_records_every_round() {
+        const NUM_HANDS: u32 = 20;
+        let strategy = PlayerStrategy::new(
+            HiLo::new(6),
+            strategy::S17DeviationStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        );
+        let player = PlayerSim::new(Money::from_dollars(10_000), strategy, TableRules::default());
+        let table = BlackjackTableSim::new(None, TableRules::default(), Some(11));
+        let mut game = BlackjackGameSim::new(table, player, NUM_HANDS, 5);
+        game.enable_trace();
+        game.run().unwrap();
+        let trace = game.take_trace();
+
+        let count =
+            |is_event: fn(&TraceEvent) -> bool| trace.iter().filter(|e| is_event(e)).count();
+        assert_eq!(
+            count(|e| matches!(e, TraceEvent::Bet { .. })),
+            NUM_HANDS as usize
+        );
+        assert_eq!(
+            count(|e| matches!(e, TraceEvent::Resolution { .. })),
+            NUM_HANDS as usize
+        );
+        assert!(count(|e| matches!(e, TraceEvent::Card { .. })) >= 4 * NUM_HANDS as usize);
+        // Every decision of the S17 deviations comes with the rule that decided it
+        assert!(trace.iter().all(|e| match e {
+            TraceEvent::Decision { reason, .. } => reason.is_some(),
+            _ => true,
+        }));
+        assert!(game.take_trace().is_empty());
+    }
+
+    #[test]
+    fn every_simulation_starts_from_a_fresh_shoe_and_count() {
+        let strategy = PlayerStrategy::new(
+            HiLo::new(6),
+            BasicStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        );
+        let player = PlayerSim::new(Money::from_dollars(10_000), strategy, TableRules::default());
+        let table = BlackjackTableSim::new(None, TableRules::default(), None);
+        let mut game = BlackjackGameSim::new(table, player, 25, 5);
+        game.enable_trace();
+
+        let mut counted = false;
+        for _ in 0..5 {
+            game.run().unwrap();
+            counted |= game.player.counts().0 != 0.0;
+            game.reset(None, Money::from_dollars(10_000));
+            assert_eq!(game.player.counts(), (0.0, 0.0));
+        }
+        // The counts only started from zero because they were reset, not because nothing was counted
+        assert!(counted);
+
+        // Every simulation's first hand is dealt from a newly shuffled shoe
+        let trace = game.take_trace();
+        let shuffles = trace
+            .iter()
+            .filter(|event| matches!(event, TraceEvent::Shuffle))
+            .count();
+        assert_eq!(shuffles, 5);
+    }
+
+    #[test]
+    fn hole_card_is_counted_once_every_round() {
+        const NUM_HANDS: u32 = 1_000;
+        let strategy = PlayerStrategy::new(
+            HiLo::new(6),
+            strategy::S17DeviationStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        );
+        let player = PlayerSim::new(
+            Money::from_dollars(1_000_000),
+            strategy,
+            TableRules::default(),
+        );
+        let table = BlackjackTableSim::new(
+            None,
+            TableRules {
+                insurance: true,
+                ..TableRules::default()
+            },
+            Some(23),
+        );
+        let mut game = BlackjackGameSim::new(table, player, NUM_HANDS, 5);
+        game.enable_trace();
+        game.run().unwrap();
+        let trace = game.take_trace();
+
+        // Split the trace into rounds, each round starts with its bet
+        let mut rounds = vec![];
+        for event in trace.iter() {
+            if let TraceEvent::Bet { .. } = event {
+                rounds.push(vec![]);
+            } else if let TraceEvent::Card {
+                recipient, card, ..
+            } = event
+            {
+                rounds
+                    .last_mut()
+                    .unwrap()
+                    .push((*recipient, Arc::clone(card)));
+            }
+        }
+        assert_eq!(rounds.len(), NUM_HANDS as usize);
+
+        for cards in rounds {
+            let hole_cards = cards
+                .iter()
+                .filter(|(recipient, _)| *recipient == Recipient::DealerFaceDown)
+                .collect::<Vec<_>>();
+            assert_eq!(hole_cards.len(), 1);
+            let counted = cards
+                .iter()
+                .filter(|(recipient, _)| *recipient != Recipient::DealerFaceDown)
+                .map(|(_, card)| card)
+                .collect::<Vec<_>>();
+            // Every card is counted once, the hole card when it is revealed
+            for (i, card) in counted.iter().enumerate() {
+                assert!(!counted[i + 1..]
+                    .iter()
+                    .any(|other| Arc::ptr_eq(card, other)));
+            }
+            assert!(counted
+                .iter()
+                .any(|card| Arc::ptr_eq(card, &hole_cards[0].1)));
+        }
+    }
+
+    #[test]
+    fn money_is_conserved_between_player_and_table() {
+        const NUM_HANDS: u32 = 5_000;
+        let strategy = PlayerStrategy::new(
+            HiLo::new(6),
+            BasicStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        );
+        let player = PlayerSim::new(
+            Money::from_dollars(100_000),
+            strategy,
+            TableRules::default(),
+        );
+        let table = BlackjackTableSim::new(
+            Some(Money::from_dollars(100_000)),
+            TableRules {
+                soft_seventeen: true,
+                insurance: true,
+                ..TableRules::default()
+            },
+            Some(3),
+        );
+        let mut game = BlackjackGameSim::new(table, player, 1, 5);
+        let total = game.player.balance() + game.table.balance.unwrap();
+
+        // Check the totals after every hand so a single misbooked bet can't be hidden by a later one
+        for _ in 0..NUM_HANDS {
+            game.run().unwrap();
+            assert_eq!(game.player.balance() + game.table.balance.unwrap(), total);
+        }
+        assert!(game.total_wins + game.total_pushes + game.total_losses > 0);
+    }
+
+    #[test]
+    fn long_run_winnings_are_exact() {
+        const NUM_HANDS: u32 = 200_000;
+        let strategy = PlayerStrategy::new(
+            HiLo::new(6),
+            BasicStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        );
+        let starting_balance = Money::from_dollars(1_000_000);
+        let player = PlayerSim::new(starting_balance, strategy, TableRules::default());
+        let table = BlackjackTableSim::new(None, TableRules::default(), Some(5));
+        let mut game = BlackjackGameSim::new(table, player, NUM_HANDS, 5);
+
+        // Every result is a multiple of half a dollar, so summing them as f64 is exact and serves as the reference
+        let reference = Arc::new(std::sync::Mutex::new(0.0f64));
+        let observed = Arc::clone(&reference);
+        game.set_hand_observer(Box::new(move |history: &HandHistory| {
+            let mut reference = observed.lock().unwrap();
+            for result in history.results.iter() {
+                match *result {
+                    HandResult::Win(amount) => *reference += amount.to_f64(),
+                    HandResult::Loss(amount) => *reference -= amount.to_f64(),
+                    _ => {}
+                }
+            }
+        }));
+        game.run().unwrap();
+
+        assert!(!game.ended_early);
+        assert_eq!(game.total_winnings.to_f64(), *reference.lock().unwrap());
+        assert_eq!(
+            game.player.balance(),
+            starting_balance + game.total_winnings
+        );
+    }
+
+    #[test]
+    fn test_shuffle_positional_uniformity() {
+        const NUM_SHUFFLES: usize = 52 * 200;
+        // Seeded, so the test can not fail by chance
+        let mut deck = DeckSim::new(1, Some(7));
+        let tracked_card = Arc::clone(&deck.cards[0]);
+        let mut position_counts = [0usize; 52];
+
+        for _i in 0..NUM_SHUFFLES {
+            deck.shuffle(1);
+            let pos = deck
+                .cards
+                .iter()
+                .position(|card| Arc::ptr_eq(card, &tracked_card))
+                .unwrap();
+            position_counts[pos] += 1;
+        }
+
+        // Chi-square statistic over the 52 positions, with 51 degrees of freedom the
+        // critical value at p = 0.001 is roughly 88
+        let expected = (NUM_SHUFFLES / 52) as f64;
+        let chi_square: f64 = position_counts
+            .iter()
+            .map(|&observed| (observed as f64 - expected).powi(2) / expected)
+            .sum();
+
+        assert!(
+            chi_square < 88.0,
+            "shuffle is not uniform, chi-square statistic of {}",
+            chi_square
+        );
+    }
+
+    #[test]
+    fn reseeded_decks_deal_the_same_shoe_whatever_their_history() {
+        let mut first = DeckSim::new(6, Some(1));
+        for _ in 0..3 {
+            first.shuffle(1);
+        }
+        let mut second = DeckSim::new(6, Some(2));
+        second.shuffle(1);
+        second.get_next_card();
+
+        first.reseed(7);
+        second.reseed(7);
+        first.shuffle(1);
+        second.shuffle(1);
+        let shoe = |deck: &mut DeckSim| {
+            std::iter::from_fn(|| deck.get_next_card())
+                .map(|card| (*card).clone())
+                .collect::<Vec<Card>>()
+        };
+        assert_eq!(shoe(&mut first), shoe(&mut second));
+    }
 }
@@ -1,47 +1,87 @@
-use crate::game::strategy::TableState;
+use crate::game::strategy::{Action, DecisionReason, Options, TableState};
 use crate::game::strategy::{BettingStrategy, CountingStrategy, DecisionStrategy, Strategy};
+use crate::game::table::TableRules;
+use crate::money::Money;
 use blackjack_lib::{compute_optimal_hand, BlackjackGameError, Card, Player};
-use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::sync::Arc;
 
+/// Enum for recording the result of a single hand played by the player
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HandResult {
+    /// The hand has not been resolved yet
+    Pending,
+    /// The hand was won, holds the amount won
+    Win(Money),
+    /// The hand was pushed, the bet is returned to the player
+    Push,
+    /// The hand was lost, holds the amount lost
+    Loss(Money),
+}
+
+/// The maximum number of hands a player may hold at once, i.e. the number of hands after splitting the maximum number of times.
+const MAX_HANDS: usize = 4;
+
 /// Struct for a simulated player
 pub struct PlayerSim<S: Strategy> {
     hand: Vec<Vec<Arc<Card>>>,
     hand_values: Vec<Vec<u8>>,
+    num_hands: usize,
     pub bets: Vec<u32>,
-    pub bets_log: HashMap<usize, f32>,
+    pub bets_log: Vec<HandResult>,
     hand_idx: usize,
-    pub balance: f32,
-    pub insurance_bet: Option<(f32, bool)>,
+    pub balance: Money,
+    pub insurance_bet: Option<(Money, bool)>,
     strategy: S,
     surrender_flag: bool,
+    split_by_value: bool,
+    double_after_split: bool,
+    table_limit: Option<Money>,
+    wagered: Money,
+    actions: Vec<Action>,
 }
 
 impl<S: Strategy> PlayerSim<S> {
-    /// Associated function to create a new `PlayerSim` struct.
-    pub fn new(starting_balance: f32, strategy: S, surrender_flag: bool) -> PlayerSim<S> {
+    /// Associated function to create a new `PlayerSim` struct, the player may surrender, split and double as `rules` allow.
+    pub fn new(starting_balance: Money, strategy: S, rules: TableRules) -> PlayerSim<S> {
         PlayerSim {
-            hand: vec![vec![]],
-            hand_values: vec![vec![]],
+            hand: vec![vec![]; MAX_HANDS],
+            hand_values: vec![vec![]; MAX_HANDS],
+            num_hands: 1,
             bets: vec![],
-            bets_log: HashMap::new(),
+            bets_log: Vec::with_capacity(MAX_HANDS),
             hand_idx: 0,
             balance: starting_balance,
             insurance_bet: None,
+            wagered: Money::ZERO,
             strategy,
-            surrender_flag,
+            surrender_flag: rules.surrender,
+            split_by_value: rules.split_by_value,
+            double_after_split: rules.double_after_split,
+            table_limit: None,
+            actions: vec![],
         }
     }
 
+    /// Sets whether any two cards of the same value may be split, e.g. a king and a queen, or only two cards of the same rank.
+    /// Overrides the rules the player was created with.
+    pub fn set_split_by_value(&mut self, split_by_value: bool) {
+        self.split_by_value = split_by_value;
+    }
+
+    /// Sets whether the hands of a split may be doubled, i.e. double after split (DAS). Overrides the rules the player was created with.
+    pub fn set_double_after_split(&mut self, double_after_split: bool) {
+        self.double_after_split = double_after_split;
+    }
+
     /// Method for determining whether or not the players turn is over
     pub fn turn_is_over(&self) -> bool {
-        self.hand_idx == self.hand.len()
+        self.hand_idx == self.num_hands
     }
 
     /// Method for determining whether the player can continue to play or not
     pub fn continue_play(&self, min_bet: u32) -> bool {
-        (self.balance as u32) >= min_bet
+        self.balance >= Money::from_dollars(min_bet.into())
     }
 
     /// Getter method for the players current bet
@@ -49,14 +89,24 @@ impl<S: Strategy> PlayerSim<S> {
         self.bets[self.hand_idx]
     }
 
+    /// Helper method that returns the bet of the current hand as `Money`.
+    fn current_stake(&self) -> Money {
+        Money::from_dollars(self.get_current_bet().into())
+    }
+
+    /// Getter method for the total amount the player has bet on the current hand, including doubles and splits.
+    pub fn wagered(&self) -> Money {
+        self.wagered
+    }
+
     /// Getter method for the players current balance.
-    pub fn balance(&self) -> f32 {
+    pub fn balance(&self) -> Money {
         self.balance
     }
 
     /// Function for getting an initial bet
     pub fn bet(&mut self) -> Result<u32, BlackjackGameError> {
-        let bet_state = self.strategy.get_current_bet_state(self.balance);
+        let bet_state = self.strategy.get_current_bet_state(self.balance.to_f32());
         let bet = self.strategy.bet(bet_state);
         if bet == 0 {
             return Err(BlackjackGameError::new("out of funds".to_string()));
@@ -65,11 +115,13 @@ impl<S: Strategy> PlayerSim<S> {
         Ok(bet)
     }
 
-    /// Function to simluate the placing of a bet, updates the `PlayerSim`'s balance and bets
+    /// Function to simluate the placing of a bet of `bet` whole dollars, updates the `PlayerSim`'s balance and bets
     /// Assumes the logic for checking whether or not the bet is valid has already been executed.
-    pub fn place_bet(&mut self, bet: f32) {
-        self.balance -= bet;
-        self.bets.push(bet as u32);
+    pub fn place_bet(&mut self, bet: u32) {
+        let stake = Money::from_dollars(bet.into());
+        self.balance -= stake;
+        self.wagered += stake;
+        self.bets.push(bet);
     }
 
     /// Method to receive a card, updates the state of the `Player`
@@ -100,7 +152,7 @@ impl<S: Strategy> PlayerSim<S> {
 
     /// Method to return a formatted version of all the players hand(s)
     pub fn formatted_hand_values(&self) -> String {
-        self.hand_values
+        self.hand_values[..self.num_hands]
             .iter()
             .map(|hand| {
                 if hand.len() == 2 {
@@ -118,47 +170,57 @@ impl<S: Strategy> PlayerSim<S> {
     }
 
     /// Public method for producing the possible options a player can choose to player their current hand
-    pub fn get_playing_options(&self, dealers_up_card: Arc<Card>) -> HashSet<String> {
-        let mut options = HashSet::new();
-        options.insert("stand".to_string());
-        options.insert("hit".to_string());
+    pub fn get_playing_options(&self, dealers_up_card: Arc<Card>) -> Options {
+        let mut options = Options::STAND | Options::HIT;
         if self.surrender_flag && self.can_surrender(dealers_up_card) {
-            options.insert("surrender".to_string());
+            options |= Options::SURRENDER;
         }
         if self.can_split() {
-            options.insert("split".to_string());
+            options |= Options::SPLIT;
         }
         if self.can_double_down() {
-            options.insert("double down".to_string());
+            options |= Options::DOUBLE;
         }
 
         options
     }
 
+    /// Sets the most the table can pay out on the player's bets this round, doubles and splits are only offered while the table
+    /// could pay every bet the player has on the table. A limit of `None` is a table that never runs out of money.
+    pub fn set_table_limit(&mut self, table_limit: Option<Money>) {
+        self.table_limit = table_limit;
+    }
+
+    /// Returns true if the table could still pay out every bet of the player after `extra_bet` is added to them.
+    fn covered_by_table(&self, extra_bet: u32) -> bool {
+        self.table_limit.is_none_or(|table_limit| {
+            Money::from_dollars((self.bets.iter().sum::<u32>() + extra_bet).into()) <= table_limit
+        })
+    }
+
     /// Returns a boolean, true if the `PlayerSim` instance can split their hand, false otherwise.
-    fn can_split(&self) -> bool {
-        self.hand.len() < 4
-            && self.hand[self.hand_idx].len() == 2
-            && self.hand[self.hand_idx][0].rank == self.hand[self.hand_idx][1].rank
-            && (self.bets[self.hand_idx] as f32) <= self.balance
+    /// The two cards must have the same value, or the same rank if the player may not split by value.
+    pub fn can_split(&self) -> bool {
+        let hand = &self.hand[self.hand_idx];
+        self.num_hands < MAX_HANDS
+            && hand.len() == 2
+            && if self.split_by_value {
+                hand[0].val == hand[1].val
+            } else {
+                hand[0].rank == hand[1].rank
+            }
+            && self.current_stake() <= self.balance
+            && self.covered_by_table(self.bets[self.hand_idx])
     }
 
     /// Returns a boolean, true if the `PlayerSim` can double down, false otherwise.
+    /// The player may double down on the first two cards of any hand, soft or hard, as long as they can afford to match their bet.
+    /// Every hand after a split came from the split, so they may only be doubled if doubling after a split is allowed.
     fn can_double_down(&self) -> bool {
-        self.hand_idx == 0
-            && (self.bets[self.hand_idx] as f32) <= self.balance
-            && if self.hand_values[self.hand_idx].len() == 2 {
-                self.hand_values[self.hand_idx][0] == 9
-                    || self.hand_values[self.hand_idx][1] == 9
-                    || self.hand_values[self.hand_idx][0] == 10
-                    || self.hand_values[self.hand_idx][1] == 10
-                    || self.hand_values[self.hand_idx][0] == 11
-                    || self.hand_values[self.hand_idx][1] == 11
-            } else {
-                self.hand_values[self.hand_idx][0] == 9
-                    || self.hand_values[self.hand_idx][0] == 10
-                    || self.hand_values[self.hand_idx][0] == 11
-            }
+        (self.num_hands == 1 || self.double_after_split)
+            && self.hand[self.hand_idx].len() == 2
+            && self.current_stake() <= self.balance
+            && self.covered_by_table(self.bets[self.hand_idx])
     }
 
     /// Returns a boolean representing whether the player has a blackjack or not.
@@ -185,63 +247,70 @@ impl<S: Strategy> PlayerSim<S> {
 
     /// Method that implements the logic for surrendering. Will return half the current bet that the player has on the table.
     pub fn can_surrender(&self, dealers_up_card: Arc<Card>) -> bool {
-        self.hand_idx == 0
-            && self.hand_values[self.hand_idx].len() == 2
+        self.num_hands == 1
+            && self.hand[self.hand_idx].len() == 2
             && (dealers_up_card.val == 1 || dealers_up_card.val == 10)
     }
 
     /// Method to update the state of the players hand when a push occurs.
     /// Change the bet of the current hand to 0, update the balance and return 0.
     pub fn push_current_hand(&mut self) {
-        let bet = self.bets[self.hand_idx];
-        self.balance += bet as f32;
+        self.balance += self.current_stake();
         self.bets[self.hand_idx] = 0;
-        self.bets_log.insert(self.hand_idx, 0.0);
+        self.log_result(self.hand_idx, HandResult::Push);
         self.stand();
     }
 
     /// Method to update the state of the players hand when a bet is lost.
-    /// Change the bet of the current hand to 0, and return the value negative value of the bet to indicate a loss occured
+    /// Change the bet of the current hand to 0, and record the value of the bet as lost
     pub fn lose_current_hand(&mut self) {
-        let bet = -(self.bets[self.hand_idx] as i32);
+        let bet = self.current_stake();
         self.bets[self.hand_idx] = 0;
-        self.bets_log.insert(self.hand_idx, bet as f32);
+        self.log_result(self.hand_idx, HandResult::Loss(bet));
         self.stand();
     }
 
     /// Method for updating the internal bookeeping of won/lost bets when the player gets a blackjack
-    pub fn blackjack(&mut self, winnings: f32) {
-        let bet = self.bets[self.hand_idx] as f32;
-        self.balance += bet;
+    pub fn blackjack(&mut self, winnings: Money) {
+        self.balance += self.current_stake();
         self.bets[self.hand_idx] = 0;
-        self.bets_log.insert(self.hand_idx, winnings);
+        self.log_result(self.hand_idx, HandResult::Win(winnings));
         self.stand();
     }
 
     /// Method to update the `PlayerSim` structs bets_log
     pub fn win_hand(&mut self, hand: usize, bet: u32) {
-        self.balance += bet as f32;
-        self.bets_log.insert(hand, bet as f32);
+        let bet = Money::from_dollars(bet.into());
+        self.balance += bet;
+        self.log_result(hand, HandResult::Win(bet));
     }
 
     /// Method to update the `PlayerSim` structs bets_log
     pub fn lose_hand(&mut self, hand: usize, bet: u32) {
-        self.bets_log.insert(hand, -(bet as f32));
+        self.log_result(hand, HandResult::Loss(Money::from_dollars(bet.into())));
     }
 
     /// Method to update the `PlayerSim` structs bets_log
     pub fn push_hand(&mut self, hand: usize, bet: u32) {
-        self.balance += bet as f32;
-        self.bets_log.insert(hand, 0.0);
+        self.balance += Money::from_dollars(bet.into());
+        self.log_result(hand, HandResult::Push);
+    }
+
+    /// Helper method for recording the `result` of the hand at position `hand` in the `PlayerSim` structs bets_log
+    fn log_result(&mut self, hand: usize, result: HandResult) {
+        if self.bets_log.len() <= hand {
+            self.bets_log.resize(hand + 1, HandResult::Pending);
+        }
+        self.bets_log[hand] = result;
     }
 
     /// Method for receiving winnings
-    pub fn collect_winnings(&mut self, winnings: f32) {
+    pub fn collect_winnings(&mut self, winnings: Money) {
         self.balance += winnings;
     }
 
     /// Method that returns a boolean, true if the player has busted on their current hand false if the current hand has not busted.
-    /// Will panic if `self.hand_idx` > `self.hand.len()`
+    /// Will panic if `self.hand_idx` > `self.num_hands`
     pub fn busted(&self) -> bool {
         if self.hand_values[self.hand_idx].len() == 2 {
             self.hand_values[self.hand_idx][0] > 21 && self.hand_values[self.hand_idx][1] > 21
@@ -250,35 +319,46 @@ impl<S: Strategy> PlayerSim<S> {
         }
     }
 
-    /// Method that will execute the logic for surrendering
-    pub fn surrender(&mut self) -> f32 {
-        let bet = self.bets[self.hand_idx] as f32;
+    /// Method that will execute the logic for surrendering, half the current bet is returned to the player
+    /// and the other half is recorded as lost.
+    pub fn surrender(&mut self) {
+        let bet = self.current_stake();
         self.bets[self.hand_idx] = 0;
-        self.balance += bet / 2.0;
+        self.balance += bet / 2;
+        self.log_result(self.hand_idx, HandResult::Loss(bet / 2));
         self.stand();
-        bet / 2.0
     }
 
     /// Method that implements the logic for doubling down. Will panic if `self.balance` is not high enough to place the bet.
     pub fn double_down(&mut self) {
-        assert!(self.bets[self.hand_idx] as f32 <= self.balance);
-        self.balance -= self.bets[self.hand_idx] as f32;
+        let stake = self.current_stake();
+        assert!(stake <= self.balance);
+        self.balance -= stake;
+        self.wagered += stake;
         self.bets[self.hand_idx] *= 2;
     }
 
     /// Method that implements the logic for splitting.
+    /// Split aces receive a single card each and are not played any further, so they can't be hit, doubled or split again.
     /// Will panic if `self.balance` is not high enough to place the bet or if the current hand is empty().
     pub fn split(&mut self, card1: Arc<Card>, card2: Arc<Card>) {
-        assert!(self.bets[self.hand_idx] as f32 <= self.balance);
+        let stake = self.current_stake();
+        assert!(stake <= self.balance);
         // Get current bet and duplicate it for the new hand
         let cur_bet = self.bets[self.hand_idx];
+        self.balance -= stake;
+        self.wagered += stake;
         self.bets.insert(self.hand_idx + 1, cur_bet);
 
+        // Move the first unused buffer from the pool to directly after the current hand
+        self.hand[self.hand_idx + 1..=self.num_hands].rotate_right(1);
+        self.hand_values[self.hand_idx + 1..=self.num_hands].rotate_right(1);
+        self.num_hands += 1;
+
         // Split the current hand, and start with empty hand values
         let new_hand_start = self.hand[self.hand_idx].pop().unwrap();
-        self.hand.insert(self.hand_idx + 1, vec![new_hand_start]);
+        self.hand[self.hand_idx + 1].push(new_hand_start);
         self.hand_values[self.hand_idx].clear();
-        self.hand_values.insert(self.hand_idx + 1, vec![]);
 
         // receive a new card for each hand
         self.hand[self.hand_idx].push(card1);
@@ -301,6 +381,12 @@ impl<S: Strategy> PlayerSim<S> {
         {
             self.hand_values[self.hand_idx + 1].push(hand2 + 10);
         }
+
+        // Both split aces are finished with the card they were dealt
+        if self.hand[self.hand_idx + 1][0].rank == "A" {
+            self.stand();
+            self.stand();
+        }
     }
 
     /// Method that checks whether the player has currently taken an insurance bet
@@ -315,11 +401,14 @@ impl<S: Strategy> PlayerSim<S> {
         self.insurance_bet = Some((bet, true));
     }
 
-    /// Method that decides whether or not to take insurance based on the players current strategy, will set the current
+    /// Method that decides whether or not to take insurance based on the players current strategy, the insurance stake
+    /// of half the current bet is deducted from the players balance when it is placed.
     pub fn take_insurance(&mut self) {
-        // If strategy decides to take insurance, place the insurance bet
-        if self.strategy.take_insurance() {
-            self.insurance_bet = Some((self.get_current_bet() as f32 / 2.0, false));
+        // If strategy decides to take insurance and the player can afford it, place the insurance bet
+        let stake = self.current_stake() / 2;
+        if stake <= self.balance && self.strategy.take_insurance() {
+            self.balance -= stake;
+            self.insurance_bet = Some((stake, false));
         }
     }
 
@@ -339,36 +428,114 @@ impl<S: Strategy> PlayerSim<S> {
         }
     }
 
-    /// Method for returning a valid option given the state of the table
-    pub fn decide_option(&self, dealers_up_card: Arc<Card>) -> Result<String, BlackjackGameError> {
-        let options = self.get_playing_options(dealers_up_card.clone());
-        let current_state = self.strategy.get_current_table_state(
+    /// Builds the table state the strategy sees for the hand currently being played.
+    fn current_table_state(
+        &self,
+        dealers_up_card: Arc<Card>,
+        cards_remaining: u32,
+    ) -> TableState<'_> {
+        self.strategy.get_current_table_state(
             &self.hand[self.hand_idx],
             &self.hand_values[self.hand_idx],
             self.get_current_bet(),
-            self.balance,
+            self.balance.to_f32(),
             dealers_up_card,
-        );
+            cards_remaining,
+            self.num_hands > 1,
+            self.num_hands as u8,
+        )
+    }
+
+    /// Method for returning a valid option given the state of the table, `cards_remaining` is the number of cards left in the shoe.
+    pub fn decide_option(
+        &self,
+        dealers_up_card: Arc<Card>,
+        cards_remaining: u32,
+    ) -> Result<Action, BlackjackGameError> {
+        let options = self.get_playing_options(dealers_up_card.clone());
+        let current_state = self.current_table_state(dealers_up_card, cards_remaining);
 
         self.strategy.decide_option(current_state, options)
     }
 
+    /// Method for returning a valid option given the state of the table, along with the rule of the strategy that decided it if it can tell.
+    pub fn decide_option_with_reason(
+        &self,
+        dealers_up_card: Arc<Card>,
+        cards_remaining: u32,
+    ) -> Result<(Action, Option<DecisionReason>), BlackjackGameError> {
+        let options = self.get_playing_options(dealers_up_card.clone());
+        let current_state = self.current_table_state(dealers_up_card, cards_remaining);
+
+        self.strategy
+            .decide_option_with_reason(current_state, options)
+    }
+
+    /// Method for recording `action` as played on the current hand, called by the table once the action is known to be available.
+    pub fn record_action(&mut self, action: Action) {
+        self.actions.push(action);
+    }
+
+    /// Method that returns the actions played this round, in the order they were played across every hand of a split.
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    /// Method that returns the best value of each of the player's hands, in the order they were played.
+    pub fn final_values(&self) -> Vec<u8> {
+        let mut values = Vec::with_capacity(self.num_hands);
+        for hand_value in self.hand_values[..self.num_hands].iter() {
+            values.push(compute_optimal_hand(hand_value));
+        }
+        values
+    }
+
+    /// Method that returns every hand the player holds, in the order they are played.
+    pub fn hands(&self) -> &[Vec<Arc<Card>>] {
+        &self.hand[..self.num_hands]
+    }
+
+    /// Method that returns the hand currently being played.
+    pub fn current_hand(&self) -> &[Arc<Card>] {
+        &self.hand[self.hand_idx]
+    }
+
+    /// Method that returns the running and true count of the player's strategy.
+    pub fn counts(&self) -> (f32, f32) {
+        let state = self.strategy.get_current_bet_state(self.balance.to_f32());
+        (state.running_count(), state.true_count())
+    }
+
     /// Method to get a string that describes the players strategy
-    pub fn label(&self) -> String {
+    pub fn label(&self) -> Arc<str> {
         self.strategy.label()
     }
 
+    /// Method to get a string that describes the players full strategy, see `Strategy::full_label()`
+    pub fn full_label(&self) -> String {
+        self.strategy.full_label()
+    }
+
     pub fn reset_strategy(&mut self) {
         self.strategy.reset();
     }
 
+    /// Method to reset the player for another hand. The hand buffers are cleared rather than reallocated,
+    /// so their capacity is reused from hand to hand.
     pub fn reset(&mut self) {
-        self.hand = vec![vec![]];
-        self.hand_values = vec![vec![]];
+        for hand in self.hand[..self.num_hands].iter_mut() {
+            hand.clear();
+        }
+        for hand_value in self.hand_values[..self.num_hands].iter_mut() {
+            hand_value.clear();
+        }
+        self.num_hands = 1;
         self.bets.clear();
         self.bets_log.clear();
         self.hand_idx = 0;
         self.insurance_bet = None;
+        self.wagered = Money::ZERO;
+        self.actions.clear();
     }
 }
 
@@ -386,9 +553,9 @@ impl<S: Strategy + Display> Display for PlayerSim<S> {
                    {:<21}${:.2}\n\
                    {}",
             "hand:",
-            self.hand,
+            &self.hand[..self.num_hands],
             "hand_value:",
-            self.hand_values,
+            &self.hand_values[..self.num_hands],
             "bets:",
             self.bets,
             "bets_log:",
@@ -401,3 +568,188 @@ impl<S: Strategy + Display> Display for PlayerSim<S> {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::strategy::{BasicStrategy, HiLo, MarginBettingStrategy, PlayerStrategy};
+
+    #[test]
+    fn test_reset_reuses_hand_buffers() {
+        let strategy = PlayerStrategy::new(
+            HiLo::new(6),
+            BasicStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        );
+        let mut player = PlayerSim::new(Money::from_dollars(500), strategy, TableRules::default());
+        player.place_bet(5);
+        player.receive_card(Arc::new(Card::new("H", "8")));
+        player.receive_card(Arc::new(Card::new("S", "8")));
+        player.split(Arc::new(Card::new("D", "3")), Arc::new(Card::new("C", "K")));
+
+        let hand_buffers = player
+            .hand
+            .iter()
+            .map(|hand| hand.as_ptr())
+            .collect::<Vec<*const Arc<Card>>>();
+
+        player.reset();
+        assert_eq!(player.hand.len(), MAX_HANDS);
+        assert!(player.hand.iter().all(|hand| hand.is_empty()));
+
+        // Play another split hand, the same buffers should be used
+        player.place_bet(5);
+        player.receive_card(Arc::new(Card::new("H", "9")));
+        player.receive_card(Arc::new(Card::new("S", "9")));
+        player.split(Arc::new(Card::new("D", "2")), Arc::new(Card::new("C", "Q")));
+
+        assert_eq!(player.hand[0].as_ptr(), hand_buffers[0]);
+        assert_eq!(player.hand[1].as_ptr(), hand_buffers[1]);
+    }
+
+    #[test]
+    fn test_bets_log_multi_split_hands() {
+        let strategy = PlayerStrategy::new(
+            HiLo::new(6),
+            BasicStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        );
+        let mut player = PlayerSim::new(Money::from_dollars(500), strategy, TableRules::default());
+        player.place_bet(10);
+        player.receive_card(Arc::new(Card::new("H", "8")));
+        player.receive_card(Arc::new(Card::new("S", "8")));
+        player.split(Arc::new(Card::new("D", "8")), Arc::new(Card::new("C", "K")));
+        player.split(Arc::new(Card::new("D", "2")), Arc::new(Card::new("C", "Q")));
+        assert_eq!(player.num_hands, 3);
+
+        // Resolve the hands out of order
+        player.push_hand(2, 10);
+        player.win_hand(0, 10);
+        player.lose_hand(1, 10);
+
+        assert_eq!(
+            player.bets_log,
+            vec![
+                HandResult::Win(Money::from_dollars(10)),
+                HandResult::Loss(Money::from_dollars(10)),
+                HandResult::Push
+            ]
+        );
+
+        player.reset();
+        assert!(player.bets_log.is_empty());
+    }
+
+    #[test]
+    fn surrender_is_only_offered_on_the_first_two_cards_of_an_unsplit_hand() {
+        let strategy = PlayerStrategy::new(
+            HiLo::new(6),
+            BasicStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        );
+        let mut player = PlayerSim::new(Money::from_dollars(500), strategy, TableRules::default());
+        let dealers_up_card = Arc::new(Card::new("C", "K"));
+        player.place_bet(10);
+        player.receive_card(Arc::new(Card::new("H", "10")));
+        player.receive_card(Arc::new(Card::new("S", "6")));
+        assert!(player
+            .get_playing_options(Arc::clone(&dealers_up_card))
+            .allows_surrender());
+        // Surrender is late surrender, only against a ten or an ace
+        assert!(!player
+            .get_playing_options(Arc::new(Card::new("C", "7")))
+            .allows_surrender());
+
+        player.receive_card(Arc::new(Card::new("D", "2")));
+        let options = player.get_playing_options(Arc::clone(&dealers_up_card));
+        assert_eq!(options, Options::HIT | Options::STAND);
+
+        // Hands dealt by a split can't be surrendered
+        player.reset();
+        player.place_bet(10);
+        player.receive_card(Arc::new(Card::new("H", "8")));
+        player.receive_card(Arc::new(Card::new("S", "8")));
+        assert!(player
+            .get_playing_options(Arc::clone(&dealers_up_card))
+            .allows_surrender());
+        player.split(Arc::new(Card::new("D", "3")), Arc::new(Card::new("C", "2")));
+        assert!(!player
+            .get_playing_options(dealers_up_card)
+            .allows_surrender());
+    }
+
+    #[test]
+    fn table_state_reports_split_hands_and_shoe_depth() {
+        let strategy = PlayerStrategy::new(
+            HiLo::new(6),
+            BasicStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        );
+        let mut player = PlayerSim::new(
+            Money::from_dollars(500),
+            strategy,
+            TableRules {
+                surrender: false,
+                ..TableRules::default()
+            },
+        );
+        let dealers_up_card = Arc::new(Card::new("C", "6"));
+        player.place_bet(10);
+        player.receive_card(Arc::new(Card::new("H", "8")));
+        player.receive_card(Arc::new(Card::new("S", "8")));
+
+        let state = player.current_table_state(Arc::clone(&dealers_up_card), 300);
+        assert_eq!(state.cards_remaining(), 300);
+        assert!(!state.is_split_hand());
+        assert_eq!(state.num_player_hands(), 1);
+
+        player.split(Arc::new(Card::new("D", "3")), Arc::new(Card::new("C", "K")));
+        let state = player.current_table_state(dealers_up_card, 298);
+        assert_eq!(state.cards_remaining(), 298);
+        assert!(state.is_split_hand());
+        assert_eq!(state.num_player_hands(), 2);
+    }
+
+    #[test]
+    fn splits_are_capped_and_split_hands_double_only_with_das() {
+        let strategy = PlayerStrategy::new(
+            HiLo::new(6),
+            BasicStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        );
+        let mut player = PlayerSim::new(
+            Money::from_dollars(500),
+            strategy,
+            TableRules {
+                surrender: false,
+                ..TableRules::default()
+            },
+        );
+        let dealers_up_card = Arc::new(Card::new("C", "6"));
+        player.place_bet(10);
+        player.receive_card(Arc::new(Card::new("H", "8")));
+        player.receive_card(Arc::new(Card::new("S", "8")));
+
+        // Keep splitting into another pair of 8s until the player holds the most hands allowed
+        for num_hands in 1..MAX_HANDS {
+            let options = player.get_playing_options(Arc::clone(&dealers_up_card));
+            assert!(options.allows_split(), "{} hands", num_hands);
+            assert!(options.allows_double(), "{} hands", num_hands);
+            player.split(Arc::new(Card::new("D", "8")), Arc::new(Card::new("C", "8")));
+        }
+        assert_eq!(player.num_hands, MAX_HANDS);
+        let options = player.get_playing_options(Arc::clone(&dealers_up_card));
+        assert!(!options.allows_split());
+        assert!(options.allows_double());
+
+        player.set_double_after_split(false);
+        let options = player.get_playing_options(dealers_up_card);
+        assert_eq!(options, Options::HIT | Options::STAND);
+        assert_eq!(
+            options,
+            [Action::Hit, Action::Stand]
+                .into_iter()
+                .collect::<Options>()
+        );
+    }
+}
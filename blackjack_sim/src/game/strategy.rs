@@ -1,7 +1,12 @@
-use std::collections::{HashMap, HashSet};
+use bitflags::bitflags;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::str::FromStr;
 use std::sync::Arc;
 
+use crate::ConfigError;
+
 pub mod prelude {
     pub use super::*;
     pub use blackjack_lib::console::player;
@@ -30,31 +35,67 @@ pub struct TableState<'a> {
     num_decks: u32,
     /// The dealers face up card
     dealers_up_card: Arc<Card>,
+    /// The number of cards left in the shoe
+    cards_remaining: u32,
+    /// Whether the current hand was dealt from a split
+    is_split_hand: bool,
+    /// The number of hands the player has in play, i.e. more than one once the player has split
+    num_player_hands: u8,
 }
 
 impl<'a> TableState<'a> {
-    /// Associated method for creating a new `TableState` object.
-    fn new(
+    /// Associated method for creating a new `TableState` object, the player's balance and the counts are taken from `counts`.
+    /// The state describes an unsplit hand at the top of a full shoe, see `TableState::with_hand_context()` for describing any other hand.
+    pub fn new(
         hand: &'a Vec<Arc<Card>>,
         hand_value: &'a Vec<u8>,
         bet: u32,
-        balance: f32,
-        running_count: f32,
-        true_count: f32,
-        num_decks: u32,
         dealers_up_card: Arc<Card>,
+        counts: BetState,
     ) -> TableState<'a> {
         TableState {
             hand,
             hand_value,
             bet,
-            balance,
-            running_count,
-            true_count,
-            num_decks,
+            balance: counts.balance,
+            running_count: counts.running_count,
+            true_count: counts.true_count,
+            num_decks: counts.num_decks,
             dealers_up_card,
+            cards_remaining: counts.num_decks * 52,
+            is_split_hand: false,
+            num_player_hands: 1,
         }
     }
+
+    /// Method that sets the number of cards left in the shoe, whether the hand was dealt from a split and the number of hands the player
+    /// has in play.
+    pub fn with_hand_context(
+        mut self,
+        cards_remaining: u32,
+        is_split_hand: bool,
+        num_player_hands: u8,
+    ) -> TableState<'a> {
+        self.cards_remaining = cards_remaining;
+        self.is_split_hand = is_split_hand;
+        self.num_player_hands = num_player_hands;
+        self
+    }
+
+    /// Getter method for the number of cards left in the shoe.
+    pub fn cards_remaining(&self) -> u32 {
+        self.cards_remaining
+    }
+
+    /// Getter method for whether the current hand was dealt from a split.
+    pub fn is_split_hand(&self) -> bool {
+        self.is_split_hand
+    }
+
+    /// Getter method for the number of hands the player has in play.
+    pub fn num_player_hands(&self) -> u8 {
+        self.num_player_hands
+    }
 }
 
 /// Struct that ecapsulates all relevant information for placing a bet. Analogous to `TableState` i.e. is essentially a vector whose components are made up of
@@ -72,7 +113,7 @@ pub struct BetState {
 
 impl BetState {
     /// Associated method for creating a new 'BetState` object.
-    fn new(balance: f32, running_count: f32, true_count: f32, num_decks: u32) -> BetState {
+    pub fn new(balance: f32, running_count: f32, true_count: f32, num_decks: u32) -> BetState {
         BetState {
             balance,
             running_count,
@@ -80,6 +121,188 @@ impl BetState {
             num_decks,
         }
     }
+
+    /// Getter method for the running count.
+    pub fn running_count(&self) -> f32 {
+        self.running_count
+    }
+
+    /// Getter method for the true count.
+    pub fn true_count(&self) -> f32 {
+        self.true_count
+    }
+}
+
+/// A way of playing the current hand, i.e. the decision a `DecisionStrategy` makes from the `Options` available to the player.
+/// Converts to and from the names used by the CLI and the API, i.e. "hit", "stand", "split", "double down" and "surrender".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Hit,
+    Stand,
+    Split,
+    DoubleDown,
+    Surrender,
+}
+
+impl Action {
+    /// Every action, in the order of the options in `Options`.
+    pub const ALL: [Action; 5] = [
+        Action::Hit,
+        Action::Stand,
+        Action::Split,
+        Action::DoubleDown,
+        Action::Surrender,
+    ];
+
+    /// Method that returns the name of the action.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Hit => "hit",
+            Action::Stand => "stand",
+            Action::Split => "split",
+            Action::DoubleDown => "double down",
+            Action::Surrender => "surrender",
+        }
+    }
+
+    /// Method that returns the option that must be available to play the action.
+    pub fn option(&self) -> Options {
+        match self {
+            Action::Hit => Options::HIT,
+            Action::Stand => Options::STAND,
+            Action::Split => Options::SPLIT,
+            Action::DoubleDown => Options::DOUBLE,
+            Action::Surrender => Options::SURRENDER,
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Action {
+    type Err = BlackjackGameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Action::ALL
+            .into_iter()
+            .find(|action| action.name() == s)
+            .ok_or_else(|| {
+                BlackjackGameError::new(format!(
+                    "unknown option '{}', expected one of: {}",
+                    s,
+                    Options::all()
+                ))
+            })
+    }
+}
+
+bitflags! {
+    /// The set of options a player may choose from to play their current hand.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Options: u8 {
+        const HIT = 1;
+        const STAND = 1 << 1;
+        const SPLIT = 1 << 2;
+        const DOUBLE = 1 << 3;
+        const SURRENDER = 1 << 4;
+    }
+}
+
+impl Options {
+    /// Associated method that returns the option with the given name, i.e. "hit", "stand", "split", "double down" or "surrender".
+    /// Returns `None` if `name` does not name an option. Unlike the `from_name()` generated by bitflags, which takes the name of a flag
+    /// such as "DOUBLE", it takes the name of the action.
+    pub fn from_action_name(name: &str) -> Option<Options> {
+        name.parse::<Action>().ok().map(|action| action.option())
+    }
+
+    /// Method that returns the actions of all the options contained in `self`.
+    pub fn actions(&self) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|action| self.contains_action(*action))
+            .collect()
+    }
+
+    /// Method that returns the names of all the options contained in `self`.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.actions().iter().map(|action| action.name()).collect()
+    }
+
+    /// Method that returns true if the option of `action` is contained in `self`, false otherwise.
+    pub fn contains_action(&self, action: Action) -> bool {
+        self.contains(action.option())
+    }
+
+    /// Method that returns true if the player may hit their current hand.
+    pub fn allows_hit(&self) -> bool {
+        self.contains(Options::HIT)
+    }
+
+    /// Method that returns true if the player may stand on their current hand.
+    pub fn allows_stand(&self) -> bool {
+        self.contains(Options::STAND)
+    }
+
+    /// Method that returns true if the player may split their current hand.
+    pub fn allows_split(&self) -> bool {
+        self.contains(Options::SPLIT)
+    }
+
+    /// Method that returns true if the player may double down on their current hand.
+    pub fn allows_double(&self) -> bool {
+        self.contains(Options::DOUBLE)
+    }
+
+    /// Method that returns true if the player may surrender their current hand.
+    pub fn allows_surrender(&self) -> bool {
+        self.contains(Options::SURRENDER)
+    }
+}
+
+impl From<Action> for Options {
+    fn from(action: Action) -> Self {
+        action.option()
+    }
+}
+
+impl FromIterator<Action> for Options {
+    fn from_iter<I: IntoIterator<Item = Action>>(iter: I) -> Self {
+        iter.into_iter().fold(Options::empty(), |options, action| {
+            options | action.option()
+        })
+    }
+}
+
+impl Display for Options {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.names().join(", "))
+    }
+}
+
+/// The rule that decided how a hand is played, see `DecisionStrategy::decide_option_with_reason()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecisionReason {
+    /// An entry of one of the basic strategy lookup tables, holds the name of the table
+    Table(&'static str),
+    /// One of the built in playing deviations that depend on the count, holds a description of the deviation
+    Deviation(&'static str),
+    /// A custom deviation of a `DeviationStrategy`, holds the index of the deviation
+    CustomDeviation(usize),
+}
+
+impl Display for DecisionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecisionReason::Table(table) => write!(f, "{} table", table),
+            DecisionReason::Deviation(deviation) => write!(f, "deviation: {}", deviation),
+            DecisionReason::CustomDeviation(i) => write!(f, "custom deviation #{}", i),
+        }
+    }
 }
 
 /// Trait for a generic decision strategy. Has only one required method `decide_option()`,
@@ -88,22 +311,94 @@ impl BetState {
 /// The implementer may implement a custom decision strategy based on the state of the table
 pub trait DecisionStrategy {
     /// Method that takes `self` by reference, `decision_state` representing the state of the table and the count,
-    /// and `options` a `HashSet<String>` representing the valid options to a player may choose to play their current hand.
-    /// This method returns a string representing the most optimal way to play the current hand given its inputs
+    /// and `options` an `Options` set representing the valid options to a player may choose to play their current hand.
+    /// This method returns the `Action` that is the most optimal way to play the current hand given its inputs
     fn decide_option<'a>(
         &self,
         decision_state: TableState<'a>,
-        options: HashSet<String>,
-    ) -> Result<String, BlackjackGameError>;
+        options: Options,
+    ) -> Result<Action, BlackjackGameError>;
+
+    /// Method that decides the option exactly like `decide_option()`, but also returns the rule that decided it when the strategy can tell.
+    /// The default implementation returns no reason, strategies built from lookup tables and deviations override it.
+    fn decide_option_with_reason<'a>(
+        &self,
+        decision_state: TableState<'a>,
+        options: Options,
+    ) -> Result<(Action, Option<DecisionReason>), BlackjackGameError> {
+        self.decide_option(decision_state, options)
+            .map(|option| (option, None))
+    }
 
     /// Method that return true or false depending whether an insurance bet should be placed or not
     fn take_insurance(&self, true_count: f32) -> bool;
+
+    /// Method for getting the name of the decision strategy
+    fn name(&self) -> String;
+
+    /// Method for cloning the decision strategy into a new trait object, so a strategy created at runtime can be duplicated.
+    /// Lookup tables are shared with the clone rather than copied.
+    fn clone_box(&self) -> Box<dyn DecisionStrategy + Send + 'static>;
+}
+
+impl<D: DecisionStrategy + ?Sized> DecisionStrategy for Box<D> {
+    fn decide_option<'a>(
+        &self,
+        decision_state: TableState<'a>,
+        options: Options,
+    ) -> Result<Action, BlackjackGameError> {
+        (**self).decide_option(decision_state, options)
+    }
+
+    fn decide_option_with_reason<'a>(
+        &self,
+        decision_state: TableState<'a>,
+        options: Options,
+    ) -> Result<(Action, Option<DecisionReason>), BlackjackGameError> {
+        (**self).decide_option_with_reason(decision_state, options)
+    }
+
+    fn take_insurance(&self, true_count: f32) -> bool {
+        (**self).take_insurance(true_count)
+    }
+
+    fn name(&self) -> String {
+        (**self).name()
+    }
+
+    fn clone_box(&self) -> Box<dyn DecisionStrategy + Send + 'static> {
+        (**self).clone_box()
+    }
+}
+
+impl Clone for Box<dyn DecisionStrategy + Send + 'static> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl Clone for Box<dyn BettingStrategy + Send + 'static> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl Clone for Box<dyn CountingStrategy + Send + 'static> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
 }
 
 /// Trait for a generic betting strategy. Allows greater composibility and customizeability for any playing strategy.
 pub trait BettingStrategy {
     /// Required method, takes `state` a `BetState` object and returns the appropriate bet value determined by the implemented strategy.
     fn bet(&self, state: BetState) -> u32;
+
+    /// Required method, returns the name of the betting strategy.
+    fn name(&self) -> String;
+
+    /// Required method, clones the betting strategy into a new trait object, so a strategy created at runtime can be duplicated.
+    fn clone_box(&self) -> Box<dyn BettingStrategy + Send + 'static>;
 }
 
 /// Trait for a specific counting srategy. Can be implemented by any object that can be used to implement a counting strategy
@@ -114,6 +409,7 @@ pub trait CountingStrategy {
     fn update(&mut self, card: Arc<Card>);
     /// Returns the current state of the table to the caller, i.e. a new `TableState` that is essentially a vector representing all
     /// of the relevant information a player would need to determine the most optimal playing strategy.
+    /// The counts are taken from `self.running_count()` and `self.true_count()`.
     fn get_current_table_state<'a>(
         &self,
         hand: &'a Vec<Arc<Card>>,
@@ -121,18 +417,64 @@ pub trait CountingStrategy {
         bet: u32,
         balance: f32,
         dealers_up_card: Arc<Card>,
-    ) -> TableState<'a>;
+        cards_remaining: u32,
+        is_split_hand: bool,
+        num_player_hands: u8,
+    ) -> TableState<'a> {
+        TableState::new(
+            hand,
+            hand_value,
+            bet,
+            dealers_up_card,
+            BetState::new(
+                balance,
+                self.running_count(),
+                self.true_count(),
+                self.num_decks(),
+            ),
+        )
+        .with_hand_context(cards_remaining, is_split_hand, num_player_hands)
+    }
 
     /// Resets the current strategy, meant be used when ever the deck gets shuffled or when starting a new game.
     fn reset(&mut self);
     /// Returns the running count as an `f32` of the counting strategy, it is implemented.
     fn running_count(&self) -> f32;
-    /// Returns the true count as an `f32` of the counting strategy, however it is implemented.
+    /// Returns the true count as an `f32` of the counting strategy, however it is implemented. Betting and deviations treat a positive true
+    /// count as an advantage, so unbalanced counts return their running count relative to their pivot per deck remaining instead.
     fn true_count(&self) -> f32;
     /// Returns the number of decks being used with the counting strategy.
     fn num_decks(&self) -> u32;
+    /// Returns the number of cards counted since the strategy was last reset.
+    fn cards_counted(&self) -> u32;
+    /// Writes the running count, the number of cards counted and the true count of the strategy to `f`, one per line.
+    /// `PlayerStrategy` and `PlayerStrategyDyn` are displayed with it.
+    fn fmt_state(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<21}{}\n{:<21}{}\n{:<21}{:.2}",
+            "running count:",
+            self.running_count(),
+            "total cards counted:",
+            self.cards_counted(),
+            "true count:",
+            self.true_count(),
+        )
+    }
     /// Returns a string representing the name of the strategy.
     fn name(&self) -> String;
+    /// Clones the counting strategy into a new trait object, so a strategy created at runtime can be duplicated.
+    /// The clone starts from the current count and is counted independently of `self` afterwards.
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static>;
+}
+
+/// The fewest decks a true count is ever divided by, so the true count stays finite and keeps its sign however many cards were counted.
+const MIN_DECKS_REMAINING: f32 = 0.25;
+
+/// Helper function shared by the counting strategies, estimates the number of decks left in a shoe of `num_decks` decks
+/// once `total_cards_counted` cards were counted. The estimate never drops below `MIN_DECKS_REMAINING`.
+fn estimated_decks_remaining(num_decks: u32, total_cards_counted: i32) -> f32 {
+    ((num_decks as f32) - ((total_cards_counted as f32) / 52.0)).max(MIN_DECKS_REMAINING)
 }
 
 /// A trait for creating dynamic strategy trait objects. Usefull for when testing multiple strategies against eachother.
@@ -143,13 +485,20 @@ pub trait Strategy {
     fn bet(&self, state: BetState) -> u32;
 
     /// Method that returns the optimal decision according to the implemented strategy.
-    /// Takes `current_state` a `TableState` struct representing the state of table and `options` a `HashSet` of `String`
+    /// Takes `current_state` a `TableState` struct representing the state of table and `options` an `Options` set
     /// representing all valid options that can currently be taken.
     fn decide_option<'a>(
         &self,
         current_state: TableState<'a>,
-        options: HashSet<String>,
-    ) -> Result<String, BlackjackGameError>;
+        options: Options,
+    ) -> Result<Action, BlackjackGameError>;
+
+    /// Method that returns the optimal decision like `decide_option()`, along with the rule that decided it if the decision strategy can tell.
+    fn decide_option_with_reason<'a>(
+        &self,
+        current_state: TableState<'a>,
+        options: Options,
+    ) -> Result<(Action, Option<DecisionReason>), BlackjackGameError>;
 
     /// Resets the current strategy. The strategy should have the same state when it was instantiated after this method is called.
     fn reset(&mut self);
@@ -161,7 +510,8 @@ pub trait Strategy {
     /// Takes `balance` as a parameter which represents the current balance of the player that is playing using the strategy.
     fn get_current_bet_state(&self, balance: f32) -> BetState;
 
-    /// Returns a `TableState` struct that represents the state of the table.
+    /// Returns a `TableState` struct that represents the state of the table, along with the number of cards left in the shoe,
+    /// whether the hand was dealt from a split and the number of hands the player has in play.
     fn get_current_table_state<'a>(
         &self,
         hand: &'a Vec<Arc<Card>>,
@@ -169,18 +519,39 @@ pub trait Strategy {
         bet: u32,
         balance: f32,
         dealers_up_card: Arc<Card>,
+        cards_remaining: u32,
+        is_split_hand: bool,
+        num_player_hands: u8,
     ) -> TableState<'a>;
 
     /// Method that decides whether or not to take insurance.
     /// All necessary information to make the decision should already be contained in the struct that implements the trait.
     fn take_insurance(&self) -> bool;
 
-    /// Method for getting a label that decsribes this strategy
-    fn label(&self) -> String;
+    /// Method for getting a label that decsribes this strategy, the label is shared so it can be attached to every summary without allocating
+    fn label(&self) -> Arc<str>;
+
+    /// Method for getting the name of the decision strategy used by this strategy
+    fn decision_strategy_name(&self) -> String;
+
+    /// Method for getting the name of the betting strategy used by this strategy
+    fn betting_strategy_name(&self) -> String;
+
+    /// Method for getting a label that describes the full strategy, i.e. `self.label()` along with the names of the decision and
+    /// betting strategies, so strategies sharing a counting strategy can still be told apart
+    fn full_label(&self) -> String {
+        format!(
+            "{} ({}, {})",
+            self.label(),
+            self.decision_strategy_name(),
+            self.betting_strategy_name()
+        )
+    }
 }
 
 /// Struct that encapsulates the logic needed for a simple margin based betting strategy, i.e. for each positive value that the true count takes it will compute the bet as
 /// `self.min_bet` * `self.margin` * ceiling(true_count)
+#[derive(Clone)]
 pub struct MarginBettingStrategy {
     margin: f32,
     min_bet: u32,
@@ -206,135 +577,333 @@ impl BettingStrategy for MarginBettingStrategy {
             u32::min(state.balance as u32, self.min_bet)
         }
     }
+
+    fn name(&self) -> String {
+        String::from("Margin")
+    }
+
+    fn clone_box(&self) -> Box<dyn BettingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
+}
+
+/// A single step of a bet ramp, once the true count reaches `true_count` the player bets `units` betting units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RampStep {
+    pub true_count: f32,
+    pub units: u32,
+}
+
+/// Struct that encapsulates the logic needed for a bet ramp, i.e. the player bets the units of the highest step of the ramp whose true count has been reached,
+/// and a single unit while the true count is below the first step.
+#[derive(Debug, Clone)]
+pub struct RampBettingStrategy {
+    ramp: Vec<RampStep>,
+    unit: u32,
+}
+
+impl RampBettingStrategy {
+    /// Associated method for returning a new `RampBettingStrategy` struct. Returns an error if the ramp is empty, its true counts are not strictly increasing
+    /// or any bet would be zero.
+    pub fn new(ramp: Vec<RampStep>, unit: u32) -> Result<RampBettingStrategy, ConfigError> {
+        if ramp.is_empty() {
+            return Err(ConfigError::new("ramp", "must not be empty"));
+        }
+        if ramp.iter().any(|step| !step.true_count.is_finite()) {
+            return Err(ConfigError::new(
+                "ramp",
+                "true counts must be finite numbers",
+            ));
+        }
+        if ramp
+            .windows(2)
+            .any(|steps| steps[0].true_count >= steps[1].true_count)
+        {
+            return Err(ConfigError::new(
+                "ramp",
+                "must be sorted by strictly increasing true count",
+            ));
+        }
+        if ramp.iter().any(|step| step.units == 0) {
+            return Err(ConfigError::new("ramp", "units must be at least 1"));
+        }
+        if unit == 0 {
+            return Err(ConfigError::new("unit", "must be at least 1"));
+        }
+        Ok(RampBettingStrategy { ramp, unit })
+    }
+}
+
+impl BettingStrategy for RampBettingStrategy {
+    /// Returns the units of the highest step reached by the true count multiplied by the unit size, capped by the player's balance.
+    fn bet(&self, state: BetState) -> u32 {
+        let units = self
+            .ramp
+            .iter()
+            .rev()
+            .find(|step| state.true_count >= step.true_count)
+            .map_or(1, |step| step.units);
+        u32::min(state.balance as u32, self.unit.saturating_mul(units))
+    }
+
+    fn name(&self) -> String {
+        String::from("Ramp")
+    }
+
+    fn clone_box(&self) -> Box<dyn BettingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
+}
+
+/// Parses a bet ramp written as comma separated `true_count:units` pairs, e.g. "0:1,1:2,2:4,3:8".
+/// Only checks the syntax, `RampBettingStrategy::new()` validates the steps themselves.
+pub fn parse_ramp(ramp: &str) -> Result<Vec<RampStep>, ConfigError> {
+    ramp.split(',')
+        .map(|step| {
+            let (true_count, units) = step.split_once(':').ok_or(ConfigError::new(
+                "ramp",
+                "must be a comma separated list of true_count:units pairs",
+            ))?;
+            let true_count = true_count
+                .trim()
+                .parse::<f32>()
+                .map_err(|_| ConfigError::new("ramp", "true counts must be numbers"))?;
+            let units = units
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| ConfigError::new("ramp", "units must be whole numbers"))?;
+            Ok(RampStep { true_count, units })
+        })
+        .collect()
+}
+
+/// Struct that encapsulates a flat betting strategy, i.e. the same amount is bet every hand regardless of the count.
+#[derive(Debug, Clone)]
+pub struct FlatBettingStrategy {
+    amount: u32,
+}
+
+impl FlatBettingStrategy {
+    /// Associated method for returning a new `FlatBettingStrategy` struct, returns an error if `amount` is zero.
+    pub fn new(amount: u32) -> Result<FlatBettingStrategy, ConfigError> {
+        if amount == 0 {
+            return Err(ConfigError::new("flat_amount", "must be at least 1"));
+        }
+        Ok(FlatBettingStrategy { amount })
+    }
+}
+
+impl BettingStrategy for FlatBettingStrategy {
+    /// Returns `self.amount`, capped by the player's balance.
+    fn bet(&self, state: BetState) -> u32 {
+        u32::min(state.balance as u32, self.amount)
+    }
+
+    fn name(&self) -> String {
+        String::from("Flat")
+    }
+
+    fn clone_box(&self) -> Box<dyn BettingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
+}
+
+/// The player's advantage gained per point of true count, a common rule of thumb for balanced counts.
+const KELLY_ADVANTAGE_PER_TRUE_COUNT: f32 = 0.005;
+
+/// The player's advantage at a true count of zero, i.e. the house edge against basic strategy.
+const KELLY_BASE_ADVANTAGE: f32 = -0.005;
+
+/// The variance of the result of a single hand of blackjack, in squared units of the bet.
+const BLACKJACK_VARIANCE: f32 = 1.3;
+
+/// Struct that encapsulates a fractional Kelly betting strategy, i.e. the player bets `fraction` of the share of their balance that maximizes
+/// the expected growth of the balance. The advantage is estimated from the true count, while it is negative the minimum bet is placed.
+#[derive(Debug, Clone)]
+pub struct KellyBettingStrategy {
+    fraction: f32,
+    min_bet: u32,
+}
+
+impl KellyBettingStrategy {
+    /// Associated method for returning a new `KellyBettingStrategy` struct, returns an error unless `fraction` is in (0, 1] and `min_bet` is at least 1.
+    pub fn new(fraction: f32, min_bet: u32) -> Result<KellyBettingStrategy, ConfigError> {
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            return Err(ConfigError::new(
+                "kelly_fraction",
+                "must be greater than 0 and at most 1",
+            ));
+        }
+        if min_bet == 0 {
+            return Err(ConfigError::new("min_bet", "must be at least 1"));
+        }
+        Ok(KellyBettingStrategy { fraction, min_bet })
+    }
+}
+
+impl BettingStrategy for KellyBettingStrategy {
+    /// Returns the fractional Kelly bet for the advantage at the current true count, at least `self.min_bet` and capped by the player's balance.
+    fn bet(&self, state: BetState) -> u32 {
+        let advantage = KELLY_BASE_ADVANTAGE + KELLY_ADVANTAGE_PER_TRUE_COUNT * state.true_count;
+        let kelly_bet = self.fraction * advantage / BLACKJACK_VARIANCE * state.balance;
+        let bet = u32::max(self.min_bet, kelly_bet.max(0.0) as u32);
+        u32::min(state.balance as u32, bet)
+    }
+
+    fn name(&self) -> String {
+        String::from("Kelly")
+    }
+
+    fn clone_box(&self) -> Box<dyn BettingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
+}
+
+/// Struct that holds the basic strategy lookup tables, keyed by the player's hand value and the dealer's up card.
+/// The tables never change once built, so a single instance is shared by every decision strategy that needs them.
+pub struct LookupTables {
+    hard_totals: HashMap<(u8, u8), Action>,
+    soft_totals: HashMap<(u8, u8), Action>,
+    /// `None` when the pair is not split and is played by its total instead
+    pair_totals: HashMap<(u8, u8), Option<Action>>,
+    surrender: HashMap<(u8, u8), Action>,
+}
+
+lazy_static! {
+    /// The basic strategy lookup tables, built once and shared across all simulations.
+    static ref BASIC_STRATEGY_TABLES: Arc<LookupTables> =
+        Arc::new(BasicStrategy::build_lookup_tables());
 }
 
 /// A struct that implments the `DecisionStrategy` trait. Decides playing option according to strict basic strategy only.
 /// The decision strategy only requires what knowing what the dealers face up card is and the players current cards.
+#[derive(Clone)]
 pub struct BasicStrategy {
-    hard_totals: HashMap<(u8, u8), String>,
-    soft_totals: HashMap<(u8, u8), String>,
-    pair_totals: HashMap<(u8, u8), String>,
-    surrender: HashMap<(u8, u8), String>,
+    tables: Arc<LookupTables>,
 }
 
 impl BasicStrategy {
     /// Associated method for populating the lookup tables used in basic strategy, intended to be a helper method.
-    fn build_lookup_tables() -> (
-        HashMap<(u8, u8), String>,
-        HashMap<(u8, u8), String>,
-        HashMap<(u8, u8), String>,
-        HashMap<(u8, u8), String>,
-    ) {
+    fn build_lookup_tables() -> LookupTables {
         // Populate hard_totals lookup table
-        let mut hard_totals: HashMap<(u8, u8), String> = HashMap::new();
+        let mut hard_totals: HashMap<(u8, u8), Action> = HashMap::new();
         for i in 2..=21 {
             for j in 1..=10 {
-                let mut option = String::new();
-                match i {
+                let option = match i {
                     9 => match j {
-                        3..=6 => option.push_str("double down"),
-                        _ => option.push_str("hit"),
+                        3..=6 => Action::DoubleDown,
+                        _ => Action::Hit,
                     },
                     10 => match j {
-                        2..=9 => option.push_str("double down"),
-                        _ => option.push_str("hit"),
+                        2..=9 => Action::DoubleDown,
+                        _ => Action::Hit,
                     },
-                    11 => option.push_str("double down"),
+                    11 => Action::DoubleDown,
                     12 => match j {
-                        1..=3 | 7..=10 => option.push_str("hit"),
-                        _ => option.push_str("stand"),
+                        1..=3 | 7..=10 => Action::Hit,
+                        _ => Action::Stand,
                     },
                     13..=16 => match j {
-                        2..=6 => option.push_str("stand"),
-                        _ => option.push_str("hit"),
+                        2..=6 => Action::Stand,
+                        _ => Action::Hit,
                     },
-                    17..=21 => option.push_str("stand"),
-                    _ => option.push_str("hit"),
-                }
+                    17..=21 => Action::Stand,
+                    _ => Action::Hit,
+                };
                 hard_totals.insert((i, j), option);
             }
         }
 
         // Populate soft totals i.e. hand that contains an ace
-        let mut soft_totals: HashMap<(u8, u8), String> = HashMap::new();
-        for i in 3..=10 {
+        let mut soft_totals: HashMap<(u8, u8), Action> = HashMap::new();
+        for i in 3..=11 {
             for j in 1..=10 {
-                let mut option = String::new();
-                match i {
-                    3..=7 => option.push_str("hit"),
-                    8 => match j {
-                        2..=6 => option.push_str("double down"),
-                        7 | 8 => option.push_str("stand"),
-                        _ => option.push_str("hit"),
+                // The soft total is keyed by its low value, i.e. 3 is A-2 or soft 13 and 11 is soft 21. Follows the multi-deck S17 chart
+                let option = match i {
+                    3 | 4 => match j {
+                        5 | 6 => Action::DoubleDown,
+                        _ => Action::Hit,
                     },
-                    9 => match j {
-                        6 => option.push_str("double down"),
-                        _ => option.push_str("stand"),
+                    5 | 6 => match j {
+                        4..=6 => Action::DoubleDown,
+                        _ => Action::Hit,
                     },
-                    _ => option.push_str("stand"),
-                }
+                    7 => match j {
+                        3..=6 => Action::DoubleDown,
+                        _ => Action::Hit,
+                    },
+                    8 => match j {
+                        3..=6 => Action::DoubleDown,
+                        2 | 7 | 8 => Action::Stand,
+                        _ => Action::Hit,
+                    },
+                    _ => Action::Stand,
+                };
 
                 soft_totals.insert((i, j), option);
             }
         }
 
         // Populate pair totals
-        let mut pair_totals: HashMap<(u8, u8), String> = HashMap::new();
+        let mut pair_totals: HashMap<(u8, u8), Option<Action>> = HashMap::new();
         for i in (2..=20).step_by(2) {
             for j in 1..=10 {
-                let mut option = String::new();
-                match i {
-                    2 => option.push_str("split"),
+                let option = match i {
+                    2 => Some(Action::Split),
                     4 | 6 => match j {
-                        2..=7 => option.push_str("split"),
-                        _ => option.push_str("default"),
+                        2..=7 => Some(Action::Split),
+                        _ => None,
                     },
                     8 => match j {
-                        5 | 6 => option.push_str("split"),
-                        _ => option.push_str("default"),
+                        5 | 6 => Some(Action::Split),
+                        _ => None,
                     },
-                    10 => option.push_str("default"),
+                    10 => None,
                     12 => match j {
-                        2..=6 => option.push_str("split"),
-                        _ => option.push_str("default"),
+                        2..=6 => Some(Action::Split),
+                        _ => None,
                     },
                     14 => match j {
-                        2..=7 => option.push_str("split"),
-                        _ => option.push_str("default"),
+                        2..=7 => Some(Action::Split),
+                        _ => None,
                     },
-                    16 => option.push_str("split"),
+                    16 => Some(Action::Split),
                     18 => match j {
-                        2..=6 | 8 | 9 => option.push_str("split"),
-                        _ => option.push_str("default"),
+                        2..=6 | 8 | 9 => Some(Action::Split),
+                        _ => None,
                     },
-                    20 => option.push_str("default"),
+                    20 => None,
                     _ => todo!(),
-                }
+                };
 
                 pair_totals.insert((i, j), option);
             }
         }
 
         // Populate surrender options if available or necessary
-        let mut surrender: HashMap<(u8, u8), String> = HashMap::new();
-        surrender.insert((15, 10), "surrender".to_string());
-        surrender.insert((16, 9), "surrender".to_string());
-        surrender.insert((16, 10), "surrender".to_string());
-        surrender.insert((16, 1), "surrender".to_string());
+        let mut surrender: HashMap<(u8, u8), Action> = HashMap::new();
+        surrender.insert((15, 10), Action::Surrender);
+        surrender.insert((16, 9), Action::Surrender);
+        surrender.insert((16, 10), Action::Surrender);
+        surrender.insert((16, 1), Action::Surrender);
+
+        LookupTables {
+            hard_totals,
+            soft_totals,
+            pair_totals,
+            surrender,
+        }
+    }
 
-        (hard_totals, soft_totals, pair_totals, surrender)
+    /// Associated method that returns a handle to the shared basic strategy lookup tables.
+    fn shared_lookup_tables() -> Arc<LookupTables> {
+        Arc::clone(&BASIC_STRATEGY_TABLES)
     }
 
     /// Associated method for creating a new `BasicStrategy` struct.
     pub fn new() -> BasicStrategy {
-        let (hard_totals, soft_totals, pair_totals, surrender) =
-            BasicStrategy::build_lookup_tables();
-
         BasicStrategy {
-            hard_totals,
-            soft_totals,
-            pair_totals,
-            surrender,
+            tables: BasicStrategy::shared_lookup_tables(),
         }
     }
 }
@@ -344,105 +913,126 @@ impl DecisionStrategy for BasicStrategy {
     fn decide_option<'a>(
         &self,
         decision_state: TableState<'a>,
-        options: HashSet<String>,
-    ) -> Result<String, BlackjackGameError> {
-        let mut option = String::new();
+        options: Options,
+    ) -> Result<Action, BlackjackGameError> {
+        self.decide_option_with_reason(decision_state, options)
+            .map(|(option, _)| option)
+    }
+
+    fn decide_option_with_reason<'a>(
+        &self,
+        decision_state: TableState<'a>,
+        options: Options,
+    ) -> Result<(Action, Option<DecisionReason>), BlackjackGameError> {
+        let mut option = None;
+        let mut reason = DecisionReason::Table("hard totals");
         let dealers_card = decision_state.dealers_up_card.val;
 
-        // First check if we should surrender or not
-        if options.contains("surrender") {
+        // First check if we should split, so pairs are split rather than surrendered
+        if options.allows_split() {
             if let Some(o) = self
-                .surrender
+                .tables
+                .pair_totals
                 .get(&(decision_state.hand_value[0], dealers_card))
             {
-                option.push_str(o.as_str());
+                if *o == Some(Action::Split) {
+                    option = *o;
+                    reason = DecisionReason::Table("pair splitting");
+                }
             }
         }
 
-        if option.is_empty() && options.contains("split") {
+        // Then check if we should surrender or not
+        if option.is_none() && options.allows_surrender() {
             if let Some(o) = self
-                .pair_totals
+                .tables
+                .surrender
                 .get(&(decision_state.hand_value[0], dealers_card))
             {
-                if o == "split" {
-                    option.push_str(o);
-                }
+                option = Some(*o);
+                reason = DecisionReason::Table("surrender");
             }
         }
 
         // Check if players hand is a soft total, if so default ot soft totals lookup table
-        if option.is_empty()
+        if option.is_none()
             && decision_state.hand_value.len() == 2
             && decision_state.hand_value[0] <= 21
             && decision_state.hand_value[1] <= 21
         {
             if let Some(opt) = self
+                .tables
                 .soft_totals
                 .get(&(decision_state.hand_value[0], dealers_card))
             {
-                if options.contains(opt.as_str()) {
-                    option.push_str(opt.as_str());
-                } else if opt == "double down" && !options.contains("double down") {
-                    option.push_str("hit");
+                reason = DecisionReason::Table("soft totals");
+                if options.contains_action(*opt) {
+                    option = Some(*opt);
+                } else if *opt == Action::DoubleDown && !options.allows_double() {
+                    option = Some(soft_double_fallback(decision_state.hand_value[0]));
                 } else {
                     return Err(BlackjackGameError {
-                        message: format!("option chosen: {}, not available for valid options {:?} with soft total of {}", opt, options, decision_state.hand_value[0])
+                        message: format!("option chosen: {}, not available for valid options [{}] with soft total of {}", opt, options, decision_state.hand_value[0])
                     });
                 }
             }
         }
 
-        if option.is_empty() {
+        if option.is_none() {
             match self
+                .tables
                 .hard_totals
                 .get(&(decision_state.hand_value[0], dealers_card))
             {
-                Some(o) if options.contains(o.as_str()) => option.push_str(o.as_str()),
-                Some(o) if o == "double down" && !options.contains("double down") => {
-                    option.push_str("hit");
+                Some(&o) if options.contains_action(o) => option = Some(o),
+                Some(Action::DoubleDown) if !options.allows_double() => {
+                    option = Some(Action::Hit);
                 }
-                _ => {
+                Some(o) => {
                     return Err(BlackjackGameError {
-                        message: "option {o} not a valid choice".to_string(),
+                        message: format!(
+                            "option chosen: {}, not available for valid options [{}] with hard total of {}",
+                            o, options, decision_state.hand_value[0]
+                        ),
                     })
                 }
+                // Reported by the check below
+                None => {}
             }
         }
 
-        if option.is_empty() {
-            return Err(BlackjackGameError {
-                message: "no valid option was selected".to_string(),
-            });
+        match option {
+            Some(option) => Ok((option, Some(reason))),
+            None => Err(no_valid_option(&decision_state, options)),
         }
-
-        Ok(option)
     }
 
     fn take_insurance(&self, true_count: f32) -> bool {
         // Never take insurance when employing basic strategy
         false
     }
+
+    fn name(&self) -> String {
+        String::from("Basic Strategy")
+    }
+
+    fn clone_box(&self) -> Box<dyn DecisionStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
 /// A struct for implementing S17 playing deviations i.e. the deviations that take into account the running/true count for deriving playing decisions.
 /// S17 stands for game implementations where the dealer stands on soft 17's, hence this struct will make playing decisions under the assumption that dealers will stand
 /// on all hands with a value of 17.
+#[derive(Clone)]
 pub struct S17DeviationStrategy {
-    hard_totals: HashMap<(u8, u8), String>,
-    soft_totals: HashMap<(u8, u8), String>,
-    pair_totals: HashMap<(u8, u8), String>,
-    // surrender: HashMap<(u8, u8), String>,
+    tables: Arc<LookupTables>,
 }
 
 impl S17DeviationStrategy {
     pub fn new() -> Self {
-        let (hard_totals, soft_totals, pair_totals, _surrender) =
-            BasicStrategy::build_lookup_tables();
         S17DeviationStrategy {
-            hard_totals,
-            soft_totals,
-            pair_totals,
-            // surrender,
+            tables: BasicStrategy::shared_lookup_tables(),
         }
     }
 }
@@ -453,46 +1043,23 @@ impl DecisionStrategy for S17DeviationStrategy {
     fn decide_option<'a>(
         &self,
         decision_state: TableState<'a>,
-        options: HashSet<String>,
-    ) -> Result<String, BlackjackGameError> {
-        let mut option = String::new();
-        let dealers_card = decision_state.dealers_up_card.val;
+        options: Options,
+    ) -> Result<Action, BlackjackGameError> {
+        self.decide_option_with_reason(decision_state, options)
+            .map(|(option, _)| option)
+    }
 
-        // First check if we should surrender or not
-        if options.contains("surrender") {
-            if decision_state.hand_value.len() == 1 {
-                if decision_state.hand_value[0] == 16 {
-                    option.push_str("surrender");
-                } else if decision_state.hand_value[0] == 15
-                    && dealers_card == 10
-                    && f32::ceil(decision_state.running_count) >= 0.0
-                {
-                    option.push_str("surrender");
-                } else if decision_state.hand_value[0] == 15
-                    && dealers_card == 1
-                    && f32::floor(decision_state.true_count) >= 2.0
-                {
-                    option.push_str("surrender");
-                }
-            } else {
-                if decision_state.hand_value[0] == 16 || decision_state.hand_value[1] == 16 {
-                    option.push_str("surrender");
-                } else if (decision_state.hand_value[0] == 15 || decision_state.hand_value[1] == 15)
-                    && dealers_card == 10
-                    && f32::ceil(decision_state.running_count) >= 0.0
-                {
-                    option.push_str("surrender");
-                } else if (decision_state.hand_value[0] == 15 || decision_state.hand_value[1] == 15)
-                    && dealers_card == 1
-                    && f32::floor(decision_state.true_count) >= 2.0
-                {
-                    option.push_str("surrender");
-                }
-            }
-        }
+    fn decide_option_with_reason<'a>(
+        &self,
+        decision_state: TableState<'a>,
+        options: Options,
+    ) -> Result<(Action, Option<DecisionReason>), BlackjackGameError> {
+        let mut option = None;
+        let mut reason = DecisionReason::Table("hard totals");
+        let dealers_card = decision_state.dealers_up_card.val;
 
         // Check splitting conditions
-        if option.is_empty() && options.contains("split") {
+        if option.is_none() && options.allows_split() {
             // First check the deviations
             if decision_state.hand[0].val == 10 && decision_state.hand[1].val == 10 {
                 // Check the deviations, if we dont have any conditions met to deviate we should not split at all
@@ -502,51 +1069,82 @@ impl DecisionStrategy for S17DeviationStrategy {
                     || (true_count >= 5.0 && dealers_card == 5)
                     || (true_count >= 4.0 && dealers_card == 6)
                 {
-                    option.push_str("split");
+                    option = Some(Action::Split);
+                    reason =
+                        DecisionReason::Deviation("split 10s vs 4, 5 or 6 at a high true count");
                 }
             } else {
                 // Check basic strategy lookup table
                 if let Some(o) = self
+                    .tables
                     .pair_totals
                     .get(&(decision_state.hand_value[0], dealers_card))
                 {
-                    if o == "split" {
-                        option.push_str(o);
+                    if *o == Some(Action::Split) {
+                        option = *o;
+                        reason = DecisionReason::Table("pair splitting");
                     }
                 }
             }
         }
 
+        // Check for surrender after splitting, so pairs are split rather than surrendered, and only with a hard total
+        if option.is_none() && options.allows_surrender() && decision_state.hand_value.len() == 1 {
+            let hand_value = decision_state.hand_value[0];
+            if hand_value == 16 && (dealers_card == 9 || dealers_card == 10 || dealers_card == 1) {
+                option = Some(Action::Surrender);
+                reason = DecisionReason::Table("surrender 16 vs 9, 10 or A");
+            } else if hand_value == 15
+                && dealers_card == 10
+                && f32::ceil(decision_state.running_count) >= 0.0
+            {
+                option = Some(Action::Surrender);
+                reason = DecisionReason::Deviation("surrender 15 vs 10 at running count >= 0");
+            } else if hand_value == 15
+                && dealers_card == 1
+                && f32::floor(decision_state.true_count) >= 2.0
+            {
+                option = Some(Action::Surrender);
+                reason = DecisionReason::Deviation("surrender 15 vs A at true count >= 2");
+            }
+        }
+
         // Check if players hand is a soft total and we have not made a decision yet
-        if option.is_empty()
+        if option.is_none()
             && decision_state.hand_value.len() == 2
             && decision_state.hand_value[0] <= 21
             && decision_state.hand_value[1] <= 21
         {
-            // Check if we should deviate first
-            if (decision_state.hand[0].val == 1 && decision_state.hand[1].val == 8)
-                || (decision_state.hand[0].val == 8 && decision_state.hand[1].val == 1)
-            {
+            // Check if we should deviate first, A-8 is doubled when the count is high enough and stood on otherwise
+            if is_ace_and(decision_state.hand, 8) {
                 let true_count = f32::floor(decision_state.true_count);
-                if dealers_card == 4 && true_count >= 3.0 {
-                    option.push_str("hit");
-                } else if (dealers_card == 5 || dealers_card == 6) && true_count >= 1.0 {
-                    option.push_str("hit");
+                if dealers_card == 4 && true_count >= 3.0 && options.allows_double() {
+                    option = Some(Action::DoubleDown);
+                    reason = DecisionReason::Deviation("double A-8 vs 4 at true count >= 3");
+                } else if (dealers_card == 5 || dealers_card == 6)
+                    && true_count >= 1.0
+                    && options.allows_double()
+                {
+                    option = Some(Action::DoubleDown);
+                    reason = DecisionReason::Deviation("double A-8 vs 5 or 6 at true count >= 1");
                 } else {
-                    option.push_str("stand");
+                    option = Some(Action::Stand);
+                    reason = DecisionReason::Table("soft totals");
                 }
             } else {
                 if let Some(opt) = self
+                    .tables
                     .soft_totals
                     .get(&(decision_state.hand_value[0], dealers_card))
                 {
-                    if options.contains(opt.as_str()) {
-                        option.push_str(opt.as_str());
-                    } else if opt == "double down" && !options.contains("double down") {
-                        option.push_str("hit");
+                    reason = DecisionReason::Table("soft totals");
+                    if options.contains_action(*opt) {
+                        option = Some(*opt);
+                    } else if *opt == Action::DoubleDown && !options.allows_double() {
+                        option = Some(soft_double_fallback(decision_state.hand_value[0]));
                     } else {
                         return Err(BlackjackGameError {
-                            message: format!("option chosen: {}, not available for valid options {:?} with soft total of {}", opt, options, decision_state.hand_value[0])
+                            message: format!("option chosen: {}, not available for valid options [{}] with soft total of {}", opt, options, decision_state.hand_value[0])
                         });
                     }
                 }
@@ -554,7 +1152,7 @@ impl DecisionStrategy for S17DeviationStrategy {
         }
 
         // Otherwise we have a hard total hand, check deviations
-        if option.is_empty() {
+        if option.is_none() {
             let (running_count, true_count) = (
                 f32::floor(decision_state.running_count),
                 f32::floor(decision_state.true_count),
@@ -563,92 +1161,112 @@ impl DecisionStrategy for S17DeviationStrategy {
                 if (dealers_card == 9 && true_count >= 4.0)
                     || (dealers_card == 10 && running_count > 0.0)
                 {
-                    option.push_str("stand");
+                    option = Some(Action::Stand);
+                    reason = DecisionReason::Deviation(
+                        "stand 16 vs 9 at true count >= 4 or vs 10 at running count > 0",
+                    );
                 }
             } else if decision_state.hand_value[0] == 15 {
                 if dealers_card == 10 && true_count >= 4.0 {
-                    option.push_str("stand");
+                    option = Some(Action::Stand);
+                    reason = DecisionReason::Deviation("stand 15 vs 10 at true count >= 4");
                 }
             } else if decision_state.hand_value[0] == 13 && true_count <= -1.0 {
-                option.push_str("hit");
+                option = Some(Action::Hit);
+                reason = DecisionReason::Deviation("hit 13 at true count <= -1");
             } else if decision_state.hand_value[0] == 12 {
                 if (dealers_card == 2 && true_count >= 3.0)
                     || (dealers_card == 3 && true_count >= 2.0)
                 {
-                    option.push_str("stand");
+                    option = Some(Action::Stand);
+                    reason = DecisionReason::Deviation(
+                        "stand 12 vs 2 at true count >= 3 or vs 3 at true count >= 2",
+                    );
                 } else if dealers_card == 4 && running_count < 0.0 {
-                    option.push_str("hit");
+                    option = Some(Action::Hit);
+                    reason = DecisionReason::Deviation("hit 12 vs 4 at running count < 0");
                 }
             } else if decision_state.hand_value[0] == 11 && dealers_card == 1 && true_count >= 1.0 {
-                option.push_str("hit");
+                option = Some(Action::Hit);
+                reason = DecisionReason::Deviation("hit 11 vs A at true count >= 1");
             } else if decision_state.hand_value[0] == 10 {
                 if (dealers_card == 10 || dealers_card == 1) && true_count >= 4.0 {
-                    option.push_str(if options.contains("double down") {
-                        "double down"
+                    option = Some(if options.allows_double() {
+                        Action::DoubleDown
                     } else {
-                        "hit"
+                        Action::Hit
                     });
+                    reason = DecisionReason::Deviation("double 10 vs 10 or A at true count >= 4");
                 }
             } else if decision_state.hand_value[0] == 9 {
                 if (dealers_card == 2 && true_count >= 1.0)
                     || (dealers_card == 7 && true_count >= 3.0)
                 {
-                    option.push_str(if options.contains("double down") {
-                        "double down"
+                    option = Some(if options.allows_double() {
+                        Action::DoubleDown
                     } else {
-                        "hit"
+                        Action::Hit
                     });
+                    reason = DecisionReason::Deviation(
+                        "double 9 vs 2 at true count >= 1 or vs 7 at true count >= 3",
+                    );
                 }
             }
 
             // If we havent meet conditions for a deviation, just play basic strategy
-            if option.is_empty() {
+            if option.is_none() {
                 match self
+                    .tables
                     .hard_totals
                     .get(&(decision_state.hand_value[0], dealers_card))
                 {
-                    Some(o) if options.contains(o.as_str()) => option.push_str(o.as_str()),
-                    Some(o) if o == "double down" && !options.contains("double down") => {
-                        option.push_str("hit");
+                    Some(&o) if options.contains_action(o) => option = Some(o),
+                    Some(Action::DoubleDown) if !options.allows_double() => {
+                        option = Some(Action::Hit);
                     }
-                    _ => {
+                    Some(o) => {
                         return Err(BlackjackGameError {
-                            message: "option {o} not a valid choice".to_string(),
+                            message: format!(
+                                "option chosen: {}, not available for valid options [{}] with hard total of {}",
+                                o, options, decision_state.hand_value[0]
+                            ),
                         })
                     }
+                    // Reported by the check below
+                    None => {}
                 }
             }
         }
 
-        if option.is_empty() {
-            return Err(BlackjackGameError {
-                message: "no valid option was selected".to_string(),
-            });
+        match option {
+            Some(option) => Ok((option, Some(reason))),
+            None => Err(no_valid_option(&decision_state, options)),
         }
-
-        Ok(option)
     }
 
     fn take_insurance(&self, true_count: f32) -> bool {
         true_count >= 3.0
     }
+    fn name(&self) -> String {
+        String::from("S17 Deviations")
+    }
+
+    fn clone_box(&self) -> Box<dyn DecisionStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
 /// A struct that implements optimal playing deviations when the dealer must hit on soft seventeens
+#[derive(Clone)]
 pub struct H17DeviationStrategy {
-    hard_totals: HashMap<(u8, u8), String>,
-    soft_totals: HashMap<(u8, u8), String>,
-    pair_totals: HashMap<(u8, u8), String>,
+    tables: Arc<LookupTables>,
 }
 
 impl H17DeviationStrategy {
     /// Associated method for creating a new `H17DeviationStrategy` instance.
     pub fn new() -> Self {
-        let (hard_totals, soft_totals, pair_totals, _) = BasicStrategy::build_lookup_tables();
         H17DeviationStrategy {
-            hard_totals,
-            soft_totals,
-            pair_totals,
+            tables: BasicStrategy::shared_lookup_tables(),
         }
     }
 }
@@ -657,30 +1275,23 @@ impl DecisionStrategy for H17DeviationStrategy {
     fn decide_option<'a>(
         &self,
         decision_state: TableState<'a>,
-        options: HashSet<String>,
-    ) -> Result<String, BlackjackGameError> {
-        let mut option = String::new();
-        let dealers_card = decision_state.dealers_up_card.val;
+        options: Options,
+    ) -> Result<Action, BlackjackGameError> {
+        self.decide_option_with_reason(decision_state, options)
+            .map(|(option, _)| option)
+    }
 
-        // Check for surrender, only when we have a hard total
-        if options.contains("surrender") {
-            if decision_state.hand_value.len() == 1 {
-                if decision_state.hand_value[0] == 17 && dealers_card == 1 {
-                    option.push_str("surrender");
-                } else if decision_state.hand_value[0] == 16 {
-                    option.push_str("surrender")
-                } else if decision_state.hand_value[0] == 15 {
-                    if dealers_card == 10 && decision_state.running_count < 0.0 {
-                        option.push_str("surrender");
-                    } else if dealers_card == 1 && decision_state.true_count >= 1.0 {
-                        option.push_str("surrender");
-                    }
-                }
-            }
-        }
+    fn decide_option_with_reason<'a>(
+        &self,
+        decision_state: TableState<'a>,
+        options: Options,
+    ) -> Result<(Action, Option<DecisionReason>), BlackjackGameError> {
+        let mut option = None;
+        let mut reason = DecisionReason::Table("hard totals");
+        let dealers_card = decision_state.dealers_up_card.val;
 
         // Check splitting conditions
-        if option.is_empty() && options.contains("split") {
+        if option.is_none() && options.allows_split() {
             // First check the deviations
             if decision_state.hand[0].val == 10 && decision_state.hand[1].val == 10 {
                 // Check the deviations, if we dont have any conditions met to deviate we should not split at all
@@ -690,58 +1301,87 @@ impl DecisionStrategy for H17DeviationStrategy {
                     || (true_count >= 5.0 && dealers_card == 5)
                     || (true_count >= 4.0 && dealers_card == 6)
                 {
-                    option.push_str("split");
+                    option = Some(Action::Split);
+                    reason =
+                        DecisionReason::Deviation("split 10s vs 4, 5 or 6 at a high true count");
                 }
             } else {
                 // Check basic strategy lookup table
                 if let Some(o) = self
+                    .tables
                     .pair_totals
                     .get(&(decision_state.hand_value[0], dealers_card))
                 {
-                    if o == "split" {
-                        option.push_str(o);
+                    if *o == Some(Action::Split) {
+                        option = *o;
+                        reason = DecisionReason::Table("pair splitting");
                     }
                 }
             }
         }
 
+        // Check for surrender after splitting, so pairs are split rather than surrendered, and only with a hard total
+        if option.is_none() && options.allows_surrender() && decision_state.hand_value.len() == 1 {
+            let hand_value = decision_state.hand_value[0];
+            if hand_value == 17 && dealers_card == 1 {
+                option = Some(Action::Surrender);
+                reason = DecisionReason::Deviation("surrender 17 vs A");
+            } else if hand_value == 16
+                && (dealers_card == 9 || dealers_card == 10 || dealers_card == 1)
+            {
+                option = Some(Action::Surrender);
+                reason = DecisionReason::Table("surrender 16 vs 9, 10 or A");
+            } else if hand_value == 15 {
+                if dealers_card == 10 && decision_state.running_count < 0.0 {
+                    option = Some(Action::Surrender);
+                    reason = DecisionReason::Deviation("surrender 15 vs 10 at running count < 0");
+                } else if dealers_card == 1 && decision_state.true_count >= 1.0 {
+                    option = Some(Action::Surrender);
+                    reason = DecisionReason::Deviation("surrender 15 vs A at true count >= 1");
+                }
+            }
+        }
+
         // Check soft totals next
-        if option.is_empty()
+        if option.is_none()
             && decision_state.hand_value.len() == 2
             && decision_state.hand_value[0] <= 21
             && decision_state.hand_value[1] <= 21
         {
             let true_count = f32::floor(decision_state.true_count);
-            if (decision_state.hand[0].val == 1 && decision_state.hand[1].val == 8)
-                || (decision_state.hand[0].val == 8 && decision_state.hand[1].val == 1)
-            {
-                if (true_count >= 3.0 && dealers_card == 4)
-                    || (true_count >= 1.0 && dealers_card == 5)
-                    || (decision_state.running_count < 0.0 && dealers_card == 6)
+            if is_ace_and(decision_state.hand, 8) {
+                if options.allows_double()
+                    && ((true_count >= 3.0 && dealers_card == 4)
+                        || (true_count >= 1.0 && dealers_card == 5)
+                        || (decision_state.running_count >= 0.0 && dealers_card == 6))
                 {
-                    option.push_str("hit");
+                    option = Some(Action::DoubleDown);
+                    reason = DecisionReason::Deviation(
+                        "double A-8 vs 4 or 5 at a high true count or vs 6 at running count >= 0",
+                    );
                 }
-            } else if (decision_state.hand[0].val == 1 && decision_state.hand[1].val == 6)
-                || (decision_state.hand[0].val == 6 && decision_state.hand[1].val == 1)
-            {
+            } else if is_ace_and(decision_state.hand, 6) {
                 if true_count >= 1.0 && dealers_card == 2 {
-                    option.push_str("stand");
+                    option = Some(Action::Stand);
+                    reason = DecisionReason::Deviation("stand A-6 vs 2 at true count >= 1");
                 }
             }
 
             // Now check basic strategy
-            if option.is_empty() {
+            if option.is_none() {
                 if let Some(opt) = self
+                    .tables
                     .soft_totals
                     .get(&(decision_state.hand_value[0], dealers_card))
                 {
-                    if options.contains(opt.as_str()) {
-                        option.push_str(opt.as_str());
-                    } else if opt == "double down" && !options.contains("double down") {
-                        option.push_str("hit");
+                    reason = DecisionReason::Table("soft totals");
+                    if options.contains_action(*opt) {
+                        option = Some(*opt);
+                    } else if *opt == Action::DoubleDown && !options.allows_double() {
+                        option = Some(soft_double_fallback(decision_state.hand_value[0]));
                     } else {
                         return Err(BlackjackGameError {
-                            message: format!("option chosen: {}, not available for valid options {:?} with soft total of {}", opt, options, decision_state.hand_value[0])
+                            message: format!("option chosen: {}, not available for valid options [{}] with soft total of {}", opt, options, decision_state.hand_value[0])
                         });
                     }
                 }
@@ -749,7 +1389,7 @@ impl DecisionStrategy for H17DeviationStrategy {
         }
 
         // Finally check hard totals
-        if option.is_empty() {
+        if option.is_none() {
             // Check deviations first
             let true_count = f32::floor(decision_state.true_count);
             if decision_state.hand_value[0] == 16 {
@@ -757,86 +1397,577 @@ impl DecisionStrategy for H17DeviationStrategy {
                     || (dealers_card == 10 && decision_state.running_count > 0.0)
                     || (dealers_card == 1 && true_count >= 3.0)
                 {
-                    option.push_str("stand");
+                    option = Some(Action::Stand);
+                    reason = DecisionReason::Deviation("stand 16 vs 9, 10 or A on a high count");
                 }
             } else if decision_state.hand_value[0] == 15 {
                 if (dealers_card == 4 && true_count >= 4.0)
                     || (dealers_card == 1 && true_count >= 5.0)
                 {
-                    option.push_str("stand");
+                    option = Some(Action::Stand);
+                    reason = DecisionReason::Deviation("stand 15 vs 4 or A on a high true count");
                 }
             } else if decision_state.hand_value[0] == 13 {
                 if dealers_card == 2 && true_count <= -1.0 {
-                    option.push_str("hit");
+                    option = Some(Action::Hit);
+                    reason = DecisionReason::Deviation("hit 13 vs 2 at true count <= -1");
                 }
             } else if decision_state.hand_value[0] == 12 {
                 if (dealers_card == 2 && true_count >= 3.0)
                     || (dealers_card == 3 && true_count >= 2.0)
                 {
-                    option.push_str("stand");
+                    option = Some(Action::Stand);
+                    reason = DecisionReason::Deviation(
+                        "stand 12 vs 2 at true count >= 3 or vs 3 at true count >= 2",
+                    );
                 } else if dealers_card == 4 && decision_state.running_count < 0.0 {
-                    option.push_str("hit");
+                    option = Some(Action::Hit);
+                    reason = DecisionReason::Deviation("hit 12 vs 4 at running count < 0");
                 }
             } else if decision_state.hand_value[0] == 10 {
                 if (dealers_card == 10 && true_count >= 4.0)
                     || (dealers_card == 1 && true_count >= 3.0)
                 {
-                    option.push_str(if options.contains("double down") {
-                        "double down"
+                    option = Some(if options.allows_double() {
+                        Action::DoubleDown
                     } else {
-                        "hit"
+                        Action::Hit
                     });
+                    reason = DecisionReason::Deviation(
+                        "double 10 vs 10 at true count >= 4 or vs A at true count >= 3",
+                    );
                 }
             } else if decision_state.hand_value[0] == 9 {
                 if (dealers_card == 2 && true_count >= 1.0)
                     || (dealers_card == 7 && true_count >= 3.0)
                 {
-                    option.push_str(if options.contains("double down") {
-                        "double down"
+                    option = Some(if options.allows_double() {
+                        Action::DoubleDown
                     } else {
-                        "hit"
+                        Action::Hit
                     });
+                    reason = DecisionReason::Deviation(
+                        "double 9 vs 2 at true count >= 1 or vs 7 at true count >= 3",
+                    );
                 }
             } else if decision_state.hand_value[0] == 8 {
                 if dealers_card == 6 && true_count >= 2.0 {
-                    option.push_str(if options.contains("double down") {
-                        "double down"
+                    option = Some(if options.allows_double() {
+                        Action::DoubleDown
                     } else {
-                        "hit"
+                        Action::Hit
                     });
+                    reason = DecisionReason::Deviation("double 8 vs 6 at true count >= 2");
                 }
             }
 
             // If we havent meet conditions for a deviation, just play basic strategy
-            if option.is_empty() {
+            if option.is_none() {
                 match self
+                    .tables
                     .hard_totals
                     .get(&(decision_state.hand_value[0], dealers_card))
                 {
-                    Some(o) if options.contains(o.as_str()) => option.push_str(o.as_str()),
-                    Some(o) if o == "double down" && !options.contains("double down") => {
-                        option.push_str("hit");
-                    }
-                    _ => {
-                        return Err(BlackjackGameError {
-                            message: "option {o} not a valid choice".to_string(),
-                        })
+                    Some(&o) if options.contains_action(o) => option = Some(o),
+                    Some(Action::DoubleDown) if !options.allows_double() => {
+                        option = Some(Action::Hit);
                     }
+                    // Reported by the check below
+                    _ => {}
                 }
             }
         }
 
-        Ok(option)
+        match option {
+            Some(option) => Ok((option, Some(reason))),
+            None => Err(no_valid_option(&decision_state, options)),
+        }
     }
 
     fn take_insurance(&self, true_count: f32) -> bool {
         true_count >= 3.0
     }
+    fn name(&self) -> String {
+        String::from("H17 Deviations")
+    }
+
+    fn clone_box(&self) -> Box<dyn DecisionStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
+}
+
+/// Helper function that checks whether `hand` is exactly two cards, an ace and a card of value `val`. The soft total deviations only apply
+/// to such hands, e.g. A-8 but not A-3-5.
+fn is_ace_and(hand: &[Arc<Card>], val: u8) -> bool {
+    matches!(hand, [first, second] if (first.val, second.val) == (1, val) || (first.val, second.val) == (val, 1))
+}
+
+/// Helper function that returns the option played instead of doubling the soft total with low value `soft_value`, when doubling is not available.
+/// Soft 18 or more stands, smaller soft totals hit.
+fn soft_double_fallback(soft_value: u8) -> Action {
+    if soft_value >= 8 {
+        Action::Stand
+    } else {
+        Action::Hit
+    }
+}
+
+/// Helper function that builds the error returned when a decision strategy could not decide how to play the hand of `decision_state`.
+fn no_valid_option(decision_state: &TableState, options: Options) -> BlackjackGameError {
+    BlackjackGameError {
+        message: format!(
+            "no valid option was selected for hand value {:?} against dealer up card {} with options [{}]",
+            decision_state.hand_value, decision_state.dealers_up_card.val, options
+        ),
+    }
+}
+
+/// The representative running counts of each running count class, see `CompiledStrategy::running_count_class()`.
+const COMPILED_RUNNING_COUNTS: [f32; 5] = [-1.0, -0.5, 0.0, 0.5, 1.0];
+/// The representative first two cards of each hand class, see `CompiledStrategy::hand_class()`.
+const COMPILED_HAND_CLASSES: [(&str, &str); 4] = [("2", "3"), ("A", "8"), ("A", "6"), ("10", "10")];
+/// The ranks of the dealer's up card, indexed by the value of the card minus one.
+const COMPILED_DEALER_CARDS: [&str; 10] = ["A", "2", "3", "4", "5", "6", "7", "8", "9", "10"];
+
+/// The state a `CompiledStrategy` looks its decisions up by, see `CompiledStrategy::index()`.
+struct CompiledKey {
+    value: u8,
+    soft: bool,
+    /// The class of the first two cards, see `CompiledStrategy::hand_class()`
+    class: usize,
+    dealers_card: u8,
+    /// See `CompiledStrategy::running_count_class()`
+    running_count_class: usize,
+    true_count: i32,
+    /// The optional options available, see `CompiledStrategy::options_from_index()`
+    option_set: usize,
+}
+
+/// A struct that wraps a `DecisionStrategy` and precomputes its decision for every combination of hand value, soft/hard hand, first two cards,
+/// dealer up card, running count class, true count bucket and available options when it is created. Deciding an option is then only a couple of array
+/// indexes, which makes it a good fit for long runs. States that fall outside of the precomputed table, i.e. true counts outside of the given range,
+/// are decided by the wrapped strategy.
+/// The wrapped strategy is assumed to only depend on the hand value, the values of the first two cards, the dealer's up card, whether the running count is
+/// at most -1, negative, zero, less than one or at least one, the floor of the true count and the available options, which holds for all decision strategies
+/// implemented in this module.
+#[derive(Clone)]
+pub struct CompiledStrategy<D: DecisionStrategy> {
+    decision_strategy: D,
+    min_true_count: i32,
+    max_true_count: i32,
+    /// The position of each decision in `Action::ALL` plus one, a stored value of 0 means the decision was not compiled.
+    /// Shared with every clone, since the table never changes once compiled
+    decisions: Arc<[u8]>,
+}
+
+impl<D: DecisionStrategy> CompiledStrategy<D> {
+    /// Associated method for creating a new `CompiledStrategy`, precomputes the decisions of `decision_strategy` for every true count bucket
+    /// from `min_true_count` to `max_true_count` inclusive.
+    pub fn new(decision_strategy: D, min_true_count: i32, max_true_count: i32) -> Self {
+        assert!(
+            min_true_count <= max_true_count,
+            "minimum true count must not be larger than the maximum true count"
+        );
+        let num_true_counts = (max_true_count - min_true_count + 1) as usize;
+        let mut compiled = CompiledStrategy {
+            decision_strategy,
+            min_true_count,
+            max_true_count,
+            decisions: Arc::from(Vec::new()),
+        };
+        let mut decisions = vec![0; 20 * 2 * 4 * 10 * 5 * num_true_counts * 8];
+
+        let dealers_up_cards = COMPILED_DEALER_CARDS
+            .iter()
+            .map(|&rank| Arc::new(Card::new("S", rank)))
+            .collect::<Vec<Arc<Card>>>();
+        let hands = COMPILED_HAND_CLASSES
+            .iter()
+            .map(|&(rank1, rank2)| {
+                vec![
+                    Arc::new(Card::new("S", rank1)),
+                    Arc::new(Card::new("S", rank2)),
+                ]
+            })
+            .collect::<Vec<Vec<Arc<Card>>>>();
+
+        for value in 2..=21u8 {
+            for soft in [false, true] {
+                let hand_value = if soft {
+                    vec![value, value + 10]
+                } else {
+                    vec![value]
+                };
+                for (class, hand) in hands.iter().enumerate() {
+                    for dealers_up_card in dealers_up_cards.iter() {
+                        for (running_count_class, &running_count) in
+                            COMPILED_RUNNING_COUNTS.iter().enumerate()
+                        {
+                            for true_count in min_true_count..=max_true_count {
+                                for option_set in 0..8 {
+                                    let options = Self::options_from_index(option_set);
+                                    let state = TableState::new(
+                                        hand,
+                                        &hand_value,
+                                        0,
+                                        Arc::clone(dealers_up_card),
+                                        BetState::new(0.0, running_count, true_count as f32, 0),
+                                    );
+                                    let decision = compiled
+                                        .decision_strategy
+                                        .decide_option(state, options)
+                                        .ok()
+                                        .and_then(|option| {
+                                            Action::ALL.iter().position(|action| *action == option)
+                                        })
+                                        .map_or(0, |position| position as u8 + 1);
+                                    let idx = compiled.index(CompiledKey {
+                                        value,
+                                        soft,
+                                        class,
+                                        dealers_card: dealers_up_card.val,
+                                        running_count_class,
+                                        true_count,
+                                        option_set,
+                                    });
+                                    decisions[idx] = decision;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        compiled.decisions = Arc::from(decisions);
+        compiled
+    }
+
+    /// Helper method that returns the index of a decision in `self.decisions`.
+    fn index(&self, key: CompiledKey) -> usize {
+        let num_true_counts = (self.max_true_count - self.min_true_count + 1) as usize;
+        let mut idx = (key.value - 2) as usize;
+        idx = idx * 2 + usize::from(key.soft);
+        idx = idx * 4 + key.class;
+        idx = idx * 10 + (key.dealers_card - 1) as usize;
+        idx = idx * 5 + key.running_count_class;
+        idx = idx * num_true_counts + (key.true_count - self.min_true_count) as usize;
+        idx * 8 + key.option_set
+    }
+
+    /// Helper method that returns the `Options` represented by `option_set`, hitting and standing are always available.
+    fn options_from_index(option_set: usize) -> Options {
+        let mut options = Options::HIT | Options::STAND;
+        if option_set & 1 != 0 {
+            options |= Options::SPLIT;
+        }
+        if option_set & 2 != 0 {
+            options |= Options::DOUBLE;
+        }
+        if option_set & 4 != 0 {
+            options |= Options::SURRENDER;
+        }
+        options
+    }
+
+    /// Helper method that classifies a hand by the card combinations decision strategies deviate on. The soft totals are only deviated on
+    /// with two cards, a pair is classified by its first two cards.
+    fn hand_class(hand: &[Arc<Card>]) -> usize {
+        match (
+            u8::min(hand[0].val, hand[1].val),
+            u8::max(hand[0].val, hand[1].val),
+        ) {
+            (1, 8) if hand.len() == 2 => 1,
+            (1, 6) if hand.len() == 2 => 2,
+            (10, 10) => 3,
+            _ => 0,
+        }
+    }
+
+    /// Helper method that classifies the running count by the thresholds decision strategies deviate on.
+    fn running_count_class(running_count: f32) -> usize {
+        if running_count <= -1.0 {
+            0
+        } else if running_count < 0.0 {
+            1
+        } else if running_count == 0.0 {
+            2
+        } else if running_count < 1.0 {
+            3
+        } else {
+            4
+        }
+    }
+
+    /// Helper method that looks up the precomputed decision for `state`, returns `None` if the decision was not precomputed.
+    fn lookup(&self, state: &TableState, options: Options) -> Option<Action> {
+        let value = *state.hand_value.first()?;
+        let soft = match state.hand_value.len() {
+            1 => false,
+            2 if state.hand_value[1] == value + 10 => true,
+            _ => return None,
+        };
+        let dealers_card = state.dealers_up_card.val;
+        if !(2..=21).contains(&value)
+            || !(1..=10).contains(&dealers_card)
+            || state.hand.len() < 2
+            || !options.contains(Options::HIT | Options::STAND)
+            || !state.true_count.is_finite()
+            || state.running_count.is_nan()
+        {
+            return None;
+        }
+
+        let true_count = f32::floor(state.true_count);
+        if true_count < self.min_true_count as f32 || true_count > self.max_true_count as f32 {
+            return None;
+        }
+
+        let option_set = usize::from(options.allows_split())
+            | usize::from(options.allows_double()) << 1
+            | usize::from(options.allows_surrender()) << 2;
+        let idx = self.index(CompiledKey {
+            value,
+            soft,
+            class: Self::hand_class(state.hand),
+            dealers_card,
+            running_count_class: Self::running_count_class(state.running_count),
+            true_count: true_count as i32,
+            option_set,
+        });
+
+        match self.decisions[idx] {
+            0 => None,
+            decision => Some(Action::ALL[decision as usize - 1]),
+        }
+    }
+}
+
+impl<D: DecisionStrategy> DecisionStrategy for CompiledStrategy<D> {
+    /// Method for deciding how to play the current hand, answered from the precomputed decision table whenever possible.
+    fn decide_option<'a>(
+        &self,
+        decision_state: TableState<'a>,
+        options: Options,
+    ) -> Result<Action, BlackjackGameError> {
+        match self.lookup(&decision_state, options) {
+            Some(option) => Ok(option),
+            None => self
+                .decision_strategy
+                .decide_option(decision_state, options),
+        }
+    }
+
+    fn decide_option_with_reason<'a>(
+        &self,
+        decision_state: TableState<'a>,
+        options: Options,
+    ) -> Result<(Action, Option<DecisionReason>), BlackjackGameError> {
+        // The precomputed table does not keep the reasons of its decisions
+        match self.lookup(&decision_state, options) {
+            Some(option) => Ok((option, None)),
+            None => self
+                .decision_strategy
+                .decide_option_with_reason(decision_state, options),
+        }
+    }
+
+    fn take_insurance(&self, true_count: f32) -> bool {
+        self.decision_strategy.take_insurance(true_count)
+    }
+
+    fn name(&self) -> String {
+        format!("{} (compiled)", self.decision_strategy.name())
+    }
+
+    fn clone_box(&self) -> Box<dyn DecisionStrategy + Send + 'static> {
+        Box::new(CompiledStrategy {
+            decision_strategy: self.decision_strategy.clone_box(),
+            min_true_count: self.min_true_count,
+            max_true_count: self.max_true_count,
+            decisions: Arc::clone(&self.decisions),
+        })
+    }
+}
+
+/// How the floored true count is compared against the threshold of a `Deviation`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    /// The deviation applies when the true count is at least the threshold
+    AtLeast,
+    /// The deviation applies when the true count is at most the threshold
+    AtMost,
+}
+
+impl Comparison {
+    /// Associated method that returns the comparison with the given symbol, i.e. ">=" or "<=". Returns `None` for any other symbol.
+    pub fn from_symbol(symbol: &str) -> Option<Comparison> {
+        match symbol {
+            ">=" => Some(Comparison::AtLeast),
+            "<=" => Some(Comparison::AtMost),
+            _ => None,
+        }
+    }
+}
+
+/// A single playing deviation, i.e. play `action` instead of what the underlying strategy would play when the player holds `hand_total`
+/// against `dealer_up_card` (1 for an ace) and the floored true count compares to `threshold`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deviation {
+    pub hand_total: u8,
+    pub soft: bool,
+    pub dealer_up_card: u8,
+    pub comparison: Comparison,
+    pub threshold: f32,
+    pub action: Action,
+}
+
+impl Deviation {
+    /// Method that checks the deviation describes a hand that can be dealt, returning every invalid field.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = vec![];
+        if self.soft && !(12..=21).contains(&self.hand_total) {
+            errors.push(ConfigError::new(
+                "hand_total",
+                "must be between 12 and 21 for soft hands",
+            ));
+        } else if !self.soft && !(4..=21).contains(&self.hand_total) {
+            errors.push(ConfigError::new(
+                "hand_total",
+                "must be between 4 and 21 for hard hands",
+            ));
+        }
+        if !(1..=10).contains(&self.dealer_up_card) {
+            errors.push(ConfigError::new(
+                "dealer_up_card",
+                "must be between 1 and 10",
+            ));
+        }
+        if !self.threshold.is_finite() {
+            errors.push(ConfigError::new("threshold", "must be a finite number"));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Method that returns true if the deviation applies to the hand described by `decision_state`.
+    fn applies(&self, decision_state: &TableState) -> bool {
+        let hand_value = decision_state.hand_value;
+        let soft = hand_value.len() == 2 && hand_value[1] <= 21;
+        let hand_total = if soft { hand_value[1] } else { hand_value[0] };
+        let true_count = f32::floor(decision_state.true_count);
+        soft == self.soft
+            && hand_total == self.hand_total
+            && decision_state.dealers_up_card.val == self.dealer_up_card
+            && match self.comparison {
+                Comparison::AtLeast => true_count >= self.threshold,
+                Comparison::AtMost => true_count <= self.threshold,
+            }
+    }
+}
+
+/// Checks every deviation in `deviations`, the field of each error is prefixed by the index of its deviation e.g. `deviations[2].hand_total`.
+pub fn validate_deviations(deviations: &[Deviation]) -> Result<(), Vec<ConfigError>> {
+    let errors = deviations
+        .iter()
+        .enumerate()
+        .filter_map(|(i, deviation)| deviation.validate().err().map(|errors| (i, errors)))
+        .flat_map(|(i, errors)| {
+            errors.into_iter().map(move |err| {
+                ConfigError::new(format!("deviations[{}].{}", i, err.field), err.constraint)
+            })
+        })
+        .collect::<Vec<ConfigError>>();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// A decision strategy that layers a list of playing deviations over another decision strategy. The first deviation that applies to a hand,
+/// and whose action is available, is played, otherwise the decision is left to the underlying strategy.
+#[derive(Clone)]
+pub struct DeviationStrategy<D: DecisionStrategy> {
+    decision_strategy: D,
+    deviations: Vec<Deviation>,
+}
+
+impl<D: DecisionStrategy> DeviationStrategy<D> {
+    /// Associated method for creating a new `DeviationStrategy`, returns the errors of every invalid deviation, see `validate_deviations()`.
+    pub fn new(
+        decision_strategy: D,
+        deviations: Vec<Deviation>,
+    ) -> Result<DeviationStrategy<D>, Vec<ConfigError>> {
+        validate_deviations(&deviations)?;
+        Ok(DeviationStrategy {
+            decision_strategy,
+            deviations,
+        })
+    }
+}
+
+impl<D: DecisionStrategy> DecisionStrategy for DeviationStrategy<D> {
+    /// Method for deciding how to play the current hand, plays the first applicable deviation before falling back on the underlying strategy.
+    fn decide_option<'a>(
+        &self,
+        decision_state: TableState<'a>,
+        options: Options,
+    ) -> Result<Action, BlackjackGameError> {
+        let deviation = self.deviations.iter().find(|deviation| {
+            options.contains_action(deviation.action) && deviation.applies(&decision_state)
+        });
+        match deviation {
+            Some(deviation) => Ok(deviation.action),
+            None => self
+                .decision_strategy
+                .decide_option(decision_state, options),
+        }
+    }
+
+    fn decide_option_with_reason<'a>(
+        &self,
+        decision_state: TableState<'a>,
+        options: Options,
+    ) -> Result<(Action, Option<DecisionReason>), BlackjackGameError> {
+        let deviation = self.deviations.iter().position(|deviation| {
+            options.contains_action(deviation.action) && deviation.applies(&decision_state)
+        });
+        match deviation {
+            Some(i) => Ok((
+                self.deviations[i].action,
+                Some(DecisionReason::CustomDeviation(i)),
+            )),
+            None => self
+                .decision_strategy
+                .decide_option_with_reason(decision_state, options),
+        }
+    }
+
+    fn take_insurance(&self, true_count: f32) -> bool {
+        self.decision_strategy.take_insurance(true_count)
+    }
+
+    fn name(&self) -> String {
+        format!("{} + custom deviations", self.decision_strategy.name())
+    }
+
+    fn clone_box(&self) -> Box<dyn DecisionStrategy + Send + 'static> {
+        Box::new(DeviationStrategy {
+            decision_strategy: self.decision_strategy.clone_box(),
+            deviations: self.deviations.clone(),
+        })
+    }
 }
 
+#[derive(Clone)]
 pub struct HiLo {
     running_count: i32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
@@ -858,7 +1989,6 @@ impl HiLo {
 
         HiLo {
             running_count: 0,
-            true_count: 0.0,
             num_decks,
             total_cards_counted: 0,
             lookup_table,
@@ -892,29 +2022,6 @@ impl CountingStrategy for HiLo {
     fn update(&mut self, card: Arc<Card>) {
         self.running_count += self.lookup_table[&card.val];
         self.total_cards_counted += 1;
-        let estimated_decks_counted =
-            (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = (self.running_count as f32) / estimated_decks_counted;
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
     }
 
     fn running_count(&self) -> f32 {
@@ -922,45 +2029,36 @@ impl CountingStrategy for HiLo {
     }
 
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        (self.running_count as f32) / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
         self.running_count = 0;
         self.total_cards_counted = 0;
-        self.true_count = 0.0;
     }
 
     fn name(&self) -> String {
         String::from("HiLo")
     }
-}
 
-impl Display for HiLo {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let width = "total cards counted:".len();
-        let num_width = f32::ceil(f32::log10(self.total_cards_counted as f32)) as usize;
-        write!(
-            f,
-            "{:<width$}{:>num_width$}\n{:<width$}{:>num_width$}\n{:<width$}{:>num_width$.2}",
-            "running count:",
-            self.running_count,
-            "total cards counted:",
-            self.total_cards_counted,
-            "true count",
-            self.true_count,
-        )
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
     }
 }
 
 /// A struct that implements the famous Wong Halves card counting strategy.
+#[derive(Clone)]
 pub struct WongHalves {
     running_count: f32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, f32>,
@@ -983,7 +2081,6 @@ impl WongHalves {
 
         WongHalves {
             running_count: 0.0,
-            true_count: 0.0,
             num_decks,
             total_cards_counted: 0,
             lookup_table,
@@ -1015,37 +2112,13 @@ impl CountingStrategy for WongHalves {
     //     }
     // }
 
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
-    }
-
     fn update(&mut self, card: Arc<Card>) {
         self.running_count += self.lookup_table[&card.val];
         self.total_cards_counted += 1;
-        let estimated_decks_counted =
-            (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = self.running_count / estimated_decks_counted;
     }
 
     fn reset(&mut self) {
         self.running_count = 0.0;
-        self.true_count = 0.0;
         self.total_cards_counted = 0;
     }
 
@@ -1054,22 +2127,34 @@ impl CountingStrategy for WongHalves {
     }
 
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        self.running_count / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn name(&self) -> String {
         String::from("Wong Halves")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
-/// Struct that implements the popular Knockout card counting strategy. No need to compute a true count.
+/// Struct that implements the popular Knockout card counting strategy. The count is unbalanced, it starts at 4 - 4 per deck and its
+/// pivot is `KO::PIVOT`.
+#[derive(Clone)]
 pub struct KO {
     running_count: i32,
     num_decks: u32,
+    total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
 }
 
@@ -1089,9 +2174,14 @@ impl KO {
         KO {
             running_count,
             num_decks,
+            total_cards_counted: 0,
             lookup_table,
         }
     }
+
+    /// The running count that counting every card of a full shoe ends at. The true count is taken relative to it, so it is 0 at the pivot
+    /// whatever the number of decks remaining, and a neutral shoe never shows a positive true count.
+    pub const PIVOT: i32 = 4;
 }
 
 impl CountingStrategy for KO {
@@ -1114,14 +2204,17 @@ impl CountingStrategy for KO {
     //     }
     // }
 
-    /// Update the count for the strategy. Since there is no need to compute true count, we only need to update the running count.
+    /// Update the count for the strategy.
     fn update(&mut self, card: Arc<Card>) {
         self.running_count += self.lookup_table[&card.val];
+        self.total_cards_counted += 1;
     }
 
-    /// Getter for the true count. Since the true count and running count are the same we only need to return the running count.
+    /// Getter for the true count. The count is unbalanced, so the true count is the running count relative to the pivot per deck remaining.
+    /// It is only positive above the pivot.
     fn true_count(&self) -> f32 {
-        self.running_count as f32
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        ((self.running_count - KO::PIVOT) as f32) / estimated_decks
     }
 
     /// Getter for the running count.
@@ -1133,42 +2226,31 @@ impl CountingStrategy for KO {
         self.num_decks
     }
 
-    /// Method that takes data about the current state of the table and returns a `TableState` object that holds all relevant information for a player to make a decision
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.running_count as f32,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
     }
 
-    /// Reset the counting strategy. We only need to reset the running count to 4 - total number of decks * 4.
+    /// Method that takes data about the current state of the table and returns a `TableState` object that holds all relevant information for a player to make a decision
+    /// Reset the counting strategy, i.e. the running count to 4 - total number of decks * 4.
     fn reset(&mut self) {
         self.running_count = 4 - (self.num_decks as i32) * 4;
+        self.total_cards_counted = 0;
     }
 
     /// Method to get the name of the strategy
     fn name(&self) -> String {
         String::from("KO")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
 /// A struct that implements the HiOpt1 counting method
+#[derive(Clone)]
 pub struct HiOptI {
     running_count: i32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
@@ -1189,7 +2271,6 @@ impl HiOptI {
 
         HiOptI {
             running_count: 0,
-            true_count: 0.0,
             num_decks,
             total_cards_counted: 0,
             lookup_table,
@@ -1219,32 +2300,9 @@ impl CountingStrategy for HiOptI {
     //     }
     // }
 
-    fn update(&mut self, card: Arc<Card>) {
-        self.running_count += self.lookup_table[&card.val];
-        self.total_cards_counted += 1;
-        let estimated_decks_played =
-            (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = (self.running_count as f32) / estimated_decks_played;
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
+    fn update(&mut self, card: Arc<Card>) {
+        self.running_count += self.lookup_table[&card.val];
+        self.total_cards_counted += 1;
     }
 
     fn running_count(&self) -> f32 {
@@ -1252,29 +2310,37 @@ impl CountingStrategy for HiOptI {
     }
 
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        (self.running_count as f32) / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
         self.running_count = 0;
         self.total_cards_counted = 0;
-        self.true_count = 0.0;
     }
 
     /// Returns the name of the strategy, useful for display purposes
     fn name(&self) -> String {
         String::from("HiOptI")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
 /// A struct that implements the HiOptII counting method
+#[derive(Clone)]
 pub struct HiOptII {
     running_count: i32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
@@ -1296,7 +2362,6 @@ impl HiOptII {
 
         HiOptII {
             running_count: 0,
-            true_count: 0.0,
             num_decks,
             total_cards_counted: 0,
             lookup_table,
@@ -1330,29 +2395,6 @@ impl CountingStrategy for HiOptII {
     fn update(&mut self, card: Arc<Card>) {
         self.running_count += self.lookup_table[&card.val];
         self.total_cards_counted += 1;
-        let estimated_decks_played =
-            (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = (self.running_count as f32) / estimated_decks_played;
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
     }
 
     fn running_count(&self) -> f32 {
@@ -1360,28 +2402,37 @@ impl CountingStrategy for HiOptII {
     }
 
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        (self.running_count as f32) / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
         self.running_count = 0;
         self.total_cards_counted = 0;
-        self.true_count = 0.0;
     }
 
     fn name(&self) -> String {
         String::from("HiOptII")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
-/// A struct that implements Red Seven counting method
+/// A struct that implements Red Seven counting method. The count is unbalanced, it starts at -2 per deck so that counting every card of the
+/// shoe ends at 0.
+#[derive(Clone)]
 pub struct RedSeven {
     running_count: i32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
@@ -1391,7 +2442,7 @@ impl RedSeven {
     pub fn new(num_decks: u32) -> Self {
         let mut lookup_table = HashMap::new();
         for i in 2..=6_u8 {
-            lookup_table.insert(i, -1);
+            lookup_table.insert(i, 1);
         }
         for i in 8..=9_u8 {
             lookup_table.insert(i, 0);
@@ -1400,13 +2451,17 @@ impl RedSeven {
         lookup_table.insert(1, -1);
 
         RedSeven {
-            running_count: 0,
-            true_count: 0.0,
+            running_count: Self::initial_running_count(num_decks),
             num_decks,
             total_cards_counted: 0,
             lookup_table,
         }
     }
+
+    /// Helper method that returns the running count of a freshly shuffled shoe of `num_decks` decks.
+    fn initial_running_count(num_decks: u32) -> i32 {
+        -2 * (num_decks as i32)
+    }
 }
 
 impl CountingStrategy for RedSeven {
@@ -1431,6 +2486,7 @@ impl CountingStrategy for RedSeven {
     // }
 
     fn update(&mut self, card: Arc<Card>) {
+        // Sevens are not in the lookup table, only the red ones are counted
         let card_index = match self.lookup_table.get(&card.val) {
             Some(v) => *v,
             None => {
@@ -1444,57 +2500,44 @@ impl CountingStrategy for RedSeven {
 
         self.running_count += card_index;
         self.total_cards_counted += 1;
-        let estimated_decks = (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = (self.running_count as f32) / estimated_decks;
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
     }
 
     fn running_count(&self) -> f32 {
         self.running_count as f32
     }
 
+    /// The pivot of the count is 0, so dividing the running count by the decks remaining leaves it positive exactly above the pivot.
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        (self.running_count as f32) / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
-        self.running_count = 0;
-        self.true_count = 0.0;
+        self.running_count = Self::initial_running_count(self.num_decks);
         self.total_cards_counted = 0;
     }
 
     fn name(&self) -> String {
         String::from("Red Seven")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
 /// A struct that implements the OmegaII card counting method
+#[derive(Clone)]
 pub struct OmegaII {
     running_count: i32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
@@ -1515,7 +2558,6 @@ impl OmegaII {
         lookup_table.insert(1, 0);
         OmegaII {
             running_count: 0,
-            true_count: 0.0,
             num_decks,
             total_cards_counted: 0,
             lookup_table,
@@ -1548,28 +2590,6 @@ impl CountingStrategy for OmegaII {
     fn update(&mut self, card: Arc<Card>) {
         self.running_count += self.lookup_table[&card.val];
         self.total_cards_counted += 1;
-        let estimated_decks = (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = (self.running_count as f32) / estimated_decks;
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
     }
 
     fn running_count(&self) -> f32 {
@@ -1577,28 +2597,38 @@ impl CountingStrategy for OmegaII {
     }
 
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        (self.running_count as f32) / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
         self.running_count = 0;
-        self.true_count = 0.0;
         self.total_cards_counted = 0;
     }
 
     fn name(&self) -> String {
         String::from("OmegaII")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
 /// A struct that implements the Ace/Five counting strategy
+#[derive(Clone)]
 pub struct AceFive {
     running_count: i32,
     num_decks: u32,
+    total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
 }
 
@@ -1620,6 +2650,7 @@ impl AceFive {
         AceFive {
             running_count: 0,
             num_decks,
+            total_cards_counted: 0,
             lookup_table,
         }
     }
@@ -1649,26 +2680,7 @@ impl CountingStrategy for AceFive {
 
     fn update(&mut self, card: Arc<Card>) {
         self.running_count += self.lookup_table[&card.val];
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.running_count as f32,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
+        self.total_cards_counted += 1;
     }
 
     fn running_count(&self) -> f32 {
@@ -1676,26 +2688,36 @@ impl CountingStrategy for AceFive {
     }
 
     fn true_count(&self) -> f32 {
-        self.running_count()
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        (self.running_count as f32) / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
         self.running_count = 0;
+        self.total_cards_counted = 0;
     }
 
     fn name(&self) -> String {
         String::from("Ace/Five")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
 /// A struct that implements the Zen Count card counting technique
+#[derive(Clone)]
 pub struct ZenCount {
     running_count: i32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
@@ -1716,7 +2738,6 @@ impl ZenCount {
         lookup_table.insert(1, -1);
         ZenCount {
             running_count: 0,
-            true_count: 0.0,
             num_decks,
             total_cards_counted: 0,
             lookup_table,
@@ -1749,28 +2770,6 @@ impl CountingStrategy for ZenCount {
     fn update(&mut self, card: Arc<Card>) {
         self.running_count += self.lookup_table[&card.val];
         self.total_cards_counted += 1;
-        let estimated_decks = (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = (self.running_count as f32) / estimated_decks;
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
     }
 
     fn running_count(&self) -> f32 {
@@ -1778,28 +2777,36 @@ impl CountingStrategy for ZenCount {
     }
 
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        (self.running_count as f32) / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
         self.running_count = 0;
-        self.true_count = 0.0;
         self.total_cards_counted = 0;
     }
 
     fn name(&self) -> String {
         String::from("Zen Count")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
 /// A struct that implements the Halves counting strategy
+#[derive(Clone)]
 pub struct Halves {
     running_count: f32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, f32>,
@@ -1820,7 +2827,6 @@ impl Halves {
         lookup_table.insert(1, -1.0);
         Halves {
             running_count: 0.0,
-            true_count: 0.0,
             num_decks,
             total_cards_counted: 0,
             lookup_table,
@@ -1853,28 +2859,6 @@ impl CountingStrategy for Halves {
     fn update(&mut self, card: Arc<Card>) {
         self.running_count += self.lookup_table[&card.val];
         self.total_cards_counted += 1;
-        let estimated_decks = (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = self.running_count / estimated_decks;
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
     }
 
     fn running_count(&self) -> f32 {
@@ -1882,28 +2866,37 @@ impl CountingStrategy for Halves {
     }
 
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        self.running_count / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
         self.running_count = 0.0;
-        self.true_count = 0.0;
         self.total_cards_counted = 0;
     }
 
     fn name(&self) -> String {
         String::from("Halves")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
-/// A struct that implements the KISS counting strategy
+/// A struct that implements the KISS counting strategy. The count is unbalanced by -4 per deck, so it starts at its
+/// pivot `KISS::PIVOT` rather than ending at it like `KO`.
+#[derive(Clone)]
 pub struct KISS {
     running_count: i32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
@@ -1920,13 +2913,15 @@ impl KISS {
             };
         }
         KISS {
-            running_count: 0,
-            true_count: 0.0,
+            running_count: Self::PIVOT,
             num_decks,
             total_cards_counted: 0,
             lookup_table,
         }
     }
+
+    /// The running count of a freshly shuffled shoe, a neutral shoe only counts down from it.
+    pub const PIVOT: i32 = 0;
 }
 
 impl CountingStrategy for KISS {
@@ -1951,57 +2946,45 @@ impl CountingStrategy for KISS {
     fn update(&mut self, card: Arc<Card>) {
         self.running_count += self.lookup_table[&card.val];
         self.total_cards_counted += 1;
-        let estimated_decks = (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = (self.running_count as f32) / estimated_decks;
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
     }
 
     fn running_count(&self) -> f32 {
         self.running_count as f32
     }
 
+    /// The count is unbalanced, so the true count is the running count relative to the pivot per deck remaining.
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        ((self.running_count - Self::PIVOT) as f32) / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
-        self.running_count = 0;
-        self.true_count = 0.0;
+        self.running_count = Self::PIVOT;
         self.total_cards_counted = 0;
     }
 
     fn name(&self) -> String {
         String::from("KISS")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
-/// A struct that implements the KISSII counting strategy
+/// A struct that implements the KISSII counting strategy. The count is unbalanced by -4 per deck, so it starts at its
+/// pivot `KISSII::PIVOT` rather than ending at it like `KO`.
+#[derive(Clone)]
 pub struct KISSII {
     running_count: i32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
@@ -2019,13 +3002,15 @@ impl KISSII {
         }
         lookup_table.insert(1, -1);
         KISSII {
-            running_count: 0,
-            true_count: 0.0,
+            running_count: Self::PIVOT,
             num_decks,
             total_cards_counted: 0,
             lookup_table,
         }
     }
+
+    /// The running count of a freshly shuffled shoe, a neutral shoe only counts down from it.
+    pub const PIVOT: i32 = 0;
 }
 
 impl CountingStrategy for KISSII {
@@ -2058,57 +3043,45 @@ impl CountingStrategy for KISSII {
         };
         self.running_count += index;
         self.total_cards_counted += 1;
-        let estimated_decks = (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = (self.running_count as f32) / estimated_decks;
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
     }
 
     fn running_count(&self) -> f32 {
         self.running_count as f32
     }
 
+    /// The count is unbalanced, so the true count is the running count relative to the pivot per deck remaining.
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        ((self.running_count - Self::PIVOT) as f32) / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
-        self.running_count = 0;
-        self.true_count = 0.0;
+        self.running_count = Self::PIVOT;
         self.total_cards_counted = 0;
     }
 
     fn name(&self) -> String {
         String::from("KISS II")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
-/// A struct that implements the KISS III counting strategy
+/// A struct that implements the KISS III counting strategy. The count is unbalanced by +2 per deck, like `KO` it starts
+/// below its pivot `KISSIII::PIVOT` and counting every card of the shoe ends at it.
+#[derive(Clone)]
 pub struct KISSIII {
     running_count: i32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
@@ -2126,13 +3099,20 @@ impl KISSIII {
         }
         lookup_table.insert(1, -1);
         KISSIII {
-            running_count: 0,
-            true_count: 0.0,
+            running_count: Self::initial_running_count(num_decks),
             num_decks,
             total_cards_counted: 0,
             lookup_table,
         }
     }
+
+    /// The running count that counting every card of a full shoe ends at.
+    pub const PIVOT: i32 = 2;
+
+    /// Helper method that returns the running count of a freshly shuffled shoe of `num_decks` decks.
+    fn initial_running_count(num_decks: u32) -> i32 {
+        Self::PIVOT - Self::PIVOT * (num_decks as i32)
+    }
 }
 
 impl CountingStrategy for KISSIII {
@@ -2165,57 +3145,45 @@ impl CountingStrategy for KISSIII {
         };
         self.running_count += index;
         self.total_cards_counted += 1;
-        let estimated_decks = (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = (self.running_count as f32) / estimated_decks;
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
     }
 
     fn running_count(&self) -> f32 {
         self.running_count as f32
     }
 
+    /// The count is unbalanced, so the true count is the running count relative to the pivot per deck remaining.
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        ((self.running_count - Self::PIVOT) as f32) / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
-        self.running_count = 0;
-        self.true_count = 0.0;
+        self.running_count = Self::initial_running_count(self.num_decks);
         self.total_cards_counted = 0;
     }
 
     fn name(&self) -> String {
         String::from("KISS III")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
-/// A struct that implements the J. Noir card counting strategy
+/// A struct that implements the J. Noir card counting strategy. The count is unbalanced by -20 per deck, so it starts at its
+/// pivot `JNoir::PIVOT` rather than ending at it like `KO`.
+#[derive(Clone)]
 pub struct JNoir {
     running_count: i32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
@@ -2231,13 +3199,15 @@ impl JNoir {
             };
         }
         JNoir {
-            running_count: 0,
-            true_count: 0.0,
+            running_count: Self::PIVOT,
             num_decks,
             total_cards_counted: 0,
             lookup_table,
         }
     }
+
+    /// The running count of a freshly shuffled shoe, a neutral shoe only counts down from it.
+    pub const PIVOT: i32 = 0;
 }
 
 impl CountingStrategy for JNoir {
@@ -2258,60 +3228,47 @@ impl CountingStrategy for JNoir {
     //     }
     // }
 
-    fn update(&mut self, card: Arc<Card>) {
-        self.running_count += self.lookup_table[&card.val];
-        self.total_cards_counted += 1;
-        let estimated_decks = (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = (self.running_count as f32) / estimated_decks;
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
+    fn update(&mut self, card: Arc<Card>) {
+        self.running_count += self.lookup_table[&card.val];
+        self.total_cards_counted += 1;
     }
 
     fn running_count(&self) -> f32 {
         self.running_count as f32
     }
 
+    /// The count is unbalanced, so the true count is the running count relative to the pivot per deck remaining.
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        ((self.running_count - Self::PIVOT) as f32) / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
-        self.running_count = 0;
-        self.true_count = 0.0;
+        self.running_count = Self::PIVOT;
         self.total_cards_counted = 0;
     }
 
     fn name(&self) -> String {
         String::from("J. Noir")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
 /// A struct that implements the Silver Fox card counting method
+#[derive(Clone)]
 pub struct SilverFox {
     running_count: i32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
@@ -2329,7 +3286,6 @@ impl SilverFox {
         }
         SilverFox {
             running_count: 0,
-            true_count: 0.0,
             num_decks,
             total_cards_counted: 0,
             lookup_table,
@@ -2359,28 +3315,6 @@ impl CountingStrategy for SilverFox {
     fn update(&mut self, card: Arc<Card>) {
         self.running_count += self.lookup_table[&card.val];
         self.total_cards_counted += 1;
-        let estimated_decks = (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = (self.running_count as f32) / estimated_decks;
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
     }
 
     fn running_count(&self) -> f32 {
@@ -2388,28 +3322,37 @@ impl CountingStrategy for SilverFox {
     }
 
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        (self.running_count as f32) / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
         self.running_count = 0;
-        self.true_count = 0.0;
         self.total_cards_counted = 0;
     }
 
     fn name(&self) -> String {
         String::from("Silver Fox")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 
-/// A struct that implements teh Unbalanced Zen 2 counting method
+/// A struct that implements the Unbalanced Zen 2 counting method. The count is unbalanced by +4 per deck, like `KO` it starts
+/// below its pivot `UnbalancedZen2::PIVOT` and counting every card of the shoe ends at it.
+#[derive(Clone)]
 pub struct UnbalancedZen2 {
     running_count: i32,
-    true_count: f32,
     num_decks: u32,
     total_cards_counted: i32,
     lookup_table: HashMap<u8, i32>,
@@ -2428,13 +3371,20 @@ impl UnbalancedZen2 {
             };
         }
         UnbalancedZen2 {
-            running_count: 0,
-            true_count: 0.0,
+            running_count: Self::initial_running_count(num_decks),
             num_decks,
             total_cards_counted: 0,
             lookup_table,
         }
     }
+
+    /// The running count that counting every card of a full shoe ends at.
+    pub const PIVOT: i32 = 4;
+
+    /// Helper method that returns the running count of a freshly shuffled shoe of `num_decks` decks.
+    fn initial_running_count(num_decks: u32) -> i32 {
+        Self::PIVOT - Self::PIVOT * (num_decks as i32)
+    }
 }
 
 impl CountingStrategy for UnbalancedZen2 {
@@ -2461,54 +3411,41 @@ impl CountingStrategy for UnbalancedZen2 {
     fn update(&mut self, card: Arc<Card>) {
         self.running_count += self.lookup_table[&card.val];
         self.total_cards_counted += 1;
-        let estimated_decks = (self.num_decks as f32) - ((self.total_cards_counted as f32) / 52.0);
-        self.true_count = (self.running_count as f32) / estimated_decks;
-    }
-
-    fn get_current_table_state<'a>(
-        &self,
-        hand: &'a Vec<Arc<Card>>,
-        hand_value: &'a Vec<u8>,
-        bet: u32,
-        balance: f32,
-        dealers_up_card: Arc<Card>,
-    ) -> TableState<'a> {
-        TableState {
-            hand,
-            hand_value,
-            bet,
-            balance,
-            running_count: self.running_count as f32,
-            true_count: self.true_count,
-            num_decks: self.num_decks,
-            dealers_up_card,
-        }
     }
 
     fn running_count(&self) -> f32 {
         self.running_count as f32
     }
 
+    /// The count is unbalanced, so the true count is the running count relative to the pivot per deck remaining.
     fn true_count(&self) -> f32 {
-        self.true_count
+        let estimated_decks = estimated_decks_remaining(self.num_decks, self.total_cards_counted);
+        ((self.running_count - Self::PIVOT) as f32) / estimated_decks
     }
 
     fn num_decks(&self) -> u32 {
         self.num_decks
     }
 
+    fn cards_counted(&self) -> u32 {
+        self.total_cards_counted as u32
+    }
+
     fn reset(&mut self) {
-        self.running_count = 0;
-        self.true_count = 0.0;
+        self.running_count = Self::initial_running_count(self.num_decks);
         self.total_cards_counted = 0;
     }
 
     fn name(&self) -> String {
         String::from("Unbalanced Zen 2")
     }
+
+    fn clone_box(&self) -> Box<dyn CountingStrategy + Send + 'static> {
+        Box::new(self.clone())
+    }
 }
 /// A struct that encapsulates everything needed to implement a specific playing to test in a simulation.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PlayerStrategy<C, D, B>
 where
     C: CountingStrategy,
@@ -2518,7 +3455,7 @@ where
     counting_strategy: C,
     decision_strategy: D,
     betting_strategy: B,
-    counting_strategy_name: String,
+    counting_strategy_name: Arc<str>,
 }
 
 impl<C, D, B> PlayerStrategy<C, D, B>
@@ -2528,7 +3465,7 @@ where
     B: BettingStrategy,
 {
     pub fn new(counting_strategy: C, decision_strategy: D, betting_strategy: B) -> Self {
-        let counting_strategy_name = counting_strategy.name();
+        let counting_strategy_name = Arc::from(counting_strategy.name());
         PlayerStrategy {
             counting_strategy,
             decision_strategy,
@@ -2540,12 +3477,12 @@ where
 
 impl<C, D, B> Display for PlayerStrategy<C, D, B>
 where
-    C: CountingStrategy + Display,
+    C: CountingStrategy,
     D: DecisionStrategy,
     B: BettingStrategy,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.counting_strategy)
+        self.counting_strategy.fmt_state(f)
     }
 }
 
@@ -2562,11 +3499,20 @@ where
     fn decide_option<'a>(
         &self,
         current_state: TableState<'a>,
-        options: HashSet<String>,
-    ) -> Result<String, BlackjackGameError> {
+        options: Options,
+    ) -> Result<Action, BlackjackGameError> {
         self.decision_strategy.decide_option(current_state, options)
     }
 
+    fn decide_option_with_reason<'a>(
+        &self,
+        current_state: TableState<'a>,
+        options: Options,
+    ) -> Result<(Action, Option<DecisionReason>), BlackjackGameError> {
+        self.decision_strategy
+            .decide_option_with_reason(current_state, options)
+    }
+
     fn reset(&mut self) {
         self.counting_strategy.reset();
     }
@@ -2591,6 +3537,9 @@ where
         bet: u32,
         balance: f32,
         dealers_up_card: Arc<Card>,
+        cards_remaining: u32,
+        is_split_hand: bool,
+        num_player_hands: u8,
     ) -> TableState<'a> {
         self.counting_strategy.get_current_table_state(
             hand,
@@ -2598,6 +3547,9 @@ where
             bet,
             balance,
             dealers_up_card,
+            cards_remaining,
+            is_split_hand,
+            num_player_hands,
         )
     }
 
@@ -2606,8 +3558,16 @@ where
             .take_insurance(self.counting_strategy.true_count())
     }
 
-    fn label(&self) -> String {
-        self.counting_strategy_name.clone()
+    fn label(&self) -> Arc<str> {
+        Arc::clone(&self.counting_strategy_name)
+    }
+
+    fn decision_strategy_name(&self) -> String {
+        self.decision_strategy.name()
+    }
+
+    fn betting_strategy_name(&self) -> String {
+        self.betting_strategy.name()
     }
 }
 
@@ -2615,11 +3575,12 @@ where
 /// Instead of using statically typed `CountingStrategy`, `DecisionStrategy` and `BettingStrategy` it uses trait objects.
 /// Useful for runtime creation if the overhead cost of using dynamic dispatch is acceptable.
 // #[derive(Debug)]
+#[derive(Clone)]
 pub struct PlayerStrategyDyn {
     counting_strategy: Box<dyn CountingStrategy + Send + 'static>,
     decision_strategy: Box<dyn DecisionStrategy + Send + 'static>,
     betting_strategy: Box<dyn BettingStrategy + Send + 'static>,
-    counting_strategy_name: String,
+    counting_strategy_name: Arc<str>,
 }
 
 impl PlayerStrategyDyn {
@@ -2628,6 +3589,12 @@ impl PlayerStrategyDyn {
     }
 }
 
+impl Display for PlayerStrategyDyn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.counting_strategy.fmt_state(f)
+    }
+}
+
 impl Strategy for PlayerStrategyDyn {
     fn bet(&self, state: BetState) -> u32 {
         self.betting_strategy.bet(state)
@@ -2636,11 +3603,20 @@ impl Strategy for PlayerStrategyDyn {
     fn decide_option<'a>(
         &self,
         current_state: TableState<'a>,
-        options: HashSet<String>,
-    ) -> Result<String, BlackjackGameError> {
+        options: Options,
+    ) -> Result<Action, BlackjackGameError> {
         self.decision_strategy.decide_option(current_state, options)
     }
 
+    fn decide_option_with_reason<'a>(
+        &self,
+        current_state: TableState<'a>,
+        options: Options,
+    ) -> Result<(Action, Option<DecisionReason>), BlackjackGameError> {
+        self.decision_strategy
+            .decide_option_with_reason(current_state, options)
+    }
+
     fn reset(&mut self) {
         self.counting_strategy.reset();
     }
@@ -2665,6 +3641,9 @@ impl Strategy for PlayerStrategyDyn {
         bet: u32,
         balance: f32,
         dealers_up_card: Arc<Card>,
+        cards_remaining: u32,
+        is_split_hand: bool,
+        num_player_hands: u8,
     ) -> TableState<'a> {
         self.counting_strategy.get_current_table_state(
             hand,
@@ -2672,6 +3651,9 @@ impl Strategy for PlayerStrategyDyn {
             bet,
             balance,
             dealers_up_card,
+            cards_remaining,
+            is_split_hand,
+            num_player_hands,
         )
     }
 
@@ -2680,8 +3662,16 @@ impl Strategy for PlayerStrategyDyn {
             .take_insurance(self.counting_strategy.true_count())
     }
 
-    fn label(&self) -> String {
-        self.counting_strategy_name.clone()
+    fn label(&self) -> Arc<str> {
+        Arc::clone(&self.counting_strategy_name)
+    }
+
+    fn decision_strategy_name(&self) -> String {
+        self.decision_strategy.name()
+    }
+
+    fn betting_strategy_name(&self) -> String {
+        self.betting_strategy.name()
     }
 }
 
@@ -2689,7 +3679,8 @@ pub struct PlayerStrategyDynBuilder {
     counting_strategy: Option<Box<dyn CountingStrategy + Send + 'static>>,
     decision_strategy: Option<Box<dyn DecisionStrategy + Send + 'static>>,
     betting_strategy: Option<Box<dyn BettingStrategy + Send + 'static>>,
-    counting_strategy_name: Option<String>,
+    counting_strategy_name: Option<Arc<str>>,
+    label: Option<Arc<str>>,
 }
 
 impl PlayerStrategyDynBuilder {
@@ -2699,6 +3690,7 @@ impl PlayerStrategyDynBuilder {
             decision_strategy: None,
             betting_strategy: None,
             counting_strategy_name: None,
+            label: None,
         }
     }
 
@@ -2707,7 +3699,7 @@ impl PlayerStrategyDynBuilder {
         counting_strategy: Box<dyn CountingStrategy + Send + 'static>,
     ) -> &mut Self {
         let name = counting_strategy.name();
-        self.counting_strategy_name = Some(name);
+        self.counting_strategy_name = Some(Arc::from(name));
         self.counting_strategy = Some(counting_strategy);
         self
     }
@@ -2728,32 +3720,605 @@ impl PlayerStrategyDynBuilder {
         self
     }
 
-    pub fn build(&mut self) -> PlayerStrategyDyn {
-        PlayerStrategyDyn {
-            counting_strategy: self
-                .counting_strategy
-                .take()
-                .expect("counting strategy should be set"),
-            decision_strategy: self
-                .decision_strategy
-                .take()
-                .expect("decision strategy should be set"),
-            betting_strategy: self
-                .betting_strategy
-                .take()
-                .expect("betting strategy should be set"),
-            counting_strategy_name: self
-                .counting_strategy_name
-                .take()
-                .expect("counting strategy name should be set"),
-        }
+    /// Overrides the label of the strategy, by default the strategy is labelled with the name of its counting strategy.
+    pub fn label(&mut self, label: &str) -> &mut Self {
+        self.label = Some(Arc::from(label));
+        self
+    }
+
+    /// Builds the strategy from the strategies that were set, returns an error naming the first strategy that was not set.
+    pub fn build(&mut self) -> Result<PlayerStrategyDyn, StrategyBuildError> {
+        let counting_strategy = self
+            .counting_strategy
+            .take()
+            .ok_or(StrategyBuildError::MissingCountingStrategy)?;
+        let decision_strategy = self
+            .decision_strategy
+            .take()
+            .ok_or(StrategyBuildError::MissingDecisionStrategy)?;
+        let betting_strategy = self
+            .betting_strategy
+            .take()
+            .ok_or(StrategyBuildError::MissingBettingStrategy)?;
+        // The name is always set along with the counting strategy
+        let counting_strategy_name = self
+            .label
+            .take()
+            .or(self.counting_strategy_name.take())
+            .ok_or(StrategyBuildError::MissingCountingStrategy)?;
+        Ok(PlayerStrategyDyn {
+            counting_strategy,
+            decision_strategy,
+            betting_strategy,
+            counting_strategy_name,
+        })
+    }
+}
+
+/// The strategy that was not set when building a `PlayerStrategyDyn`, see `PlayerStrategyDynBuilder::build()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrategyBuildError {
+    MissingCountingStrategy,
+    MissingDecisionStrategy,
+    MissingBettingStrategy,
+}
+
+impl Display for StrategyBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let strategy = match self {
+            StrategyBuildError::MissingCountingStrategy => "counting",
+            StrategyBuildError::MissingDecisionStrategy => "decision",
+            StrategyBuildError::MissingBettingStrategy => "betting",
+        };
+        write!(f, "the {} strategy was not set", strategy)
     }
 }
 
+impl std::error::Error for StrategyBuildError {}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn building_a_strategy_without_every_part_names_the_missing_part() {
+        let error = PlayerStrategyDyn::new()
+            .counting_strategy(Box::new(HiLo::new(6)))
+            .betting_strategy(Box::new(MarginBettingStrategy::new(3.0, 5)))
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(error, StrategyBuildError::MissingDecisionStrategy);
+        assert_eq!(error.to_string(), "the decision strategy was not set");
+
+        let strategy = PlayerStrategyDyn::new()
+            .counting_strategy(Box::new(HiLo::new(6)))
+            .decision_strategy(Box::new(BasicStrategy::new()))
+            .betting_strategy(Box::new(MarginBettingStrategy::new(3.0, 5)))
+            .build()
+            .unwrap();
+        assert_eq!(&*strategy.label(), "HiLo");
+    }
+
+    #[test]
+    fn cloned_strategies_count_independently_of_the_original() {
+        let mut strategy = PlayerStrategy::new(
+            HiLo::new(6),
+            S17DeviationStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        );
+        strategy.update(Arc::new(Card::new("H", "2")));
+        strategy.update(Arc::new(Card::new("S", "5")));
+
+        // The clone starts from the count of the original
+        let mut clone = strategy.clone();
+        assert_eq!(clone.get_current_bet_state(500.0).running_count, 2.0);
+
+        strategy.update(Arc::new(Card::new("C", "K")));
+        clone.update(Arc::new(Card::new("D", "6")));
+        assert_eq!(strategy.get_current_bet_state(500.0).running_count, 1.0);
+        assert_eq!(clone.get_current_bet_state(500.0).running_count, 3.0);
+
+        let mut dyn_strategy = PlayerStrategyDyn::new()
+            .counting_strategy(Box::new(HiLo::new(6)))
+            .decision_strategy(Box::new(CompiledStrategy::new(BasicStrategy::new(), -2, 2)))
+            .betting_strategy(Box::new(MarginBettingStrategy::new(3.0, 5)))
+            .build()
+            .unwrap();
+        dyn_strategy.update(Arc::new(Card::new("H", "3")));
+        let mut dyn_clone = dyn_strategy.clone();
+        dyn_strategy.reset();
+        assert_eq!(dyn_strategy.get_current_bet_state(500.0).running_count, 0.0);
+        assert_eq!(dyn_clone.get_current_bet_state(500.0).running_count, 1.0);
+        dyn_clone.update(Arc::new(Card::new("S", "A")));
+        assert_eq!(dyn_clone.get_current_bet_state(500.0).running_count, 0.0);
+        assert_eq!(
+            dyn_clone.decision_strategy_name(),
+            dyn_strategy.decision_strategy_name()
+        );
+    }
+
+    #[test]
+    fn strategies_sharing_a_counting_strategy_have_distinct_full_labels() {
+        let basic = PlayerStrategy::new(
+            HiLo::new(6),
+            BasicStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        );
+        let deviations = PlayerStrategyDyn::new()
+            .counting_strategy(Box::new(HiLo::new(6)))
+            .decision_strategy(Box::new(S17DeviationStrategy::new()))
+            .betting_strategy(Box::new(FlatBettingStrategy::new(5).unwrap()))
+            .build()
+            .unwrap();
+        assert_eq!(basic.label(), deviations.label());
+        assert_eq!(basic.full_label(), "HiLo (Basic Strategy, Margin)");
+        assert_eq!(deviations.full_label(), "HiLo (S17 Deviations, Flat)");
+    }
+
+    #[test]
+    fn strategies_display_the_state_of_their_count() {
+        let mut hi_lo = PlayerStrategy::new(
+            HiLo::new(1),
+            BasicStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        );
+        for rank in ["2", "3", "4", "K"] {
+            hi_lo.update(Arc::new(Card::new("H", rank)));
+        }
+        assert_eq!(
+            hi_lo.to_string(),
+            "running count:       2\n\
+             total cards counted: 4\n\
+             true count:          2.17"
+        );
+
+        let mut wong_halves = PlayerStrategyDyn::new()
+            .counting_strategy(Box::new(WongHalves::new(1)))
+            .decision_strategy(Box::new(BasicStrategy::new()))
+            .betting_strategy(Box::new(MarginBettingStrategy::new(3.0, 5)))
+            .build()
+            .unwrap();
+        for rank in ["5", "5", "7", "K"] {
+            wong_halves.update(Arc::new(Card::new("S", rank)));
+        }
+        assert_eq!(
+            wong_halves.to_string(),
+            "running count:       2.5\n\
+             total cards counted: 4\n\
+             true count:          2.71"
+        );
+    }
+
+    #[test]
+    fn ramp_bets_the_units_of_the_highest_step_reached() {
+        let ramp = vec![
+            RampStep {
+                true_count: 1.0,
+                units: 2,
+            },
+            RampStep {
+                true_count: 3.0,
+                units: 8,
+            },
+        ];
+        let strategy = RampBettingStrategy::new(ramp, 5).unwrap();
+        assert_eq!(strategy.bet(BetState::new(500.0, 0.0, -1.0, 6)), 5);
+        assert_eq!(strategy.bet(BetState::new(500.0, 0.0, 1.5, 6)), 10);
+        assert_eq!(strategy.bet(BetState::new(500.0, 0.0, 4.0, 6)), 40);
+        // Bets never exceed the player's balance
+        assert_eq!(strategy.bet(BetState::new(25.0, 0.0, 4.0, 6)), 25);
+    }
+
+    #[test]
+    fn margin_bets_scale_with_the_ceiling_of_the_true_count() {
+        let strategy = MarginBettingStrategy::new(3.0, 5);
+        assert_eq!(strategy.bet(BetState::new(500.0, 12.0, 2.0, 6)), 30);
+        assert_eq!(strategy.bet(BetState::new(500.0, 7.0, 1.2, 6)), 30);
+        assert_eq!(strategy.bet(BetState::new(500.0, 0.0, 0.0, 6)), 5);
+        assert_eq!(strategy.bet(BetState::new(500.0, -6.0, -1.0, 6)), 5);
+        // Bets never exceed the player's balance
+        assert_eq!(strategy.bet(BetState::new(20.0, 12.0, 2.0, 6)), 20);
+    }
+
+    #[test]
+    fn deviations_override_the_underlying_strategy() {
+        let stand_16_vs_10 = Deviation {
+            hand_total: 16,
+            soft: false,
+            dealer_up_card: 10,
+            comparison: Comparison::AtLeast,
+            threshold: 0.0,
+            action: Action::Stand,
+        };
+        let strategy = DeviationStrategy::new(BasicStrategy::new(), vec![stand_16_vs_10]).unwrap();
+        let hand = vec![Arc::new(Card::new("H", "9")), Arc::new(Card::new("S", "7"))];
+        let hand_value = vec![16];
+        let dealers_up_card = Arc::new(Card::new("C", "K"));
+        let options = Options::HIT | Options::STAND;
+        let state = |true_count| {
+            TableState::new(
+                &hand,
+                &hand_value,
+                5,
+                Arc::clone(&dealers_up_card),
+                BetState::new(500.0, 0.0, true_count, 6),
+            )
+        };
+        assert_eq!(
+            strategy.decide_option(state(0.5), options).unwrap(),
+            Action::Stand
+        );
+        assert_eq!(
+            strategy.decide_option(state(-1.0), options).unwrap(),
+            Action::Hit
+        );
+    }
+
+    /// Helper function that decides how `strategy` plays the hard hand of `ranks` against `dealers_up_card` at a count of zero.
+    fn decide_hard_hand<D: DecisionStrategy>(
+        strategy: &D,
+        ranks: [&str; 2],
+        dealers_up_card: &str,
+        options: Options,
+    ) -> Action {
+        let hand = ranks
+            .iter()
+            .map(|&rank| Arc::new(Card::new("H", rank)))
+            .collect::<Vec<Arc<Card>>>();
+        let hand_value = vec![hand.iter().map(|card| card.val).sum::<u8>()];
+        let state = TableState::new(
+            &hand,
+            &hand_value,
+            5,
+            Arc::new(Card::new("C", dealers_up_card)),
+            BetState::new(500.0, 0.0, 0.0, 6),
+        );
+        strategy.decide_option(state, options).unwrap()
+    }
+
+    #[test]
+    fn soft_totals_follow_the_multi_deck_s17_chart() {
+        // Each row holds the soft total and the play against a 2 through 10 and then an ace, H is hit, S is stand and D is double down
+        const CHART: [(u8, &str); 9] = [
+            (13, "HHHDDHHHHH"),
+            (14, "HHHDDHHHHH"),
+            (15, "HHDDDHHHHH"),
+            (16, "HHDDDHHHHH"),
+            (17, "HDDDDHHHHH"),
+            (18, "SDDDDSSHHH"),
+            (19, "SSSSSSSSSS"),
+            (20, "SSSSSSSSSS"),
+            (21, "SSSSSSSSSS"),
+        ];
+        let tables = BasicStrategy::build_lookup_tables();
+        for (soft_total, row) in CHART {
+            for (column, play) in row.chars().enumerate() {
+                let dealers_card = if column == 9 { 1 } else { column as u8 + 2 };
+                let expected = match play {
+                    'H' => Action::Hit,
+                    'S' => Action::Stand,
+                    _ => Action::DoubleDown,
+                };
+                assert_eq!(
+                    tables.soft_totals[&(soft_total - 10, dealers_card)],
+                    expected,
+                    "soft {} vs {}",
+                    soft_total,
+                    dealers_card
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn soft_18_stands_when_doubling_is_not_available() {
+        let strategy = BasicStrategy::new();
+        let hand = vec![Arc::new(Card::new("H", "A")), Arc::new(Card::new("S", "7"))];
+        let hand_value = vec![8, 18];
+        let decide = |dealers_up_card: &str, options: Options| {
+            let state = TableState::new(
+                &hand,
+                &hand_value,
+                5,
+                Arc::new(Card::new("C", dealers_up_card)),
+                BetState::new(500.0, 0.0, 0.0, 6),
+            );
+            strategy.decide_option(state, options).unwrap()
+        };
+
+        assert_eq!(
+            decide("4", Options::HIT | Options::STAND | Options::DOUBLE),
+            Action::DoubleDown
+        );
+        assert_eq!(decide("4", Options::HIT | Options::STAND), Action::Stand);
+        assert_eq!(decide("9", Options::HIT | Options::STAND), Action::Hit);
+    }
+
+    #[test]
+    fn a_8_is_doubled_at_a_high_count_and_stood_on_otherwise() {
+        let s17_strategy = S17DeviationStrategy::new();
+        let h17_strategy = H17DeviationStrategy::new();
+        let decide = |strategy: &dyn DecisionStrategy,
+                      ranks: &[&str],
+                      running_count: f32,
+                      true_count: f32,
+                      dealers_up_card: &str,
+                      options: Options| {
+            let hand = ranks
+                .iter()
+                .map(|&rank| Arc::new(Card::new("H", rank)))
+                .collect::<Vec<Arc<Card>>>();
+            let low_value = hand.iter().map(|card| card.val).sum::<u8>();
+            let hand_value = vec![low_value, low_value + 10];
+            let state = TableState::new(
+                &hand,
+                &hand_value,
+                5,
+                Arc::new(Card::new("C", dealers_up_card)),
+                BetState::new(500.0, running_count, true_count, 6),
+            );
+            strategy.decide_option(state, options).unwrap()
+        };
+        let options = Options::HIT | Options::STAND | Options::DOUBLE;
+
+        assert_eq!(
+            decide(&s17_strategy, &["A", "8"], 6.0, 3.0, "4", options),
+            Action::DoubleDown
+        );
+        assert_eq!(
+            decide(&s17_strategy, &["8", "A"], 2.0, 1.0, "6", options),
+            Action::DoubleDown
+        );
+        assert_eq!(
+            decide(&s17_strategy, &["A", "8"], 0.0, 0.0, "6", options),
+            Action::Stand
+        );
+        assert_eq!(
+            decide(&h17_strategy, &["A", "8"], 0.0, 0.0, "6", options),
+            Action::DoubleDown
+        );
+        assert_eq!(
+            decide(&h17_strategy, &["A", "8"], -1.0, 0.0, "6", options),
+            Action::Stand
+        );
+        // Without doubling, or with more than two cards, the soft 19 is stood on
+        assert_eq!(
+            decide(
+                &s17_strategy,
+                &["A", "8"],
+                6.0,
+                3.0,
+                "4",
+                options - Options::DOUBLE
+            ),
+            Action::Stand
+        );
+        assert_eq!(
+            decide(&h17_strategy, &["A", "3", "5"], 6.0, 3.0, "4", options),
+            Action::Stand
+        );
+        // Soft 21 stands whatever the dealer shows, e.g. A-5-5 or a split ten that draws an ace
+        assert_eq!(
+            decide(&s17_strategy, &["A", "5", "5"], 0.0, 0.0, "6", options),
+            Action::Stand
+        );
+        assert_eq!(
+            decide(&BasicStrategy::new(), &["10", "A"], 0.0, 0.0, "6", options),
+            Action::Stand
+        );
+    }
+
+    #[test]
+    fn undecided_hands_are_reported_instead_of_an_empty_option() {
+        let hand = ["10", "10", "2"]
+            .iter()
+            .map(|&rank| Arc::new(Card::new("H", rank)))
+            .collect::<Vec<Arc<Card>>>();
+        // A busted hand is outside of every lookup table, so no rule decides it
+        let hand_value = vec![22];
+        let state = TableState::new(
+            &hand,
+            &hand_value,
+            5,
+            Arc::new(Card::new("C", "6")),
+            BetState::new(500.0, 0.0, 0.0, 6),
+        );
+        let error = H17DeviationStrategy::new()
+            .decide_option(state, Options::HIT | Options::STAND)
+            .unwrap_err();
+        assert!(error.message.contains("hand value [22]"));
+        assert!(error.message.contains("dealer up card 6"));
+        assert!(error.message.contains("options [hit, stand]"));
+    }
+
+    #[test]
+    fn deviation_strategies_only_surrender_16_against_9_10_or_ace() {
+        let options =
+            Options::HIT | Options::STAND | Options::DOUBLE | Options::SPLIT | Options::SURRENDER;
+        let s17_strategy = S17DeviationStrategy::new();
+        let h17_strategy = H17DeviationStrategy::new();
+
+        // Pairs of 8s are split before surrender is considered
+        assert_eq!(
+            decide_hard_hand(&s17_strategy, ["8", "8"], "6", options),
+            Action::Split
+        );
+        assert_eq!(
+            decide_hard_hand(&h17_strategy, ["8", "8"], "6", options),
+            Action::Split
+        );
+        assert_eq!(
+            decide_hard_hand(&s17_strategy, ["8", "8"], "10", options),
+            Action::Split
+        );
+        // A 16 against a weak up card is played by basic strategy, i.e. stood on, not surrendered
+        let options = options - Options::SPLIT;
+        assert_eq!(
+            decide_hard_hand(&s17_strategy, ["9", "7"], "2", options),
+            Action::Stand
+        );
+        assert_eq!(
+            decide_hard_hand(&h17_strategy, ["9", "7"], "2", options),
+            Action::Stand
+        );
+        assert_eq!(
+            decide_hard_hand(&s17_strategy, ["10", "6"], "10", options),
+            Action::Surrender
+        );
+        assert_eq!(
+            decide_hard_hand(&h17_strategy, ["10", "6"], "10", options),
+            Action::Surrender
+        );
+    }
+
+    #[test]
+    fn decisions_report_the_rule_that_decided_them() {
+        let stand_16_vs_10 = Deviation {
+            hand_total: 16,
+            soft: false,
+            dealer_up_card: 10,
+            comparison: Comparison::AtLeast,
+            threshold: 2.0,
+            action: Action::Stand,
+        };
+        let custom_strategy =
+            DeviationStrategy::new(BasicStrategy::new(), vec![stand_16_vs_10]).unwrap();
+        let s17_strategy = S17DeviationStrategy::new();
+        let hand = vec![Arc::new(Card::new("H", "9")), Arc::new(Card::new("S", "7"))];
+        let hand_value = vec![16];
+        let dealers_up_card = Arc::new(Card::new("C", "K"));
+        let options = Options::HIT | Options::STAND;
+        let state = |running_count, true_count| {
+            TableState::new(
+                &hand,
+                &hand_value,
+                5,
+                Arc::clone(&dealers_up_card),
+                BetState::new(500.0, running_count, true_count, 6),
+            )
+        };
+
+        assert_eq!(
+            s17_strategy
+                .decide_option_with_reason(state(1.0, 0.5), options)
+                .unwrap(),
+            (
+                Action::Stand,
+                Some(DecisionReason::Deviation(
+                    "stand 16 vs 9 at true count >= 4 or vs 10 at running count > 0"
+                ))
+            )
+        );
+        assert_eq!(
+            s17_strategy
+                .decide_option_with_reason(state(-1.0, -0.5), options)
+                .unwrap(),
+            (Action::Hit, Some(DecisionReason::Table("hard totals")))
+        );
+        assert_eq!(
+            custom_strategy
+                .decide_option_with_reason(state(6.0, 2.0), options)
+                .unwrap(),
+            (Action::Stand, Some(DecisionReason::CustomDeviation(0)))
+        );
+        assert_eq!(
+            custom_strategy
+                .decide_option_with_reason(state(6.0, 1.0), options)
+                .unwrap(),
+            (Action::Hit, Some(DecisionReason::Table("hard totals")))
+        );
+    }
+
+    #[test]
+    fn invalid_deviations_report_their_index() {
+        let deviation = Deviation {
+            hand_total: 25,
+            soft: false,
+            dealer_up_card: 11,
+            comparison: Comparison::AtMost,
+            threshold: 1.0,
+            action: Action::Hit,
+        };
+        let errors = DeviationStrategy::new(BasicStrategy::new(), vec![deviation])
+            .err()
+            .unwrap();
+        let fields = errors
+            .into_iter()
+            .map(|err| err.field)
+            .collect::<Vec<String>>();
+        assert_eq!(
+            fields,
+            vec!["deviations[0].hand_total", "deviations[0].dealer_up_card"]
+        );
+    }
+
+    #[test]
+    fn ramps_are_parsed_from_count_unit_pairs() {
+        assert_eq!(
+            parse_ramp("0:1, 1:2,2.5:4").unwrap(),
+            vec![
+                RampStep {
+                    true_count: 0.0,
+                    units: 1
+                },
+                RampStep {
+                    true_count: 1.0,
+                    units: 2
+                },
+                RampStep {
+                    true_count: 2.5,
+                    units: 4
+                },
+            ]
+        );
+        assert_eq!(
+            parse_ramp("-1:1").unwrap(),
+            vec![RampStep {
+                true_count: -1.0,
+                units: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn malformed_ramps_are_rejected() {
+        for ramp in ["", "1", "1:2,3", "a:2", "1:b", "1:-2", "1:2:3"] {
+            assert!(parse_ramp(ramp).is_err(), "{} should not parse", ramp);
+        }
+    }
+
+    #[test]
+    fn kelly_bets_grow_with_the_true_count() {
+        let strategy = KellyBettingStrategy::new(0.5, 5).unwrap();
+        // No advantage, so the minimum is bet
+        assert_eq!(strategy.bet(BetState::new(10_000.0, 0.0, 0.0, 6)), 5);
+        // 1.5% advantage at a true count of 4, half of 1.5% / 1.3 of the balance
+        assert_eq!(strategy.bet(BetState::new(10_000.0, 0.0, 4.0, 6)), 57);
+        assert_eq!(strategy.bet(BetState::new(3.0, 0.0, 4.0, 6)), 3);
+        assert!(KellyBettingStrategy::new(0.0, 5).is_err());
+        assert!(KellyBettingStrategy::new(1.5, 5).is_err());
+    }
+
+    #[test]
+    fn invalid_ramps_are_rejected() {
+        assert!(RampBettingStrategy::new(vec![], 5).is_err());
+        let unsorted = vec![
+            RampStep {
+                true_count: 3.0,
+                units: 4,
+            },
+            RampStep {
+                true_count: 1.0,
+                units: 2,
+            },
+        ];
+        assert_eq!(
+            RampBettingStrategy::new(unsorted, 5).unwrap_err(),
+            ConfigError::new("ramp", "must be sorted by strictly increasing true count")
+        );
+    }
+
     #[test]
     fn test_dynamic_strategy_creation() {
         let mut strategies: Vec<Box<dyn Strategy>> = vec![];
@@ -2774,4 +4339,305 @@ mod test {
         // println!("{:#?}", strategies);
         assert!(true);
     }
+
+    #[test]
+    fn test_lookup_tables_are_shared() {
+        let strategy1 = BasicStrategy::new();
+        let strategy2 = BasicStrategy::new();
+        let strategy3 = S17DeviationStrategy::new();
+        let strategy4 = H17DeviationStrategy::new();
+
+        assert!(Arc::ptr_eq(&strategy1.tables, &strategy2.tables));
+        assert!(Arc::ptr_eq(&strategy1.tables, &strategy3.tables));
+        assert!(Arc::ptr_eq(&strategy1.tables, &strategy4.tables));
+    }
+
+    #[test]
+    fn test_options_names_round_trip() {
+        let options = Options::HIT | Options::STAND | Options::DOUBLE;
+        assert_eq!(options.names(), vec!["hit", "stand", "double down"]);
+        assert_eq!(options.to_string(), "hit, stand, double down");
+        assert!(options.contains_action(Action::DoubleDown));
+        assert!(!options.contains_action(Action::Split));
+        assert!("default".parse::<Action>().is_err());
+        for action in Action::ALL {
+            assert_eq!(action.to_string().parse::<Action>().unwrap(), action);
+            assert_eq!(
+                Options::from_action_name(action.name()),
+                Some(action.option())
+            );
+        }
+    }
+
+    #[test]
+    fn test_lazy_true_count_matches_eager_computation() {
+        let cards = ["2", "5", "K", "A", "3", "9", "7", "Q", "4", "6"]
+            .iter()
+            .map(|&rank| Arc::new(Card::new("S", rank)))
+            .collect::<Vec<Arc<Card>>>();
+        let mut hi_lo = HiLo::new(6);
+        let mut wong_halves = WongHalves::new(6);
+
+        let (mut hi_lo_running_count, mut wong_halves_running_count) = (0i32, 0.0f32);
+        for (i, card) in cards.iter().enumerate() {
+            hi_lo.update(Arc::clone(card));
+            wong_halves.update(Arc::clone(card));
+
+            // Compute the true count eagerly, exactly as it was computed on every update
+            hi_lo_running_count += hi_lo.lookup_table[&card.val];
+            wong_halves_running_count += wong_halves.lookup_table[&card.val];
+            let estimated_decks = 6.0 - (((i + 1) as f32) / 52.0);
+            let hi_lo_eager = (hi_lo_running_count as f32) / estimated_decks;
+            let wong_halves_eager = wong_halves_running_count / estimated_decks;
+
+            assert_eq!(hi_lo.true_count(), hi_lo_eager);
+            assert_eq!(wong_halves.true_count(), wong_halves_eager);
+        }
+
+        hi_lo.reset();
+        assert_eq!(hi_lo.true_count(), 0.0);
+    }
+
+    #[test]
+    fn true_counts_stay_finite_past_the_end_of_the_shoe() {
+        let mut counting_strategies: Vec<Box<dyn CountingStrategy>> = vec![
+            Box::new(HiLo::new(1)),
+            Box::new(WongHalves::new(1)),
+            Box::new(KO::new(1)),
+            Box::new(HiOptI::new(1)),
+            Box::new(HiOptII::new(1)),
+            Box::new(RedSeven::new(1)),
+            Box::new(OmegaII::new(1)),
+            Box::new(AceFive::new(1)),
+            Box::new(ZenCount::new(1)),
+            Box::new(Halves::new(1)),
+            Box::new(KISS::new(1)),
+            Box::new(KISSII::new(1)),
+            Box::new(KISSIII::new(1)),
+            Box::new(JNoir::new(1)),
+            Box::new(SilverFox::new(1)),
+            Box::new(UnbalancedZen2::new(1)),
+        ];
+        for counting_strategy in counting_strategies.iter_mut() {
+            // Count more low cards than a single deck holds, the running count only grows so the true count must stay positive
+            for _ in 0..2 * 52 {
+                counting_strategy.update(Arc::new(Card::new("H", "5")));
+                let true_count = counting_strategy.true_count();
+                assert!(true_count.is_finite(), "{}", counting_strategy.name());
+            }
+            assert!(
+                counting_strategy.true_count() > 0.0,
+                "{}",
+                counting_strategy.name()
+            );
+        }
+    }
+
+    #[test]
+    fn ko_only_raises_bets_above_its_pivot() {
+        let betting_strategy = MarginBettingStrategy::new(2.0, 5);
+        let bet = |counting_strategy: &dyn CountingStrategy| {
+            betting_strategy.bet(BetState::new(
+                1000.0,
+                counting_strategy.running_count(),
+                counting_strategy.true_count(),
+                6,
+            ))
+        };
+
+        let mut ko = KO::new(6);
+        assert_eq!(ko.running_count(), -20.0);
+        while ko.running_count() < 10.0 {
+            if ko.running_count() <= KO::PIVOT as f32 {
+                assert_eq!(bet(&ko), 5, "running count {}", ko.running_count());
+            } else {
+                assert!(bet(&ko) > 5, "running count {}", ko.running_count());
+            }
+            ko.update(Arc::new(Card::new("S", "2")));
+        }
+        ko.reset();
+        assert_eq!(ko.running_count(), -20.0);
+        assert_eq!(bet(&ko), 5);
+
+        // The balanced counts bet as they did, more as soon as the running count is positive
+        let mut hi_lo = HiLo::new(6);
+        assert_eq!(bet(&hi_lo), 5);
+        hi_lo.update(Arc::new(Card::new("S", "2")));
+        assert_eq!(hi_lo.true_count(), 1.0 / (6.0 - 1.0 / 52.0));
+        assert_eq!(bet(&hi_lo), 10);
+    }
+
+    #[test]
+    fn red_seven_counts_a_full_shoe_down_to_the_pivot() {
+        let mut red_seven = RedSeven::new(2);
+        assert_eq!(red_seven.running_count(), -4.0);
+        let count_of = |suit: &'static str, rank: &'static str| {
+            let mut red_seven = RedSeven::new(1);
+            red_seven.update(Arc::new(Card::new(suit, rank)));
+            red_seven.running_count() + 2.0
+        };
+        assert_eq!(count_of("S", "2"), 1.0);
+        assert_eq!(count_of("C", "6"), 1.0);
+        assert_eq!(count_of("H", "7"), 1.0);
+        assert_eq!(count_of("D", "7"), 1.0);
+        assert_eq!(count_of("S", "7"), 0.0);
+        assert_eq!(count_of("C", "7"), 0.0);
+        assert_eq!(count_of("H", "9"), 0.0);
+        assert_eq!(count_of("D", "K"), -1.0);
+        assert_eq!(count_of("S", "A"), -1.0);
+
+        for _ in 0..2 {
+            for suit in blackjack_lib::SUITS {
+                for rank in blackjack_lib::RANKS {
+                    red_seven.update(Arc::new(Card::new(suit, rank)));
+                }
+            }
+        }
+        // The count is unbalanced by +2 per deck, which the initial running count makes up for
+        assert_eq!(red_seven.running_count(), 0.0);
+
+        red_seven.reset();
+        assert_eq!(red_seven.running_count(), -4.0);
+    }
+
+    #[test]
+    fn unbalanced_counts_take_their_true_count_relative_to_their_pivot() {
+        let counting_strategies: Vec<(Box<dyn CountingStrategy>, i32)> = vec![
+            (Box::new(KO::new(2)), KO::PIVOT),
+            (Box::new(KISS::new(2)), KISS::PIVOT),
+            (Box::new(KISSII::new(2)), KISSII::PIVOT),
+            (Box::new(KISSIII::new(2)), KISSIII::PIVOT),
+            (Box::new(JNoir::new(2)), JNoir::PIVOT),
+            (Box::new(UnbalancedZen2::new(2)), UnbalancedZen2::PIVOT),
+        ];
+        for (mut counting_strategy, pivot) in counting_strategies {
+            let name = counting_strategy.name();
+            let initial_running_count = counting_strategy.running_count();
+            let initial_true_count = counting_strategy.true_count();
+            assert_eq!(
+                initial_true_count,
+                (initial_running_count - pivot as f32) / 2.0,
+                "{}",
+                name
+            );
+            for _ in 0..2 {
+                for suit in blackjack_lib::SUITS {
+                    for rank in blackjack_lib::RANKS {
+                        counting_strategy.update(Arc::new(Card::new(suit, rank)));
+                    }
+                }
+            }
+            // The count either starts or ends a full shoe at its pivot, and is never above it at either end
+            let final_running_count = counting_strategy.running_count();
+            assert!(
+                initial_running_count == pivot as f32 || final_running_count == pivot as f32,
+                "{}",
+                name
+            );
+            assert!(initial_true_count <= 0.0, "{}", name);
+            assert!(counting_strategy.true_count() <= 0.0, "{}", name);
+
+            counting_strategy.reset();
+            assert_eq!(
+                counting_strategy.running_count(),
+                initial_running_count,
+                "{}",
+                name
+            );
+        }
+    }
+
+    /// Helper function for the equivalence test, computes the hand value of `hand` the same way `PlayerSim` does.
+    fn hand_value(hand: &[Arc<Card>]) -> Vec<u8> {
+        let mut hand_value: Vec<u8> = vec![];
+        for card in hand {
+            if hand_value.is_empty() {
+                hand_value.push(card.val);
+            } else {
+                hand_value[0] += card.val;
+                if hand_value.len() == 2 {
+                    hand_value[1] += card.val;
+                }
+            }
+            if hand_value.len() == 1 && hand_value[0] <= 11 && card.val == 1 {
+                let alt_val = hand_value[0] + 10;
+                hand_value.push(alt_val);
+            }
+        }
+        hand_value
+    }
+
+    fn assert_compiled_strategy_equivalent<D: DecisionStrategy>(
+        decision_strategy: D,
+        compiled_strategy: CompiledStrategy<D>,
+    ) {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        const RANKS: [&str; 13] = [
+            "A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K",
+        ];
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut num_compared = 0;
+
+        while num_compared < 20_000 {
+            let num_cards = rng.gen_range(2..=4);
+            let hand = (0..num_cards)
+                .map(|_| Arc::new(Card::new("S", RANKS[rng.gen_range(0..RANKS.len())])))
+                .collect::<Vec<Arc<Card>>>();
+            let hand_value = hand_value(&hand);
+            if hand_value.iter().all(|value| *value > 21) {
+                continue;
+            }
+            let dealers_up_card = Arc::new(Card::new("H", RANKS[rng.gen_range(0..RANKS.len())]));
+            let running_count = rng.gen_range(-20.0..20.0);
+            let true_count = rng.gen_range(-8.0..8.0);
+            let mut options = Options::HIT | Options::STAND;
+            if rng.gen_bool(0.5) {
+                options |= Options::DOUBLE;
+            }
+            if rng.gen_bool(0.5) {
+                options |= Options::SURRENDER;
+            }
+            if num_cards == 2 && hand[0].val == hand[1].val && rng.gen_bool(0.5) {
+                options |= Options::SPLIT;
+            }
+
+            let state = || {
+                TableState::new(
+                    &hand,
+                    &hand_value,
+                    5,
+                    Arc::clone(&dealers_up_card),
+                    BetState::new(500.0, running_count, true_count, 6),
+                )
+            };
+            let expected = decision_strategy.decide_option(state(), options);
+            let actual = compiled_strategy.decide_option(state(), options);
+            match (expected, actual) {
+                (Ok(expected), Ok(actual)) => assert_eq!(
+                    expected, actual,
+                    "hand {:?}, dealer {:?}, running count {}, true count {}, options {:?}",
+                    hand, dealers_up_card, running_count, true_count, options
+                ),
+                (Err(_), Err(_)) => {}
+                (expected, actual) => panic!("expected {:?}, got {:?}", expected, actual),
+            }
+            num_compared += 1;
+        }
+    }
+
+    #[test]
+    fn test_compiled_strategy_equivalence() {
+        assert_compiled_strategy_equivalent(
+            BasicStrategy::new(),
+            CompiledStrategy::new(BasicStrategy::new(), -10, 10),
+        );
+        assert_compiled_strategy_equivalent(
+            S17DeviationStrategy::new(),
+            CompiledStrategy::new(S17DeviationStrategy::new(), -10, 10),
+        );
+        assert_compiled_strategy_equivalent(
+            H17DeviationStrategy::new(),
+            CompiledStrategy::new(H17DeviationStrategy::new(), -10, 10),
+        );
+    }
 }
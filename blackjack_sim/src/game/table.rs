@@ -1,14 +1,164 @@
-use crate::game::player::PlayerSim;
+use crate::game::player::{HandResult, PlayerSim};
 use crate::game::strategy::{
-    BasicStrategy, BettingStrategy, DecisionStrategy, HiLo, MarginBettingStrategy, PlayerStrategy,
-    Strategy,
+    Action, BasicStrategy, BettingStrategy, DecisionReason, DecisionStrategy, HiLo,
+    MarginBettingStrategy, Options, PlayerStrategy, S17DeviationStrategy, Strategy, WongHalves,
 };
 use crate::game::DeckSim;
+use crate::money::Money;
 use crate::strategy::CountingStrategy;
 use blackjack_lib::{BlackjackGameError, BlackjackTable, Card};
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 use std::sync::Arc;
 
+/// The house rules a game is played by, shared by the table and the player. The default rules match the defaults of `BlackjackSimulatorConfig`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TableRules {
+    /// The number of decks in the shoe
+    pub num_decks: usize,
+    /// Ignored, the deck is always shuffled with a single Fisher-Yates pass. Kept for compatibility.
+    pub num_shuffles: u32,
+    /// Whether the dealer hits a soft 17
+    pub soft_seventeen: bool,
+    /// Whether insurance is offered when the dealer shows an ace
+    pub insurance: bool,
+    /// Whether the player may surrender their first two cards
+    pub surrender: bool,
+    /// Whether any two cards of the same value may be split, e.g. a king and a queen, rather than only two cards of the same rank.
+    pub split_by_value: bool,
+    /// Whether the hands of a split may be doubled, i.e. double after split (DAS).
+    pub double_after_split: bool,
+    /// The fraction of the shoe dealt before it is reshuffled, i.e. where the cut card is placed.
+    pub penetration: f32,
+}
+
+impl Default for TableRules {
+    fn default() -> Self {
+        TableRules {
+            num_decks: 6,
+            num_shuffles: 7,
+            soft_seventeen: false,
+            insurance: false,
+            surrender: true,
+            split_by_value: true,
+            double_after_split: true,
+            penetration: 0.8,
+        }
+    }
+}
+
+impl TableRules {
+    /// What a natural pays as the numerator and denominator of a fraction of the bet, i.e. 3:2. Every table pays naturals the same.
+    pub const BLACKJACK_PAYOUT: (i64, i64) = (3, 2);
+}
+
+/// Who a traced card was dealt to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Recipient {
+    Player,
+    Dealer,
+    /// The dealer's face down card, it is not counted until it is revealed
+    DealerFaceDown,
+}
+
+/// An event of a traced game, see `BlackjackTableSim::enable_trace()`.
+#[derive(Debug, Clone)]
+pub enum TraceEvent {
+    /// The deck was shuffled and the player's strategy was reset
+    Shuffle,
+    /// A new round started with the player betting `bet` from a balance of `balance`
+    Bet {
+        round: u32,
+        bet: u32,
+        balance: Money,
+    },
+    /// A card was dealt or revealed, along with the player's running and true count after counting it
+    Card {
+        recipient: Recipient,
+        card: Arc<Card>,
+        running_count: f32,
+        true_count: f32,
+    },
+    /// The player decided how to play `hand` from `options`, `reason` is the rule that decided it if the strategy can tell
+    Decision {
+        hand: Vec<Arc<Card>>,
+        options: Options,
+        option: Action,
+        reason: Option<DecisionReason>,
+    },
+    /// The round was resolved, `results` holds the result of each of the player's hands
+    Resolution {
+        dealer_hand: String,
+        results: Vec<HandResult>,
+        winnings: Money,
+    },
+}
+
+impl Display for TraceEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format_card = |card: &Arc<Card>| format!("{}{}", card.rank, card.suit);
+        match self {
+            TraceEvent::Shuffle => write!(f, "shuffle, the count is reset"),
+            TraceEvent::Bet {
+                round,
+                bet,
+                balance,
+            } => write!(
+                f,
+                "round {}: bet {} with a balance of {}",
+                round, bet, balance
+            ),
+            TraceEvent::Card {
+                recipient: Recipient::DealerFaceDown,
+                ..
+            } => write!(f, "  dealer <- face down card"),
+            TraceEvent::Card {
+                recipient,
+                card,
+                running_count,
+                true_count,
+            } => write!(
+                f,
+                "  {} <- {:<4}running count {:.1}, true count {:.2}",
+                if *recipient == Recipient::Player {
+                    "player"
+                } else {
+                    "dealer"
+                },
+                format_card(card),
+                running_count,
+                true_count
+            ),
+            TraceEvent::Decision {
+                hand,
+                options,
+                option,
+                reason,
+            } => write!(
+                f,
+                "  hand [{}], options [{}]: {} ({})",
+                hand.iter()
+                    .map(format_card)
+                    .collect::<Vec<String>>()
+                    .join(" "),
+                options,
+                option,
+                reason.map_or(String::from("no reason given"), |reason| reason.to_string())
+            ),
+            TraceEvent::Resolution {
+                dealer_hand,
+                results,
+                winnings,
+            } => write!(
+                f,
+                "  dealer has {}, results {:?}, winnings {}",
+                dealer_hand, results, winnings
+            ),
+        }
+    }
+}
+
 pub struct DealersHandSim {
     pub hand: Vec<Arc<Card>>,
     pub hand_value: Vec<u8>,
@@ -56,6 +206,16 @@ impl DealersHandSim {
         }
     }
 
+    /// Returns the best value of the hand and whether it is soft, i.e. the high value of a soft hand unless it busts, in which case the ace
+    /// only counts as one and the hand is hard.
+    pub fn best_value(&self) -> (u8, bool) {
+        match self.hand_value[..] {
+            [_, high] if high <= 21 => (high, true),
+            [low, _] | [low] => (low, false),
+            _ => (0, false),
+        }
+    }
+
     /// Methods that checks if the dealer has a blackjack
     pub fn has_blackjack(&self) -> bool {
         self.hand.len() == 2
@@ -70,50 +230,125 @@ impl DealersHandSim {
     }
 }
 
-/// Struct for a simulated blackjack game
+/// Helper function that returns the winnings of a natural on a bet of `bet`, see `TableRules::BLACKJACK_PAYOUT`.
+fn blackjack_winnings(bet: Money) -> Money {
+    let (numerator, denominator) = TableRules::BLACKJACK_PAYOUT;
+    bet * numerator / denominator
+}
+
+/// Struct for a simulated blackjack game.
+/// Money only moves between the player and the table: a stake leaves the player's balance when it is placed,
+/// a lost bet's stake goes to the table, a won bet's stake is returned along with its winnings paid by the table
+/// (1.5 times the bet for a blackjack), a push returns the stake and a surrender returns half the stake with the other half going to the table.
+/// Every bet is settled in `finish_hand`, so the player's and the table's balances always add up to the same total between hands.
+/// The table never pays out more than its balance: a bet is only placed if the table could pay it as a natural, and doubles and splits
+/// are only offered while the table could pay every bet the player has on the table.
+/// A table without a balance never runs out of money, it covers every bet and keeps no account of what it wins or pays out.
 pub struct BlackjackTableSim {
-    pub balance: f32,
-    pub hand_log: Option<(i32, i32, i32, f32)>,
+    pub balance: Option<Money>,
+    pub hand_log: Option<(i32, i32, i32, Money)>,
     final_cards: Vec<Arc<Card>>,
     pub dealers_hand: DealersHandSim,
     pub num_player_blackjacks: i32,
-    // n_decks: usize,
-    n_shuffles: u32,
+    /// The winnings of the insurance bet taken during the last hand, `None` if no insurance bet was taken
+    pub insurance_winnings: Option<Money>,
     deck: DeckSim,
-    soft_seventeen: bool,
-    insurance: bool,
+    rules: TableRules,
+    dealer_plays_out: bool,
+    trace: Option<Vec<TraceEvent>>,
 }
 
 impl BlackjackTableSim {
-    pub fn new(
-        starting_balance: f32,
-        n_decks: usize,
-        n_shuffles: u32,
-        soft_seventeen: bool,
-        insurance: bool,
-    ) -> Self {
+    /// Associated function to create a new table playing by `rules`, `seed` makes the shuffles of the deck reproducible.
+    /// A `starting_balance` of `None` creates a table that never runs out of money.
+    pub fn new(starting_balance: Option<Money>, rules: TableRules, seed: Option<u64>) -> Self {
         let dealers_hand = DealersHandSim::new();
-        let deck = DeckSim::new(n_decks);
+        let mut deck = DeckSim::new(rules.num_decks, seed);
+        deck.set_penetration(rules.penetration);
         BlackjackTableSim {
             balance: starting_balance,
             hand_log: None,
             final_cards: vec![],
             dealers_hand,
             num_player_blackjacks: 0,
-            n_shuffles,
+            insurance_winnings: None,
             deck,
-            soft_seventeen,
-            insurance,
+            rules,
+            dealer_plays_out: false,
+            trace: None,
         }
     }
 
-    /// Helper method for determining whether or not the dealer needs to draw more cards at the end of the hand
-    /// Method panics if the hand value vector does not contain two values i.e. dealer does not have a soft total.
-    fn dealer_draws_soft_total(&self) -> bool {
-        assert!(self.dealers_hand.hand_value.len() == 2);
-        (self.dealers_hand.hand_value[0] < 17 && self.dealers_hand.hand_value[1] < 17)
-            || (self.soft_seventeen
-                && (self.dealers_hand.hand_value[0] <= 17 && self.dealers_hand.hand_value[1] <= 17))
+    /// Getter method for the rules the table plays by.
+    pub fn rules(&self) -> &TableRules {
+        &self.rules
+    }
+
+    /// Returns true if the table could pay out `amount`, a table without a balance covers any amount.
+    fn covers(&self, amount: Money) -> bool {
+        self.balance.is_none_or(|balance| balance >= amount)
+    }
+
+    /// Adds `amount` to the table's balance, a table without a balance keeps no account of what it wins or pays out.
+    fn settle(&mut self, amount: Money) {
+        if let Some(ref mut balance) = self.balance {
+            *balance += amount;
+        }
+    }
+
+    /// Sets whether the dealer draws out their hand even when every one of the player's hands was already resolved, i.e. busted,
+    /// surrendered or paid as a natural. By default the dealer only reveals the hole card in that case, as most houses do when dealing
+    /// to a single player, so such rounds use fewer cards from the shoe.
+    pub fn set_dealer_plays_out(&mut self, dealer_plays_out: bool) {
+        self.dealer_plays_out = dealer_plays_out;
+    }
+
+    /// Starts recording every card dealt, decision made and hand resolved, the events are collected with `self.take_trace()`.
+    /// Tracing is meant for walking through a few rounds, it slows down long simulations.
+    pub fn enable_trace(&mut self) {
+        self.trace = Some(vec![]);
+    }
+
+    /// Returns true if the table is being traced.
+    pub fn is_tracing(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    /// Records `event` if the table is being traced.
+    pub fn record(&mut self, event: TraceEvent) {
+        if let Some(ref mut trace) = self.trace {
+            trace.push(event);
+        }
+    }
+
+    /// Returns the events recorded since tracing was enabled or the trace was last taken.
+    pub fn take_trace(&mut self) -> Vec<TraceEvent> {
+        self.trace.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Helper method that records `card` being dealt to `recipient`, along with the count of `player` after counting it.
+    fn trace_card<S: Strategy>(
+        &mut self,
+        recipient: Recipient,
+        card: &Arc<Card>,
+        player: &PlayerSim<S>,
+    ) {
+        if self.trace.is_some() {
+            let (running_count, true_count) = player.counts();
+            self.record(TraceEvent::Card {
+                recipient,
+                card: Arc::clone(card),
+                running_count,
+                true_count,
+            });
+        }
+    }
+
+    /// Helper method for determining whether or not the dealer needs to draw more cards at the end of the hand.
+    /// The dealer draws below 17 and stands on a hard 17 or more, a soft 17 is only drawn to if the dealer hits soft 17.
+    fn dealer_draws(&self) -> bool {
+        let (value, soft) = self.dealers_hand.best_value();
+        value < 17 || (value == 17 && soft && self.rules.soft_seventeen)
     }
 }
 
@@ -124,49 +359,57 @@ impl<S: Strategy> BlackjackTable<PlayerSim<S>> for BlackjackTableSim {
         player: &mut PlayerSim<S>,
         bet: f32,
     ) -> Result<(), blackjack_lib::BlackjackGameError> {
-        if bet <= 0.0 {
+        // The bet crosses the `BlackjackTable` trait as a float, bets are whole dollars
+        let stake = Money::from_f32(bet);
+        if stake <= Money::ZERO {
             return Err(BlackjackGameError {
                 message: "bet must be a positive amount".to_string(),
             });
-        } else if self.balance < 1.5 * bet {
+        } else if !self.covers(blackjack_winnings(stake)) {
             return Err(BlackjackGameError {
                 message: "insufficient table balance to payout bet".to_string(),
             });
         }
-        Ok(player.place_bet(bet))
+        Ok(player.place_bet(bet as u32))
     }
 
     /// Simulates dealing a hand of blackjack, the method may panic if `player` has not placed a valid bet.
     fn deal_hand(&mut self, player: &mut PlayerSim<S>) {
         assert!(!player.bets.is_empty());
+        player.set_table_limit(self.balance);
 
         if self.deck.shuffle_flag {
-            self.deck.shuffle(self.n_shuffles);
+            self.deck.shuffle(self.rules.num_shuffles);
             player.reset_strategy();
+            self.record(TraceEvent::Shuffle);
         }
 
         // Now deal cards to player and dealer
         let mut cur_card = self.deck.get_next_card().unwrap();
         player.receive_card(Arc::clone(&cur_card));
         player.update_strategy(Some(&cur_card));
+        self.trace_card(Recipient::Player, &cur_card, player);
 
         // First card to dealer is face up so the players strategy should be aware of it
         cur_card = self.deck.get_next_card().unwrap();
         self.dealers_hand.receive_card(Arc::clone(&cur_card));
         player.update_strategy(Some(&cur_card));
+        self.trace_card(Recipient::Dealer, &cur_card, player);
 
         cur_card = self.deck.get_next_card().unwrap();
         player.receive_card(Arc::clone(&cur_card));
         player.update_strategy(Some(&cur_card));
+        self.trace_card(Recipient::Player, &cur_card, player);
 
         // This card is face down so the players strategy should not take this card into account
         cur_card = self.deck.get_next_card().unwrap();
+        self.trace_card(Recipient::DealerFaceDown, &cur_card, player);
         self.dealers_hand.receive_card(cur_card);
 
         // Check for insurance bet conditions
-        if self.insurance
+        if self.rules.insurance
             && self.dealers_hand.hand[0].rank == "A"
-            && self.balance >= player.get_current_bet() as f32
+            && self.covers(Money::from_dollars(player.get_current_bet().into()))
         {
             // Player decides to take or not to take the insurance bet here
             player.take_insurance();
@@ -176,10 +419,10 @@ impl<S: Strategy> BlackjackTable<PlayerSim<S>> for BlackjackTableSim {
         // in addition we need to update the players strategy, i.e. the counting strategy
         if self.dealers_hand.has_blackjack() {
             // Check if player has insurance, if so mark insurance bet as payable
-            if self.insurance && player.has_insurance_bet() {
+            if self.rules.insurance && player.has_insurance_bet() {
                 player.win_insurance();
             }
-            player.update_strategy(Some(&self.dealers_hand.hand[1]));
+            self.reveal_hole_card();
             if player.has_blackjack() {
                 player.push_current_hand();
                 self.num_player_blackjacks += 1;
@@ -187,9 +430,8 @@ impl<S: Strategy> BlackjackTable<PlayerSim<S>> for BlackjackTableSim {
                 player.lose_current_hand();
             }
         } else if player.has_blackjack() {
-            let current_bet = player.get_current_bet() as f32;
-            self.balance -= current_bet * 1.5;
-            player.blackjack(current_bet * 1.5);
+            let current_bet = Money::from_dollars(player.get_current_bet().into());
+            player.blackjack(blackjack_winnings(current_bet));
             self.num_player_blackjacks += 1;
         }
     }
@@ -201,6 +443,7 @@ impl<S: Strategy> BlackjackTable<PlayerSim<S>> for BlackjackTableSim {
         let card = self.deck.get_next_card().unwrap();
         player.receive_card(Arc::clone(&card));
         player.update_strategy(Some(&card));
+        self.trace_card(Recipient::Player, &card, player);
         if player.busted() {
             player.lose_current_hand();
         }
@@ -213,6 +456,7 @@ impl<S: Strategy> BlackjackTable<PlayerSim<S>> for BlackjackTableSim {
         let card = self.deck.get_next_card().unwrap();
         player.receive_card(Arc::clone(&card));
         player.update_strategy(Some(&card));
+        self.trace_card(Recipient::Player, &card, player);
         player.stand();
     }
 
@@ -224,7 +468,9 @@ impl<S: Strategy> BlackjackTable<PlayerSim<S>> for BlackjackTableSim {
         );
         player.split(Arc::clone(&card1), Arc::clone(&card2));
         player.update_strategy(Some(&card1));
+        self.trace_card(Recipient::Player, &card1, player);
         player.update_strategy(Some(&card2));
+        self.trace_card(Recipient::Player, &card2, player);
     }
 
     /// Method that calls the `player`'s stand method.
@@ -232,60 +478,37 @@ impl<S: Strategy> BlackjackTable<PlayerSim<S>> for BlackjackTableSim {
         player.stand();
     }
 
-    /// Method that computes and returns the optimal final hand for the dealer at the end of a hand of blackjack
+    /// Method that computes and returns the optimal final hand for the dealer at the end of a hand of blackjack, i.e. draws until the dealer stands
+    /// and returns the best value of the dealer's hand, see `DealersHandSim::best_value()`.
     fn get_dealers_optimal_final_hand(&mut self) -> u8 {
-        // Reveal dealers face down card here
-        self.final_cards
-            .push(Arc::clone(&self.dealers_hand.hand[1]));
-
-        if self.dealers_hand.hand_value.len() == 2 {
-            while self.dealer_draws_soft_total() {
-                let next_card = self.deck.get_next_card().unwrap();
-                self.dealers_hand.receive_card(Arc::clone(&next_card));
-                self.final_cards.push(next_card);
-            }
-
-            // Ensure we have a valid hand according to the rules of blackjack
-            while (self.dealers_hand.hand_value[0] > 21 && self.dealers_hand.hand_value[1] < 17)
-                || (self.dealers_hand.hand_value[0] < 17 && self.dealers_hand.hand_value[1] > 21)
-            {
-                let next_card = self.deck.get_next_card().unwrap();
-                self.dealers_hand.receive_card(Arc::clone(&next_card));
-                self.final_cards.push(next_card);
-            }
-
-            if self.dealers_hand.hand_value[0] <= 21 && self.dealers_hand.hand_value[1] <= 21 {
-                return u8::max(
-                    self.dealers_hand.hand_value[0],
-                    self.dealers_hand.hand_value[1],
-                );
-            } else {
-                return u8::min(
-                    self.dealers_hand.hand_value[0],
-                    self.dealers_hand.hand_value[1],
-                );
-            }
-        }
-
-        while self.dealers_hand.hand_value[0] < 17 {
+        self.reveal_hole_card();
+        while self.dealer_draws() {
             let next_card = self.deck.get_next_card().unwrap();
             self.dealers_hand.receive_card(Arc::clone(&next_card));
             self.final_cards.push(next_card);
         }
-
-        self.dealers_hand.hand_value[0]
+        self.dealers_hand.best_value().0
     }
 
-    /// Method for finishing the hand and deciding what bet(s) `player` wins or loses
+    /// Method for finishing the hand and deciding what bet(s) `player` wins or loses.
+    /// The dealer always reveals the hole card, but only draws out their hand if one of the player's hands is still live,
+    /// unless the table is set to have the dealer play out every round, see `set_dealer_plays_out()`.
     fn finish_hand(&mut self, player: &mut PlayerSim<S>) {
-        if let Some(players_final_hands) = player.get_optimal_hands() {
+        // The hole card is revealed whether or not the dealer has to play their hand, so it is always counted
+        self.reveal_hole_card();
+        let players_final_hands = player.get_optimal_hands();
+        if players_final_hands.is_none() && self.dealer_plays_out {
+            <BlackjackTableSim as BlackjackTable<PlayerSim<S>>>::get_dealers_optimal_final_hand(
+                self,
+            );
+        }
+        if let Some(players_final_hands) = players_final_hands {
             let dealers_optimal_hand =
                 <BlackjackTableSim as BlackjackTable<PlayerSim<S>>>::get_dealers_optimal_final_hand(
                     self,
                 );
             for (i, bet, hand) in players_final_hands {
                 if dealers_optimal_hand > 21 || hand > dealers_optimal_hand {
-                    self.balance -= bet as f32;
                     player.win_hand(i, bet);
                 } else if dealers_optimal_hand == hand {
                     player.push_hand(i, bet);
@@ -295,41 +518,65 @@ impl<S: Strategy> BlackjackTable<PlayerSim<S>> for BlackjackTableSim {
             }
         }
 
-        // Update the players strategy
-        player.update_strategy(self.final_cards.iter());
+        // Update the players strategy, one card at a time when tracing so the count is recorded after each card
+        if self.trace.is_some() {
+            for i in 0..self.final_cards.len() {
+                let card = Arc::clone(&self.final_cards[i]);
+                player.update_strategy(Some(&card));
+                self.trace_card(Recipient::Dealer, &card, player);
+            }
+        } else {
+            player.update_strategy(self.final_cards.iter());
+        }
 
-        let (mut hands_won, mut hands_pushed, mut hands_lost, mut winnings) = (0, 0, 0, 0.0);
-        for (_, bet) in player.bets_log.iter() {
-            if *bet > 0.0 || *bet < 0.0 {
-                winnings += *bet;
-                if *bet < 0.0 {
-                    hands_lost += 1;
-                    self.balance -= *bet;
-                } else {
+        // Settle every bet, stakes have already left the player's balance and those of won and pushed hands have been returned,
+        // so the table pays out the winnings of won bets and keeps the stakes of lost ones
+        let (mut hands_won, mut hands_pushed, mut hands_lost, mut winnings) =
+            (0, 0, 0, Money::ZERO);
+        let mut payout = Money::ZERO;
+        for result in player.bets_log.iter() {
+            match *result {
+                HandResult::Win(amount) => {
                     hands_won += 1;
+                    winnings += amount;
+                    payout += amount;
+                    self.settle(-amount);
                 }
-            } else {
-                hands_pushed += 1;
+                HandResult::Loss(amount) => {
+                    hands_lost += 1;
+                    winnings -= amount;
+                    self.settle(amount);
+                }
+                HandResult::Push => hands_pushed += 1,
+                HandResult::Pending => {}
             }
         }
 
-        if self.insurance && player.has_insurance_bet() {
-            match player.insurance_bet {
+        // Insurance pays 2:1, a won insurance bet also returns its stake
+        self.insurance_winnings = None;
+        if self.rules.insurance && player.has_insurance_bet() {
+            let insurance_winnings = match player.insurance_bet {
                 Some((bet, flag)) if flag => {
-                    self.balance -= bet;
-                    winnings += 2.0 * bet;
-                    player.collect_winnings(bet);
-                }
-                Some((bet, flag)) => {
-                    self.balance += bet;
-                    winnings -= bet;
+                    payout += bet * 3;
+                    bet * 2
                 }
+                Some((bet, _)) => -bet,
                 _ => panic!("insurance bet should have been placed"),
             };
+            self.settle(-insurance_winnings);
+            winnings += insurance_winnings;
+            self.insurance_winnings = Some(insurance_winnings);
         }
 
-        if winnings > 0.0 {
-            player.collect_winnings(winnings);
+        player.collect_winnings(payout);
+
+        if self.trace.is_some() {
+            let event = TraceEvent::Resolution {
+                dealer_hand: self.dealers_hand.formatted_hand_values(),
+                results: player.bets_log.clone(),
+                winnings,
+            };
+            self.record(event);
         }
 
         self.hand_log = Some((hands_won, hands_pushed, hands_lost, winnings));
@@ -337,21 +584,35 @@ impl<S: Strategy> BlackjackTable<PlayerSim<S>> for BlackjackTableSim {
 }
 
 impl BlackjackTableSim {
-    /// Takes a `PlayerSim<S>` struct, a HashMap<i32, String> representing the options available during the current turn (these options will be decided during runtime), and an i32 `option`.
-    /// The method decides what method to call the implements the appropriate logic, returns a `Result<(), BlackjackGameError>` since the method is fallible.
+    /// Takes a `PlayerSim<S>` struct and the `Action` `option` the player chose to play their current hand with.
+    /// The method calls the method that implements the logic of the action, returns an error naming the available options if `option` is not one of them.
     pub fn play_option<S: Strategy>(
         &mut self,
         player: &mut PlayerSim<S>,
-        option: String,
+        option: Action,
     ) -> Result<(), BlackjackGameError> {
-        match option.as_str() {
-            "stand" => Ok(self.stand(player)),
-            "hit" => Ok(self.hit(player)),
-            "split" => Ok(self.split(player)),
-            "double down" => Ok(self.double_down(player)),
-            "surrender" => Ok(self.surrender(player)),
-            _ => Err(BlackjackGameError::new("option not available".to_string())),
+        // Checked here, since e.g. splitting a hand that can't be split would deal cards to a hand that doesn't exist
+        let options = player.get_playing_options(self.dealers_face_up_card());
+        if !options.contains_action(option) {
+            return Err(BlackjackGameError::new(format!(
+                "option {} not available for the current hand, valid options are [{}]",
+                option, options
+            )));
+        }
+        player.record_action(option);
+        match option {
+            Action::Stand => self.stand(player),
+            Action::Hit => self.hit(player),
+            Action::Split => self.split(player),
+            Action::DoubleDown => self.double_down(player),
+            Action::Surrender => self.surrender(player),
         }
+        Ok(())
+    }
+
+    /// Getter method for the number of cards left in the shoe.
+    pub fn cards_remaining(&self) -> u32 {
+        self.deck.cards_remaining()
     }
 
     /// Getter method for the dealers face up card.
@@ -359,17 +620,39 @@ impl BlackjackTableSim {
         Arc::clone(&self.dealers_hand.hand[0])
     }
 
+    /// Method for starting a new shoe, clears the state of the last round and the next hand will be dealt from a freshly shuffled shoe.
+    pub fn new_shoe(&mut self) {
+        self.reset();
+        self.deck.shuffle_flag = true;
+    }
+
+    /// Method for reseeding the deck, the next hand will be dealt from a freshly shuffled shoe.
+    pub fn reseed(&mut self, seed: u64) {
+        self.deck.reseed(seed);
+    }
+
+    /// Helper method that reveals the dealer's face down card, the revealed card is counted along with the cards the dealer draws
+    /// in `finish_hand`. The card is only revealed once per round, however many times the method is called.
+    fn reveal_hole_card(&mut self) {
+        if self.final_cards.is_empty() {
+            self.final_cards
+                .push(Arc::clone(&self.dealers_hand.hand[1]));
+        }
+    }
+
     /// Method for reseting the table for another round, does not reshuffle deck.
     pub fn reset(&mut self) {
         self.final_cards.clear();
         self.dealers_hand.reset();
         self.num_player_blackjacks = 0;
+        self.insurance_winnings = None;
+        self.hand_log = None;
     }
 
-    //TODO: implement surrender functionality eventually
+    /// Method for surrendering the player's current hand, the lost half of the bet is collected in `finish_hand`
+    /// and the hand is left out of the comparison with the dealer.
     pub fn surrender<S: Strategy>(&mut self, player: &mut PlayerSim<S>) {
-        let surrender_amount = player.surrender();
-        self.balance += surrender_amount;
+        player.surrender();
     }
 }
 
@@ -379,11 +662,11 @@ fn test_single_hand() {
     let decision_strategy = BasicStrategy::new();
     let betting_strategy = MarginBettingStrategy::new(3.0, 5);
     let strategy = PlayerStrategy::new(counting_strategy, decision_strategy, betting_strategy);
-    let mut player = PlayerSim::new(500.0, strategy, true);
+    let mut player = PlayerSim::new(Money::from_dollars(500), strategy, TableRules::default());
     // let mut table = <BlackjackTableSim as BlackjackTable<
     //     PlayerSim<PlayerStrategy<HiLo, BasicStrategy, MarginBettingStrategy>>,
     // >>::new(f32::MAX, 6, 7);
-    let mut table = BlackjackTableSim::new(f32::MAX, 6, 7, false, false);
+    let mut table = BlackjackTableSim::new(None, TableRules::default(), None);
 
     // Get the bet from the player and place a bet
     let bet = if let Ok(b) = player.bet() {
@@ -391,7 +674,7 @@ fn test_single_hand() {
     } else {
         panic!("player returned a bet of 0");
     };
-    player.place_bet(bet as f32);
+    player.place_bet(bet);
 
     // Display the player struct for debuggin purposes
     println!("{}", player);
@@ -415,7 +698,10 @@ fn test_single_hand() {
 
     println!("playing options = {:?}", options);
 
-    let decision_result = player.decide_option(Arc::clone(&table.dealers_hand.hand[0]));
+    let decision_result = player.decide_option(
+        Arc::clone(&table.dealers_hand.hand[0]),
+        table.cards_remaining(),
+    );
 
     if decision_result.is_ok() {
         println!("option chosen = {}", decision_result.as_ref().ok().unwrap());
@@ -443,11 +729,11 @@ fn test_single_hand_loop() {
     let decision_strategy = BasicStrategy::new();
     let betting_strategy = MarginBettingStrategy::new(3.0, 5);
     let strategy = PlayerStrategy::new(counting_strategy, decision_strategy, betting_strategy);
-    let mut player = PlayerSim::new(500.0, strategy, true);
+    let mut player = PlayerSim::new(Money::from_dollars(500), strategy, TableRules::default());
     // let mut table = <BlackjackTableSim as BlackjackTable<
     //     PlayerSim<PlayerStrategy<HiLo, BasicStrategy, MarginBettingStrategy>>,
     // >>::new(f32::MAX, 6, 7);
-    let mut table = BlackjackTableSim::new(f32::MAX, 6, 7, false, false);
+    let mut table = BlackjackTableSim::new(None, TableRules::default(), None);
 
     // Get bet from player
     let bet = match player.bet() {
@@ -462,7 +748,7 @@ fn test_single_hand_loop() {
         }
     };
 
-    player.place_bet(bet as f32);
+    player.place_bet(bet);
 
     // Display player
     println!("{}", player);
@@ -487,7 +773,10 @@ fn test_single_hand_loop() {
         let options = player.get_playing_options(table.dealers_face_up_card());
         println!("options: {:?}", options);
 
-        let decision_result = player.decide_option(Arc::clone(&table.dealers_hand.hand[0]));
+        let decision_result = player.decide_option(
+            Arc::clone(&table.dealers_hand.hand[0]),
+            table.cards_remaining(),
+        );
 
         let decision = match decision_result {
             Ok(d) => {
@@ -529,3 +818,574 @@ fn test_single_hand_loop() {
 
     assert!(true);
 }
+
+#[test]
+fn test_reseeded_tables_share_first_shoe() {
+    let mut player1 = PlayerSim::new(
+        Money::from_dollars(500),
+        PlayerStrategy::new(
+            HiLo::new(6),
+            BasicStrategy::new(),
+            MarginBettingStrategy::new(3.0, 5),
+        ),
+        TableRules::default(),
+    );
+    let mut player2 = PlayerSim::new(
+        Money::from_dollars(500),
+        PlayerStrategy::new(
+            WongHalves::new(6),
+            S17DeviationStrategy::new(),
+            MarginBettingStrategy::new(2.0, 5),
+        ),
+        TableRules::default(),
+    );
+    let mut table1 = BlackjackTableSim::new(None, TableRules::default(), None);
+    let mut table2 = BlackjackTableSim::new(None, TableRules::default(), None);
+    table1.reseed(7);
+    table2.reseed(7);
+
+    // Dealing the first hand shuffles the shoe
+    player1.place_bet(5);
+    player2.place_bet(10);
+    table1.deal_hand(&mut player1);
+    table2.deal_hand(&mut player2);
+
+    let shoe = |table: &BlackjackTableSim| {
+        table
+            .deck
+            .cards
+            .iter()
+            .map(|card| (card.suit, card.rank))
+            .collect::<Vec<_>>()
+    };
+    assert!(shoe(&table1) == shoe(&table2));
+}
+
+/// The rules most of the tests below are played by: a single deck and no surrender.
+#[cfg(test)]
+fn single_deck_rules() -> TableRules {
+    TableRules {
+        num_decks: 1,
+        surrender: false,
+        ..TableRules::default()
+    }
+}
+
+/// Helper for the tests below, seats a $500 player playing basic strategy at a single deck $1,000 table whose shoe deals
+/// `ranks` in order.
+#[cfg(test)]
+fn single_deck_game(
+    ranks: &[&str],
+) -> (
+    PlayerSim<PlayerStrategy<HiLo, BasicStrategy, MarginBettingStrategy>>,
+    BlackjackTableSim,
+) {
+    let strategy = PlayerStrategy::new(
+        HiLo::new(1),
+        BasicStrategy::new(),
+        MarginBettingStrategy::new(3.0, 10),
+    );
+    let player = PlayerSim::new(Money::from_dollars(500), strategy, single_deck_rules());
+    let mut table =
+        BlackjackTableSim::new(Some(Money::from_dollars(1_000)), single_deck_rules(), None);
+    table.deck.cards = ranks
+        .iter()
+        .map(|&rank| Arc::new(Card::new("S", rank)))
+        .collect();
+    table.deck.shuffle_flag = false;
+    (player, table)
+}
+
+#[test]
+fn surrendering_16_against_10_loses_half_the_bet() {
+    let strategy = PlayerStrategy::new(
+        HiLo::new(1),
+        BasicStrategy::new(),
+        MarginBettingStrategy::new(3.0, 10),
+    );
+    let rules = TableRules {
+        surrender: true,
+        ..single_deck_rules()
+    };
+    let mut player = PlayerSim::new(Money::from_dollars(500), strategy, rules);
+    let mut table = BlackjackTableSim::new(Some(Money::from_dollars(1_000)), rules, Some(5));
+
+    // Rig the shoe so the player is dealt 10, 6 against the dealer's 10, 7 and would win by hitting a 5
+    table.deck.cards = ["10", "10", "6", "7", "5", "5"]
+        .iter()
+        .map(|&rank| Arc::new(Card::new("S", rank)))
+        .collect();
+    table.deck.shuffle_flag = false;
+
+    player.place_bet(10);
+    table.deal_hand(&mut player);
+    let decision = player
+        .decide_option(table.dealers_face_up_card(), table.cards_remaining())
+        .unwrap();
+    assert_eq!(decision, Action::Surrender);
+    table.play_option(&mut player, decision).unwrap();
+    assert!(player.turn_is_over());
+    table.finish_hand(&mut player);
+
+    assert_eq!(table.hand_log, Some((0, 0, 1, Money::from_dollars(-5))));
+    assert_eq!(player.balance(), Money::from_dollars(495));
+    assert_eq!(table.balance, Some(Money::from_dollars(1_005)));
+}
+
+/// Helper for the insurance tests, returns a player at a high count and a table offering insurance whose shoe deals `ranks` in order.
+#[cfg(test)]
+fn insurance_game(
+    ranks: &[&'static str],
+) -> (
+    PlayerSim<PlayerStrategy<HiLo, S17DeviationStrategy, MarginBettingStrategy>>,
+    BlackjackTableSim,
+) {
+    let strategy = PlayerStrategy::new(
+        HiLo::new(1),
+        S17DeviationStrategy::new(),
+        MarginBettingStrategy::new(3.0, 10),
+    );
+    let mut player = PlayerSim::new(Money::from_dollars(500), strategy, single_deck_rules());
+    // Ten low cards seen push the true count well above the insurance index of 3
+    let low_cards = (0..10)
+        .map(|_| Arc::new(Card::new("S", "2")))
+        .collect::<Vec<_>>();
+    player.update_strategy(low_cards.iter());
+
+    let mut table = BlackjackTableSim::new(
+        Some(Money::from_dollars(1_000)),
+        TableRules {
+            insurance: true,
+            ..single_deck_rules()
+        },
+        Some(5),
+    );
+    table.deck.cards = ranks
+        .iter()
+        .map(|&rank| Arc::new(Card::new("S", rank)))
+        .collect();
+    table.deck.shuffle_flag = false;
+    (player, table)
+}
+
+#[test]
+fn insurance_pays_two_to_one_on_a_dealer_blackjack() {
+    // The player's 16 loses to the dealer's blackjack, the insurance bet of half the stake makes up for it
+    let (mut player, mut table) = insurance_game(&["9", "A", "7", "K"]);
+    player.place_bet(10);
+    table.deal_hand(&mut player);
+    assert!(player.has_insurance_bet());
+    assert!(player.turn_is_over());
+    table.finish_hand(&mut player);
+
+    assert_eq!(table.insurance_winnings, Some(Money::from_dollars(10)));
+    assert_eq!(table.hand_log, Some((0, 0, 1, Money::from_dollars(0))));
+    assert_eq!(player.balance(), Money::from_dollars(500));
+    assert_eq!(table.balance, Some(Money::from_dollars(1_000)));
+}
+
+#[test]
+fn insurance_is_forfeited_without_a_dealer_blackjack() {
+    // The player's 20 beats the dealer's soft 18, but the insurance bet is lost
+    let (mut player, mut table) = insurance_game(&["10", "A", "10", "7"]);
+    player.place_bet(10);
+    table.deal_hand(&mut player);
+    assert!(player.has_insurance_bet());
+    table.play_option(&mut player, Action::Stand).unwrap();
+    table.finish_hand(&mut player);
+
+    assert_eq!(table.insurance_winnings, Some(Money::from_dollars(-5)));
+    assert_eq!(table.hand_log, Some((1, 0, 0, Money::from_dollars(5))));
+    assert_eq!(player.balance(), Money::from_dollars(505));
+    assert_eq!(table.balance, Some(Money::from_dollars(995)));
+}
+
+/// Helper for the soft 17 tests, plays a player's 18 standing against the dealer's soft 17 of an ace and a six,
+/// with `ranks` dealt after the first four cards. Returns the hand log and the number of cards the dealer ended with.
+#[cfg(test)]
+fn stand_18_against_soft_17(
+    soft_seventeen: bool,
+    ranks: &[&'static str],
+) -> (Option<(i32, i32, i32, Money)>, usize) {
+    let ranks = ["10", "A", "8", "6"]
+        .iter()
+        .chain(ranks.iter())
+        .copied()
+        .collect::<Vec<_>>();
+    let (mut player, mut table) = single_deck_game(&ranks);
+    table.rules.soft_seventeen = soft_seventeen;
+
+    player.place_bet(10);
+    table.deal_hand(&mut player);
+    table.play_option(&mut player, Action::Stand).unwrap();
+    table.finish_hand(&mut player);
+    (table.hand_log, table.dealers_hand.hand.len())
+}
+
+#[test]
+fn dealer_hits_soft_17_only_under_h17() {
+    // Standing on soft 17 the player's 18 wins, hitting it the dealer draws a 2 to make 19
+    let (hand_log, dealer_cards) = stand_18_against_soft_17(false, &["2"]);
+    assert_eq!(hand_log, Some((1, 0, 0, Money::from_dollars(10))));
+    assert_eq!(dealer_cards, 2);
+
+    let (hand_log, dealer_cards) = stand_18_against_soft_17(true, &["2"]);
+    assert_eq!(hand_log, Some((0, 0, 1, Money::from_dollars(-10))));
+    assert_eq!(dealer_cards, 3);
+}
+
+#[test]
+fn player_18_loses_more_often_against_h17() {
+    const NUM_TRIALS: u64 = 500;
+    let mut losses = [0, 0];
+    for seed in 0..NUM_TRIALS {
+        // Draw the dealer's extra cards from the same shuffled deck under both rules
+        let mut deck = DeckSim::new(1, Some(seed));
+        deck.shuffle(1);
+        let ranks = deck.cards.iter().map(|card| card.rank).collect::<Vec<_>>();
+        for (i, soft_seventeen) in [false, true].into_iter().enumerate() {
+            let (hand_log, _) = stand_18_against_soft_17(soft_seventeen, &ranks);
+            losses[i] += hand_log.unwrap().2;
+        }
+    }
+    assert_eq!(losses[0], 0);
+    assert!(losses[1] > 0);
+}
+
+#[test]
+fn soft_18_doubles_against_a_4() {
+    let (mut player, mut table) = single_deck_game(&["A", "4", "7", "10", "5", "K", "9"]);
+
+    player.place_bet(10);
+    table.deal_hand(&mut player);
+    let options = player.get_playing_options(table.dealers_face_up_card());
+    assert!(options.contains(Options::DOUBLE));
+    let decision = player
+        .decide_option(table.dealers_face_up_card(), table.cards_remaining())
+        .unwrap();
+    assert_eq!(decision, Action::DoubleDown);
+    table.play_option(&mut player, decision).unwrap();
+
+    // Doubling deals exactly one more card and ends the player's turn with twice the bet
+    assert!(player.turn_is_over());
+    assert_eq!(player.bets, vec![20]);
+    assert_eq!(table.deck.deck_pos, 5);
+    assert_eq!(player.balance(), Money::from_dollars(480));
+}
+
+#[test]
+fn split_aces_get_one_card_and_pay_even_money() {
+    // Each ace draws a ten, the dealer's 16 draws a 2 to stand on 18
+    let (mut player, mut table) = single_deck_game(&["A", "6", "A", "10", "10", "K", "2", "9"]);
+
+    player.place_bet(10);
+    table.deal_hand(&mut player);
+    assert!(player
+        .get_playing_options(table.dealers_face_up_card())
+        .contains(Options::SPLIT));
+    table.play_option(&mut player, Action::Split).unwrap();
+
+    // Neither ace can be played any further
+    assert!(player.turn_is_over());
+    table.finish_hand(&mut player);
+
+    // Both 21s win even money rather than paying as blackjacks
+    assert_eq!(table.hand_log, Some((2, 0, 0, Money::from_dollars(20))));
+    assert_eq!(table.num_player_blackjacks, 0);
+    assert_eq!(player.balance(), Money::from_dollars(520));
+}
+
+#[test]
+fn ten_value_cards_split_only_by_value() {
+    let deal_king_queen = |split_by_value: bool, balance: Money| {
+        let (mut player, mut table) = single_deck_game(&["K", "6", "Q", "10", "4", "5", "9"]);
+        player.balance = balance;
+        player.set_split_by_value(split_by_value);
+        player.place_bet(10);
+        table.deal_hand(&mut player);
+        (player, table)
+    };
+
+    let (mut player, mut table) = deal_king_queen(true, Money::from_dollars(500));
+    assert!(player.can_split());
+    table.play_option(&mut player, Action::Split).unwrap();
+    assert_eq!(player.bets, vec![10, 10]);
+
+    let (mut player, mut table) = deal_king_queen(false, Money::from_dollars(500));
+    assert!(!player.can_split());
+    let error = table.play_option(&mut player, Action::Split).unwrap_err();
+    assert_eq!(
+        error.message,
+        "option split not available for the current hand, valid options are [hit, stand, double down]"
+    );
+    assert_eq!(player.bets, vec![10]);
+
+    // A split the player can't afford is refused rather than dealt
+    let (mut player, mut table) = deal_king_queen(true, Money::from_dollars(15));
+    assert!(!player.can_split());
+    assert!(table.play_option(&mut player, Action::Split).is_err());
+    assert_eq!(player.bets, vec![10]);
+    assert_eq!(player.balance(), Money::from_dollars(5));
+}
+
+#[test]
+fn natural_pays_three_to_two_once() {
+    let (mut player, mut table) = single_deck_game(&["A", "6", "K", "10", "5"]);
+
+    player.place_bet(10);
+    table.deal_hand(&mut player);
+    assert!(player.turn_is_over());
+    table.finish_hand(&mut player);
+
+    // The stake is returned when the natural is dealt, the winnings are paid when the hand is settled
+    assert_eq!(table.hand_log, Some((1, 0, 0, Money::from_dollars(15))));
+    assert_eq!(table.num_player_blackjacks, 1);
+    assert_eq!(player.balance(), Money::from_dollars(515));
+    assert_eq!(table.balance, Some(Money::from_dollars(985)));
+}
+
+#[test]
+fn dealer_only_draws_when_a_hand_is_live() {
+    // The player's 16 against the dealer's 16, the king busts whoever draws it first
+    let play_16 = |option: Action, dealer_plays_out: bool| {
+        let (mut player, mut table) = single_deck_game(&["10", "6", "6", "10", "K", "5", "9"]);
+        table.set_dealer_plays_out(dealer_plays_out);
+        player.place_bet(10);
+        table.deal_hand(&mut player);
+        table.play_option(&mut player, option).unwrap();
+        if !player.turn_is_over() {
+            table.play_option(&mut player, Action::Stand).unwrap();
+        }
+        table.finish_hand(&mut player);
+        (table.deck.deck_pos, table.final_cards.len())
+    };
+
+    // Standing leaves a live hand so the dealer draws, after the player busts the dealer only reveals the hole card
+    assert_eq!(play_16(Action::Stand, false), (5, 2));
+    assert_eq!(play_16(Action::Hit, false), (5, 1));
+    assert_eq!(play_16(Action::Hit, true), (6, 2));
+}
+
+#[test]
+fn rounds_do_not_inherit_the_previous_hand_log() {
+    use crate::game::BlackjackGameSim;
+
+    // The dealer's blackjack beats the player's 16 on the deal, then the player's 20 beats the dealer's 18
+    let (player, table) = single_deck_game(&["9", "A", "7", "K", "10", "6", "10", "10", "2", "9"]);
+    let mut game = BlackjackGameSim::new(table, player, 1, 10);
+
+    game.run().unwrap();
+    assert_eq!(
+        (game.total_wins, game.total_pushes, game.total_losses),
+        (0, 0, 1)
+    );
+    assert!(game.table.hand_log.is_none());
+
+    game.run().unwrap();
+    assert_eq!(
+        (game.total_wins, game.total_pushes, game.total_losses),
+        (1, 0, 1)
+    );
+    assert_eq!(game.rounds, 2);
+}
+
+#[test]
+fn small_tables_only_offer_doubles_and_splits_they_can_cover() {
+    let options_at = |table_balance: Money| {
+        let (mut player, mut table) = single_deck_game(&["8", "6", "8", "10", "5"]);
+        table.balance = Some(table_balance);
+        player.place_bet(10);
+        table.deal_hand(&mut player);
+        player.get_playing_options(table.dealers_face_up_card())
+    };
+
+    // Doubling or splitting a bet of 10 puts 20 at stake
+    let options = options_at(Money::from_dollars(20));
+    assert!(options.contains(Options::DOUBLE | Options::SPLIT));
+    let options = options_at(Money::from_dollars(19));
+    assert!(!options.intersects(Options::DOUBLE | Options::SPLIT));
+}
+
+#[test]
+fn bets_the_table_cannot_pay_as_a_natural_are_refused() {
+    use crate::game::BlackjackGameSim;
+
+    let strategy = PlayerStrategy::new(
+        HiLo::new(1),
+        BasicStrategy::new(),
+        MarginBettingStrategy::new(3.0, 10),
+    );
+    let player = PlayerSim::new(Money::from_dollars(500), strategy, single_deck_rules());
+    let table = BlackjackTableSim::new(Some(Money::from_dollars(14)), single_deck_rules(), Some(3));
+    let mut game = BlackjackGameSim::new(table, player, 10, 10);
+    assert!(game.run().is_err());
+    assert_eq!(game.rounds, 0);
+    assert_eq!(game.player.balance(), Money::from_dollars(500));
+}
+
+#[test]
+fn a_balance_below_the_minimum_ends_the_simulation_early() {
+    use crate::game::{BlackjackGameSim, Ruin};
+
+    let strategy = PlayerStrategy::new(
+        HiLo::new(1),
+        BasicStrategy::new(),
+        MarginBettingStrategy::new(3.0, 5),
+    );
+    let player = PlayerSim::new(Money::from_dollars(7), strategy, single_deck_rules());
+    let mut table =
+        BlackjackTableSim::new(Some(Money::from_dollars(1_000)), single_deck_rules(), None);
+    // The dealer's blackjack leaves the player with $2 at a $5 table
+    table.deck.cards = ["9", "A", "7", "K", "10", "6", "10", "10"]
+        .iter()
+        .map(|&rank| Arc::new(Card::new("S", rank)))
+        .collect();
+    table.deck.shuffle_flag = false;
+    let mut game = BlackjackGameSim::new(table, player, 10, 5);
+
+    game.run().unwrap();
+    assert!(game.ended_early);
+    assert_eq!(game.rounds, 1);
+    assert_eq!(
+        game.ruin,
+        Some(Ruin {
+            round: 1,
+            balance: Money::from_dollars(2)
+        })
+    );
+}
+
+#[test]
+fn split_hands_double_only_when_doubling_after_a_split_is_allowed() {
+    let doubles_offered = |double_after_split: bool| {
+        // The 6s against a 5 draw a 3 and a 4
+        let (mut player, mut table) = single_deck_game(&["6", "5", "6", "10", "3", "4"]);
+        player.set_double_after_split(double_after_split);
+
+        player.place_bet(10);
+        table.deal_hand(&mut player);
+        assert!(player
+            .get_playing_options(table.dealers_face_up_card())
+            .contains(Options::DOUBLE));
+        table.play_option(&mut player, Action::Split).unwrap();
+
+        let mut offered = vec![];
+        for _ in 0..2 {
+            offered.push(
+                player
+                    .get_playing_options(table.dealers_face_up_card())
+                    .contains(Options::DOUBLE),
+            );
+            table.play_option(&mut player, Action::Stand).unwrap();
+        }
+        assert!(player.turn_is_over());
+        offered
+    };
+
+    assert_eq!(doubles_offered(true), vec![true, true]);
+    assert_eq!(doubles_offered(false), vec![false, false]);
+}
+
+/// Helper for the dealer drawing tests, deals `dealer` to the dealer and draws out the dealer's hand from a shoe holding `shoe`.
+/// Returns the dealer's final value and the number of cards in the dealer's hand.
+#[cfg(test)]
+fn draw_out_dealer(dealer: &[&str], shoe: &[&str], soft_seventeen: bool) -> (u8, usize) {
+    type Player = PlayerSim<PlayerStrategy<HiLo, BasicStrategy, MarginBettingStrategy>>;
+    let mut table = BlackjackTableSim::new(
+        Some(Money::from_dollars(1_000)),
+        TableRules {
+            soft_seventeen,
+            ..single_deck_rules()
+        },
+        None,
+    );
+    for &rank in dealer {
+        table
+            .dealers_hand
+            .receive_card(Arc::new(Card::new("S", rank)));
+    }
+    table.deck.cards = shoe
+        .iter()
+        .map(|&rank| Arc::new(Card::new("S", rank)))
+        .collect();
+    table.deck.shuffle_flag = false;
+    let value =
+        <BlackjackTableSim as BlackjackTable<Player>>::get_dealers_optimal_final_hand(&mut table);
+    (value, table.dealers_hand.hand.len())
+}
+
+#[test]
+fn dealer_draws_to_the_best_value_of_soft_hands() {
+    // The dealer's cards, the shoe, and the final value and number of cards when standing and when hitting soft 17
+    type Case = (
+        &'static [&'static str],
+        &'static [&'static str],
+        (u8, usize),
+        (u8, usize),
+    );
+    let cases: [Case; 5] = [
+        // Soft 16 draws a 10 to a hard 16 with a dead ace, which draws again
+        (&["A", "5"], &["10", "5"], (21, 4), (21, 4)),
+        // Soft 17 only draws when hitting soft 17
+        (&["A", "6"], &["2"], (17, 2), (19, 3)),
+        // A hard 16 draws an ace to a hard 17
+        (&["K", "6"], &["A", "2"], (17, 3), (17, 3)),
+        // Three aces and a 4 make a soft 17, hitting it draws a 10 to a hard 17
+        (&["A", "A"], &["A", "4", "10"], (17, 4), (17, 5)),
+        // A soft hand that busts its high value keeps drawing on its low value
+        (&["A", "2"], &["9", "3", "4"], (19, 5), (19, 5)),
+    ];
+    for (dealer, shoe, standing, hitting) in cases {
+        assert_eq!(
+            draw_out_dealer(dealer, shoe, false),
+            standing,
+            "{:?}",
+            dealer
+        );
+        assert_eq!(draw_out_dealer(dealer, shoe, true), hitting, "{:?}", dealer);
+    }
+}
+
+#[test]
+fn hand_observer_records_the_actions_of_a_split_round() {
+    use crate::game::{BlackjackGameSim, HandHistory};
+    use std::sync::Mutex;
+
+    // The 8s against a 6 draw a 3 and a 10, the 11 doubles into a 9 and the dealer's 16 busts with a 10
+    let (player, table) = single_deck_game(&["8", "6", "8", "10", "3", "10", "9", "10", "2", "2"]);
+    let mut game = BlackjackGameSim::new(table, player, 1, 10);
+
+    let histories = Arc::new(Mutex::new(vec![]));
+    let observed = Arc::clone(&histories);
+    game.set_hand_observer(Box::new(move |history: &HandHistory| {
+        observed.lock().unwrap().push(history.clone())
+    }));
+    game.run().unwrap();
+
+    let histories = histories.lock().unwrap();
+    assert_eq!(histories.len(), 1);
+    let history = &histories[0];
+    assert_eq!(
+        history
+            .initial_cards
+            .iter()
+            .map(|card| card.rank)
+            .collect::<Vec<&str>>(),
+        vec!["8", "8"]
+    );
+    assert_eq!(history.dealer_up.rank, "6");
+    assert_eq!(
+        history.actions,
+        vec![Action::Split, Action::DoubleDown, Action::Stand]
+    );
+    assert_eq!(history.final_values, vec![20, 18]);
+    assert_eq!(history.bet, 10);
+    assert_eq!(
+        history.results,
+        vec![
+            HandResult::Win(Money::from_dollars(20)),
+            HandResult::Win(Money::from_dollars(10))
+        ]
+    );
+    assert_eq!(history.true_count_at_bet, 0.0);
+}
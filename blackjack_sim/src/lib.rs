@@ -1,16 +1,31 @@
+//! Library for simulating card counting strategies in blackjack, the crate is organized as follows:
+//! - `game` simulates a single game, with `game::player` holding the `PlayerSim`, `game::table` the one `BlackjackTableSim`
+//!   and `game::strategy` the counting, decision and betting strategies.
+//! - `registry` creates strategies by name for the binaries.
+//! - `write` collects and writes the summaries of finished simulations.
+//! - The crate root runs many games as a simulation, see `BlackjackSimulator` and `MulStrategyBlackjackSimulator`.
+
 pub mod game;
+pub mod money;
+pub mod registry;
 pub mod write;
 
 use blackjack_lib::{BlackjackTable, Card, Deck};
 pub use game::prelude::*;
 use game::strategy::CountingStrategy;
 use prelude::PlayerStrategyDyn;
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
 use std::error::Error;
 use std::fmt::Display;
-use std::iter::FromIterator;
+use std::iter::{FromIterator, Sum};
+use std::marker::PhantomData;
+use std::ops::AddAssign;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::mpsc::{self, channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
+use write::{CollectEvent, HandLogWriter, HandLogger};
 
 use strategy::{
     BasicStrategy, BettingStrategy, DecisionStrategy, HiLo, MarginBettingStrategy, Strategy,
@@ -19,21 +34,183 @@ use strategy::{
 pub mod prelude {
     pub use super::{
         strategy::prelude::*, BlackjackSimulation, BlackjackSimulator, BlackjackSimulatorConfig,
-        BlackjackSimulatorConfigBuilder, MulStrategyBlackjackSimulator,
-        MulStrategyBlackjackSimulatorBuilder, SimulationError, SimulationSummary,
+        BlackjackSimulatorConfigBuilder, ConfigError, ConfigOverride,
+        MulStrategyBlackjackSimulator, MulStrategyBlackjackSimulatorBuilder,
+        ParallelBlackjackSimulator, SimulationDescriptor, SimulationError, SimulationProgress,
+        SimulationSummary,
     };
 }
 
+/// The outcomes of the hands of a `SimulationSummary` as fractions of the hands played, see `SimulationSummary::hand_rates()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandRates {
+    pub win_pct: f32,
+    pub push_pct: f32,
+    pub loss_pct: f32,
+    pub avg_winnings_per_hand: f32,
+}
+
 /// Simple struct for recording all of the interesting data points accumulated during a simulation
+#[derive(Clone)]
 pub struct SimulationSummary {
     pub wins: i32,
     pub pushes: i32,
     pub losses: i32,
     pub early_endings: i32,
-    pub winnings: f32,
+    pub winnings: Money,
+    /// The number of hands played, i.e. the hands that had a bet resolved, every per-hand statistic is relative to it.
+    /// Hands never dealt because the player ran out of funds are not counted, and every hand of a split counts separately
     pub num_hands: u32,
     pub player_blackjacks: i32,
-    pub label: String,
+    pub label: Arc<str>,
+    /// The label describing the full strategy simulated, i.e. its counting, decision and betting strategies, see `Strategy::full_label()`
+    pub strategy: Arc<str>,
+    /// Whether the simulation was run in common random numbers mode, i.e. against the same shoes as every other strategy.
+    pub common_random_numbers: bool,
+    /// The number of rounds played, i.e. the number of initial bets placed
+    pub rounds: u32,
+    /// The sum of squared deviations of the winnings of each round from their mean, see `combine_m2()` for accumulating it
+    pub winnings_m2: f64,
+    /// The total amount bet, including doubles and splits
+    pub total_wagered: Money,
+    /// The largest drop of the player's balance from its highest point during any single simulation
+    pub max_drawdown: Money,
+    /// The number of insurance bets taken
+    pub insurance_bets: i32,
+    /// The net winnings of the insurance bets taken, included in `winnings`
+    pub insurance_winnings: Money,
+    /// The fewest rounds played before the player was ruined in any simulation that ended early, `None` if none did
+    pub min_rounds_to_ruin: Option<u32>,
+    /// The most rounds played before the player was ruined in any simulation that ended early, `None` if none did
+    pub max_rounds_to_ruin: Option<u32>,
+    /// The total number of rounds played before ruin by the simulations that ended early, see `mean_rounds_to_ruin()`
+    pub total_rounds_to_ruin: u32,
+}
+
+impl SimulationSummary {
+    /// Returns the mean number of rounds played before the player was ruined, `None` if no simulation ended early.
+    pub fn mean_rounds_to_ruin(&self) -> Option<f32> {
+        if self.early_endings == 0 {
+            return None;
+        }
+        Some(self.total_rounds_to_ruin as f32 / self.early_endings as f32)
+    }
+
+    /// Returns the sample standard deviation of the winnings of a single round, `None` if fewer than two rounds were played.
+    pub fn std_dev(&self) -> Option<f32> {
+        if self.rounds < 2 {
+            return None;
+        }
+        Some((self.winnings_m2 / (self.rounds - 1) as f64).sqrt() as f32)
+    }
+
+    /// Returns the player's edge, i.e. the winnings as a fraction of the total amount wagered. `None` if nothing was wagered.
+    pub fn edge(&self) -> Option<f32> {
+        if self.total_wagered == Money::ZERO {
+            return None;
+        }
+        Some((self.winnings.to_f64() / self.total_wagered.to_f64()) as f32)
+    }
+
+    /// Returns the win, push and loss rates and the average winnings per hand, `None` if no hands were played.
+    pub fn hand_rates(&self) -> Option<HandRates> {
+        if self.num_hands == 0 {
+            return None;
+        }
+        let total_hands = self.num_hands as f32;
+        Some(HandRates {
+            win_pct: self.wins as f32 / total_hands,
+            push_pct: self.pushes as f32 / total_hands,
+            loss_pct: self.losses as f32 / total_hands,
+            avg_winnings_per_hand: self.winnings.to_f32() / total_hands,
+        })
+    }
+
+    /// Returns N0, the number of rounds after which the expected winnings equal one standard deviation of the winnings.
+    /// `None` if the standard deviation is unknown or the average winnings per round are zero.
+    pub fn n0(&self) -> Option<f32> {
+        let std_dev = self.std_dev()? as f64;
+        let mean = self.winnings.to_f64() / self.rounds as f64;
+        if mean == 0.0 {
+            return None;
+        }
+        Some((std_dev * std_dev / (mean * mean)) as f32)
+    }
+
+    /// Merges the totals recorded in `other` into `self`, used to accumulate the summaries of the simulations of a single strategy.
+    /// The derived statistics, e.g. `SimulationSummary::std_dev()`, are only computed from the merged totals.
+    pub fn merge(&mut self, other: &SimulationSummary) {
+        // Must be combined before the winnings are merged, since it depends on the mean winnings of both summaries
+        self.winnings_m2 = combine_m2(
+            self.rounds,
+            self.winnings.to_f64(),
+            self.winnings_m2,
+            other.rounds,
+            other.winnings.to_f64(),
+            other.winnings_m2,
+        );
+        self.rounds += other.rounds;
+        self.num_hands += other.num_hands;
+        self.total_wagered += other.total_wagered;
+        // Every simulation starts from the same balance, so drawdowns are not additive
+        self.max_drawdown = self.max_drawdown.max(other.max_drawdown);
+        self.wins += other.wins;
+        self.pushes += other.pushes;
+        self.losses += other.losses;
+        self.winnings += other.winnings;
+        self.player_blackjacks += other.player_blackjacks;
+        self.insurance_bets += other.insurance_bets;
+        self.insurance_winnings += other.insurance_winnings;
+        self.early_endings += other.early_endings;
+        self.min_rounds_to_ruin = match (self.min_rounds_to_ruin, other.min_rounds_to_ruin) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.max_rounds_to_ruin = match (self.max_rounds_to_ruin, other.max_rounds_to_ruin) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.total_rounds_to_ruin += other.total_rounds_to_ruin;
+    }
+}
+
+impl AddAssign<&SimulationSummary> for SimulationSummary {
+    fn add_assign(&mut self, other: &SimulationSummary) {
+        self.merge(other);
+    }
+}
+
+/// Merges every summary into the first one, keeping its labels. `None` if there are no summaries to merge.
+impl<'a> Sum<&'a SimulationSummary> for Option<SimulationSummary> {
+    fn sum<I: Iterator<Item = &'a SimulationSummary>>(mut iter: I) -> Self {
+        let mut merged = iter.next()?.clone();
+        for summary in iter {
+            merged += summary;
+        }
+        Some(merged)
+    }
+}
+
+/// Combines the sums of squared deviations from the mean `m2_a` and `m2_b` of two sets of rounds, given the number of rounds
+/// and total winnings of each set. The deviations of each set are measured from that set's own mean, so the squared difference
+/// of the two means is added back in, i.e. the parallel variance algorithm of Chan et al.
+pub fn combine_m2(
+    rounds_a: u32,
+    winnings_a: f64,
+    m2_a: f64,
+    rounds_b: u32,
+    winnings_b: f64,
+    m2_b: f64,
+) -> f64 {
+    if rounds_a == 0 {
+        return m2_b;
+    }
+    if rounds_b == 0 {
+        return m2_a;
+    }
+    let (n_a, n_b) = (rounds_a as f64, rounds_b as f64);
+    let delta = winnings_b / n_b - winnings_a / n_a;
+    m2_a + m2_b + delta * delta * n_a * n_b / (n_a + n_b)
 }
 
 impl Display for SimulationSummary {
@@ -41,22 +218,30 @@ impl Display for SimulationSummary {
         const width: usize = 80;
         const text_width: usize = "number of player blackjacks".len() + 20;
         const num_width: usize = width - text_width;
-        let total_hands = self.wins + self.losses + self.pushes;
+        let total_hands = self.num_hands;
+        // The rates are undefined for a simulation without any hands
+        let optional = |value: Option<f32>, precision: usize| {
+            value.map_or(String::from("n/a"), |v| format!("{:.*}", precision, v))
+        };
+        let rates = self.hand_rates();
+        if self.common_random_numbers {
+            writeln!(f, "shoes: common random numbers")?;
+        }
         let body = format!(
             "{}{}\n\
         {:<text_width$}{:>num_width$}\n\
         {:<text_width$}{:>num_width$}\n\
         {:<text_width$}{:>num_width$}\n\
-        {:<text_width$}{:>num_width$.2}\n\
         {:<text_width$}{:>num_width$}\n\
         {:<text_width$}{:>num_width$}\n\
         {:<text_width$}{:>num_width$}\n\
-        {:<text_width$}{:>num_width$.2}\n\
-        {:<text_width$}{:>num_width$.2}\n\
-        {:<text_width$}{:>num_width$.2}\n\
-        {:<text_width$}{:>num_width$.2}\n",
+        {:<text_width$}{:>num_width$}\n\
+        {:<text_width$}{:>num_width$}\n\
+        {:<text_width$}{:>num_width$}\n\
+        {:<text_width$}{:>num_width$}\n\
+        {:<text_width$}{:>num_width$}\n",
             "strategy: ",
-            self.label,
+            self.strategy,
             "hands won",
             self.wins,
             "hands pushed",
@@ -72,47 +257,215 @@ impl Display for SimulationSummary {
             "total hands played",
             total_hands,
             "win percentage",
-            (self.wins as f32) / (total_hands as f32),
+            optional(rates.map(|rates| rates.win_pct), 2),
             "push percentage",
-            (self.pushes as f32) / (total_hands as f32),
+            optional(rates.map(|rates| rates.push_pct), 2),
             "loss percentage",
-            (self.losses as f32) / (total_hands as f32),
+            optional(rates.map(|rates| rates.loss_pct), 2),
             "average winnings per hand",
-            self.winnings / (total_hands as f32)
+            optional(rates.map(|rates| rates.avg_winnings_per_hand), 2)
         );
-        write!(f, "{}", body)
+        write!(f, "{}", body)?;
+
+        // The derived statistics are undefined for too few rounds as well, e.g. the standard deviation of a single round
+        writeln!(
+            f,
+            "{:<text_width$}{:>num_width$}",
+            "total wagered", self.total_wagered
+        )?;
+        writeln!(
+            f,
+            "{:<text_width$}{:>num_width$}",
+            "edge",
+            optional(self.edge(), 4)
+        )?;
+        writeln!(
+            f,
+            "{:<text_width$}{:>num_width$}",
+            "standard deviation per round",
+            optional(self.std_dev(), 2)
+        )?;
+        writeln!(
+            f,
+            "{:<text_width$}{:>num_width$}",
+            "maximum drawdown", self.max_drawdown
+        )?;
+        // Only shown when insurance was offered and taken, so runs without insurance are unchanged
+        if self.insurance_bets > 0 {
+            writeln!(
+                f,
+                "{:<text_width$}{:>num_width$}",
+                "insurance bets taken", self.insurance_bets
+            )?;
+            writeln!(
+                f,
+                "{:<text_width$}{:>num_width$}",
+                "insurance winnings", self.insurance_winnings
+            )?;
+        }
+        // Only shown when a player was ruined, the mean is defined whenever the minimum and maximum are
+        if let (Some(min), Some(mean), Some(max)) = (
+            self.min_rounds_to_ruin,
+            self.mean_rounds_to_ruin(),
+            self.max_rounds_to_ruin,
+        ) {
+            writeln!(
+                f,
+                "{:<text_width$}{:>num_width$}",
+                "rounds to ruin (min/mean/max)",
+                format!("{}/{:.1}/{}", min, mean, max)
+            )?;
+        }
+        writeln!(
+            f,
+            "{:<text_width$}{:>num_width$}",
+            "N0 (rounds)",
+            optional(self.n0(), 0)
+        )
+    }
+}
+
+/// Describes a simulation added to a `MulStrategyBlackjackSimulator`, i.e. the names of the strategies it tests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationDescriptor {
+    pub counting_strategy: Arc<str>,
+    pub decision_strategy: String,
+    pub betting_strategy: String,
+}
+
+impl SimulationDescriptor {
+    /// Associated method for creating the `SimulationDescriptor` of a simulation testing `strategy`.
+    pub fn new<S: Strategy>(strategy: &S) -> Self {
+        SimulationDescriptor {
+            counting_strategy: strategy.label(),
+            decision_strategy: strategy.decision_strategy_name(),
+            betting_strategy: strategy.betting_strategy_name(),
+        }
     }
 }
 
 #[derive(Debug)]
 pub enum SimulationError {
-    GameError(String),
-    SendingError(String),
-    WriteError(String),
+    /// A game of the strategy with the given id and label failed with `source`. `simulation_index` is the index of the simulation that failed among
+    /// every simulation of the strategy, and `simulations_completed` the number of simulations finished before it by the simulator that ran it,
+    /// the two differ when the simulations of a strategy are split between threads
+    StrategyError {
+        strategy_id: usize,
+        label: Arc<str>,
+        simulation_index: u64,
+        simulations_completed: u32,
+        source: BlackjackGameError,
+    },
+    /// The summary of simulation `simulation_index` of the strategy with the given id and label could not be sent, because the collecting
+    /// thread has stopped receiving
+    ChannelClosed {
+        strategy_id: usize,
+        label: Arc<str>,
+        simulation_index: u32,
+    },
+    /// The thread collecting the summaries panicked
+    CollectorPanicked,
+    /// Writing the summaries or the hand log failed
+    Io(std::io::Error),
+    /// The run was stopped through the cancel flag of the simulator before every simulation finished
+    Cancelled,
+    /// A simulation could not be added, since the simulator already holds the given maximum number of simulations
+    TooManySimulations(usize),
 }
 
 impl Display for SimulationError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            SimulationError::GameError(s)
-            | SimulationError::SendingError(s)
-            | SimulationError::WriteError(s) => write!(f, "{}", s),
+            SimulationError::StrategyError {
+                strategy_id,
+                label,
+                simulations_completed,
+                source,
+                ..
+            } => write!(
+                f,
+                "strategy #{} failed: simulation '{}' failed after {} simulations: {}",
+                strategy_id, label, simulations_completed, source.message
+            ),
+            SimulationError::ChannelClosed {
+                strategy_id,
+                label,
+                simulation_index,
+            } => write!(
+                f,
+                "strategy #{} failed: simulation '{}' could not send the summary of simulation {}, the collecting thread has stopped",
+                strategy_id, label, simulation_index
+            ),
+            SimulationError::CollectorPanicked => write!(f, "the collecting thread panicked"),
+            SimulationError::Io(e) => write!(f, "{}", e),
+            SimulationError::Cancelled => write!(f, "simulation cancelled"),
+            SimulationError::TooManySimulations(limit) => write!(
+                f,
+                "unable to add simulation, at most {} simulations may be added",
+                limit
+            ),
         }
     }
 }
 
-impl Error for SimulationError {}
+impl Error for SimulationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SimulationError::StrategyError { source, .. } => Some(source),
+            SimulationError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 pub trait BlackjackSimulation: Send {
     /// Required method, the method that will be called to run all simulations.
-    fn run(&mut self) -> Result<(), BlackjackGameError>;
+    fn run(&mut self) -> Result<(), SimulationError>;
     ///Required method, the method that will be called to run a single simulation.
     fn run_single_simulation(&mut self) -> Result<(), BlackjackGameError>;
     /// Required method, the method that will display the stats recorded for a given simulation.
     fn display_stats(&self);
-    /// Required method, the method that will reset the simulation
+    /// Required method, the method that will reset the simulation along with the data it has recorded,
+    /// so every summary taken between resets covers only the simulations run in between
     fn reset(&mut self);
     /// Required method, the method for producing output statistics/data recorded during the simulation
     fn summary(&self) -> SimulationSummary;
+    /// Required method, returns the label of the strategy being simulated
+    fn label(&self) -> Arc<str>;
+    /// Required method, sets a shared flag that is checked between hands, once set the simulation stops as soon as possible.
+    fn set_cancel_flag(&mut self, cancel_flag: Arc<AtomicBool>);
+    /// Required method, sets the `HandLogger` every hand played is streamed to, tagged with `strategy_id`.
+    fn set_hand_logger(&mut self, hand_logger: HandLogger, strategy_id: usize);
+    /// Required method, returns a handle to the progress counters of the simulation. The handle can be taken before the simulation
+    /// is moved onto its thread and read from any other thread while it runs.
+    fn progress(&self) -> SimulationProgress;
+    /// Provided method, returns the number of rounds played so far, see `SimulationProgress::hands_completed()`.
+    fn hands_completed(&self) -> u64 {
+        self.progress().hands_completed()
+    }
+    /// Provided method, returns the number of simulations finished so far, see `SimulationProgress::simulations_completed()`.
+    fn simulations_completed(&self) -> u32 {
+        self.progress().simulations_completed()
+    }
+}
+
+/// A shared handle to the progress counters of a `BlackjackSimulation`, updated by the simulation as it runs. Cloning the handle shares
+/// the counters, they only ever increase, even when the simulation is reset.
+#[derive(Debug, Clone, Default)]
+pub struct SimulationProgress {
+    hands_completed: Arc<AtomicU64>,
+    simulations_completed: Arc<AtomicU32>,
+}
+
+impl SimulationProgress {
+    /// Returns the number of rounds played so far, by every simulation including the one currently running.
+    pub fn hands_completed(&self) -> u64 {
+        self.hands_completed.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of simulations that have finished so far.
+    pub fn simulations_completed(&self) -> u32 {
+        self.simulations_completed.load(Ordering::Relaxed)
+    }
 }
 
 /// Struct for running a number of simulations for a specific strategy.
@@ -125,83 +478,149 @@ where
     S: Strategy,
 {
     game: BlackjackGameSim<S>,
-    player_starting_balance: f32,
-    table_starting_balance: f32,
+    player_starting_balance: Money,
+    table_starting_balance: Option<Money>,
     num_simulations: u32,
-    hands_per_simulation: u32,
     accumulated_wins: i32,
     accumulated_pushes: i32,
     accumulated_losses: i32,
-    accumulated_winnings: f32,
+    accumulated_winnings: Money,
     num_early_endings: i32,
     num_player_blackjacks: i32,
+    accumulated_insurance_bets: i32,
+    accumulated_insurance_winnings: Money,
+    min_rounds_to_ruin: Option<u32>,
+    max_rounds_to_ruin: Option<u32>,
+    total_rounds_to_ruin: u32,
+    accumulated_rounds: u32,
+    accumulated_hands_played: u32,
+    accumulated_winnings_m2: f64,
+    accumulated_wagered: Money,
+    max_drawdown: Money,
     silent: bool,
+    seed: Option<u64>,
+    simulations_started: u64,
+    common_random_numbers: bool,
+    progress: SimulationProgress,
 }
 
 impl<S: Strategy> BlackjackSimulator<S> {
-    pub fn new(
-        strategy: S,
-        player_starting_balance: f32,
-        table_starting_balance: f32,
-        num_simulations: u32,
-        num_decks: usize,
-        num_shuffles: u32,
-        min_bet: u32,
-        hands_per_simulation: u32,
-        silent: bool,
-        surrender: bool,
-        soft_seventeen: bool,
-        insurance: bool,
-    ) -> Self {
-        let player = PlayerSim::new(player_starting_balance, strategy, surrender);
-        // let table = <BlackjackTableSim as BlackjackTable<PlayerSim<S>>>::new(
-        //     table_starting_balance,
-        //     num_decks,
-        //     num_shuffles,
-        //     soft_seventeen,
-        // );
-        let table = BlackjackTableSim::new(
-            table_starting_balance,
-            num_decks,
-            num_shuffles,
-            soft_seventeen,
-            insurance,
-        );
-        let game = BlackjackGameSim::new(table, player, hands_per_simulation, min_bet);
+    /// Associated function to create a new `BlackjackSimulator` that plays `config.num_simulations` simulations of `strategy`
+    /// at a table following the rules of `config`, the deck is shuffled from `config.seed`.
+    pub fn new(strategy: S, config: &BlackjackSimulatorConfig) -> Self {
+        let rules = TableRules::from(config);
+        let player_starting_balance = Money::from_f32(config.player_starting_balance);
+        let table_starting_balance = config.table_balance();
+        let player = PlayerSim::new(player_starting_balance, strategy, rules);
+        let table = BlackjackTableSim::new(table_starting_balance, rules, config.seed);
+        let mut game =
+            BlackjackGameSim::new(table, player, config.hands_per_simulation, config.min_bet);
+        let progress = SimulationProgress::default();
+        game.set_hands_counter(Arc::clone(&progress.hands_completed));
         Self {
             game,
             player_starting_balance,
             table_starting_balance,
-            num_simulations,
-            hands_per_simulation,
+            num_simulations: config.num_simulations,
             accumulated_wins: 0,
             accumulated_pushes: 0,
             accumulated_losses: 0,
-            accumulated_winnings: 0.0,
+            accumulated_winnings: Money::ZERO,
             num_early_endings: 0,
             num_player_blackjacks: 0,
-            silent,
+            accumulated_insurance_bets: 0,
+            accumulated_insurance_winnings: Money::ZERO,
+            min_rounds_to_ruin: None,
+            max_rounds_to_ruin: None,
+            total_rounds_to_ruin: 0,
+            accumulated_rounds: 0,
+            accumulated_hands_played: 0,
+            accumulated_winnings_m2: 0.0,
+            accumulated_wagered: Money::ZERO,
+            max_drawdown: Money::ZERO,
+            silent: config.silent,
+            seed: config.seed,
+            simulations_started: 0,
+            common_random_numbers: config.common_random_numbers,
+            progress,
+        }
+    }
+
+    /// Helper method called before each simulation. If the simulator was given a seed, the deck is reseeded from it and the
+    /// index of the simulation, so every simulation starts from a fresh shoe that only depends on the seed and the index.
+    fn start_simulation(&mut self) {
+        if let Some(seed) = self.seed {
+            self.game
+                .reseed(derive_seed(seed, self.simulations_started as usize));
+        }
+        self.simulations_started += 1;
+    }
+
+    /// Helper method that adds the data recorded by the game during the last simulation to the accumulated data.
+    fn record_simulation(&mut self) {
+        // Must be combined before the winnings are accumulated, since it depends on the mean of the rounds played so far
+        self.accumulated_winnings_m2 = combine_m2(
+            self.accumulated_rounds,
+            self.accumulated_winnings.to_f64(),
+            self.accumulated_winnings_m2,
+            self.game.rounds,
+            self.game.total_winnings.to_f64(),
+            self.game.winnings_m2,
+        );
+        self.accumulated_rounds += self.game.rounds;
+        self.accumulated_hands_played += self.game.hands_played;
+        self.accumulated_wins += self.game.total_wins;
+        self.accumulated_pushes += self.game.total_pushes;
+        self.accumulated_losses += self.game.total_losses;
+        self.accumulated_winnings += self.game.total_winnings;
+        self.accumulated_wagered += self.game.total_wagered;
+        self.num_player_blackjacks += self.game.num_player_blackjacks;
+        self.accumulated_insurance_bets += self.game.insurance_bets;
+        self.accumulated_insurance_winnings += self.game.insurance_winnings;
+        // Every simulation starts from the same balance, so the largest drawdown of any simulation is the largest overall
+        self.max_drawdown = self.max_drawdown.max(self.game.max_drawdown);
+        if self.game.ended_early {
+            self.num_early_endings += 1;
         }
+        if let Some(ruin) = self.game.ruin {
+            self.min_rounds_to_ruin = Some(
+                self.min_rounds_to_ruin
+                    .map_or(ruin.round, |min| min.min(ruin.round)),
+            );
+            self.max_rounds_to_ruin = Some(
+                self.max_rounds_to_ruin
+                    .map_or(ruin.round, |max| max.max(ruin.round)),
+            );
+            self.total_rounds_to_ruin += ruin.round;
+        }
+        self.progress
+            .simulations_completed
+            .fetch_add(1, Ordering::Relaxed);
     }
 }
 
 impl<S: Strategy + Send> BlackjackSimulation for BlackjackSimulator<S> {
-    /// Method that will run the simulation, recording the necessary data. Returns a `Result<(), BlackjackGameError> if an error occurs during any simulation.
-    fn run(&mut self) -> Result<(), BlackjackGameError> {
+    /// Method that will run the simulation, recording the necessary data. Returns `SimulationError::StrategyError` if any simulation fails,
+    /// the strategy of a simulator run on its own is strategy #1, and `SimulationError::Cancelled` if the simulation is cancelled.
+    fn run(&mut self) -> Result<(), SimulationError> {
         // Run the simulation
         for i in 0..self.num_simulations {
-            if let Err(e) = self.game.run() {
-                return Err(e);
+            let simulation_index = self.simulations_started;
+            self.start_simulation();
+            if let Err(source) = self.game.run() {
+                return Err(SimulationError::StrategyError {
+                    strategy_id: 1,
+                    label: self.label(),
+                    simulation_index,
+                    simulations_completed: i,
+                    source,
+                });
             }
-            // Record data from simulation
-            self.accumulated_wins += self.game.total_wins;
-            self.accumulated_pushes += self.game.total_pushes;
-            self.accumulated_losses += self.game.total_losses;
-            self.accumulated_winnings += self.game.total_winnings;
-            self.num_player_blackjacks += self.game.num_player_blackjacks;
-            if self.game.ended_early {
-                self.num_early_endings += 1;
+            if self.game.is_cancelled() {
+                return Err(SimulationError::Cancelled);
             }
+            // Record data from simulation
+            self.record_simulation();
             if !self.silent {
                 println!("simulation #{}", i + 1);
                 self.game.display_stats();
@@ -216,18 +635,12 @@ impl<S: Strategy + Send> BlackjackSimulation for BlackjackSimulator<S> {
 
     /// Method to run a single simulation. The state of the simulation is not reset afterwards, nor is any output displayed to the console.
     fn run_single_simulation(&mut self) -> Result<(), BlackjackGameError> {
+        self.start_simulation();
         if let Err(e) = self.game.run() {
             return Err(e);
         }
         // Record the data from the simulation
-        self.accumulated_wins += self.game.total_wins;
-        self.accumulated_pushes += self.game.total_pushes;
-        self.accumulated_losses += self.game.total_losses;
-        self.accumulated_winnings += self.game.total_winnings;
-        self.num_player_blackjacks += self.game.num_player_blackjacks;
-        if self.game.ended_early {
-            self.num_early_endings += 1;
-        }
+        self.record_simulation();
         if !self.silent {
             self.game.display_stats();
         }
@@ -258,9 +671,18 @@ impl<S: Strategy + Send> BlackjackSimulation for BlackjackSimulator<S> {
             "total losses:", self.accumulated_losses
         );
         println!(
-            "{:<text_width$}{:>numeric_width$.2}",
+            "{:<text_width$}{:>numeric_width$}",
             "total winnings:", self.accumulated_winnings
         );
+        println!(
+            "{:<text_width$}{:>numeric_width$}",
+            "total hands played:", self.accumulated_hands_played
+        );
+        println!(
+            "{:<text_width$}{:>numeric_width$.2}",
+            "average winnings per hand:",
+            self.accumulated_winnings.to_f32() / (self.accumulated_hands_played as f32)
+        );
         println!(
             "{:<text_width$}{:>numeric_width$}",
             "number of player blackjacks:", self.num_player_blackjacks
@@ -280,17 +702,145 @@ impl<S: Strategy + Send> BlackjackSimulation for BlackjackSimulator<S> {
             pushes: self.accumulated_pushes,
             early_endings: self.num_early_endings,
             winnings: self.accumulated_winnings,
-            num_hands: self.num_simulations * self.hands_per_simulation,
+            num_hands: self.accumulated_hands_played,
             player_blackjacks: self.num_player_blackjacks,
             label: self.game.label(),
+            strategy: Arc::from(self.game.full_label()),
+            common_random_numbers: self.common_random_numbers,
+            rounds: self.accumulated_rounds,
+            winnings_m2: self.accumulated_winnings_m2,
+            total_wagered: self.accumulated_wagered,
+            max_drawdown: self.max_drawdown,
+            insurance_bets: self.accumulated_insurance_bets,
+            insurance_winnings: self.accumulated_insurance_winnings,
+            min_rounds_to_ruin: self.min_rounds_to_ruin,
+            max_rounds_to_ruin: self.max_rounds_to_ruin,
+            total_rounds_to_ruin: self.total_rounds_to_ruin,
         }
     }
 
+    fn label(&self) -> Arc<str> {
+        self.game.label()
+    }
+
     /// Method for reseting the state of the simulation, so it can be run again.
     /// Note that a simulation must be reset before running another simulation, otherwise the data produced is not meaningful.
+    /// The accumulated data is cleared as well, so `self.summary()` only covers the simulations run since the last reset.
+    /// The count of simulations started is kept, so the next simulation is still dealt from the next seeded shoe.
     fn reset(&mut self) {
         self.game
             .reset(self.table_starting_balance, self.player_starting_balance);
+        self.accumulated_wins = 0;
+        self.accumulated_pushes = 0;
+        self.accumulated_losses = 0;
+        self.accumulated_winnings = Money::ZERO;
+        self.num_early_endings = 0;
+        self.num_player_blackjacks = 0;
+        self.accumulated_insurance_bets = 0;
+        self.accumulated_insurance_winnings = Money::ZERO;
+        self.min_rounds_to_ruin = None;
+        self.max_rounds_to_ruin = None;
+        self.total_rounds_to_ruin = 0;
+        self.accumulated_rounds = 0;
+        self.accumulated_hands_played = 0;
+        self.accumulated_winnings_m2 = 0.0;
+        self.accumulated_wagered = Money::ZERO;
+        self.max_drawdown = Money::ZERO;
+    }
+
+    fn set_cancel_flag(&mut self, cancel_flag: Arc<AtomicBool>) {
+        self.game.set_cancel_flag(cancel_flag);
+    }
+
+    fn set_hand_logger(&mut self, hand_logger: HandLogger, strategy_id: usize) {
+        self.game.set_hand_logger(hand_logger, strategy_id);
+    }
+
+    fn progress(&self) -> SimulationProgress {
+        self.progress.clone()
+    }
+}
+
+/// Struct for running the simulations of a single strategy across multiple threads. The simulations are partitioned between the threads,
+/// each thread plays its share with its own `BlackjackSimulator` and the results are merged once every thread has finished.
+/// Since every thread needs its own strategy, the strategy is given as a factory closure instead of a single instance.
+/// If the configuration has a seed, the merged results are identical to running all simulations on a single thread.
+pub struct ParallelBlackjackSimulator<S, F>
+where
+    S: Strategy + Send,
+    F: Fn() -> S + Send + Sync,
+{
+    strategy_factory: F,
+    config: BlackjackSimulatorConfig,
+    num_threads: usize,
+    _strategy: PhantomData<fn() -> S>,
+}
+
+impl<S, F> ParallelBlackjackSimulator<S, F>
+where
+    S: Strategy + Send,
+    F: Fn() -> S + Send + Sync,
+{
+    /// Associated method for creating a new `ParallelBlackjackSimulator`. `strategy_factory` is called once per thread to build the strategy
+    /// that thread plays with, `num_threads` is the maximum number of threads the simulations are partitioned across.
+    pub fn new(strategy_factory: F, config: BlackjackSimulatorConfig, num_threads: usize) -> Self {
+        assert!(num_threads > 0, "must run with at least one thread");
+        ParallelBlackjackSimulator {
+            strategy_factory,
+            config,
+            num_threads,
+            _strategy: PhantomData,
+        }
+    }
+
+    /// Method that runs all of the configured simulations and returns the merged `SimulationSummary`.
+    /// Returns the first error encountered by any thread.
+    pub fn run(&self) -> Result<SimulationSummary, SimulationError> {
+        let num_threads = usize::min(
+            self.num_threads,
+            self.config.num_simulations.max(1) as usize,
+        );
+        let (shard_size, remainder) = (
+            self.config.num_simulations / num_threads as u32,
+            self.config.num_simulations % num_threads as u32,
+        );
+
+        let results = thread::scope(|scope| {
+            let mut handles = vec![];
+            let mut first_simulation = 0u32;
+            for shard in 0..num_threads as u32 {
+                // Spread the remainder across the first few shards
+                let num_simulations = shard_size + u32::from(shard < remainder);
+                let shard_start = first_simulation;
+                first_simulation += num_simulations;
+
+                handles.push(scope.spawn(move || {
+                    let mut simulator = BlackjackSimulator::new(
+                        (self.strategy_factory)(),
+                        &BlackjackSimulatorConfig {
+                            num_simulations,
+                            ..self.config
+                        },
+                    );
+                    // Each simulation is seeded by its index, so the shard continues where the previous shard stops
+                    simulator.simulations_started = shard_start as u64;
+                    simulator.run().map(|_| simulator.summary())
+                }));
+            }
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<Result<SimulationSummary, SimulationError>>>()
+        });
+
+        let summaries = results
+            .into_iter()
+            .collect::<Result<Vec<SimulationSummary>, SimulationError>>()?;
+        Ok(summaries
+            .iter()
+            .sum::<Option<SimulationSummary>>()
+            .expect("at least one thread is run"))
     }
 }
 
@@ -306,217 +856,475 @@ type WriteFn = Box<
         + 'static,
 >;
 
-/// A type alias for a write function that returns output as a `Result<String, E>`. Gives
-/// flexibility to the process of writing output resulting from simulations
-type WriteFnOut = Box<
-    dyn Fn(
-            Receiver<(Option<SimulationSummary>, usize)>,
-            HashSet<usize>,
-        ) -> Result<String, Box<dyn std::error::Error + Send + 'static>>
-        + Send
-        + 'static,
->;
-
 /// This struct is for testing multiple strategies at once, designed to give the use options to customize different parameters of the
 /// game while testing multiple strategies. Tests each strategy in parallel to speed up computation.
 pub struct MulStrategyBlackjackSimulator {
     simulations: Vec<Box<dyn BlackjackSimulation>>,
+    descriptors: Vec<SimulationDescriptor>,
     pub config: BlackjackSimulatorConfig,
+    cancel_flag: Arc<AtomicBool>,
+    hand_log: Option<Box<dyn Write + Send + 'static>>,
+    hand_log_ids: Option<HashSet<usize>>,
+    simulations_added: usize,
+    max_simulations: Option<usize>,
 }
 
 impl MulStrategyBlackjackSimulator {
     /// Method that returns a new `MulStrategyBlackjackSimulatorBuilder` object.
-    pub fn new(config: BlackjackSimulatorConfig) -> MulStrategyBlackjackSimulatorBuilder {
+    /// If `config` enables common random numbers without a seed, a random seed is chosen so all strategies still share their shoes.
+    pub fn new(mut config: BlackjackSimulatorConfig) -> MulStrategyBlackjackSimulatorBuilder {
+        if config.common_random_numbers && config.seed.is_none() {
+            config.seed = Some(rand::random());
+        }
         MulStrategyBlackjackSimulatorBuilder {
             simulations: None,
+            descriptors: vec![],
             config: config,
+            max_simulations: None,
+            hand_log: None,
+            hand_log_ids: None,
         }
     }
 
+    /// Returns the maximum number of simulations `self.add_simulation()` allows, `None` if the number is not limited.
+    pub fn max_simulations(&self) -> Option<usize> {
+        self.max_simulations
+    }
+
     /// A public getter that returns an immutable reference to `self.simulations`.
-    pub fn simulations(&self) -> &Vec<Box<dyn BlackjackSimulation>> {
+    pub fn simulations(&self) -> &[Box<dyn BlackjackSimulation>] {
         &self.simulations
     }
 
-    /// The method that will run each of the strategies in a configured simulation. Each strategy gets tested in a new thread,
+    /// A public getter that returns the descriptions of the simulations, in the same order as `self.simulations()`.
+    pub fn descriptors(&self) -> &[SimulationDescriptor] {
+        &self.descriptors
+    }
+
+    /// Returns the flag used to cancel a run, storing `true` in it from any thread makes every simulation thread stop as soon as possible
+    /// and the run return `SimulationError::Cancelled`. The flag is also set by the simulator itself when any simulation returns an error.
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.cancel_flag)
+    }
+
+    /// Requests cancellation of the current or next run, see `self.cancel_flag()`.
+    pub fn cancel(&self) {
+        self.cancel_flag.store(true, Ordering::Relaxed);
+    }
+
+    /// Enables per hand logging for the next run, every hand played by every strategy is streamed as csv to `writer`.
+    /// At most `self.config.hand_log_buffer_size` hands are buffered, the simulations block while the writer catches up.
+    pub fn hand_log(&mut self, writer: Box<dyn Write + Send + 'static>) -> &mut Self {
+        self.hand_log = Some(writer);
+        self
+    }
+
+    /// Limits the hand log to the simulations with the given ids, i.e. their positions in the order they were added starting at 1.
+    /// Every simulation is logged if this is never called.
+    pub fn hand_log_only(&mut self, ids: HashSet<usize>) -> &mut Self {
+        self.hand_log_ids = Some(ids);
+        self
+    }
+
+    /// Helper method that spawns the hand log writing thread if hand logging is enabled, and hands a logger to every simulation.
+    fn start_hand_log(&mut self) -> Option<HandLogWriter> {
+        let writer = self.hand_log.take()?;
+        let (hand_log_writer, hand_logger) =
+            HandLogWriter::spawn(writer, self.config.hand_log_buffer_size);
+        // The ids match the ids used for the summaries, i.e. the order the simulations were added in
+        for (i, simulation) in self.simulations.iter_mut().enumerate() {
+            if self
+                .hand_log_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&(i + 1)))
+            {
+                simulation.set_hand_logger(hand_logger.clone(), i + 1);
+            }
+        }
+        Some(hand_log_writer)
+    }
+
+    /// The method that will run each of the strategies in a configured simulation. The strategies are tested on at most `self.config.max_threads` threads,
     /// the output of each simulation gets sent to the stats module for writing a summary of results to a chosen destination.
+    /// The summaries of the strategies that finished are passed on to `write_fn` once the run is over, so they are written even if another strategy fails.
     pub fn run(
         &mut self,
         file_out: Box<dyn Write + Send + 'static>,
         write_fn: WriteFn,
     ) -> Result<(), SimulationError> {
-        // Open channel
-        let (write_sender, write_receiver) = mpsc::channel::<(Option<SimulationSummary>, usize)>();
-
-        // Collect thread handles
-        let mut handles = vec![];
-        self.simulations.reverse();
-        let mut id = 1usize;
-
-        // Create unique id's for each simulation, that way the writing thread knows when one simulation is done
-        let ids = HashSet::from_iter(1..=self.simulations.len());
-
-        // Spawn thread for writing recorded information
-        let write_handle = thread::spawn(move || write_fn(write_receiver, ids, file_out));
-
-        while let Some(mut simulation) = self.simulations.pop() {
-            // Clone the sender to the write_receiver
-            let write_sender_clone = write_sender.clone();
-            let num_simulations = self.config.num_simulations;
+        let (sender, receiver) = mpsc::channel();
+        let mut ids = HashSet::new();
+        let result = self.run_with(|summary, id| {
+            ids.insert(id);
+            sender
+                .send((Some(summary), id))
+                .expect("the receiver is held until the summaries are written");
+            sender
+                .send((None, id))
+                .expect("the receiver is held until the summaries are written");
+        });
+        drop(sender);
+        let written = write_fn(receiver, ids, file_out);
+        result?;
+        written.map_err(SimulationError::Io)
+    }
 
-            // Spawn the thread for each simulation
-            let handle = thread::spawn(move || {
-                for _i in 0..num_simulations {
-                    if let Err(e) = simulation.run_single_simulation() {
-                        return Err(SimulationError::GameError(e.message));
-                    }
-                    // record data from simulation
-                    let summary = simulation.summary();
-                    // send data to stats module
-                    if let Err(e) = write_sender_clone.send((Some(summary), id)) {
-                        return Err(SimulationError::SendingError(format!("{}", e)));
-                    }
-                    // reset simulation
-                    simulation.reset();
-                }
-                // Tell the stats thread we are finished with this simulation
-                if let Err(e) = write_sender_clone.send((None, id)) {
-                    return Err(SimulationError::SendingError(format!("{}", e)));
-                }
-                Ok(())
-            });
+    /// A method almost identical to `self.run()`, except that the summaries are handed to `collector` instead of being written out.
+    /// `collector` is called on its own thread with the channel every summary is sent on and the ids of the simulations, see
+    /// `write::collect_summaries()`, and whatever it returns is the result of the run, e.g. the summaries formatted as json.
+    /// Fails if a simulation fails, the hand log can not be written or the collector panics.
+    ///
+    /// ```
+    /// use blackjack_sim::prelude::*;
+    /// use blackjack_sim::write;
+    ///
+    /// let config = BlackjackSimulatorConfig::new()
+    ///     .num_simulations(2)
+    ///     .hands_per_simulation(10)
+    ///     .build();
+    /// let mut simulator = MulStrategyBlackjackSimulator::new(config)
+    ///     .simulation(PlayerStrategy::new(
+    ///         HiLo::new(6),
+    ///         BasicStrategy::new(),
+    ///         MarginBettingStrategy::new(3.0, 5),
+    ///     ))
+    ///     .build();
+    /// let (summaries, unfinished) = simulator
+    ///     .run_return_out(|receiver, ids| write::collect_summaries(receiver, ids, |_| {}))
+    ///     .unwrap();
+    /// assert!(unfinished.is_empty());
+    /// assert_eq!(&*summaries[&1].label, "HiLo");
+    /// ```
+    pub fn run_return_out<F, T>(&mut self, collector: F) -> Result<T, SimulationError>
+    where
+        F: FnOnce(Receiver<(Option<SimulationSummary>, usize)>, HashSet<usize>) -> T
+            + Send
+            + 'static,
+        T: Send + 'static,
+    {
+        let (result, collected) = self.run_collecting(collector);
+        result?;
+        Ok(collected.expect("the collecting thread finished"))
+    }
 
-            handles.push(handle);
-            id += 1;
-        }
+    /// Runs every strategy like `self.run()`, but instead of writing the summaries calls `f` with the summary and id of each strategy
+    /// that finished, in order of id. `f` is called on the calling thread once every simulation thread has been joined, so it is called
+    /// for the strategies that finished even if the run fails, and the error is returned afterwards.
+    pub fn run_with<F>(&mut self, f: F) -> Result<(), SimulationError>
+    where
+        F: FnMut(SimulationSummary, usize),
+    {
+        self.run_with_events(|_| {}, f)
+    }
 
-        for (i, handle) in handles.into_iter().enumerate() {
-            if let Err(e) = handle.join().unwrap() {
-                eprintln!("error occured for simulation #{}", i + 1);
-                return Err(e);
+    /// A method almost identical to `self.run_with()`, except that `on_event` is called on the collecting thread as the summaries of the run
+    /// arrive, see `write::collect_summaries()`, e.g. to show the progress of the run.
+    pub fn run_with_events<E, F>(&mut self, on_event: E, mut f: F) -> Result<(), SimulationError>
+    where
+        E: FnMut(CollectEvent) + Send + 'static,
+        F: FnMut(SimulationSummary, usize),
+    {
+        let (result, collected) =
+            self.run_collecting(|receiver, ids| write::collect_summaries(receiver, ids, on_event));
+        if let Some((mut summaries, unfinished)) = collected {
+            let mut ids = summaries
+                .keys()
+                .filter(|id| !unfinished.contains(id))
+                .copied()
+                .collect::<Vec<usize>>();
+            ids.sort_unstable();
+            for id in ids {
+                f(summaries.remove(&id).unwrap(), id);
             }
         }
-
-        // Make sure write_handle has finished as well
-        if let Err(e) = write_handle.join().unwrap() {
-            return Err(SimulationError::WriteError(format!("{}", e)));
-        }
-
-        Ok(())
+        result
     }
 
-    /// A method almost identical to `self.run()` except that it returns the results of the simulation as a `Result<String, dyn Error>`.
-    pub fn run_return_out(
-        &mut self,
-        write_fn: WriteFnOut,
-    ) -> Result<String, Box<dyn std::error::Error + Send + 'static>> {
+    /// Helper method that runs every strategy, sending their summaries to `collector` on its own thread. Returns the result of the run,
+    /// along with whatever `collector` returned unless it panicked.
+    fn run_collecting<F, T>(&mut self, collector: F) -> (Result<(), SimulationError>, Option<T>)
+    where
+        F: FnOnce(Receiver<(Option<SimulationSummary>, usize)>, HashSet<usize>) -> T
+            + Send
+            + 'static,
+        T: Send + 'static,
+    {
         // Open channel
         let (write_sender, write_receiver) = mpsc::channel::<(Option<SimulationSummary>, usize)>();
 
-        // Collect thread handles
-        let mut handles: Vec<JoinHandle<Result<(), SimulationError>>> = vec![];
-        self.simulations.reverse();
-        let mut id: usize = 1;
+        let hand_log_writer = self.start_hand_log();
+        // The simulations are consumed by the run
+        self.descriptors.clear();
 
-        // Create unique Id's for each simulation that way the thread responsible for writing will know when all simulations are finished
+        // Create unique id's for each simulation, that way the collecting thread knows when one simulation is done
         let ids = HashSet::from_iter(1..=self.simulations.len());
 
-        // spawn thread for writing
-        let write_handle = thread::spawn(move || write_fn(write_receiver, ids));
-
-        // spawn a new thread for each simulation
-        while let Some(mut sim) = self.simulations.pop() {
-            let write_sender_clone = write_sender.clone();
-            let num_simulations = self.config.num_simulations;
-
-            let handle = thread::spawn(move || {
-                for _i in 0..num_simulations {
-                    // Run a single simulation
-                    if let Err(e) = sim.run_single_simulation() {
-                        return Err(SimulationError::GameError(e.message));
-                    }
-                    let simulation_summary = sim.summary();
-                    // Record data, i.e. pass simulation summary to thread responsible for writing
-                    if let Err(e) = write_sender_clone.send((Some(simulation_summary), id)) {
-                        return Err(SimulationError::SendingError(format!("{}", e)));
-                    }
-                    // Reset simulation for next iteration
-                    sim.reset();
-                }
-
-                // Tell writing thread we are finished with this simulation
-                if let Err(e) = write_sender_clone.send((None, id)) {
-                    return Err(SimulationError::SendingError(format!("{}", e)));
-                }
+        // Spawn thread for collecting recorded information
+        let collect_handle = thread::spawn(move || collector(write_receiver, ids));
 
-                Ok(())
-            });
+        let handles = spawn_simulation_threads(
+            std::mem::take(&mut self.simulations),
+            &self.config,
+            &write_sender,
+            &self.cancel_flag,
+        );
 
-            id += 1;
-            handles.push(handle);
-        }
+        // Drop the original sender, so the collecting thread is not left waiting if every simulation thread exits early
+        drop(write_sender);
 
-        // Ensure that all handles finish
-        for (i, handle) in handles.into_iter().enumerate() {
-            if let Err(e) = handle.join().unwrap() {
-                eprintln!("an error occured with simulation #{}", i + 1);
-                return Err(Box::new(e));
-            }
-        }
+        let result = join_simulation_threads(handles);
 
-        match write_handle.join().unwrap() {
-            Ok(res) => Ok(res),
-            Err(e) => Err(e),
-        }
+        // Make sure the collecting thread has finished as well
+        let collected = collect_handle.join().ok();
+        // Every simulation, and with it every hand logger, has been dropped so the hand log can finish writing
+        let hand_log_result = hand_log_writer.map_or(Ok(()), |w| w.finish());
+        // A collector that panicked stops receiving summaries, so it is reported over the errors of the simulations it left sending
+        let result = if collected.is_none() {
+            Err(SimulationError::CollectorPanicked)
+        } else {
+            result.and(hand_log_result.map_err(SimulationError::Io))
+        };
+        (result, collected)
     }
 
     /// A method for adding a simulation to the simulator, takes `strategy` and then creates a new simulation which is represented as trait object of type `BlackjackSimulation`,
     ///  the adding it to `self.simulations`.
-    pub fn add_simulation<S: Strategy + Send + 'static>(&mut self, strategy: S) {
+    /// Returns `SimulationError::TooManySimulations` if the simulator already holds `self.max_simulations()` simulations.
+    pub fn add_simulation<S: Strategy + Send + 'static>(
+        &mut self,
+        strategy: S,
+    ) -> Result<(), SimulationError> {
+        let config = self.config;
+        self.add_simulation_with_config(strategy, config)
+    }
+
+    /// A method for adding a simulation that plays with its own configurations instead of `self.config`, e.g. a different number of decks.
+    /// The number of simulations run is always taken from `self.config`. Returns `SimulationError::TooManySimulations` if the simulator already
+    /// holds `self.max_simulations()` simulations.
+    pub fn add_simulation_with_config<S: Strategy + Send + 'static>(
+        &mut self,
+        strategy: S,
+        config: BlackjackSimulatorConfig,
+    ) -> Result<(), SimulationError> {
+        if let Some(limit) = self.max_simulations {
+            if self.simulations.len() >= limit {
+                return Err(SimulationError::TooManySimulations(limit));
+            }
+        }
+        // Seeds are derived from the number of simulations ever added, so a removed simulation's seed is never reused
+        self.simulations_added += 1;
+        let seed = simulation_seed(&config, self.simulations_added);
+        self.descriptors.push(SimulationDescriptor::new(&strategy));
         // Create trait object
         let simulation: Box<dyn BlackjackSimulation> = Box::new(BlackjackSimulator::new(
             strategy,
-            self.config.player_starting_balance,
-            self.config.table_starting_balance,
-            self.config.num_simulations,
-            self.config.num_decks,
-            self.config.num_shuffles,
-            self.config.min_bet,
-            self.config.hands_per_simulation,
-            self.config.silent,
-            self.config.surrender,
-            self.config.soft_seventeen,
-            self.config.insurance,
+            &BlackjackSimulatorConfig {
+                num_simulations: self.config.num_simulations,
+                seed,
+                ..config
+            },
         ));
         self.simulations.push(simulation);
+        Ok(())
+    }
+
+    /// A method for removing the simulation at index `idx` from `self.simulations`, returns false if there is no simulation at `idx`.
+    pub fn remove_simulation(&mut self, idx: usize) -> bool {
+        if idx >= self.simulations.len() {
+            return false;
+        }
+        self.simulations.remove(idx);
+        self.descriptors.remove(idx);
+        true
+    }
+}
+
+/// Helper function that runs `num_simulations` simulations of `simulation` on the current thread, sending each summary to the writing thread tagged with `id`.
+/// Stops early if `cancel_flag` is set, and sets it if the simulation returns an error so the other simulation threads stop as well.
+/// Errors are returned as `SimulationError::StrategyError`, so the caller knows which strategy failed.
+fn run_simulation_thread(
+    mut simulation: Box<dyn BlackjackSimulation>,
+    num_simulations: u32,
+    id: usize,
+    write_sender: Sender<(Option<SimulationSummary>, usize)>,
+    cancel_flag: Arc<AtomicBool>,
+) -> Result<(), SimulationError> {
+    simulation.set_cancel_flag(Arc::clone(&cancel_flag));
+    for simulations_completed in 0..num_simulations {
+        if cancel_flag.load(Ordering::Relaxed) {
+            return Err(SimulationError::Cancelled);
+        }
+        // Run a single simulation
+        if let Err(source) = simulation.run_single_simulation() {
+            cancel_flag.store(true, Ordering::Relaxed);
+            return Err(SimulationError::StrategyError {
+                strategy_id: id,
+                label: simulation.label(),
+                simulation_index: simulations_completed as u64,
+                simulations_completed,
+                source,
+            });
+        }
+        // The simulation may have been cancelled part way through, in which case its results are incomplete
+        if cancel_flag.load(Ordering::Relaxed) {
+            return Err(SimulationError::Cancelled);
+        }
+        // Record data, i.e. pass simulation summary to thread responsible for writing
+        if write_sender.send((Some(simulation.summary()), id)).is_err() {
+            cancel_flag.store(true, Ordering::Relaxed);
+            return Err(SimulationError::ChannelClosed {
+                strategy_id: id,
+                label: simulation.label(),
+                simulation_index: simulations_completed,
+            });
+        }
+        // Reset simulation for next iteration
+        simulation.reset();
     }
+
+    // Tell writing thread we are finished with this simulation
+    if write_sender.send((None, id)).is_err() {
+        cancel_flag.store(true, Ordering::Relaxed);
+        return Err(SimulationError::ChannelClosed {
+            strategy_id: id,
+            label: simulation.label(),
+            simulation_index: num_simulations,
+        });
+    }
+    Ok(())
+}
+
+/// Helper function that runs `simulations` on at most `config.max_threads` threads, each thread takes the next simulation that has not
+/// been started once it finishes its current one. The summaries of each simulation are tagged with its position in `simulations`, starting at 1.
+fn spawn_simulation_threads(
+    simulations: Vec<Box<dyn BlackjackSimulation>>,
+    config: &BlackjackSimulatorConfig,
+    write_sender: &Sender<(Option<SimulationSummary>, usize)>,
+    cancel_flag: &Arc<AtomicBool>,
+) -> Vec<JoinHandle<Result<(), SimulationError>>> {
+    let max_threads = config.max_threads.unwrap_or_else(|| {
+        thread::available_parallelism().map_or(1, |parallelism| parallelism.get())
+    });
+    let num_threads = max_threads.clamp(1, simulations.len().max(1));
+    let num_simulations = config.num_simulations;
+    let queue = Arc::new(Mutex::new(
+        (1..)
+            .zip(simulations)
+            .collect::<VecDeque<(usize, Box<dyn BlackjackSimulation>)>>(),
+    ));
+
+    (0..num_threads)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let write_sender = write_sender.clone();
+            let cancel_flag = Arc::clone(cancel_flag);
+            thread::spawn(move || -> Result<(), SimulationError> {
+                loop {
+                    // The lock is released before the simulation is run
+                    let next = queue.lock().unwrap().pop_front();
+                    let Some((id, simulation)) = next else {
+                        return Ok(());
+                    };
+                    run_simulation_thread(
+                        simulation,
+                        num_simulations,
+                        id,
+                        write_sender.clone(),
+                        Arc::clone(&cancel_flag),
+                    )?;
+                }
+            })
+        })
+        .collect()
+}
+
+/// Helper function that waits for every simulation thread to finish. If any thread failed, the error of the thread that caused the
+/// cancellation is returned in preference to the `SimulationError::Cancelled` errors of the threads it stopped.
+fn join_simulation_threads(
+    handles: Vec<JoinHandle<Result<(), SimulationError>>>,
+) -> Result<(), SimulationError> {
+    let mut result = Ok(());
+    for (i, handle) in handles.into_iter().enumerate() {
+        match handle.join().unwrap() {
+            Ok(()) => {}
+            Err(SimulationError::Cancelled) => {
+                if result.is_ok() {
+                    result = Err(SimulationError::Cancelled);
+                }
+            }
+            Err(e) => {
+                eprintln!("error occured in simulation thread #{}", i + 1);
+                if matches!(result, Ok(()) | Err(SimulationError::Cancelled)) {
+                    result = Err(e);
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Helper function that deterministically derives the seed of a single simulation from the master `seed` and the simulation's `id`,
+/// so each strategy tested by a `MulStrategyBlackjackSimulator` is reproducible but plays with its own sequence of shuffles.
+fn derive_seed(seed: u64, id: usize) -> u64 {
+    // SplitMix64 finalizer, spreads consecutive ids across the whole range of seeds
+    let mut z = seed.wrapping_add((id as u64).wrapping_mul(0x9E3779B97F4A7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
 }
 
-unsafe impl Send for MulStrategyBlackjackSimulator {}
+/// Helper function that returns the seed for the simulation with the given `id`. In common random numbers mode every simulation
+/// shares the master seed so all strategies face the same shoes, otherwise each simulation gets its own seed derived from its `id`.
+fn simulation_seed(config: &BlackjackSimulatorConfig, id: usize) -> Option<u64> {
+    if config.common_random_numbers {
+        config.seed
+    } else {
+        config.seed.map(|seed| derive_seed(seed, id))
+    }
+}
 
 /// Struct for building a `MulStrategyBlackjackSimulator` object
 pub struct MulStrategyBlackjackSimulatorBuilder {
     simulations: Option<Vec<Box<dyn BlackjackSimulation>>>,
+    descriptors: Vec<SimulationDescriptor>,
     config: BlackjackSimulatorConfig,
+    max_simulations: Option<usize>,
+    hand_log: Option<Box<dyn Write + Send + 'static>>,
+    hand_log_ids: Option<HashSet<usize>>,
 }
 
 impl MulStrategyBlackjackSimulatorBuilder {
+    /// Method for enabling per hand logging once built, see `MulStrategyBlackjackSimulator::hand_log()`.
+    pub fn hand_log(&mut self, writer: Box<dyn Write + Send + 'static>) -> &mut Self {
+        self.hand_log = Some(writer);
+        self
+    }
+
+    /// Method for limiting the hand log to the simulations with the given ids, see `MulStrategyBlackjackSimulator::hand_log_only()`.
+    pub fn hand_log_only(&mut self, ids: HashSet<usize>) -> &mut Self {
+        self.hand_log_ids = Some(ids);
+        self
+    }
+
+    /// Method for limiting the number of simulations that can be added with `MulStrategyBlackjackSimulator::add_simulation()` once built,
+    /// the simulations added through the builder are not limited.
+    pub fn max_simulations(&mut self, limit: usize) -> &mut Self {
+        self.max_simulations = Some(limit);
+        self
+    }
+
     /// Method for adding a new simulation to the vector of simulations, the only required input is struct that implements the `Strategy` trait,
     /// the rest of the configurations for the simulation are taken from the preset `BlackjackSimulatorConfig` object that was passed during object creation.
     pub fn simulation<S: Strategy + Send + 'static>(&mut self, strategy: S) -> &mut Self {
+        let id = self.simulations.as_ref().map_or(0, |sims| sims.len()) + 1;
+        let seed = simulation_seed(&self.config, id);
+        self.descriptors.push(SimulationDescriptor::new(&strategy));
         let simulation = Box::new(BlackjackSimulator::new(
             strategy,
-            self.config.player_starting_balance,
-            self.config.table_starting_balance,
-            self.config.num_simulations,
-            self.config.num_decks,
-            self.config.num_shuffles,
-            self.config.min_bet,
-            self.config.hands_per_simulation,
-            self.config.silent,
-            self.config.surrender,
-            self.config.soft_seventeen,
-            self.config.insurance,
+            &BlackjackSimulatorConfig {
+                seed,
+                ..self.config
+            },
         ));
         if let Some(ref mut sim_vec) = self.simulations {
             sim_vec.push(simulation);
@@ -528,20 +1336,30 @@ impl MulStrategyBlackjackSimulatorBuilder {
 
     /// Method that builds a `MulStrategyBlackjackSimulator` object
     pub fn build(&mut self) -> MulStrategyBlackjackSimulator {
+        let simulations = self.simulations.take().unwrap_or(vec![]);
         MulStrategyBlackjackSimulator {
-            simulations: self.simulations.take().unwrap_or(vec![]),
+            simulations_added: simulations.len(),
+            simulations,
+            descriptors: std::mem::take(&mut self.descriptors),
             config: self.config,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+            hand_log: self.hand_log.take(),
+            hand_log_ids: self.hand_log_ids.take(),
+            max_simulations: self.max_simulations,
         }
     }
 }
 
-/// Struct for configuring a single `BlackjackSimulator` object
-#[derive(Clone, Copy)]
+/// Struct for configuring a single `BlackjackSimulator` object, fields left out when deserializing take their default values
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct BlackjackSimulatorConfig {
     pub player_starting_balance: f32,
-    pub table_starting_balance: f32,
+    /// The starting balance of the table, `None` for a table that never runs out of money.
+    pub table_starting_balance: Option<f32>,
     pub num_simulations: u32,
     pub num_decks: usize,
+    /// Ignored, the deck is always shuffled with a single Fisher-Yates pass. Kept for compatibility.
     pub num_shuffles: u32,
     pub min_bet: u32,
     pub hands_per_simulation: u32,
@@ -549,6 +1367,21 @@ pub struct BlackjackSimulatorConfig {
     pub surrender: bool,
     pub soft_seventeen: bool,
     pub insurance: bool,
+    /// Whether any two cards of the same value may be split, e.g. a king and a queen, rather than only two cards of the same rank.
+    pub split_by_value: bool,
+    /// Whether the hands of a split may be doubled, i.e. double after split (DAS).
+    pub double_after_split: bool,
+    /// The fraction of the shoe dealt before it is reshuffled, i.e. where the cut card is placed.
+    pub penetration: f32,
+    /// Optional seed for the random number generator used to shuffle the deck, setting it makes the simulation reproducible.
+    pub seed: Option<u64>,
+    /// Whether every strategy should be played against the same sequence of shoes.
+    pub common_random_numbers: bool,
+    /// The maximum number of hands buffered by the hand log before the simulations block, see `MulStrategyBlackjackSimulator::hand_log()`.
+    pub hand_log_buffer_size: usize,
+    /// The maximum number of threads the strategies of a `MulStrategyBlackjackSimulator` are run on, `None` uses one thread per logical CPU.
+    /// Never more threads than strategies are used.
+    pub max_threads: Option<usize>,
 }
 
 impl BlackjackSimulatorConfig {
@@ -568,32 +1401,190 @@ impl BlackjackSimulatorConfig {
             surrender: None,
             soft_seventeen: None,
             insurance: None,
+            split_by_value: None,
+            double_after_split: None,
+            penetration: None,
+            seed: None,
+            common_random_numbers: None,
+            hand_log_buffer_size: None,
+            max_threads: None,
         }
     }
 }
 
-impl Default for BlackjackSimulatorConfig {
-    /// Returns the standard configurations for a game of blackjack.
-    fn default() -> Self {
-        BlackjackSimulatorConfig::new().build()
+impl From<&BlackjackSimulatorConfig> for TableRules {
+    fn from(config: &BlackjackSimulatorConfig) -> Self {
+        TableRules {
+            num_decks: config.num_decks,
+            num_shuffles: config.num_shuffles,
+            soft_seventeen: config.soft_seventeen,
+            insurance: config.insurance,
+            surrender: config.surrender,
+            split_by_value: config.split_by_value,
+            double_after_split: config.double_after_split,
+            penetration: config.penetration,
+        }
     }
 }
 
-/// Struct to implement builder pattern for `BlackjackSimulatorConfig`
-#[derive(Clone, Copy)]
-pub struct BlackjackSimulatorConfigBuilder {
-    player_starting_balance: Option<f32>,
-    table_starting_balance: Option<f32>,
-    num_simulations: Option<u32>,
-    num_decks: Option<usize>,
-    num_shuffles: Option<u32>,
-    min_bet: Option<u32>,
-    hands_per_simulation: Option<u32>,
-    silent: Option<bool>,
-    surrender: Option<bool>,
-    soft_seventeen: Option<bool>,
-    insurance: Option<bool>,
-}
+impl BlackjackSimulatorConfig {
+    /// Returns the starting balance of the table, or `None` for a table that never runs out of money.
+    pub fn table_balance(&self) -> Option<Money> {
+        self.table_starting_balance.map(Money::from_f32)
+    }
+
+    /// Checks that the configurations describe a game that can be played, returning every invalid field.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = vec![];
+        let valid_balance =
+            self.player_starting_balance.is_finite() && self.player_starting_balance > 0.0;
+        if !valid_balance {
+            errors.push(ConfigError::new(
+                "player_starting_balance",
+                "must be a positive number",
+            ));
+        }
+        let valid_table_balance = self
+            .table_starting_balance
+            .is_none_or(|balance| balance.is_finite() && balance > 0.0);
+        if !valid_table_balance {
+            errors.push(ConfigError::new(
+                "table_starting_balance",
+                "must be a positive number",
+            ));
+        }
+        if self.num_simulations == 0 {
+            errors.push(ConfigError::new("num_simulations", "must be at least 1"));
+        }
+        if self.num_decks == 0 {
+            errors.push(ConfigError::new("num_decks", "must be at least 1"));
+        }
+        if self.hands_per_simulation == 0 {
+            errors.push(ConfigError::new(
+                "hands_per_simulation",
+                "must be at least 1",
+            ));
+        }
+        if !(self.penetration > 0.0 && self.penetration < 1.0) {
+            errors.push(ConfigError::new(
+                "penetration",
+                "must be greater than 0 and less than 1",
+            ));
+        }
+        if self.max_threads == Some(0) {
+            errors.push(ConfigError::new("max_threads", "must be at least 1"));
+        }
+        // An invalid balance is already reported, so the minimum bet is only compared against a valid one
+        if self.min_bet == 0 {
+            errors.push(ConfigError::new("min_bet", "must be at least 1"));
+        } else if valid_balance && (self.min_bet as f32) > self.player_starting_balance {
+            errors.push(ConfigError::new(
+                "min_bet",
+                "must not be greater than player_starting_balance",
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// A single field of a `BlackjackSimulatorConfig` with the value it should be overridden with, see `BlackjackSimulatorConfig::with_override()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigOverride {
+    PlayerStartingBalance(f32),
+    NumDecks(usize),
+    MinBet(u32),
+    HandsPerSimulation(u32),
+    Penetration(f32),
+}
+
+impl BlackjackSimulatorConfig {
+    /// Returns a copy of the configurations with the field named by `config_override` replaced, useful for sweeping a single parameter.
+    pub fn with_override(&self, config_override: ConfigOverride) -> BlackjackSimulatorConfig {
+        let mut config = *self;
+        match config_override {
+            ConfigOverride::PlayerStartingBalance(balance) => {
+                config.player_starting_balance = balance
+            }
+            ConfigOverride::NumDecks(num_decks) => config.num_decks = num_decks,
+            ConfigOverride::MinBet(min_bet) => config.min_bet = min_bet,
+            ConfigOverride::HandsPerSimulation(hands) => config.hands_per_simulation = hands,
+            ConfigOverride::Penetration(penetration) => config.penetration = penetration,
+        }
+        config
+    }
+}
+
+/// Checks that `margin` can be used as the margin of a `MarginBettingStrategy`.
+pub fn validate_betting_margin(margin: f32) -> Result<(), ConfigError> {
+    if margin.is_finite() && margin > 0.0 {
+        Ok(())
+    } else {
+        Err(ConfigError::new(
+            "betting_margin",
+            "must be a positive number",
+        ))
+    }
+}
+
+/// An invalid field of a configuration, along with the constraint it violates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub field: String,
+    pub constraint: &'static str,
+}
+
+impl ConfigError {
+    /// Associated method for creating a new `ConfigError`.
+    pub fn new<S: Into<String>>(field: S, constraint: &'static str) -> ConfigError {
+        ConfigError {
+            field: field.into(),
+            constraint,
+        }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.field, self.constraint)
+    }
+}
+
+impl Error for ConfigError {}
+
+impl Default for BlackjackSimulatorConfig {
+    /// Returns the standard configurations for a game of blackjack.
+    fn default() -> Self {
+        BlackjackSimulatorConfig::new().build()
+    }
+}
+
+/// Struct to implement builder pattern for `BlackjackSimulatorConfig`
+#[derive(Clone, Copy)]
+pub struct BlackjackSimulatorConfigBuilder {
+    player_starting_balance: Option<f32>,
+    table_starting_balance: Option<f32>,
+    num_simulations: Option<u32>,
+    num_decks: Option<usize>,
+    num_shuffles: Option<u32>,
+    min_bet: Option<u32>,
+    hands_per_simulation: Option<u32>,
+    silent: Option<bool>,
+    surrender: Option<bool>,
+    soft_seventeen: Option<bool>,
+    insurance: Option<bool>,
+    split_by_value: Option<bool>,
+    double_after_split: Option<bool>,
+    penetration: Option<f32>,
+    seed: Option<u64>,
+    common_random_numbers: Option<bool>,
+    hand_log_buffer_size: Option<usize>,
+    max_threads: Option<usize>,
+}
 
 impl BlackjackSimulatorConfigBuilder {
     /// Method for changing the starting balance of the player.
@@ -620,7 +1611,11 @@ impl BlackjackSimulatorConfigBuilder {
         self
     }
 
-    /// Method for setting the number of shuffles when shuffling is needed during the simulation
+    /// Method for setting the number of shuffles when shuffling is needed during the simulation.
+    /// The deck is shuffled with a single Fisher-Yates pass, so this value is ignored.
+    #[deprecated(
+        note = "the deck is always shuffled with a single Fisher-Yates pass, this value is ignored"
+    )]
     pub fn num_shuffles(&mut self, shuffles: u32) -> &mut Self {
         self.num_shuffles = Some(shuffles);
         self
@@ -664,11 +1659,56 @@ impl BlackjackSimulatorConfigBuilder {
         self
     }
 
+    /// Method for setting the flag that determines if any two cards of the same value may be split, e.g. a king and a queen.
+    /// If set to false only two cards of the same rank may be split, default is true.
+    pub fn split_by_value(&mut self, split_by_value: bool) -> &mut Self {
+        self.split_by_value = Some(split_by_value);
+        self
+    }
+
+    /// Method for setting the flag that determines if the hands of a split may be doubled, i.e. double after split (DAS). Default is true.
+    pub fn double_after_split(&mut self, double_after_split: bool) -> &mut Self {
+        self.double_after_split = Some(double_after_split);
+        self
+    }
+
+    /// Method for setting the fraction of the shoe dealt before it is reshuffled, i.e. where the cut card is placed. Default is 0.8.
+    pub fn penetration(&mut self, penetration: f32) -> &mut Self {
+        self.penetration = Some(penetration);
+        self
+    }
+
+    /// Method for setting the seed used to shuffle the deck. Simulations run with the same seed and configuration produce identical results.
+    pub fn seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Method for setting the flag that determines if all strategies are played against the same shoes (common random numbers).
+    /// Sharing the shoes removes most of the variance caused by the cards dealt when comparing strategies.
+    pub fn common_random_numbers(&mut self, common_random_numbers: bool) -> &mut Self {
+        self.common_random_numbers = Some(common_random_numbers);
+        self
+    }
+
+    /// Method for setting the maximum number of hands the hand log buffers in memory, once it is full the simulations wait for the writer.
+    pub fn hand_log_buffer_size(&mut self, size: usize) -> &mut Self {
+        self.hand_log_buffer_size = Some(size);
+        self
+    }
+
+    /// Method for setting the maximum number of threads the strategies are run on, a single thread runs the strategies one after another.
+    pub fn max_threads(&mut self, max_threads: usize) -> &mut Self {
+        self.max_threads = Some(max_threads);
+        self
+    }
+
     /// Method for building a `BlackjackSimulatorCofig` object from the given `BlackjackSimulatorConfigBuilder` object.
+    /// The configurations are not checked, see `try_build()` and `BlackjackSimulatorConfig::validate()`.
     pub fn build(&mut self) -> BlackjackSimulatorConfig {
         BlackjackSimulatorConfig {
             player_starting_balance: self.player_starting_balance.unwrap_or(500.0),
-            table_starting_balance: self.table_starting_balance.unwrap_or(f32::MAX),
+            table_starting_balance: self.table_starting_balance,
             num_simulations: self.num_simulations.unwrap_or(100),
             num_decks: self.num_decks.unwrap_or(6),
             num_shuffles: self.num_shuffles.unwrap_or(7),
@@ -678,8 +1718,23 @@ impl BlackjackSimulatorConfigBuilder {
             surrender: self.surrender.unwrap_or(true),
             soft_seventeen: self.soft_seventeen.unwrap_or(false),
             insurance: self.insurance.unwrap_or(false),
+            split_by_value: self.split_by_value.unwrap_or(true),
+            double_after_split: self.double_after_split.unwrap_or(true),
+            penetration: self.penetration.unwrap_or(0.8),
+            seed: self.seed,
+            common_random_numbers: self.common_random_numbers.unwrap_or(false),
+            hand_log_buffer_size: self.hand_log_buffer_size.unwrap_or(1024),
+            max_threads: self.max_threads,
         }
     }
+
+    /// Method for building a `BlackjackSimulatorConfig` like `build()`, returns every invalid field instead if the configurations
+    /// describe a game that can't be played, see `BlackjackSimulatorConfig::validate()`.
+    pub fn try_build(&mut self) -> Result<BlackjackSimulatorConfig, Vec<ConfigError>> {
+        let config = self.build();
+        config.validate()?;
+        Ok(config)
+    }
 }
 
 #[cfg(test)]
@@ -690,6 +1745,185 @@ mod tests {
         MarginBettingStrategy, PlayerStrategy, Strategy, WongHalves, KO,
     };
 
+    // Simulators are moved onto worker threads, so they must stay `Send` without any `unsafe impl`
+    static_assertions::assert_impl_all!(MulStrategyBlackjackSimulator: Send);
+    static_assertions::assert_impl_all!(BlackjackSimulator<PlayerStrategyDyn>: Send);
+    static_assertions::assert_impl_all!(SimulationSummary: Send, Sync);
+
+    #[test]
+    fn merging_summaries_combines_every_field() {
+        let first = SimulationSummary {
+            wins: 3,
+            pushes: 1,
+            losses: 2,
+            early_endings: 1,
+            winnings: Money::from_dollars(10),
+            num_hands: 6,
+            player_blackjacks: 1,
+            label: Arc::from("HiLo"),
+            strategy: Arc::from("HiLo (Basic Strategy, Margin)"),
+            common_random_numbers: true,
+            rounds: 4,
+            winnings_m2: 5.0,
+            total_wagered: Money::from_dollars(40),
+            max_drawdown: Money::from_dollars(25),
+            insurance_bets: 1,
+            insurance_winnings: Money::from_dollars(-5),
+            min_rounds_to_ruin: Some(3),
+            max_rounds_to_ruin: Some(3),
+            total_rounds_to_ruin: 3,
+        };
+        let second = SimulationSummary {
+            wins: 1,
+            pushes: 0,
+            losses: 1,
+            early_endings: 2,
+            winnings: Money::from_dollars(2),
+            num_hands: 2,
+            player_blackjacks: 0,
+            label: Arc::from("KO"),
+            strategy: Arc::from("KO (Basic Strategy, Flat)"),
+            common_random_numbers: false,
+            rounds: 2,
+            winnings_m2: 2.0,
+            total_wagered: Money::from_dollars(15),
+            max_drawdown: Money::from_dollars(40),
+            insurance_bets: 2,
+            insurance_winnings: Money::from_dollars(10),
+            min_rounds_to_ruin: Some(1),
+            max_rounds_to_ruin: Some(7),
+            total_rounds_to_ruin: 8,
+        };
+
+        let mut merged = first.clone();
+        merged += &second;
+        assert_eq!(merged.wins, 4);
+        assert_eq!(merged.pushes, 1);
+        assert_eq!(merged.losses, 3);
+        assert_eq!(merged.early_endings, 3);
+        assert_eq!(merged.winnings, Money::from_dollars(12));
+        assert_eq!(merged.num_hands, 8);
+        assert_eq!(merged.player_blackjacks, 1);
+        assert_eq!(&*merged.label, "HiLo");
+        assert_eq!(&*merged.strategy, "HiLo (Basic Strategy, Margin)");
+        assert!(merged.common_random_numbers);
+        assert_eq!(merged.rounds, 6);
+        // The means of the two summaries are 2.5 and 1, so 1.5^2 * 4 * 2 / 6 = 3 is added to the sum of both
+        assert!((merged.winnings_m2 - 10.0).abs() < 1e-9);
+        assert_eq!(merged.total_wagered, Money::from_dollars(55));
+        assert_eq!(merged.max_drawdown, Money::from_dollars(40));
+        assert_eq!(merged.insurance_bets, 3);
+        assert_eq!(merged.insurance_winnings, Money::from_dollars(5));
+        assert_eq!(merged.min_rounds_to_ruin, Some(1));
+        assert_eq!(merged.max_rounds_to_ruin, Some(7));
+        assert_eq!(merged.total_rounds_to_ruin, 11);
+
+        let summed = [first, second]
+            .iter()
+            .sum::<Option<SimulationSummary>>()
+            .unwrap();
+        assert_eq!(summed.winnings, merged.winnings);
+        assert_eq!(summed.winnings_m2, merged.winnings_m2);
+        assert!(std::iter::empty::<&SimulationSummary>()
+            .sum::<Option<SimulationSummary>>()
+            .is_none());
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert_eq!(BlackjackSimulatorConfig::default().validate(), Ok(()));
+        assert_eq!(validate_betting_margin(2.0), Ok(()));
+    }
+
+    #[test]
+    fn table_rules_follow_the_config() {
+        assert_eq!(
+            TableRules::from(&BlackjackSimulatorConfig::default()),
+            TableRules::default()
+        );
+
+        let config = BlackjackSimulatorConfig::new()
+            .num_decks(2)
+            .soft_seventeen(true)
+            .double_after_split(false)
+            .build();
+        let rules = TableRules::from(&config);
+        assert_eq!(rules.num_decks, 2);
+        assert!(rules.soft_seventeen);
+        assert!(!rules.double_after_split);
+        assert!(rules.surrender);
+
+        // Rules missing from a serialized table are the default rules
+        let rules: TableRules = serde_json::from_str(r#"{"num_decks": 1}"#).unwrap();
+        assert_eq!(
+            rules,
+            TableRules {
+                num_decks: 1,
+                ..TableRules::default()
+            }
+        );
+    }
+
+    #[test]
+    fn config_override_replaces_a_single_field() {
+        let config = BlackjackSimulatorConfig::default();
+        let overridden = config.with_override(ConfigOverride::NumDecks(2));
+        assert_eq!(overridden.num_decks, 2);
+        assert_eq!(overridden.min_bet, config.min_bet);
+        assert_eq!(
+            overridden.player_starting_balance,
+            config.player_starting_balance
+        );
+        assert_eq!(config.num_decks, 6);
+
+        let overridden = config.with_override(ConfigOverride::Penetration(0.65));
+        assert_eq!(overridden.penetration, 0.65);
+        assert_eq!(TableRules::from(&overridden).penetration, 0.65);
+    }
+
+    #[test]
+    fn invalid_config_reports_every_field() {
+        let config = BlackjackSimulatorConfig::new()
+            .player_starting_balance(-10.0)
+            .num_simulations(0)
+            .num_decks(0)
+            .hands_per_simulation(0)
+            .min_bet(0)
+            .penetration(1.0)
+            .build();
+        let fields = config
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|err| err.field)
+            .collect::<Vec<String>>();
+        assert_eq!(
+            fields,
+            vec![
+                "player_starting_balance",
+                "num_simulations",
+                "num_decks",
+                "hands_per_simulation",
+                "penetration",
+                "min_bet"
+            ]
+        );
+
+        let config = BlackjackSimulatorConfig::new()
+            .player_starting_balance(10.0)
+            .min_bet(25)
+            .build();
+        assert_eq!(
+            config.validate(),
+            Err(vec![ConfigError::new(
+                "min_bet",
+                "must not be greater than player_starting_balance"
+            )])
+        );
+        assert!(validate_betting_margin(-3.0).is_err());
+        assert!(validate_betting_margin(f32::NAN).is_err());
+    }
+
     #[test]
     fn simple_simulation_test() {
         const MIN_BET: u32 = 5;
@@ -701,17 +1935,13 @@ mod tests {
 
         let mut simulator = BlackjackSimulator::new(
             strategy,
-            500.0,
-            f32::MAX,
-            50,
-            6,
-            7,
-            MIN_BET,
-            400,
-            false,
-            true,
-            false,
-            false,
+            &BlackjackSimulatorConfig::new()
+                .num_simulations(50)
+                .num_decks(NUM_DECKS as usize)
+                .min_bet(MIN_BET)
+                .hands_per_simulation(400)
+                .silent(false)
+                .build(),
         );
 
         if let Err(e) = simulator.run() {
@@ -722,6 +1952,25 @@ mod tests {
         assert!(true);
     }
 
+    #[test]
+    fn cancelled_simulator_returns_cancelled() {
+        let mut simulator = BlackjackSimulator::new(
+            PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ),
+            &BlackjackSimulatorConfig::new()
+                .player_starting_balance(500.0)
+                .num_simulations(50)
+                .hands_per_simulation(400)
+                .seed(1)
+                .build(),
+        );
+        simulator.set_cancel_flag(Arc::new(AtomicBool::new(true)));
+        assert!(matches!(simulator.run(), Err(SimulationError::Cancelled)));
+    }
+
     #[test]
     fn run_multiple_simulations() {
         let mut simulator = MulStrategyBlackjackSimulator::new(BlackjackSimulatorConfig::default())
@@ -753,4 +2002,740 @@ mod tests {
         // test passed if we get to this point
         assert!(true);
     }
+
+    #[test]
+    fn progress_counters_increase_while_the_simulation_runs() {
+        let mut simulator = BlackjackSimulator::new(
+            PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ),
+            &BlackjackSimulatorConfig::new()
+                .player_starting_balance(1_000_000.0)
+                .num_simulations(50)
+                .hands_per_simulation(200)
+                .seed(31)
+                .build(),
+        );
+        // The handle is taken before the simulation is moved onto its thread
+        let progress = simulator.progress();
+        let handle = thread::spawn(move || simulator.run());
+
+        let (mut hands, mut simulations) = (0, 0);
+        while !handle.is_finished() {
+            let (current_hands, current_simulations) =
+                (progress.hands_completed(), progress.simulations_completed());
+            assert!(current_hands >= hands);
+            assert!(current_simulations >= simulations);
+            (hands, simulations) = (current_hands, current_simulations);
+        }
+        handle.join().unwrap().unwrap();
+        assert_eq!(progress.simulations_completed(), 50);
+        assert_eq!(progress.hands_completed(), 50 * 200);
+    }
+
+    #[test]
+    fn six_dollar_bankrolls_are_ruined_within_a_few_rounds() {
+        // A single lost hand at a $5 table leaves a $6 bankroll unable to bet again
+        let mut simulator = BlackjackSimulator::new(
+            PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ),
+            &BlackjackSimulatorConfig::new()
+                .player_starting_balance(6.0)
+                .num_simulations(20)
+                .hands_per_simulation(1000)
+                .seed(29)
+                .build(),
+        );
+        simulator.run().unwrap();
+        let summary = simulator.summary();
+        assert!(summary.early_endings > 0);
+        let min = summary.min_rounds_to_ruin.unwrap();
+        let max = summary.max_rounds_to_ruin.unwrap();
+        let mean = summary.mean_rounds_to_ruin().unwrap();
+        assert!((1..=3).contains(&min));
+        assert!(min as f32 <= mean && mean <= max as f32);
+        assert!(max < 1000);
+        // The rounds played before ruin are every round played by the simulations that ended early
+        assert!(summary.total_rounds_to_ruin <= summary.rounds);
+    }
+
+    #[test]
+    fn per_hand_rates_only_count_hands_played() {
+        // A player starting with a few minimum bets goes bankrupt long before every hand is dealt
+        let mut simulator = BlackjackSimulator::new(
+            PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ),
+            &BlackjackSimulatorConfig::new()
+                .player_starting_balance(20.0)
+                .num_simulations(10)
+                .hands_per_simulation(1000)
+                .seed(13)
+                .build(),
+        );
+        simulator.run().unwrap();
+        let summary = simulator.summary();
+        assert!(summary.early_endings > 0);
+        assert!(summary.num_hands < 10 * 1000);
+        assert_eq!(
+            summary.num_hands as i32,
+            summary.wins + summary.pushes + summary.losses
+        );
+
+        let avg_winnings_per_hand = summary.winnings.to_f32() / summary.num_hands as f32;
+        let text = format!("{}", summary);
+        assert!(text.contains(&format!("{:.2}\n", avg_winnings_per_hand)));
+        assert!(text.contains(&format!(" {}\n", summary.num_hands)));
+        let json = write::format_summaries_as_json(&std::collections::HashMap::from([(
+            1,
+            summary.clone(),
+        )]));
+        let row = &serde_json::from_str::<serde_json::Value>(&json).unwrap()[0];
+        assert_eq!(
+            row["avg_winnings_per_hand"].as_f64().unwrap() as f32,
+            avg_winnings_per_hand
+        );
+        assert_eq!(
+            row["win_pct"].as_f64().unwrap() as f32,
+            summary.wins as f32 / summary.num_hands as f32
+        );
+    }
+
+    #[test]
+    fn seeded_simulations_are_reproducible() {
+        let build_simulator = || {
+            BlackjackSimulator::new(
+                PlayerStrategy::new(
+                    HiLo::new(6),
+                    BasicStrategy::new(),
+                    MarginBettingStrategy::new(3.0, 5),
+                ),
+                &BlackjackSimulatorConfig::new()
+                    .player_starting_balance(500.0)
+                    .num_simulations(20)
+                    .hands_per_simulation(200)
+                    .seed(42)
+                    .build(),
+            )
+        };
+
+        let mut simulator1 = build_simulator();
+        let mut simulator2 = build_simulator();
+        simulator1.run().unwrap();
+        simulator2.run().unwrap();
+
+        assert_eq!(
+            format!("{}", simulator1.summary()),
+            format!("{}", simulator2.summary())
+        );
+    }
+
+    #[test]
+    fn common_random_numbers_share_seeds() {
+        let config = BlackjackSimulatorConfig::new()
+            .seed(11)
+            .common_random_numbers(true)
+            .build();
+        assert_eq!(simulation_seed(&config, 1), simulation_seed(&config, 2));
+
+        let config = BlackjackSimulatorConfig::new().seed(11).build();
+        assert_ne!(simulation_seed(&config, 1), simulation_seed(&config, 2));
+    }
+
+    #[test]
+    fn parallel_simulation_matches_single_threaded() {
+        let config = BlackjackSimulatorConfig::new()
+            .num_simulations(23)
+            .hands_per_simulation(200)
+            .seed(5)
+            .build();
+        let strategy_factory = || {
+            PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            )
+        };
+
+        let mut single_threaded = BlackjackSimulator::new(strategy_factory(), &config);
+        single_threaded.run().unwrap();
+
+        let parallel = ParallelBlackjackSimulator::new(strategy_factory, config, 4)
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            format!("{}", single_threaded.summary()),
+            format!("{}", parallel)
+        );
+        assert_eq!(single_threaded.summary().num_hands, parallel.num_hands);
+        assert_eq!(single_threaded.summary().rounds, parallel.rounds);
+        assert_eq!(
+            single_threaded.summary().max_drawdown,
+            parallel.max_drawdown
+        );
+    }
+
+    /// A simulation used for testing cancellation, it sleeps for a short while during every simulation and fails if `fail_after` simulations have run.
+    struct ScriptedSimulation {
+        simulations_run: u32,
+        fail_after: Option<u32>,
+    }
+
+    impl BlackjackSimulation for ScriptedSimulation {
+        fn run(&mut self) -> Result<(), SimulationError> {
+            self.run_single_simulation()
+                .map_err(|source| SimulationError::StrategyError {
+                    strategy_id: 1,
+                    label: self.label(),
+                    simulation_index: self.simulations_run as u64,
+                    simulations_completed: self.simulations_run,
+                    source,
+                })
+        }
+
+        fn run_single_simulation(&mut self) -> Result<(), BlackjackGameError> {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            if self.fail_after == Some(self.simulations_run) {
+                return Err(BlackjackGameError::new("scripted failure".to_string()));
+            }
+            self.simulations_run += 1;
+            Ok(())
+        }
+
+        fn display_stats(&self) {}
+
+        fn reset(&mut self) {}
+
+        fn summary(&self) -> SimulationSummary {
+            SimulationSummary {
+                wins: 0,
+                pushes: 0,
+                losses: 0,
+                early_endings: 0,
+                winnings: Money::ZERO,
+                num_hands: 0,
+                player_blackjacks: 0,
+                label: Arc::from("scripted"),
+                strategy: Arc::from("scripted"),
+                common_random_numbers: false,
+                rounds: 0,
+                winnings_m2: 0.0,
+                total_wagered: Money::ZERO,
+                max_drawdown: Money::ZERO,
+                insurance_bets: 0,
+                insurance_winnings: Money::ZERO,
+                min_rounds_to_ruin: None,
+                max_rounds_to_ruin: None,
+                total_rounds_to_ruin: 0,
+            }
+        }
+
+        fn label(&self) -> Arc<str> {
+            Arc::from("scripted")
+        }
+
+        fn set_cancel_flag(&mut self, _cancel_flag: Arc<AtomicBool>) {}
+
+        fn set_hand_logger(&mut self, _hand_logger: HandLogger, _strategy_id: usize) {}
+
+        fn progress(&self) -> SimulationProgress {
+            SimulationProgress::default()
+        }
+    }
+
+    #[test]
+    fn failing_simulation_cancels_the_others() {
+        // Every strategy gets its own thread, otherwise a healthy strategy could run to completion before the failing one starts
+        let config = BlackjackSimulatorConfig::new()
+            .num_simulations(100_000)
+            .max_threads(3)
+            .build();
+        let mut simulator = MulStrategyBlackjackSimulator::new(config).build();
+        for fail_after in [None, Some(3), None] {
+            simulator.simulations.push(Box::new(ScriptedSimulation {
+                simulations_run: 0,
+                fail_after,
+            }));
+        }
+
+        // Without cancellation the healthy simulations would take several minutes to finish
+        let start = std::time::Instant::now();
+        let result = simulator.run(Box::new(std::io::sink()), Box::new(write::write_summaries));
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+        let error = result.expect_err("expected the run to fail");
+        assert_eq!(
+            error.to_string(),
+            "strategy #2 failed: simulation 'scripted' failed after 3 simulations: scripted failure"
+        );
+        match error {
+            SimulationError::StrategyError {
+                strategy_id,
+                ref label,
+                simulation_index,
+                simulations_completed,
+                ..
+            } => {
+                assert_eq!(strategy_id, 2);
+                assert_eq!(&**label, "scripted");
+                assert_eq!(simulation_index, 3);
+                assert_eq!(simulations_completed, 3);
+            }
+            ref e => panic!("expected the scripted failure, got: {}", e),
+        }
+        // The error the game returned is kept as the source
+        let game_error = error
+            .source()
+            .and_then(|source| source.downcast_ref::<BlackjackGameError>());
+        assert!(matches!(game_error, Some(e) if e.message == "scripted failure"));
+    }
+
+    #[test]
+    fn strategies_that_finished_are_written_when_another_fails() {
+        // A single thread runs the strategies one after the other, so the first finishes before the second fails
+        let config = BlackjackSimulatorConfig::new()
+            .num_simulations(5)
+            .max_threads(1)
+            .build();
+        let mut simulator = MulStrategyBlackjackSimulator::new(config).build();
+        for fail_after in [None, Some(3)] {
+            simulator.simulations.push(Box::new(ScriptedSimulation {
+                simulations_run: 0,
+                fail_after,
+            }));
+        }
+
+        let buffer = Arc::new(std::sync::Mutex::new(vec![]));
+        let result = simulator.run(
+            Box::new(SharedBuffer(Arc::clone(&buffer))),
+            Box::new(write::write_summaries_csv),
+        );
+        assert!(matches!(
+            result,
+            Err(SimulationError::StrategyError { strategy_id: 2, .. })
+        ));
+        let output = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        let rows = output.lines().skip(1).collect::<Vec<&str>>();
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].starts_with("1,\"scripted\","));
+    }
+
+    #[test]
+    fn cancelled_run_returns_promptly() {
+        let config = BlackjackSimulatorConfig::new()
+            .num_simulations(100_000)
+            .build();
+        let mut simulator = MulStrategyBlackjackSimulator::new(config)
+            .simulation(PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ))
+            .build();
+
+        let cancel_flag = simulator.cancel_flag();
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            cancel_flag.store(true, Ordering::Relaxed);
+        });
+
+        let start = std::time::Instant::now();
+        let result = simulator.run(Box::new(std::io::sink()), Box::new(write::write_summaries));
+        canceller.join().unwrap();
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+        assert!(matches!(result, Err(SimulationError::Cancelled)));
+    }
+
+    /// A writer that appends to a shared buffer, so the test can inspect what was written after the writer has been moved.
+    struct SharedBuffer(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn hand_log_streams_every_hand() {
+        let config = BlackjackSimulatorConfig::new()
+            .num_simulations(3)
+            .hands_per_simulation(20)
+            .player_starting_balance(1_000_000.0)
+            .hand_log_buffer_size(4)
+            .build();
+        let mut simulator = MulStrategyBlackjackSimulator::new(config)
+            .simulation(PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ))
+            .simulation(PlayerStrategy::new(
+                KO::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ))
+            .build();
+
+        let buffer = Arc::new(std::sync::Mutex::new(vec![]));
+        simulator.hand_log(Box::new(SharedBuffer(Arc::clone(&buffer))));
+        simulator
+            .run(Box::new(std::io::sink()), Box::new(write::write_summaries))
+            .unwrap();
+
+        let log = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        let records = log.lines().skip(1).collect::<Vec<&str>>();
+        assert_eq!(records.len(), 2 * 3 * 20);
+        for strategy_id in ["1", "2"] {
+            let num_hands = records
+                .iter()
+                .filter(|line| line.split(',').next() == Some(strategy_id))
+                .count();
+            assert_eq!(num_hands, 3 * 20);
+        }
+    }
+
+    #[test]
+    fn run_with_calls_back_once_per_strategy() {
+        let config = BlackjackSimulatorConfig::new()
+            .num_simulations(3)
+            .hands_per_simulation(10)
+            .player_starting_balance(1_000_000.0)
+            .build();
+        let mut simulator = MulStrategyBlackjackSimulator::new(config)
+            .simulation(PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ))
+            .simulation(PlayerStrategy::new(
+                KO::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ))
+            .build();
+
+        let mut summaries = vec![];
+        simulator
+            .run_with(|summary, id| summaries.push((id, summary)))
+            .unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].0, 1);
+        assert_eq!(&*summaries[0].1.label, "HiLo");
+        assert_eq!(summaries[1].0, 2);
+        assert_eq!(&*summaries[1].1.label, "KO");
+        for (_, summary) in summaries {
+            assert_eq!(summary.rounds, 3 * 10);
+        }
+    }
+
+    #[test]
+    fn run_return_out_returns_what_the_collector_aggregates() {
+        let config = BlackjackSimulatorConfig::new()
+            .num_simulations(3)
+            .hands_per_simulation(10)
+            .player_starting_balance(1_000_000.0)
+            .build();
+        let strategy = || {
+            PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            )
+        };
+        let mut simulator = MulStrategyBlackjackSimulator::new(config)
+            .simulation(strategy())
+            .simulation(strategy())
+            .build();
+        assert_eq!(simulator.simulations().len(), 2);
+
+        // Counts the summaries sent by each simulation, along with the ids it was given
+        let (counts, ids) = simulator
+            .run_return_out(|receiver, ids| {
+                let mut counts = std::collections::HashMap::new();
+                for (summary, id) in receiver {
+                    if summary.is_some() {
+                        *counts.entry(id).or_insert(0) += 1;
+                    }
+                }
+                (counts, ids)
+            })
+            .unwrap();
+        assert_eq!(ids, HashSet::from([1, 2]));
+        assert_eq!(counts[&1], 3);
+        assert_eq!(counts[&2], 3);
+        assert!(simulator.simulations().is_empty());
+
+        let mut simulator = MulStrategyBlackjackSimulator::new(config)
+            .simulation(strategy())
+            .build();
+        let result = simulator.run_return_out(|_, _| -> u32 { panic!("collector failed") });
+        assert!(matches!(result, Err(SimulationError::CollectorPanicked)));
+    }
+
+    #[test]
+    fn hand_log_only_logs_the_given_simulations() {
+        let config = BlackjackSimulatorConfig::new()
+            .num_simulations(2)
+            .hands_per_simulation(10)
+            .player_starting_balance(1_000_000.0)
+            .build();
+        let buffer = Arc::new(std::sync::Mutex::new(vec![]));
+        let mut simulator = MulStrategyBlackjackSimulator::new(config)
+            .simulation(PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ))
+            .simulation(PlayerStrategy::new(
+                KO::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ))
+            .hand_log(Box::new(SharedBuffer(Arc::clone(&buffer))))
+            .hand_log_only(HashSet::from([2]))
+            .build();
+        simulator
+            .run(Box::new(std::io::sink()), Box::new(write::write_summaries))
+            .unwrap();
+
+        let log = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        let records = log.lines().skip(1).collect::<Vec<&str>>();
+        assert_eq!(records.len(), 2 * 10);
+        assert!(records.iter().all(|line| line.starts_with("2,")));
+    }
+
+    #[test]
+    fn multi_strategy_summaries_match_a_single_threaded_run() {
+        let config = BlackjackSimulatorConfig::new()
+            .num_simulations(6)
+            .hands_per_simulation(100)
+            .seed(29)
+            .build();
+        let strategy = || {
+            PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            )
+        };
+
+        let mut single_threaded = BlackjackSimulator::new(
+            strategy(),
+            &BlackjackSimulatorConfig {
+                seed: simulation_seed(&config, 1),
+                ..config
+            },
+        );
+        single_threaded.run().unwrap();
+
+        let mut simulator = MulStrategyBlackjackSimulator::new(config)
+            .simulation(strategy())
+            .build();
+        let buffer = Arc::new(std::sync::Mutex::new(vec![]));
+        simulator
+            .run(
+                Box::new(SharedBuffer(Arc::clone(&buffer))),
+                Box::new(write::write_summaries),
+            )
+            .unwrap();
+        let output = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+
+        // Each simulation is counted once, rather than once for every simulation run after it
+        assert!(output.contains(&format!("{}", single_threaded.summary())));
+    }
+
+    /// Helper function that runs three strategies seeded with `seed` on at most `max_threads` threads, and returns the written summaries.
+    fn run_on_threads(max_threads: Option<usize>, seed: u64) -> String {
+        let mut builder = BlackjackSimulatorConfig::new();
+        builder
+            .num_simulations(4)
+            .hands_per_simulation(50)
+            .seed(seed);
+        if let Some(max_threads) = max_threads {
+            builder.max_threads(max_threads);
+        }
+        let mut simulator = MulStrategyBlackjackSimulator::new(builder.build())
+            .simulation(PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ))
+            .simulation(PlayerStrategy::new(
+                KO::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ))
+            .simulation(PlayerStrategy::new(
+                WongHalves::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(2.0, 5),
+            ))
+            .build();
+
+        let buffer = Arc::new(std::sync::Mutex::new(vec![]));
+        simulator
+            .run(
+                Box::new(SharedBuffer(Arc::clone(&buffer))),
+                Box::new(write::write_summaries),
+            )
+            .unwrap();
+        let output = buffer.lock().unwrap().clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn single_threaded_run_matches_parallel_run() {
+        let parallel = run_on_threads(None, 17);
+        assert_eq!(run_on_threads(Some(1), 17), parallel);
+        assert_eq!(run_on_threads(Some(2), 17), parallel);
+        assert_eq!(parallel.matches("simulation #").count(), 3);
+    }
+
+    #[test]
+    fn only_tables_without_a_starting_balance_are_unlimited() {
+        assert_eq!(BlackjackSimulatorConfig::default().table_balance(), None);
+        let config = BlackjackSimulatorConfig::new()
+            .table_starting_balance(10_000.0)
+            .build();
+        assert_eq!(config.table_balance(), Some(Money::from_dollars(10_000)));
+        let config = BlackjackSimulatorConfig::new()
+            .table_starting_balance(f32::INFINITY)
+            .build();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn try_build_rejects_each_invalid_field() {
+        // The invalid field and a change to the builder that makes it invalid
+        type Case = (&'static str, fn(&mut BlackjackSimulatorConfigBuilder));
+        let cases: [Case; 8] = [
+            ("player_starting_balance", |builder| {
+                builder.player_starting_balance(0.0);
+            }),
+            ("table_starting_balance", |builder| {
+                builder.table_starting_balance(-1.0);
+            }),
+            ("num_simulations", |builder| {
+                builder.num_simulations(0);
+            }),
+            ("num_decks", |builder| {
+                builder.num_decks(0);
+            }),
+            ("hands_per_simulation", |builder| {
+                builder.hands_per_simulation(0);
+            }),
+            ("max_threads", |builder| {
+                builder.max_threads(0);
+            }),
+            ("min_bet", |builder| {
+                builder.min_bet(0);
+            }),
+            ("min_bet", |builder| {
+                builder.player_starting_balance(4.0);
+            }),
+        ];
+        for (field, invalidate) in cases {
+            let mut builder = BlackjackSimulatorConfig::new();
+            invalidate(&mut builder);
+            let fields = builder
+                .try_build()
+                .unwrap_err()
+                .into_iter()
+                .map(|err| err.field)
+                .collect::<Vec<String>>();
+            assert_eq!(fields, vec![field]);
+        }
+
+        assert_eq!(
+            BlackjackSimulatorConfig::new().num_decks(2).try_build(),
+            Ok(BlackjackSimulatorConfig::new().num_decks(2).build())
+        );
+    }
+
+    #[test]
+    fn zero_threads_are_rejected() {
+        let config = BlackjackSimulatorConfig::new().max_threads(0).build();
+        let errors = config.validate().unwrap_err();
+        assert_eq!(
+            errors,
+            vec![ConfigError::new("max_threads", "must be at least 1")]
+        );
+    }
+
+    #[test]
+    fn add_simulation_respects_the_limit() {
+        let mut simulator = MulStrategyBlackjackSimulator::new(BlackjackSimulatorConfig::default())
+            .max_simulations(1)
+            .build();
+        let strategy = || {
+            PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            )
+        };
+        assert!(simulator.add_simulation(strategy()).is_ok());
+        assert!(matches!(
+            simulator.add_simulation(strategy()),
+            Err(SimulationError::TooManySimulations(1))
+        ));
+        assert_eq!(simulator.simulations().len(), 1);
+        assert_eq!(simulator.descriptors().len(), 1);
+    }
+
+    #[test]
+    fn remove_simulation_keeps_the_others() {
+        let mut simulator =
+            MulStrategyBlackjackSimulator::new(BlackjackSimulatorConfig::default()).build();
+        simulator
+            .add_simulation(PlayerStrategy::new(
+                HiLo::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ))
+            .unwrap();
+        simulator
+            .add_simulation(PlayerStrategy::new(
+                KO::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ))
+            .unwrap();
+        simulator
+            .add_simulation(PlayerStrategy::new(
+                WongHalves::new(6),
+                BasicStrategy::new(),
+                MarginBettingStrategy::new(3.0, 5),
+            ))
+            .unwrap();
+
+        assert!(simulator.remove_simulation(1));
+        assert!(!simulator.remove_simulation(2));
+        let labels = simulator
+            .simulations()
+            .iter()
+            .map(|simulation| simulation.label())
+            .collect::<Vec<Arc<str>>>();
+        assert_eq!(
+            labels,
+            vec![Arc::<str>::from("HiLo"), Arc::<str>::from("Wong Halves")]
+        );
+        let descriptors = simulator.descriptors();
+        assert_eq!(descriptors.len(), 2);
+        assert_eq!(descriptors[1].counting_strategy, Arc::from("Wong Halves"));
+        assert_eq!(descriptors[1].decision_strategy, "Basic Strategy");
+        assert_eq!(descriptors[1].betting_strategy, "Margin");
+    }
 }
@@ -1,16 +1,23 @@
-use blackjack_sim::strategy::{
-    AceFive, BasicStrategy, CountingStrategy, Halves, HiLo, HiOptI, HiOptII, JNoir,
-    MarginBettingStrategy, OmegaII, PlayerStrategy, RedSeven, S17DeviationStrategy, SilverFox,
-    UnbalancedZen2, WongHalves, ZenCount, KISS, KISSII, KISSIII, KO,
+use blackjack_sim::registry::{
+    BettingParameters, BettingStrategyDescriptor, CountingStrategyDescriptor,
+    DecisionStrategyDescriptor,
 };
+use blackjack_sim::strategy::{parse_ramp, CompiledStrategy, PlayerStrategyDyn, RampStep};
 
+use blackjack_sim::write::{format_comparison, CollectEvent, HandRecord, SummaryFormat};
 use blackjack_sim::{
-    write::write_summaries, BlackjackSimulatorConfig, BlackjackSimulatorConfigBuilder,
-    MulStrategyBlackjackSimulator, MulStrategyBlackjackSimulatorBuilder,
+    registry, validate_betting_margin, BlackjackGameSim, BlackjackSimulatorConfig,
+    BlackjackTableSim, ConfigError, ConfigOverride, Money, MulStrategyBlackjackSimulator,
+    PlayerSim, SimulationError, TableRules,
 };
-use clap::Parser;
+use clap::error::ErrorKind;
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::Write;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 #[derive(Parser)]
 #[command(name = "Card Counting Simulator")]
@@ -19,8 +26,45 @@ use std::io::Write;
 #[command(
     about = "Simulates the common card counting strategies, and records/displays the data produced by each simulation"
 )]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
-    /// Optional argument to set the starting balance of the table
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// The arguments of the run subcommand, so the simulations can be run without naming it
+    #[command(flatten)]
+    run: RunArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Runs the simulations, the default when no subcommand is given. Boxed, since the run has far more arguments than the other subcommands
+    Run(Box<RunArgs>),
+    /// Lists the registered counting, decision and betting strategies
+    ListStrategies,
+    /// Plays a few rounds with a single strategy, printing every card, count, decision and result
+    Trace(TraceArgs),
+}
+
+impl Cli {
+    /// Returns the arguments of the run, `None` if another subcommand was given.
+    fn into_run_args(self) -> Option<RunArgs> {
+        match self.command {
+            Some(Command::Run(args)) => Some(*args),
+            Some(Command::ListStrategies) | Some(Command::Trace(_)) => None,
+            None => Some(self.run),
+        }
+    }
+}
+
+/// Every field of `BlackjackSimulatorConfig` has an argument, arguments that are left out take the defaults of `BlackjackSimulatorConfigBuilder::build()`.
+#[derive(Args)]
+struct RunArgs {
+    /// Optional TOML or JSON file with the game configurations and the strategies to simulate, the other arguments override its values
+    #[arg(short = 'c', long, value_name = "CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Optional argument to set the starting balance of the table, the table never runs out of money if it is left out
     #[arg(short = 't', long, value_name = "TABLE")]
     table_starting_balance: Option<f32>,
 
@@ -28,38 +72,186 @@ struct Cli {
     #[arg(short = 'f', long, value_name = "FILE")]
     file_out: Option<std::path::PathBuf>,
 
-    /// Sets the players starting balance for each simulation
+    /// The format the summaries are written in, the seed and table rules of the run are only recorded in the header of the text format
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t = OutputFormatArg::Text)]
+    output_format: OutputFormatArg,
+
+    /// Ranks the strategies by their average winnings per hand in a table written after the summaries. The table is written to stderr
+    /// unless the output format is text, so the other formats stay parseable
+    #[arg(long)]
+    compare: bool,
+
+    /// Also writes the strategy comparison to this file, requires --compare
+    #[arg(long, value_name = "FILE", requires = "compare")]
+    compare_out: Option<PathBuf>,
+
+    /// Writes a csv row for every hand played by every strategy to this file. The file grows with the number of simulations and hands,
+    /// a warning is printed when it is expected to be large
+    #[arg(long, value_name = "FILE")]
+    hand_log: Option<PathBuf>,
+
+    /// Comma separated list of the counting strategies whose hands are logged, defaults to every strategy. Requires --hand-log
+    #[arg(long, value_name = "STRATEGIES", requires = "hand_log")]
+    hand_log_strategy: Option<String>,
+
+    /// Prints nothing besides the summaries, hiding the progress of the run which is otherwise shown when stdout is a terminal
+    #[arg(short = 'q', long, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Prints the stats of every simulation as it finishes, given twice also writes a csv row for every hand to stderr
+    /// unless --hand-log is given
+    #[arg(short = 'v', long, action = ArgAction::Count)]
+    verbose: u8,
+
+    /// Sets the players starting balance for each simulation, defaults to 500
     #[arg(short = 'p', long, value_name = "PLAYER")]
-    player_starting_balance: f32,
+    player_starting_balance: Option<f32>,
 
-    /// Sets the total number of simulations that will be run
+    /// Sets the total number of simulations that will be run, defaults to 100
     #[arg(short = 'n', long, value_name = "SIMULATIONS")]
-    num_simulations: u32,
+    num_simulations: Option<u32>,
 
-    /// Sets the number of decks that are used in the blackjack game
+    /// Sets the number of decks that are used in the blackjack game, defaults to 6
     #[arg(short = 'd', long, value_name = "DECKS")]
-    num_decks: usize,
+    num_decks: Option<usize>,
 
-    /// Determines the maximum number of hands played for any given simulation
+    /// Determines the maximum number of hands played for any given simulation, defaults to 50
     #[arg(short = 'r', long, value_name = "HANDS")]
-    hands_per_simulation: u32,
+    hands_per_simulation: Option<u32>,
 
-    /// Determines the minimum bet required
+    /// Determines the minimum bet required, defaults to 5
     #[arg(short = 'b', long, value_name = "BET")]
-    min_bet: u32,
+    min_bet: Option<u32>,
 
-    /// Decides whether or not to display output from each simulation run
-    #[arg(short = 'g', long, value_name = "SILENT")]
+    /// Deprecated, use --verbose instead. Decides whether or not to display output from each simulation run
+    #[arg(
+        short = 'g',
+        long,
+        value_name = "SILENT",
+        hide = true,
+        conflicts_with_all = ["quiet", "verbose"]
+    )]
     silent_game: Option<bool>,
 
-    /// Decides whether surrender is a valid play at the blackjack table
-    #[arg(short = 's', long, value_name = "SURRENDER")]
-    surrender: bool,
+    /// Decides whether surrender is a valid play at the blackjack table, defaults to true. Giving the flag without a value allows surrender
+    #[arg(
+        short = 's',
+        long,
+        value_name = "SURRENDER",
+        num_args = 0..=1,
+        default_missing_value = "true"
+    )]
+    surrender: Option<bool>,
+
+    /// Ignored, the deck is always shuffled with a single Fisher-Yates pass. Kept for compatibility with the library configurations
+    #[arg(long, value_name = "SHUFFLES")]
+    num_shuffles: Option<u32>,
 
-    /// Decides the margin to increase bets by
+    /// The betting strategy every counting strategy bets with. Defaults to margin
+    #[arg(long, value_enum, value_name = "BETTING")]
+    betting_strategy: Option<BettingStrategyArg>,
+
+    /// Decides the margin to increase bets by, only used by the margin betting strategy
     #[arg(short = 'm', long, value_name = "MARGIN")]
     betting_margin: Option<f32>,
 
+    /// The amount bet every hand by the flat betting strategy, defaults to the minimum bet
+    #[arg(long, value_name = "AMOUNT")]
+    flat_amount: Option<u32>,
+
+    /// The bet ramp of the ramp betting strategy as comma separated true_count:units pairs, e.g. "0:1,1:2,2:4,3:8". A unit is the minimum bet
+    #[arg(long, value_name = "RAMP", allow_hyphen_values = true)]
+    ramp: Option<String>,
+
+    /// The fraction of the full Kelly bet placed by the kelly betting strategy, defaults to one half
+    #[arg(long, value_name = "FRACTION")]
+    kelly_fraction: Option<f32>,
+
+    /// Decides whether or not the dealer hits on soft seventeens
+    #[arg(short = 'e', long, value_name = "SEVENTEEN")]
+    soft_seventeen: Option<bool>,
+
+    /// Decides whether or not the game allows insurance bets to be taken
+    #[arg(short = 'i', long, value_name = "INSURANCE")]
+    insurance: Option<bool>,
+
+    /// Decides whether any two cards of the same value may be split, e.g. a king and a queen, or only a pair of the same rank. Defaults to true
+    #[arg(long, value_name = "SPLIT_BY_VALUE")]
+    split_by_value: Option<bool>,
+
+    /// Decides whether the hands of a split may be doubled, i.e. double after split (DAS). Defaults to true
+    #[arg(long, value_name = "DOUBLE_AFTER_SPLIT")]
+    double_after_split: Option<bool>,
+
+    /// Sets the fraction of the shoe dealt before it is reshuffled, i.e. where the cut card is placed. Defaults to 0.8
+    #[arg(long, value_name = "PENETRATION")]
+    penetration: Option<f32>,
+
+    /// Optional seed for shuffling the deck, runs with the same seed and arguments produce identical results. A random seed is chosen and printed if none is given
+    #[arg(long, value_name = "SEED")]
+    seed: Option<u64>,
+
+    /// Decides whether every strategy is played against the same sequence of shoes, defaults to false
+    #[arg(long, value_name = "COMMON")]
+    common_random_numbers: Option<bool>,
+
+    /// The maximum number of hands buffered by the hand log, defaults to 1024
+    #[arg(long, value_name = "HANDS")]
+    hand_log_buffer_size: Option<usize>,
+
+    /// The maximum number of threads the strategies are run on, defaults to the number of logical CPUs. Never more threads than strategies are used
+    #[arg(long, value_name = "THREADS")]
+    threads: Option<usize>,
+
+    /// Optional comma separated list of the counting strategies to simulate, e.g. HiLo,KO,ZenCount. Names are case insensitive, defaults to every strategy
+    #[arg(long, value_name = "STRATEGIES")]
+    strategies: Option<String>,
+
+    /// The decision strategy every counting strategy plays with, one of basic, s17 or h17. Defaults to s17
+    #[arg(long, value_name = "DECISION")]
+    decision_strategy: Option<String>,
+
+    /// Precomputes the decisions of the decision strategy for true counts from -10 to 10 before the run, which speeds up long runs
+    #[arg(long)]
+    compile_decision_strategy: bool,
+
+    /// Runs the strategies once for every value of a parameter, e.g. betting_margin=1.5,2.0,2.5. The parameter is one of betting_margin,
+    /// num_decks or penetration, and the summaries of each value are labelled with it
+    #[arg(long, value_name = "PARAMETER=VALUES")]
+    sweep: Option<String>,
+}
+
+/// The arguments of the trace subcommand, the game configurations that are left out take the defaults of `BlackjackSimulatorConfigBuilder::build()`.
+#[derive(Args)]
+struct TraceArgs {
+    /// The number of rounds to play
+    #[arg(short = 'n', long, value_name = "ROUNDS", default_value_t = 10)]
+    rounds: u32,
+
+    /// The counting strategy to play with, names are case insensitive
+    #[arg(long, value_name = "COUNTING", default_value = "HiLo")]
+    counting_strategy: String,
+
+    /// The decision strategy to play with, one of basic, s17 or h17
+    #[arg(long, value_name = "DECISION", default_value = "s17")]
+    decision_strategy: String,
+
+    /// Sets the players starting balance, defaults to 500
+    #[arg(short = 'p', long, value_name = "PLAYER")]
+    player_starting_balance: Option<f32>,
+
+    /// Sets the number of decks that are used in the blackjack game, defaults to 6
+    #[arg(short = 'd', long, value_name = "DECKS")]
+    num_decks: Option<usize>,
+
+    /// Determines the minimum bet required, the player bets with the margin betting strategy. Defaults to 5
+    #[arg(short = 'b', long, value_name = "BET")]
+    min_bet: Option<u32>,
+
+    /// Decides whether surrender is a valid play at the blackjack table, defaults to true
+    #[arg(short = 's', long, value_name = "SURRENDER")]
+    surrender: Option<bool>,
+
     /// Decides whether or not the dealer hits on soft seventeens
     #[arg(short = 'e', long, value_name = "SEVENTEEN")]
     soft_seventeen: Option<bool>,
@@ -67,133 +259,1835 @@ struct Cli {
     /// Decides whether or not the game allows insurance bets to be taken
     #[arg(short = 'i', long, value_name = "INSURANCE")]
     insurance: Option<bool>,
+
+    /// Decides whether any two cards of the same value may be split, e.g. a king and a queen, or only a pair of the same rank. Defaults to true
+    #[arg(long, value_name = "SPLIT_BY_VALUE")]
+    split_by_value: Option<bool>,
+
+    /// Decides whether the hands of a split may be doubled, i.e. double after split (DAS). Defaults to true
+    #[arg(long, value_name = "DOUBLE_AFTER_SPLIT")]
+    double_after_split: Option<bool>,
+
+    /// Optional seed for shuffling the deck, a random seed is chosen and printed if none is given
+    #[arg(long, value_name = "SEED")]
+    seed: Option<u64>,
+}
+
+/// The betting strategies that can be chosen from the command line.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BettingStrategyArg {
+    Margin,
+    Flat,
+    Ramp,
+    Kelly,
+}
+
+impl BettingStrategyArg {
+    /// Returns the name the betting strategy is registered under.
+    fn registry_name(self) -> &'static str {
+        match self {
+            BettingStrategyArg::Margin => "Margin",
+            BettingStrategyArg::Flat => "Flat",
+            BettingStrategyArg::Ramp => "Ramp",
+            BettingStrategyArg::Kelly => "Kelly",
+        }
+    }
+}
+
+/// The summary formats that can be chosen from the command line.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormatArg {
+    Text,
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl OutputFormatArg {
+    /// Returns the library's summary format.
+    fn summary_format(self) -> SummaryFormat {
+        match self {
+            OutputFormatArg::Text => SummaryFormat::Text,
+            OutputFormatArg::Csv => SummaryFormat::Csv,
+            OutputFormatArg::Json => SummaryFormat::Json,
+            OutputFormatArg::Markdown => SummaryFormat::Markdown,
+        }
+    }
+}
+
+/// How much a run prints besides the summaries, see `--quiet` and `--verbose`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Verbosity {
+    /// Nothing but the summaries
+    Silent,
+    /// The summaries and the progress of the run
+    Summary,
+    /// The stats of every simulation as well, as they finish
+    PerSimulation,
+    /// A csv row for every hand as well
+    PerHand,
+}
+
+impl Verbosity {
+    /// Associated method that maps the verbosity flags onto a `Verbosity`. `silent_game` is the value of the deprecated `--silent-game`,
+    /// which can not be given along with the other flags.
+    fn from_flags(quiet: bool, verbose: u8, silent_game: Option<bool>) -> Verbosity {
+        match (quiet, verbose, silent_game) {
+            (true, _, _) => Verbosity::Silent,
+            (false, 0, Some(false)) => Verbosity::PerSimulation,
+            (false, 0, _) => Verbosity::Summary,
+            (false, 1, _) => Verbosity::PerSimulation,
+            (false, _, _) => Verbosity::PerHand,
+        }
+    }
+}
+
+impl RunArgs {
+    /// Returns the verbosity of the run.
+    fn verbosity(&self) -> Verbosity {
+        Verbosity::from_flags(self.quiet, self.verbose, self.silent_game)
+    }
+}
+
+/// The betting margin used when neither the command line nor the config file set one.
+const DEFAULT_BETTING_MARGIN: f32 = 2.0;
+
+/// The contents of a config file, i.e. the game configurations along with the strategies to simulate.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SimulationFile {
+    /// Fields that are left out take their default values
+    #[serde(default)]
+    config: BlackjackSimulatorConfig,
+    /// Every counting strategy is simulated if no strategies are given
+    #[serde(default)]
+    strategies: Vec<StrategySpec>,
+}
+
+/// A strategy to simulate, described by the names of its counting, decision and betting strategies along with the parameters of the betting strategy.
+#[derive(Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StrategySpec {
+    counting: String,
+    decision: Option<String>,
+    betting: Option<String>,
+    betting_margin: Option<f32>,
+    flat_amount: Option<u32>,
+    ramp: Option<String>,
+    kelly_fraction: Option<f32>,
+}
+
+/// A `StrategySpec` with every name resolved and the command line overrides applied.
+struct ResolvedStrategy {
+    counting: &'static CountingStrategyDescriptor,
+    decision: &'static DecisionStrategyDescriptor,
+    betting: &'static BettingStrategyDescriptor,
+    betting_params: BettingParameters,
+    /// The point of the parameter sweep the strategy is run at, `None` if the run is not a sweep
+    sweep_point: Option<SweepPoint>,
+}
+
+impl ResolvedStrategy {
+    /// Returns the label the strategy's summaries are written under.
+    fn label(&self) -> String {
+        match self.sweep_point {
+            Some(point) => format!(
+                "{} ({} betting) with {}",
+                self.counting.name, self.betting.name, point
+            ),
+            None => format!("{} ({} betting)", self.counting.name, self.betting.name),
+        }
+    }
+
+    /// Returns the configurations the strategy is simulated with, `config` with the swept parameter replaced.
+    fn config(&self, config: BlackjackSimulatorConfig) -> BlackjackSimulatorConfig {
+        match self.sweep_point {
+            Some(SweepPoint {
+                parameter: SweepParameter::NumDecks,
+                value,
+            }) => config.with_override(ConfigOverride::NumDecks(value as usize)),
+            Some(SweepPoint {
+                parameter: SweepParameter::Penetration,
+                value,
+            }) => config.with_override(ConfigOverride::Penetration(value)),
+            _ => config,
+        }
+    }
+}
+
+/// The parameters that can be swept by `--sweep`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SweepParameter {
+    BettingMargin,
+    NumDecks,
+    Penetration,
+}
+
+impl std::fmt::Display for SweepParameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SweepParameter::BettingMargin => write!(f, "betting_margin"),
+            SweepParameter::NumDecks => write!(f, "num_decks"),
+            SweepParameter::Penetration => write!(f, "penetration"),
+        }
+    }
+}
+
+/// A parameter sweep parsed from `--sweep`, the strategies are run once for each of `values`.
+#[derive(Debug, PartialEq)]
+struct Sweep {
+    parameter: SweepParameter,
+    values: Vec<f32>,
+}
+
+/// A single value of a parameter sweep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SweepPoint {
+    parameter: SweepParameter,
+    value: f32,
+}
+
+impl std::fmt::Display for SweepPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {}", self.parameter, self.value)
+    }
+}
+
+/// Helper function that parses the grid of a `--sweep`, given as the parameter followed by its comma separated values e.g. `num_decks=1,2,6`.
+fn parse_sweep(spec: &str) -> Result<Sweep, String> {
+    let Some((parameter, values)) = spec.split_once('=') else {
+        return Err(format!(
+            "invalid --sweep {}, expected PARAMETER=VALUES e.g. betting_margin=1.5,2.0",
+            spec
+        ));
+    };
+    let parameter = match parameter.trim() {
+        "betting_margin" => SweepParameter::BettingMargin,
+        "num_decks" => SweepParameter::NumDecks,
+        "penetration" => SweepParameter::Penetration,
+        parameter => {
+            return Err(format!(
+                "unknown sweep parameter {}, supported parameters are: betting_margin, num_decks, penetration",
+                parameter
+            ))
+        }
+    };
+    let mut parsed: Vec<f32> = vec![];
+    for value in values.split(',').map(str::trim) {
+        let value = value
+            .parse::<f32>()
+            .map_err(|_| format!("invalid {} value in --sweep: {}", parameter, value))?;
+        match parameter {
+            SweepParameter::BettingMargin if validate_betting_margin(value).is_err() => {
+                return Err(format!(
+                    "--sweep betting_margin values must be positive numbers, got {}",
+                    value
+                ));
+            }
+            SweepParameter::NumDecks if value < 1.0 || value.fract() != 0.0 => {
+                return Err(format!(
+                    "--sweep num_decks values must be positive whole numbers, got {}",
+                    value
+                ));
+            }
+            SweepParameter::Penetration if !(value > 0.0 && value < 1.0) => {
+                return Err(format!(
+                    "--sweep penetration values must be greater than 0 and less than 1, got {}",
+                    value
+                ));
+            }
+            _ => {}
+        }
+        if parsed.contains(&value) {
+            return Err(format!(
+                "--sweep values must be unique, {} is repeated",
+                value
+            ));
+        }
+        parsed.push(value);
+    }
+    Ok(Sweep {
+        parameter,
+        values: parsed,
+    })
+}
+
+/// Helper function that repeats `strategies` for every value of `sweep`, grouped by value so each value's summaries are written together.
+fn sweep_strategies(
+    strategies: Vec<ResolvedStrategy>,
+    sweep: &Sweep,
+) -> Result<Vec<ResolvedStrategy>, String> {
+    if sweep.parameter == SweepParameter::BettingMargin {
+        if let Some(strategy) = strategies
+            .iter()
+            .find(|strategy| !strategy.betting.needs_margin)
+        {
+            return Err(format!(
+                "--sweep betting_margin can only be used with the margin betting strategy, {} bets with {}",
+                strategy.counting.name, strategy.betting.name
+            ));
+        }
+    }
+    let mut swept = vec![];
+    for &value in &sweep.values {
+        for strategy in &strategies {
+            let mut betting_params = strategy.betting_params.clone();
+            if sweep.parameter == SweepParameter::BettingMargin {
+                betting_params.margin = value;
+            }
+            swept.push(ResolvedStrategy {
+                betting_params,
+                sweep_point: Some(SweepPoint {
+                    parameter: sweep.parameter,
+                    value,
+                }),
+                ..*strategy
+            });
+        }
+    }
+    Ok(swept)
+}
+
+/// Helper function that reads a config file, the format is decided by its extension.
+fn read_config_file(path: &Path) -> Result<SimulationFile, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("unable to read {}: {}", path.display(), e))?;
+    let parsed = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => toml::from_str(&contents).map_err(|e| e.to_string()),
+        Some("json") => serde_json::from_str(&contents).map_err(|e| e.to_string()),
+        _ => return Err(format!("{} must be a .toml or .json file", path.display())),
+    };
+    parsed.map_err(|e| format!("invalid config file {}: {}", path.display(), e))
+}
+
+/// Helper function that applies the configurations given on the command line to `config`, the configurations read from the config file if one was given.
+/// A seed is chosen at random if neither of them set one.
+fn simulator_config(
+    cli: &RunArgs,
+    config: Option<BlackjackSimulatorConfig>,
+) -> BlackjackSimulatorConfig {
+    let mut config = config.unwrap_or_default();
+    if let Some(balance) = cli.table_starting_balance {
+        config.table_starting_balance = Some(balance);
+    }
+    if let Some(balance) = cli.player_starting_balance {
+        config.player_starting_balance = balance;
+    }
+    if let Some(num_simulations) = cli.num_simulations {
+        config.num_simulations = num_simulations;
+    }
+    if let Some(num_decks) = cli.num_decks {
+        config.num_decks = num_decks;
+    }
+    if let Some(num_shuffles) = cli.num_shuffles {
+        config.num_shuffles = num_shuffles;
+    }
+    if let Some(hands) = cli.hands_per_simulation {
+        config.hands_per_simulation = hands;
+    }
+    if let Some(min_bet) = cli.min_bet {
+        config.min_bet = min_bet;
+    }
+    // The config file decides whether the stats of every simulation are shown, unless a verbosity flag is given
+    match cli.verbosity() {
+        Verbosity::Summary if cli.silent_game.is_none() => {}
+        verbosity => config.silent = verbosity < Verbosity::PerSimulation,
+    }
+    if let Some(surrender) = cli.surrender {
+        config.surrender = surrender;
+    }
+    if let Some(soft_seventeen) = cli.soft_seventeen {
+        config.soft_seventeen = soft_seventeen;
+    }
+    if let Some(insurance) = cli.insurance {
+        config.insurance = insurance;
+    }
+    if let Some(split_by_value) = cli.split_by_value {
+        config.split_by_value = split_by_value;
+    }
+    if let Some(double_after_split) = cli.double_after_split {
+        config.double_after_split = double_after_split;
+    }
+    if let Some(penetration) = cli.penetration {
+        config.penetration = penetration;
+    }
+    if cli.seed.is_some() {
+        config.seed = cli.seed;
+    }
+    if let Some(common_random_numbers) = cli.common_random_numbers {
+        config.common_random_numbers = common_random_numbers;
+    }
+    if let Some(size) = cli.hand_log_buffer_size {
+        config.hand_log_buffer_size = size;
+    }
+    if cli.threads.is_some() {
+        config.max_threads = cli.threads;
+    }
+    // Always run with a seed, so the run can be reproduced from the seed recorded in the output
+    if config.seed.is_none() {
+        config.seed = Some(rand::random());
+    }
+    config
+}
+
+/// Helper function that formats the header block written before the summaries, recording the seed of the run and the rules of the table
+/// so that saved results describe the game they were played under.
+fn format_header(config: &BlackjackSimulatorConfig) -> String {
+    let seed = config
+        .seed
+        .map_or(String::from("none"), |seed| seed.to_string());
+    let allowed = |allowed: bool| if allowed { "allowed" } else { "not allowed" };
+    let rules = [
+        ("seed", seed),
+        ("decks", config.num_decks.to_string()),
+        (
+            "dealer soft 17",
+            String::from(if config.soft_seventeen {
+                "hits"
+            } else {
+                "stands"
+            }),
+        ),
+        (
+            "blackjack pays",
+            format!(
+                "{}:{}",
+                TableRules::BLACKJACK_PAYOUT.0,
+                TableRules::BLACKJACK_PAYOUT.1
+            ),
+        ),
+        ("surrender", String::from(allowed(config.surrender))),
+        ("insurance", String::from(allowed(config.insurance))),
+        (
+            "split",
+            String::from(if config.split_by_value {
+                "any two cards of the same value"
+            } else {
+                "pairs of the same rank"
+            }),
+        ),
+        (
+            "double after split",
+            String::from(allowed(config.double_after_split)),
+        ),
+        ("penetration", config.penetration.to_string()),
+    ];
+    let mut header = format!("{:-^80}\n", "run");
+    for (rule, value) in rules {
+        header.push_str(&format!("{:<20}{:>60}\n", rule, value));
+    }
+    header.push_str(&format!("{}\n", "-".repeat(80)));
+    header
+}
+
+/// Helper function that resolves the strategies to simulate. `--strategies` replaces the strategies of the config file, while the decision and
+/// betting arguments override the values of every strategy. `ramp` is the bet ramp given on the command line.
+fn resolve_strategies(
+    cli: &RunArgs,
+    file_strategies: Vec<StrategySpec>,
+    ramp: Option<Vec<RampStep>>,
+    min_bet: u32,
+) -> Result<Vec<ResolvedStrategy>, String> {
+    let specs = match cli.strategies {
+        Some(ref list) => registry::parse_counting_strategies(list)?
+            .into_iter()
+            .map(|descriptor| StrategySpec {
+                counting: descriptor.name.to_string(),
+                ..Default::default()
+            })
+            .collect(),
+        None if !file_strategies.is_empty() => file_strategies,
+        None => registry::COUNTING_STRATEGIES
+            .iter()
+            .map(|descriptor| StrategySpec {
+                counting: descriptor.name.to_string(),
+                ..Default::default()
+            })
+            .collect(),
+    };
+
+    let mut strategies = vec![];
+    for spec in specs {
+        let counting = registry::find_counting_strategy(&spec.counting)
+            .ok_or(format!("unknown counting strategy: {}", spec.counting))?;
+        let decision_name = cli
+            .decision_strategy
+            .as_deref()
+            .or(spec.decision.as_deref())
+            .unwrap_or("s17");
+        let decision = registry::find_decision_strategy(decision_name).ok_or_else(|| {
+            let valid_names = registry::DECISION_STRATEGIES
+                .iter()
+                .map(|descriptor| {
+                    descriptor
+                        .aliases
+                        .first()
+                        .copied()
+                        .unwrap_or(descriptor.name)
+                })
+                .collect::<Vec<&str>>()
+                .join(", ");
+            format!(
+                "unknown decision strategy: {}, valid strategies are: {}",
+                decision_name, valid_names
+            )
+        })?;
+        let betting_arg = match (cli.betting_strategy, spec.betting) {
+            (Some(betting), _) => betting,
+            (None, Some(ref name)) => BettingStrategyArg::from_str(name, true).map_err(|_| {
+                format!(
+                    "unknown betting strategy: {}, valid strategies are: margin, flat, ramp, kelly",
+                    name
+                )
+            })?,
+            (None, None) => BettingStrategyArg::Margin,
+        };
+        let betting = registry::betting_strategy(betting_arg.registry_name())
+            .expect("every command line betting strategy is registered");
+        let ramp = match (ramp.clone(), spec.ramp) {
+            (Some(ramp), _) => Some(ramp),
+            (None, Some(ref ramp)) => Some(parse_ramp(ramp).map_err(|e| e.to_string())?),
+            (None, None) => None,
+        };
+        let betting_params = BettingParameters {
+            margin: cli
+                .betting_margin
+                .or(spec.betting_margin)
+                .unwrap_or(DEFAULT_BETTING_MARGIN),
+            min_bet,
+            flat_amount: cli.flat_amount.or(spec.flat_amount),
+            ramp,
+            unit: None,
+            kelly_fraction: cli.kelly_fraction.or(spec.kelly_fraction),
+        };
+        if betting.needs_margin {
+            validate_betting_margin(betting_params.margin)
+                .map_err(|e| describe_config_error(&e))?;
+        }
+        // Check the parameters once, so the strategy can be created for the simulation without failing
+        (betting.constructor)(&betting_params).map_err(|e| e.to_string())?;
+        strategies.push(ResolvedStrategy {
+            counting,
+            decision,
+            betting,
+            betting_params,
+            sweep_point: None,
+        });
+    }
+    Ok(strategies)
+}
+
+/// The exit code used when the arguments or the config file describe a run that can not be simulated.
+const INVALID_ARGUMENTS_EXIT_CODE: i32 = 3;
+
+/// Helper function that returns the command line argument that sets the configuration `field`.
+fn argument_name(field: &str) -> String {
+    match field {
+        "silent" => String::from("--silent-game"),
+        "max_threads" => String::from("--threads"),
+        _ => format!("--{}", field.replace('_', "-")),
+    }
+}
+
+/// Helper function that describes `error` in terms of the command line arguments, so the message says which argument to change.
+fn describe_config_error(error: &ConfigError) -> String {
+    // Constraints may refer to other fields, e.g. the minimum bet must not be greater than player_starting_balance
+    let constraint = error
+        .constraint
+        .split(' ')
+        .map(|word| {
+            if word.contains('_') {
+                argument_name(word)
+            } else {
+                word.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ");
+    format!("{} {}", argument_name(&error.field), constraint)
+}
+
+/// Helper function that checks the configurations and the strategies of a run before it starts, returning every problem found
+/// instead of stopping at the first one.
+fn validate_run(
+    cli: &RunArgs,
+    config: &BlackjackSimulatorConfig,
+    file_strategies: Vec<StrategySpec>,
+    ramp: Option<Vec<RampStep>>,
+) -> Result<Vec<ResolvedStrategy>, Vec<String>> {
+    let mut problems = config
+        .validate()
+        .err()
+        .unwrap_or_default()
+        .iter()
+        .map(describe_config_error)
+        .collect::<Vec<String>>();
+    let sweep = match cli.sweep.as_deref().map(parse_sweep).transpose() {
+        Ok(sweep) => sweep,
+        Err(problem) => {
+            problems.push(problem);
+            None
+        }
+    };
+    let resolved =
+        resolve_strategies(cli, file_strategies, ramp, config.min_bet).and_then(|strategies| {
+            match sweep {
+                Some(ref sweep) => sweep_strategies(strategies, sweep),
+                None => Ok(strategies),
+            }
+        });
+    match resolved {
+        Ok(strategies) => {
+            if let Err(problem) = hand_log_ids(cli, &strategies) {
+                problems.push(problem);
+            }
+            if problems.is_empty() {
+                Ok(strategies)
+            } else {
+                Err(problems)
+            }
+        }
+        Err(problem) => {
+            problems.push(problem);
+            Err(problems)
+        }
+    }
+}
+
+/// Helper function that returns the ids of the strategies named by `--hand-log-strategy`, `None` if every strategy is logged.
+/// Returns an error if none of the named strategies are simulated.
+fn hand_log_ids(
+    cli: &RunArgs,
+    strategies: &[ResolvedStrategy],
+) -> Result<Option<HashSet<usize>>, String> {
+    let Some(ref names) = cli.hand_log_strategy else {
+        return Ok(None);
+    };
+    let logged = registry::parse_counting_strategies(names)?;
+    let ids = strategies
+        .iter()
+        .enumerate()
+        .filter(|(_, strategy)| logged.iter().any(|d| d.name == strategy.counting.name))
+        .map(|(i, _)| i + 1)
+        .collect::<HashSet<usize>>();
+    if ids.is_empty() {
+        return Err(format!(
+            "--hand-log-strategy {} names none of the simulated strategies",
+            names
+        ));
+    }
+    Ok(Some(ids))
+}
+
+/// Where the hands of a run are logged, see `--hand-log`.
+struct HandLog {
+    writer: Box<dyn Write + Send + 'static>,
+    /// The ids of the logged strategies, `None` if every strategy is logged
+    ids: Option<HashSet<usize>>,
+}
+
+/// The estimated size of the hand log above which a warning is printed.
+const HAND_LOG_WARNING_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Helper function that estimates the size in bytes of the hand log of a run logging `num_logged` strategies, from the length of a typical row.
+fn estimated_hand_log_bytes(config: &BlackjackSimulatorConfig, num_logged: usize) -> u64 {
+    let bet = config.min_bet.saturating_mul(4);
+    let row = HandRecord {
+        strategy_id: num_logged,
+        simulation: config.num_simulations as u64,
+        hand: config.hands_per_simulation,
+        bet,
+        wins: 0,
+        pushes: 0,
+        losses: 1,
+        winnings: -Money::from_dollars(bet.into()),
+        balance: Money::from_f32(config.player_starting_balance),
+    }
+    .to_csv_line();
+    let num_rows =
+        num_logged as u64 * config.num_simulations as u64 * config.hands_per_simulation as u64;
+    HandRecord::CSV_HEADER.len() as u64 + num_rows * row.len() as u64
+}
+
+/// Helper function that opens the hand log of the run if `--hand-log` was given, warning when the log is expected to be large. The hands are
+/// logged to stderr instead if the verbosity is `Verbosity::PerHand`.
+fn open_hand_log(
+    cli: &RunArgs,
+    config: &BlackjackSimulatorConfig,
+    strategies: &[ResolvedStrategy],
+) -> std::io::Result<Option<HandLog>> {
+    let Some(ref path) = cli.hand_log else {
+        // At the highest verbosity the hands are written to stderr instead
+        return Ok((cli.verbosity() == Verbosity::PerHand).then(|| HandLog {
+            writer: Box::new(std::io::stderr()),
+            ids: None,
+        }));
+    };
+    let ids = hand_log_ids(cli, strategies).expect("the logged strategies were checked");
+    let num_logged = ids.as_ref().map_or(strategies.len(), HashSet::len);
+    let estimated_bytes = estimated_hand_log_bytes(config, num_logged);
+    if estimated_bytes > HAND_LOG_WARNING_BYTES {
+        eprintln!(
+            "warning: the hand log {} is expected to grow to about {} MB, use --hand-log-strategy to log fewer strategies",
+            path.display(),
+            estimated_bytes / (1024 * 1024)
+        );
+    }
+    Ok(Some(HandLog {
+        writer: Box::new(File::create(path)?),
+        ids,
+    }))
+}
+
+/// Helper function that builds a simulator running a simulation for each of `strategies`, logging their hands to `hand_log` if given.
+/// If `compile` is true the decision strategies are wrapped in a `CompiledStrategy`, precomputing their decisions for true counts from -10 to 10.
+fn build_simulator(
+    config: BlackjackSimulatorConfig,
+    strategies: &[ResolvedStrategy],
+    hand_log: Option<HandLog>,
+    compile: bool,
+) -> MulStrategyBlackjackSimulator {
+    let mut simulator_builder = MulStrategyBlackjackSimulator::new(config);
+    if let Some(hand_log) = hand_log {
+        simulator_builder.hand_log(hand_log.writer);
+        if let Some(ids) = hand_log.ids {
+            simulator_builder.hand_log_only(ids);
+        }
+    }
+    let mut simulator = simulator_builder.build();
+    for strategy in strategies {
+        // Swept strategies may be played with their own number of decks
+        let config = strategy.config(config);
+        let label = strategy.label();
+        let mut decision = (strategy.decision.constructor)();
+        if compile {
+            decision = Box::new(CompiledStrategy::new(decision, -10, 10));
+        }
+        simulator
+            .add_simulation_with_config(
+                PlayerStrategyDyn::new()
+                    .counting_strategy((strategy.counting.constructor)(config.num_decks as u32))
+                    .decision_strategy(decision)
+                    .betting_strategy(
+                        (strategy.betting.constructor)(&strategy.betting_params)
+                            .expect("the betting parameters were checked"),
+                    )
+                    .label(&label)
+                    .build()
+                    .expect("every part of the strategy is set"),
+                config,
+            )
+            .expect("the number of simulations is not limited");
+    }
+    simulator
+}
+
+/// Helper function that plays the rounds of the trace subcommand, writing every traced event to `out`.
+/// The events traced before an error are still written.
+fn trace_rounds<W: Write>(args: &TraceArgs, out: &mut W) -> Result<(), String> {
+    let mut config = BlackjackSimulatorConfig::default();
+    if let Some(balance) = args.player_starting_balance {
+        config.player_starting_balance = balance;
+    }
+    if let Some(num_decks) = args.num_decks {
+        config.num_decks = num_decks;
+    }
+    if let Some(min_bet) = args.min_bet {
+        config.min_bet = min_bet;
+    }
+    if let Some(surrender) = args.surrender {
+        config.surrender = surrender;
+    }
+    if let Some(soft_seventeen) = args.soft_seventeen {
+        config.soft_seventeen = soft_seventeen;
+    }
+    if let Some(insurance) = args.insurance {
+        config.insurance = insurance;
+    }
+    if let Some(split_by_value) = args.split_by_value {
+        config.split_by_value = split_by_value;
+    }
+    if let Some(double_after_split) = args.double_after_split {
+        config.double_after_split = double_after_split;
+    }
+    config.hands_per_simulation = args.rounds;
+    config.seed = Some(args.seed.unwrap_or_else(rand::random));
+    config
+        .validate()
+        .map_err(|errors| describe_config_error(&errors[0]))?;
+
+    let counting = registry::find_counting_strategy(&args.counting_strategy).ok_or(format!(
+        "unknown counting strategy: {}",
+        args.counting_strategy
+    ))?;
+    let decision = registry::find_decision_strategy(&args.decision_strategy).ok_or(format!(
+        "unknown decision strategy: {}",
+        args.decision_strategy
+    ))?;
+    let betting =
+        registry::betting_strategy("Margin").expect("the margin betting strategy is registered");
+    let betting_params = BettingParameters {
+        margin: DEFAULT_BETTING_MARGIN,
+        min_bet: config.min_bet,
+        flat_amount: None,
+        ramp: None,
+        unit: None,
+        kelly_fraction: None,
+    };
+    let strategy = PlayerStrategyDyn::new()
+        .counting_strategy((counting.constructor)(config.num_decks as u32))
+        .decision_strategy((decision.constructor)())
+        .betting_strategy((betting.constructor)(&betting_params).map_err(|e| e.to_string())?)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let rules = TableRules::from(&config);
+    let player = PlayerSim::new(
+        Money::from_f32(config.player_starting_balance),
+        strategy,
+        rules,
+    );
+    let table = BlackjackTableSim::new(config.table_balance(), rules, config.seed);
+    let mut game =
+        BlackjackGameSim::new(table, player, config.hands_per_simulation, config.min_bet);
+    game.enable_trace();
+
+    writeln!(
+        out,
+        "tracing {} with {} and margin betting, seed {}",
+        counting.name,
+        decision.name,
+        config.seed.expect("a seed is always chosen")
+    )
+    .map_err(|e| e.to_string())?;
+    let result = game.run();
+    for event in game.take_trace() {
+        writeln!(out, "{}", event).map_err(|e| e.to_string())?;
+    }
+    result.map_err(|e| e.to_string())
+}
+
+/// How often the progress line is redrawn.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Tracks how many simulations of each strategy of a run have finished.
+struct RunProgress {
+    labels: Vec<String>,
+    completed: Vec<u32>,
+    num_simulations: u32,
+}
+
+impl RunProgress {
+    /// Associated method for creating the progress of a run of `num_simulations` simulations for each of the strategies labelled `labels`,
+    /// the strategy with id `i` is labelled `labels[i - 1]`.
+    fn new(labels: Vec<String>, num_simulations: u32) -> RunProgress {
+        RunProgress {
+            completed: vec![0; labels.len()],
+            labels,
+            num_simulations,
+        }
+    }
+
+    /// Records that a single simulation of the strategy with the given id has finished.
+    fn record(&mut self, id: usize) {
+        if let Some(completed) = self.completed.get_mut(id - 1) {
+            *completed += 1;
+        }
+    }
+
+    /// Returns the percentage of the simulations of every strategy that have finished.
+    fn percent_complete(&self) -> f32 {
+        let total = self.num_simulations as u64 * self.completed.len() as u64;
+        if total == 0 {
+            return 100.0;
+        }
+        let completed = self.completed.iter().map(|&c| c as u64).sum::<u64>();
+        (completed as f64 / total as f64 * 100.0) as f32
+    }
+
+    /// Formats the progress line, showing the strategy with the given id along with the progress of the whole run.
+    fn line(&self, id: usize) -> String {
+        format!(
+            "{}: {}/{} simulations, {:.1}% of the run complete",
+            self.labels[id - 1],
+            self.completed[id - 1],
+            self.num_simulations,
+            self.percent_complete()
+        )
+    }
+}
+
+/// The progress line drawn on stderr while a run is in progress, it is cleared once dropped at the end of the run.
+struct ProgressLine {
+    progress: RunProgress,
+    last_drawn: Option<Instant>,
+    len: usize,
+}
+
+impl ProgressLine {
+    /// Records that a single simulation of the strategy with the given id has finished, redrawing the line at most every `PROGRESS_INTERVAL`.
+    fn record(&mut self, id: usize) {
+        self.progress.record(id);
+        if self
+            .last_drawn
+            .is_none_or(|drawn| drawn.elapsed() >= PROGRESS_INTERVAL)
+        {
+            let line = self.progress.line(id);
+            let len = self.len;
+            // Pad with spaces to cover the rest of a longer previous line
+            eprint!("\r{:<len$}", line);
+            self.len = line.len();
+            self.last_drawn = Some(Instant::now());
+        }
+    }
+}
+
+impl Drop for ProgressLine {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            eprint!("\r{}\r", " ".repeat(self.len));
+        }
+    }
+}
+
+/// Helper function that returns an event handler for `MulStrategyBlackjackSimulator::run_with_events()`, drawing the progress of the run
+/// on a single line of stderr until the run is over.
+fn with_progress(
+    labels: Vec<String>,
+    num_simulations: u32,
+) -> impl FnMut(CollectEvent) + Send + 'static {
+    let mut line = ProgressLine {
+        progress: RunProgress::new(labels, num_simulations),
+        last_drawn: None,
+        len: 0,
+    };
+    move |event| {
+        if let CollectEvent::Simulation { id, .. } = event {
+            line.record(id);
+        }
+    }
+}
+
+/// Helper function that describes the error a run failed with, the error of a failed strategy already names it and how far it got.
+fn describe_run_error(error: &SimulationError) -> String {
+    match error {
+        SimulationError::StrategyError {
+            label,
+            simulations_completed,
+            source,
+            ..
+        } => format!(
+            "simulation '{}' failed after {} simulations: {}",
+            label, simulations_completed, source.message
+        ),
+        error => error.to_string(),
+    }
+}
+
+/// Helper function that runs `simulator`, writing the summaries of the strategies that finished to `writer` in the output format of `cli`
+/// even if another strategy fails. If `--compare` is given the strategy comparison is written once the run succeeds, see `format_comparison()`,
+/// it is appended to the output of the text format and written to stderr otherwise so the other formats stay parseable. `on_event` is called
+/// as the summaries of the run arrive.
+fn run_and_write(
+    cli: &RunArgs,
+    simulator: &mut MulStrategyBlackjackSimulator,
+    writer: &mut dyn Write,
+    on_event: impl FnMut(CollectEvent) + Send + 'static,
+) -> Result<(), SimulationError> {
+    let summary_format = cli.output_format.summary_format();
+    let mut summaries = HashMap::new();
+    let result = simulator.run_with_events(on_event, |summary, id| {
+        summaries.insert(id, summary);
+    });
+    writer
+        .write_all(summary_format.format(&summaries).as_bytes())
+        .and_then(|_| writer.flush())
+        .map_err(SimulationError::Io)?;
+    result?;
+
+    if cli.compare {
+        let comparison = format_comparison(&summaries, simulator.config.num_simulations);
+        if summary_format == SummaryFormat::Text {
+            writer
+                .write_all(comparison.as_bytes())
+                .and_then(|_| writer.flush())
+                .map_err(SimulationError::Io)?;
+        } else {
+            eprint!("{}", comparison);
+        }
+        if let Some(ref path) = cli.compare_out {
+            std::fs::write(path, &comparison).map_err(SimulationError::Io)?;
+        }
+    }
+    Ok(())
+}
+
+/// Helper function that checks the companion flags of the betting strategy, each may only be given along with the strategy that uses it.
+/// The check is left to `resolve_strategies()` when a config file is given without `--betting-strategy`, since the flags then override the values of
+/// the strategies in the file. Returns the parsed bet ramp if one was given.
+fn betting_args(cli: &RunArgs) -> Result<Option<Vec<RampStep>>, clap::Error> {
+    let companion_flags = [
+        (
+            "--betting-margin",
+            cli.betting_margin.is_some(),
+            BettingStrategyArg::Margin,
+        ),
+        (
+            "--flat-amount",
+            cli.flat_amount.is_some(),
+            BettingStrategyArg::Flat,
+        ),
+        ("--ramp", cli.ramp.is_some(), BettingStrategyArg::Ramp),
+        (
+            "--kelly-fraction",
+            cli.kelly_fraction.is_some(),
+            BettingStrategyArg::Kelly,
+        ),
+    ];
+    let betting_strategy = match cli.betting_strategy {
+        Some(betting_strategy) => Some(betting_strategy),
+        None if cli.config.is_none() => Some(BettingStrategyArg::Margin),
+        None => None,
+    };
+    for (flag, given, strategy) in companion_flags {
+        if given && betting_strategy.is_some_and(|betting| betting != strategy) {
+            let name = strategy.to_possible_value().unwrap();
+            return Err(Cli::command().error(
+                ErrorKind::ArgumentConflict,
+                format!(
+                    "{} can only be used with '--betting-strategy {}'",
+                    flag,
+                    name.get_name()
+                ),
+            ));
+        }
+    }
+    match cli.ramp {
+        Some(ref ramp) => parse_ramp(ramp).map(Some).map_err(|err| {
+            Cli::command().error(
+                ErrorKind::ValueValidation,
+                format!("invalid value '{}' for '--ramp': {}", ramp, err),
+            )
+        }),
+        None if cli.config.is_none() && betting_strategy == Some(BettingStrategyArg::Ramp) => {
+            Err(Cli::command().error(
+                ErrorKind::MissingRequiredArgument,
+                "'--betting-strategy ramp' requires '--ramp'",
+            ))
+        }
+        None => Ok(None),
+    }
 }
 
 fn main() -> std::io::Result<()> {
     // Get command line arguments to
-    let cli = Cli::parse();
+    let cli = match Cli::parse() {
+        Cli {
+            command: Some(Command::ListStrategies),
+            ..
+        } => {
+            print!("{}", registry::format_strategy_list());
+            return Ok(());
+        }
+        Cli {
+            command: Some(Command::Trace(args)),
+            ..
+        } => {
+            if let Err(e) = trace_rounds(&args, &mut std::io::stdout()) {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        cli => cli
+            .into_run_args()
+            .expect("every other invocation is a run"),
+    };
+    let ramp = betting_args(&cli).unwrap_or_else(|err| err.exit());
+    if cli.silent_game.is_some() {
+        eprintln!("warning: --silent-game is deprecated and will be removed in the next release, use --verbose to show the stats of every simulation");
+    }
+    let file = match cli.config {
+        Some(ref path) => match read_config_file(path) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                std::process::exit(INVALID_ARGUMENTS_EXIT_CODE);
+            }
+        },
+        None => None,
+    };
+    let (file_config, file_strategies) = match file {
+        Some(file) => (Some(file.config), file.strategies),
+        None => (None, vec![]),
+    };
     // Build configuration for simulation
-    let config = BlackjackSimulatorConfig::new()
-        .player_starting_balance(cli.player_starting_balance)
-        .table_starting_balance(cli.table_starting_balance.unwrap_or(f32::MAX))
-        .num_simulations(cli.num_simulations)
-        .num_decks(cli.num_decks)
-        .hands_per_simulation(cli.hands_per_simulation)
-        .min_bet(cli.min_bet)
-        .silent(cli.silent_game.unwrap_or(true))
-        .surrender(cli.surrender)
-        .soft_seventeen(cli.soft_seventeen.unwrap_or(false))
-        .insurance(cli.insurance.unwrap_or(false))
-        .build();
+    let config = simulator_config(&cli, file_config);
+    let strategies = match validate_run(&cli, &config, file_strategies, ramp) {
+        Ok(strategies) => strategies,
+        Err(problems) => {
+            for problem in problems {
+                eprintln!("error: {}", problem);
+            }
+            std::process::exit(INVALID_ARGUMENTS_EXIT_CODE);
+        }
+    };
+    let mut warned = HashSet::new();
+    for strategy in strategies.iter() {
+        let decision_strategy = strategy.decision;
+        let Some(soft_seventeen) = decision_strategy.soft_seventeen else {
+            continue;
+        };
+        if soft_seventeen != config.soft_seventeen && warned.insert(decision_strategy.name) {
+            eprintln!(
+                "warning: the {} decision strategy is meant for a dealer that {} soft seventeen, but the dealer {}",
+                decision_strategy.name,
+                if soft_seventeen { "hits" } else { "stands on" },
+                if config.soft_seventeen { "hits" } else { "stands on" }
+            );
+        }
+    }
 
     // Get other configurations out of cli
-    let out_writer: Box<dyn Write + Send + 'static> = if cli.file_out.is_some() {
-        Box::new(File::create(cli.file_out.unwrap())?)
+    let mut out_writer: Box<dyn Write + Send + 'static> = if let Some(path) = &cli.file_out {
+        Box::new(File::create(path)?)
     } else {
         Box::new(std::io::stdout())
     };
+    let summary_format = cli.output_format.summary_format();
+    // Only the text blocks have a header, the other formats must stay parseable
+    if summary_format == SummaryFormat::Text {
+        out_writer.write_all(format_header(&config).as_bytes())?;
+    }
 
-    let betting_margin = match cli.betting_margin {
-        Some(b) => b,
-        None => 2.0,
-    };
+    // Build the simulator, with a simulation for each chosen strategy
+    let hand_log = open_hand_log(&cli, &config, &strategies)?;
+    let mut simulator =
+        build_simulator(config, &strategies, hand_log, cli.compile_decision_strategy);
+    let on_event: Box<dyn FnMut(CollectEvent) + Send> =
+        if cli.verbosity() > Verbosity::Silent && std::io::stdout().is_terminal() {
+            let labels = strategies.iter().map(ResolvedStrategy::label).collect();
+            Box::new(with_progress(labels, config.num_simulations))
+        } else {
+            Box::new(|_| {})
+        };
 
-    let num_decks = cli.num_decks as u32;
-    let min_bet = cli.min_bet;
-
-    // Build the simulator
-    let mut simulator = MulStrategyBlackjackSimulator::new(config)
-        .simulation(PlayerStrategy::new(
-            HiLo::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            WongHalves::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            KO::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            RedSeven::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            HiOptI::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            HiOptII::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            AceFive::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            OmegaII::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            ZenCount::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            Halves::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            KISS::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            KISSII::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            KISSIII::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            SilverFox::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            JNoir::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .simulation(PlayerStrategy::new(
-            UnbalancedZen2::new(num_decks),
-            S17DeviationStrategy::new(),
-            MarginBettingStrategy::new(betting_margin, min_bet),
-        ))
-        .build();
-
-    // Run simulation and check for error
-    println!("Running simulations...");
-
-    if let Err(err) = simulator.run(out_writer, Box::new(write_summaries)) {
-        eprintln!("error: {}", err);
+    // Run simulation and check for error, progress goes to stderr so the summaries can be piped from stdout
+    eprintln!(
+        "Running simulations with seed {}...",
+        config.seed.expect("a seed is always chosen")
+    );
+
+    if let Err(err) = run_and_write(&cli, &mut simulator, &mut out_writer, on_event) {
+        eprintln!("error: {}", describe_run_error(&err));
         std::process::exit(1);
     }
 
-    println!("Simulations complete.");
+    eprintln!("Simulations complete.");
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Helper function that parses `args`, which must describe a run.
+    fn parse_run_args<I: IntoIterator<Item = T>, T: Into<std::ffi::OsString> + Clone>(
+        args: I,
+    ) -> RunArgs {
+        Cli::try_parse_from(args)
+            .unwrap()
+            .into_run_args()
+            .expect("the arguments describe a run")
+    }
+
+    /// Helper function that resolves the arguments the way `main()` does, and returns the output of the simulations.
+    fn run_with_args(args: &[&str]) -> String {
+        let cli = parse_run_args(std::iter::once("blackjack_sim").chain(args.iter().copied()));
+        let ramp = betting_args(&cli).unwrap();
+        let (file_config, file_strategies) = match cli.config {
+            Some(ref path) => {
+                let file = read_config_file(path).unwrap();
+                (Some(file.config), file.strategies)
+            }
+            None => (None, vec![]),
+        };
+        let config = simulator_config(&cli, file_config);
+        let mut strategies =
+            resolve_strategies(&cli, file_strategies, ramp, config.min_bet).unwrap();
+        if let Some(ref sweep) = cli.sweep {
+            strategies = sweep_strategies(strategies, &parse_sweep(sweep).unwrap()).unwrap();
+        }
+        let mut output = vec![];
+        if cli.output_format.summary_format() == SummaryFormat::Text {
+            output.write_all(format_header(&config).as_bytes()).unwrap();
+        }
+        let hand_log = open_hand_log(&cli, &config, &strategies).unwrap();
+        let mut simulator =
+            build_simulator(config, &strategies, hand_log, cli.compile_decision_strategy);
+        run_and_write(&cli, &mut simulator, &mut output, |_| {}).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    /// Helper function that writes `contents` to a config file in the temp directory, and returns its path.
+    fn write_config_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    const FLAGS: &[&str] = &[
+        "-p",
+        "500",
+        "-n",
+        "20",
+        "-d",
+        "6",
+        "-r",
+        "50",
+        "-b",
+        "5",
+        "-s",
+        "--seed",
+        "7",
+        "--strategies",
+        "HiLo,KO",
+        "--decision-strategy",
+        "h17",
+        "-e",
+        "true",
+        "--betting-strategy",
+        "ramp",
+        "--ramp",
+        "0:1,2:4",
+    ];
+
+    #[test]
+    fn toml_file_run_matches_flag_run() {
+        let path = write_config_file(
+            "sim.toml",
+            r#"
+[config]
+player_starting_balance = 500.0
+num_simulations = 20
+num_decks = 6
+hands_per_simulation = 50
+min_bet = 5
+surrender = true
+soft_seventeen = true
+seed = 7
+
+[[strategies]]
+counting = "HiLo"
+decision = "h17"
+betting = "ramp"
+ramp = "0:1,2:4"
+
+[[strategies]]
+counting = "ko"
+decision = "h17"
+betting = "Ramp"
+ramp = "0:1,2:4"
+"#,
+        );
+        let from_file = run_with_args(&["--config", path.to_str().unwrap()]);
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(from_file, run_with_args(FLAGS));
+    }
+
+    #[test]
+    fn json_file_run_matches_flag_run() {
+        let path = write_config_file(
+            "sim.json",
+            r#"{
+                "config": {"player_starting_balance": 500.0, "num_simulations": 20, "num_decks": 6, "hands_per_simulation": 50,
+                    "min_bet": 5, "surrender": true, "soft_seventeen": true, "seed": 7},
+                "strategies": [
+                    {"counting": "HiLo", "decision": "h17", "betting": "ramp", "ramp": "0:1,2:4"},
+                    {"counting": "KO", "decision": "h17", "betting": "ramp", "ramp": "0:1,2:4"}
+                ]
+            }"#,
+        );
+        let from_file = run_with_args(&["--config", path.to_str().unwrap()]);
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(from_file, run_with_args(FLAGS));
+    }
+
+    #[test]
+    fn flags_override_the_config_file() {
+        let path = write_config_file(
+            "override.toml",
+            r#"
+[config]
+num_simulations = 20
+num_decks = 2
+hands_per_simulation = 50
+seed = 3
+
+[[strategies]]
+counting = "HiLo"
+betting = "flat"
+"#,
+        );
+        let from_file = run_with_args(&[
+            "--config",
+            path.to_str().unwrap(),
+            "-d",
+            "6",
+            "--seed",
+            "7",
+            "--strategies",
+            "HiLo,KO",
+            "--decision-strategy",
+            "h17",
+            "-e",
+            "true",
+            "-s",
+            "--betting-strategy",
+            "ramp",
+            "--ramp",
+            "0:1,2:4",
+        ]);
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(from_file, run_with_args(FLAGS));
+    }
+
+    #[test]
+    fn example_config_file_is_valid() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("sim.example.toml");
+        let file = read_config_file(&path).unwrap();
+        assert_eq!(file.config.validate(), Ok(()));
+        let cli = parse_run_args(["blackjack_sim", "--config", path.to_str().unwrap()]);
+        let strategies =
+            resolve_strategies(&cli, file.strategies, None, file.config.min_bet).unwrap();
+        assert!(!strategies.is_empty());
+    }
+
+    #[test]
+    fn default_args_match_the_library_defaults() {
+        let cli = parse_run_args(["blackjack_sim"]);
+        let config = simulator_config(&cli, None);
+        // The seed is chosen at random when none is given
+        assert!(config.seed.is_some());
+        assert_eq!(
+            BlackjackSimulatorConfig {
+                seed: None,
+                ..config
+            },
+            BlackjackSimulatorConfig::default()
+        );
+    }
+
+    #[test]
+    fn same_seed_produces_identical_output() {
+        let args = [
+            "-p", "500", "-n", "10", "-d", "6", "-r", "100", "-b", "5", "--seed", "42",
+        ];
+        let output = run_with_args(&args);
+        assert!(output.contains("seed"));
+        assert!(output.contains("42"));
+        assert_eq!(output, run_with_args(&args));
+    }
+
+    #[test]
+    fn seed_is_chosen_when_omitted() {
+        let cli = parse_run_args([
+            "blackjack_sim",
+            "-p",
+            "500",
+            "-n",
+            "1",
+            "-d",
+            "6",
+            "-r",
+            "1",
+            "-b",
+            "5",
+        ]);
+        let config = simulator_config(&cli, None);
+        let seed = config.seed.expect("a seed is chosen");
+        assert!(format_header(&config).contains(&seed.to_string()));
+    }
+
+    #[test]
+    fn header_echoes_the_table_rules() {
+        let cli = Cli::parse_from([
+            "blackjack_sim",
+            "-d",
+            "2",
+            "-e",
+            "true",
+            "-s",
+            "false",
+            "-i",
+            "true",
+            "--split-by-value",
+            "false",
+            "--double-after-split",
+            "false",
+        ])
+        .into_run_args()
+        .unwrap();
+        let header = format_header(&simulator_config(&cli, None));
+        let rule = |name: &str| {
+            header
+                .lines()
+                .find(|line| line.starts_with(name))
+                .map(|line| line[name.len()..].trim().to_string())
+        };
+        assert_eq!(rule("decks").as_deref(), Some("2"));
+        assert_eq!(rule("dealer soft 17").as_deref(), Some("hits"));
+        assert_eq!(rule("blackjack pays").as_deref(), Some("3:2"));
+        assert_eq!(rule("surrender").as_deref(), Some("not allowed"));
+        assert_eq!(rule("insurance").as_deref(), Some("allowed"));
+        assert_eq!(rule("split").as_deref(), Some("pairs of the same rank"));
+        assert_eq!(rule("double after split").as_deref(), Some("not allowed"));
+    }
+
+    const FORMAT_ARGS: &[&str] = &[
+        "-n",
+        "5",
+        "--seed",
+        "11",
+        "--strategies",
+        "HiLo,KO",
+        "--output-format",
+    ];
+
+    #[test]
+    fn json_output_is_valid_json() {
+        let output = run_with_args(&[FORMAT_ARGS, &["json"][..]].concat());
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let rows = json.as_array().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0]["strategy"].as_str().unwrap().starts_with("HiLo"));
+    }
+
+    #[test]
+    fn csv_output_has_the_csv_header() {
+        let output = run_with_args(&[FORMAT_ARGS, &["csv"][..]].concat());
+        let mut lines = output.lines();
+        assert_eq!(
+            lines.next(),
+            Some(blackjack_sim::write::SUMMARY_CSV_HEADER.trim_end())
+        );
+        let columns = blackjack_sim::write::SUMMARY_CSV_HEADER.split(',').count();
+        assert_eq!(lines.clone().count(), 2);
+        // Labels are quoted and contain no commas, so every row splits into the header's columns
+        assert!(lines.all(|line| line.split(',').count() == columns));
+    }
+
+    #[test]
+    fn markdown_output_is_a_table() {
+        let output = run_with_args(&[FORMAT_ARGS, &["markdown"][..]].concat());
+        let lines = output.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 4);
+        assert!(lines
+            .iter()
+            .all(|line| line.starts_with("| ") && line.ends_with(" |")));
+    }
+
+    #[test]
+    fn text_output_is_the_default() {
+        let output = run_with_args(&FORMAT_ARGS[..FORMAT_ARGS.len() - 1]);
+        assert!(output.starts_with(&"-".repeat(38)));
+        assert!(output.contains("simulation #1"));
+        assert_eq!(
+            output,
+            run_with_args(&[FORMAT_ARGS, &["text"][..]].concat())
+        );
+    }
+
+    #[test]
+    fn comparison_follows_the_summaries() {
+        const ARGS: &[&str] = &[
+            "-n",
+            "5",
+            "-r",
+            "20",
+            "--seed",
+            "3",
+            "--strategies",
+            "HiLo,KO",
+        ];
+        let path = std::env::temp_dir().join(format!("{}-comparison.txt", std::process::id()));
+        let compare_args = ["--compare", "--compare-out", path.to_str().unwrap()];
+
+        let compared = run_with_args(&[ARGS, &compare_args[..]].concat());
+        let comparison = std::fs::read_to_string(&path).unwrap();
+        assert!(comparison.contains("strategy comparison"));
+        // The title, the column names, a row for each strategy and the closing line
+        assert_eq!(comparison.lines().count(), 5);
+        assert_eq!(compared, format!("{}{}", run_with_args(ARGS), comparison));
+
+        // The comparison goes to stderr, so other formats stay parseable
+        let json_args = [ARGS, &["--output-format", "json"][..]].concat();
+        assert_eq!(
+            run_with_args(&[&json_args[..], &["--compare"][..]].concat()),
+            run_with_args(&json_args)
+        );
+    }
+
+    #[test]
+    fn compare_out_requires_compare() {
+        assert!(Cli::try_parse_from(["blackjack_sim", "--compare-out", "comparison.txt"]).is_err());
+    }
+
+    #[test]
+    fn hand_log_has_a_row_for_every_hand() {
+        let path = std::env::temp_dir().join(format!("{}-hand-log.csv", std::process::id()));
+        let args = [
+            "-n",
+            "3",
+            "-r",
+            "20",
+            "-p",
+            "100000",
+            "--seed",
+            "3",
+            "--strategies",
+            "HiLo,KO",
+            "--hand-log",
+            path.to_str().unwrap(),
+        ];
+        // Logging the hands leaves the summaries as they are
+        assert_eq!(run_with_args(&args), run_with_args(&args[..10]));
+        let log = std::fs::read_to_string(&path).unwrap();
+        let mut lines = log.lines();
+        assert_eq!(lines.next(), Some(HandRecord::CSV_HEADER.trim_end()));
+        assert_eq!(lines.count(), 2 * 3 * 20);
+
+        run_with_args(&[&args[..], &["--hand-log-strategy", "KO"][..]].concat());
+        let log = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(log.lines().skip(1).count(), 3 * 20);
+        assert!(log.lines().skip(1).all(|line| line.starts_with("2,")));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn sweep_spec_is_parsed() {
+        assert_eq!(
+            parse_sweep("betting_margin=1.5, 2,2.5"),
+            Ok(Sweep {
+                parameter: SweepParameter::BettingMargin,
+                values: vec![1.5, 2.0, 2.5]
+            })
+        );
+        assert_eq!(
+            parse_sweep("num_decks=1,6").map(|sweep| sweep.parameter),
+            Ok(SweepParameter::NumDecks)
+        );
+        assert_eq!(
+            parse_sweep("penetration=0.65,0.75").map(|sweep| sweep.parameter),
+            Ok(SweepParameter::Penetration)
+        );
+        for spec in [
+            "betting_margin",
+            "betting_margin=",
+            "betting_margin=1.5,x",
+            "betting_margin=0",
+            "betting_margin=2,2",
+            "num_decks=1.5",
+            "num_decks=0",
+            "penetration=0",
+            "penetration=1",
+            "min_bet=5,10",
+        ] {
+            assert!(parse_sweep(spec).is_err(), "{} should be rejected", spec);
+        }
+    }
+
+    #[test]
+    fn sweep_labels_every_grid_point() {
+        let output = run_with_args(&[
+            "-n",
+            "2",
+            "-r",
+            "10",
+            "--seed",
+            "3",
+            "--strategies",
+            "HiLo,KO",
+            "--sweep",
+            "num_decks=1,2",
+            "--output-format",
+            "csv",
+        ]);
+        let labels = output
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').nth(1).unwrap().trim_matches('"'))
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            labels,
+            [
+                "HiLo (Margin betting) with num_decks = 1",
+                "KO (Margin betting) with num_decks = 1",
+                "HiLo (Margin betting) with num_decks = 2",
+                "KO (Margin betting) with num_decks = 2",
+            ]
+        );
+
+        let cli = parse_run_args(["blackjack_sim", "--betting-strategy", "flat"]);
+        let strategies = resolve_strategies(&cli, vec![], None, 5).unwrap();
+        let sweep = parse_sweep("betting_margin=1.5,2").unwrap();
+        assert!(sweep_strategies(strategies, &sweep).is_err());
+    }
+
+    #[test]
+    fn run_errors_name_the_failed_strategy() {
+        let error = SimulationError::StrategyError {
+            strategy_id: 7,
+            label: Arc::from("RedSeven (margin betting)"),
+            simulation_index: 42,
+            simulations_completed: 42,
+            source: blackjack_sim::BlackjackGameError::new(String::from(
+                "option not a valid choice",
+            )),
+        };
+        assert_eq!(
+            describe_run_error(&error),
+            "simulation 'RedSeven (margin betting)' failed after 42 simulations: option not a valid choice"
+        );
+        assert_eq!(
+            describe_run_error(&SimulationError::Cancelled),
+            "simulation cancelled"
+        );
+    }
+
+    #[test]
+    fn verbosity_follows_the_flags() {
+        assert_eq!(Verbosity::from_flags(false, 0, None), Verbosity::Summary);
+        assert_eq!(Verbosity::from_flags(true, 0, None), Verbosity::Silent);
+        assert_eq!(
+            Verbosity::from_flags(false, 1, None),
+            Verbosity::PerSimulation
+        );
+        assert_eq!(Verbosity::from_flags(false, 2, None), Verbosity::PerHand);
+        assert_eq!(Verbosity::from_flags(false, 3, None), Verbosity::PerHand);
+        assert_eq!(
+            Verbosity::from_flags(false, 0, Some(true)),
+            Verbosity::Summary
+        );
+        assert_eq!(
+            Verbosity::from_flags(false, 0, Some(false)),
+            Verbosity::PerSimulation
+        );
+    }
+
+    #[test]
+    fn silent_game_is_a_hidden_alias_of_verbose() {
+        let cli = parse_run_args(["blackjack_sim", "--silent-game", "false"]);
+        assert_eq!(cli.verbosity(), Verbosity::PerSimulation);
+        assert!(!simulator_config(&cli, None).silent);
+        let cli = parse_run_args(["blackjack_sim", "-g", "true"]);
+        assert!(simulator_config(&cli, None).silent);
+        assert!(!simulator_config(&parse_run_args(["blackjack_sim", "-v"]), None).silent);
+
+        assert!(Cli::try_parse_from(["blackjack_sim", "-g", "false", "-v"]).is_err());
+        assert!(Cli::try_parse_from(["blackjack_sim", "-q", "-v"]).is_err());
+        assert!(!Cli::command()
+            .render_help()
+            .to_string()
+            .contains("--silent-game"));
+    }
+
+    #[test]
+    fn hand_log_strategy_requires_hand_log() {
+        assert!(Cli::try_parse_from(["blackjack_sim", "--hand-log-strategy", "HiLo"]).is_err());
+    }
+
+    #[test]
+    fn run_subcommand_matches_the_flat_invocation() {
+        let args = ["-n", "5", "--seed", "3", "--strategies", "HiLo"];
+        let output = run_with_args(&args);
+        assert_eq!(output, run_with_args(&[&["run"][..], &args[..]].concat()));
+    }
+
+    #[test]
+    fn list_strategies_is_not_a_run() {
+        let cli = Cli::try_parse_from(["blackjack_sim", "list-strategies"]).unwrap();
+        assert!(cli.into_run_args().is_none());
+        assert!(Cli::try_parse_from(["blackjack_sim", "-n", "5", "list-strategies"]).is_err());
+    }
+
+    /// Helper function that parses the arguments of the trace subcommand and returns its output.
+    fn trace_with_args(args: &[&str]) -> String {
+        let cli = Cli::try_parse_from(["blackjack_sim", "trace"].iter().chain(args)).unwrap();
+        let Some(Command::Trace(trace_args)) = cli.command else {
+            panic!("the arguments describe a trace");
+        };
+        let mut output = vec![];
+        trace_rounds(&trace_args, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn trace_walks_through_every_round() {
+        let output = trace_with_args(&["-n", "3", "--seed", "7", "--decision-strategy", "h17"]);
+        assert!(output.starts_with("tracing HiLo with H17 Deviations and margin betting, seed 7"));
+        for round in 1..=3 {
+            assert!(output.contains(&format!("round {}: bet", round)));
+        }
+        assert!(!output.contains("round 4:"));
+        assert_eq!(output.matches("dealer has").count(), 3);
+        assert!(output.contains("running count"));
+        assert_eq!(
+            output,
+            trace_with_args(&["-n", "3", "--seed", "7", "--decision-strategy", "h17"])
+        );
+    }
+
+    #[test]
+    fn trace_is_not_a_run() {
+        let cli = Cli::try_parse_from(["blackjack_sim", "trace", "-n", "2"]).unwrap();
+        assert!(cli.into_run_args().is_none());
+    }
+
+    #[test]
+    fn single_threaded_run_matches_parallel_run() {
+        let args = [
+            "-n",
+            "10",
+            "--seed",
+            "9",
+            "--strategies",
+            "HiLo,KO,Zen Count",
+        ];
+        let single_threaded = run_with_args(&[&args[..], &["--threads", "1"][..]].concat());
+        assert_eq!(single_threaded, run_with_args(&args));
+    }
+
+    /// Helper function that validates a run described by `args`, returning the problems found.
+    fn validation_problems(args: &[&str]) -> Vec<String> {
+        let cli = parse_run_args(std::iter::once("blackjack_sim").chain(args.iter().copied()));
+        let config = simulator_config(&cli, None);
+        validate_run(&cli, &config, vec![], None)
+            .err()
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn each_invalid_argument_is_reported() {
+        for (args, expected) in [
+            (&["-b", "0"][..], "--min-bet must be at least 1"),
+            (&["-d", "0"][..], "--num-decks must be at least 1"),
+            (&["-n", "0"][..], "--num-simulations must be at least 1"),
+            (
+                &["-r", "0"][..],
+                "--hands-per-simulation must be at least 1",
+            ),
+            (&["--threads", "0"][..], "--threads must be at least 1"),
+            (
+                &["-p", "0"][..],
+                "--player-starting-balance must be a positive number",
+            ),
+            (
+                &["--table-starting-balance=-5"][..],
+                "--table-starting-balance must be a positive number",
+            ),
+            (
+                &["-p", "10", "-b", "25"][..],
+                "--min-bet must not be greater than --player-starting-balance",
+            ),
+            (
+                &["--betting-margin=-1"][..],
+                "--betting-margin must be a positive number",
+            ),
+            (
+                &["--penetration", "1"][..],
+                "--penetration must be greater than 0 and less than 1",
+            ),
+        ] {
+            let problems = validation_problems(args);
+            assert!(
+                problems.iter().any(|problem| problem == expected),
+                "{:?} reported {:?}",
+                args,
+                problems
+            );
+        }
+    }
+
+    #[test]
+    fn every_problem_is_reported_at_once() {
+        let problems = validation_problems(&["-b", "0", "-d", "0", "-n", "0", "-m", "0"]);
+        assert_eq!(
+            problems,
+            vec![
+                "--num-simulations must be at least 1",
+                "--num-decks must be at least 1",
+                "--min-bet must be at least 1",
+                "--betting-margin must be a positive number",
+            ]
+        );
+        assert!(validation_problems(&["-p", "500", "-b", "5"]).is_empty());
+    }
+
+    #[test]
+    fn progress_is_aggregated_across_strategies() {
+        let mut progress = RunProgress::new(vec![String::from("HiLo"), String::from("KO")], 4);
+        assert_eq!(progress.percent_complete(), 0.0);
+        progress.record(1);
+        progress.record(1);
+        progress.record(2);
+        assert_eq!(progress.percent_complete(), 37.5);
+        assert_eq!(
+            progress.line(1),
+            "HiLo: 2/4 simulations, 37.5% of the run complete"
+        );
+        for _ in 0..3 {
+            progress.record(2);
+        }
+        progress.record(1);
+        progress.record(1);
+        assert_eq!(progress.percent_complete(), 100.0);
+        assert_eq!(
+            progress.line(2),
+            "KO: 4/4 simulations, 100.0% of the run complete"
+        );
+        assert_eq!(RunProgress::new(vec![], 4).percent_complete(), 100.0);
+    }
+
+    #[test]
+    fn compiled_decision_strategies_do_not_change_the_output() {
+        let args = ["-n", "5", "--seed", "3", "--strategies", "HiLo,KO"];
+        let compiled = [&args[..], &["--compile-decision-strategy"]].concat();
+        // Only the labels of the strategies tell the compiled decision strategies apart
+        assert_eq!(
+            run_with_args(&args),
+            run_with_args(&compiled).replace(" (compiled)", "")
+        );
+    }
+
+    #[test]
+    fn progress_does_not_change_the_output() {
+        let args = ["-n", "5", "--seed", "5", "--strategies", "HiLo,KO"];
+        let cli = parse_run_args(std::iter::once("blackjack_sim").chain(args));
+        let config = simulator_config(&cli, None);
+        let strategies = resolve_strategies(&cli, vec![], None, config.min_bet).unwrap();
+        let labels = strategies.iter().map(ResolvedStrategy::label).collect();
+        let mut output = vec![];
+        run_and_write(
+            &cli,
+            &mut build_simulator(config, &strategies, None, false),
+            &mut output,
+            with_progress(labels, config.num_simulations),
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(run_with_args(&args).ends_with(&output));
+    }
+
+    #[test]
+    fn invalid_config_files_are_rejected() {
+        let unknown_field = write_config_file("unknown.toml", "[config]\nnum_decs = 6\n");
+        assert!(read_config_file(&unknown_field).is_err());
+        std::fs::remove_file(unknown_field).unwrap();
+        let wrong_extension = write_config_file("sim.yaml", "num_decks: 6\n");
+        assert!(read_config_file(&wrong_extension).is_err());
+        std::fs::remove_file(wrong_extension).unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+//! Module for the `Money` type every balance, bet and winnings of a simulation is kept in.
+//! Amounts are counted in whole cents, so totals accumulated over millions of hands are exact. Floating point amounts
+//! are only converted to and from `Money` where they enter or leave the simulation, e.g. the configurations and the summaries written out.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Display};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// An amount of money in whole cents. Arithmetic saturates at the bounds instead of overflowing. Serialized as its number of cents.
+/// A table that never runs out of money has no balance at all rather than `Money::MAX`, see `BlackjackTableSim::balance`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Money(i64);
+
+impl Money {
+    /// No money at all.
+    pub const ZERO: Money = Money(0);
+    /// The largest amount that can be represented.
+    pub const MAX: Money = Money(i64::MAX);
+
+    /// Associated method for creating an amount of `cents` cents.
+    pub const fn from_cents(cents: i64) -> Money {
+        Money(cents)
+    }
+
+    /// Associated method for creating an amount of whole `dollars`.
+    pub const fn from_dollars(dollars: i64) -> Money {
+        Money(dollars.saturating_mul(100))
+    }
+
+    /// Associated method for converting a floating point amount of dollars, rounded to the nearest cent.
+    /// Amounts too large to be represented saturate, e.g. `f32::MAX` becomes `Money::MAX`, and `NaN` becomes `Money::ZERO`.
+    pub fn from_f64(dollars: f64) -> Money {
+        // Casting a float to an integer saturates, and maps NaN to zero
+        Money((dollars * 100.0).round() as i64)
+    }
+
+    /// Associated method for converting a floating point amount of dollars, see `Money::from_f64()`.
+    pub fn from_f32(dollars: f32) -> Money {
+        Money::from_f64(dollars as f64)
+    }
+
+    /// Returns the amount in cents.
+    pub const fn cents(self) -> i64 {
+        self.0
+    }
+
+    /// Returns the amount in dollars as an `f64`, for statistics and output.
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+
+    /// Returns the amount in dollars as an `f32`, for the strategies, which bet from a floating point balance.
+    pub fn to_f32(self) -> f32 {
+        self.to_f64() as f32
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(self.0.saturating_neg())
+    }
+}
+
+impl Mul<i64> for Money {
+    type Output = Money;
+
+    fn mul(self, rhs: i64) -> Money {
+        Money(self.0.saturating_mul(rhs))
+    }
+}
+
+/// Divides the amount, rounding towards zero to a whole cent. Bets are whole dollars, so halving one is always exact.
+impl Div<i64> for Money {
+    type Output = Money;
+
+    fn div(self, rhs: i64) -> Money {
+        Money(self.0 / rhs)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.copied().sum()
+    }
+}
+
+/// Formats the amount in dollars with two decimals, e.g. `-12.50`. The width and alignment of the formatter are respected,
+/// the precision is always two decimals.
+impl Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cents = self.0.unsigned_abs();
+        f.pad_integral(
+            self.0 >= 0,
+            "",
+            &format!("{}.{:02}", cents / 100, cents % 100),
+        )
+    }
+}
+
+impl Debug for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn amounts_are_formatted_in_dollars_and_cents() {
+        assert_eq!(Money::from_cents(1250).to_string(), "12.50");
+        assert_eq!(Money::from_cents(-5).to_string(), "-0.05");
+        assert_eq!(format!("{:>8}", Money::from_dollars(-3)), "   -3.00");
+        assert_eq!(
+            format!("{:?}", Some(Money::from_dollars(15))),
+            "Some(15.00)"
+        );
+    }
+
+    #[test]
+    fn floating_point_amounts_are_rounded_to_the_nearest_cent() {
+        assert_eq!(Money::from_f32(0.1), Money::from_cents(10));
+        // Halves are rounded away from zero
+        assert_eq!(Money::from_f64(-0.125), Money::from_cents(-13));
+        assert_eq!(Money::from_f32(f32::MAX), Money::MAX);
+        assert_eq!(Money::from_f64(f64::NAN), Money::ZERO);
+        assert_eq!(Money::from_dollars(500).to_f64(), 500.0);
+    }
+
+    #[test]
+    fn arithmetic_saturates_instead_of_overflowing() {
+        assert_eq!(Money::MAX + Money::from_dollars(5), Money::MAX);
+        assert_eq!(
+            Money::ZERO - Money::MAX - Money::from_dollars(5),
+            Money::from_cents(i64::MIN)
+        );
+        assert_eq!(Money::from_dollars(15) * 3 / 2, Money::from_cents(2250));
+        assert_eq!(-Money::from_dollars(2), Money::from_dollars(-2));
+        assert_eq!(
+            [Money::from_cents(1), Money::from_cents(2)]
+                .iter()
+                .sum::<Money>(),
+            Money::from_cents(3)
+        );
+    }
+}
@@ -0,0 +1,643 @@
+//! Module that keeps the list of every counting, decision and betting strategy that can be created at runtime by name.
+//! Both the binaries and the library create strategies from these lists, so the names accepted can not drift apart.
+
+use crate::game::strategy::{
+    AceFive, BasicStrategy, BettingStrategy, CountingStrategy, DecisionStrategy,
+    FlatBettingStrategy, H17DeviationStrategy, Halves, HiLo, HiOptI, HiOptII, JNoir,
+    KellyBettingStrategy, MarginBettingStrategy, OmegaII, RampBettingStrategy, RampStep, RedSeven,
+    S17DeviationStrategy, SilverFox, UnbalancedZen2, WongHalves, ZenCount, KISS, KISSII, KISSIII,
+    KO,
+};
+use crate::ConfigError;
+
+/// Describes a counting strategy that can be created at runtime.
+#[derive(Debug)]
+pub struct CountingStrategyDescriptor {
+    /// The canonical name of the strategy
+    pub name: &'static str,
+    /// A one line description of the strategy, listed by the command line
+    pub description: &'static str,
+    /// Other names the strategy is known by, e.g. the label returned by `CountingStrategy::name()`
+    pub aliases: &'static [&'static str],
+    /// Whether the tags of the count sum to zero over a full deck, the true count of an unbalanced count is taken relative to its pivot
+    pub balanced: bool,
+    /// Creates the strategy for a shoe of the given number of decks
+    pub constructor: fn(u32) -> Box<dyn CountingStrategy + Send + 'static>,
+}
+
+/// Describes a decision strategy that can be created at runtime.
+pub struct DecisionStrategyDescriptor {
+    /// The canonical name of the strategy
+    pub name: &'static str,
+    /// A one line description of the strategy, listed by the command line
+    pub description: &'static str,
+    /// Other names the strategy is known by, e.g. the short names accepted by the command line
+    pub aliases: &'static [&'static str],
+    /// Whether the strategy was built for a dealer that hits soft seventeen, `None` if it does not depend on the rule
+    pub soft_seventeen: Option<bool>,
+    /// Creates the strategy
+    pub constructor: fn() -> Box<dyn DecisionStrategy + Send + 'static>,
+}
+
+/// Describes a betting strategy that can be created at runtime.
+pub struct BettingStrategyDescriptor {
+    /// The canonical name of the strategy
+    pub name: &'static str,
+    /// A one line description of the strategy, listed by the command line
+    pub description: &'static str,
+    /// Other names the strategy is known by
+    pub aliases: &'static [&'static str],
+    /// Whether the strategy uses the betting margin
+    pub needs_margin: bool,
+    /// Creates the strategy from its parameters, returns an error if a parameter it needs is missing or invalid
+    pub constructor:
+        fn(&BettingParameters) -> Result<Box<dyn BettingStrategy + Send + 'static>, ConfigError>,
+}
+
+/// The fraction of the full Kelly bet that is placed when no fraction is given, half Kelly is the usual compromise between growth and variance.
+pub const DEFAULT_KELLY_FRACTION: f32 = 0.5;
+
+/// The parameters every betting strategy is created from, each strategy only reads the parameters it needs.
+#[derive(Debug, Clone, Default)]
+pub struct BettingParameters {
+    /// The margin bets are increased by, used by the `Margin` strategy
+    pub margin: f32,
+    /// The minimum bet of the table
+    pub min_bet: u32,
+    /// The amount bet every hand by the `Flat` strategy, defaults to `min_bet`
+    pub flat_amount: Option<u32>,
+    /// The bet ramp of the `Ramp` strategy, required by it
+    pub ramp: Option<Vec<RampStep>>,
+    /// The betting unit of the `Ramp` strategy, defaults to `min_bet`
+    pub unit: Option<u32>,
+    /// The fraction of the full Kelly bet placed by the `Kelly` strategy, defaults to `DEFAULT_KELLY_FRACTION`
+    pub kelly_fraction: Option<f32>,
+}
+
+impl BettingParameters {
+    /// Associated method for creating parameters from the betting margin and the minimum bet, the optional parameters are left unset.
+    pub fn new(margin: f32, min_bet: u32) -> BettingParameters {
+        BettingParameters {
+            margin,
+            min_bet,
+            ..Default::default()
+        }
+    }
+}
+
+/// Every counting strategy that can be created at runtime.
+pub static COUNTING_STRATEGIES: &[CountingStrategyDescriptor] = &[
+    CountingStrategyDescriptor {
+        name: "HiLo",
+        description: "Balanced level one count, twos through sixes count +1 and tens and aces count -1",
+        aliases: &[],
+        balanced: true,
+        constructor: |num_decks| Box::new(HiLo::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "Wong Halves",
+        description: "Balanced level three count with half point tags, one of the most accurate counts",
+        aliases: &[],
+        balanced: true,
+        constructor: |num_decks| Box::new(WongHalves::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "KO",
+        description: "Unbalanced Knock-Out count, HiLo with sevens counted +1 and a true count relative to its pivot",
+        aliases: &[],
+        balanced: false,
+        constructor: |num_decks| Box::new(KO::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "HiOptI",
+        description: "Balanced level one count, threes through sixes count +1, tens count -1 and aces are neutral",
+        aliases: &[],
+        balanced: true,
+        constructor: |num_decks| Box::new(HiOptI::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "HiOptII",
+        description: "Balanced level two count that leaves aces neutral",
+        aliases: &[],
+        balanced: true,
+        constructor: |num_decks| Box::new(HiOptII::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "Red Seven",
+        description: "Unbalanced HiLo that also counts red sevens +1",
+        aliases: &[],
+        balanced: false,
+        constructor: |num_decks| Box::new(RedSeven::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "OmegaII",
+        description: "Balanced level two count that leaves aces neutral",
+        aliases: &[],
+        balanced: true,
+        constructor: |num_decks| Box::new(OmegaII::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "AceFive",
+        description: "Simple count where fives count +1 and aces count -1",
+        aliases: &["Ace/Five"],
+        balanced: true,
+        constructor: |num_decks| Box::new(AceFive::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "Zen Count",
+        description: "Balanced level two count that counts aces -1",
+        aliases: &[],
+        balanced: true,
+        constructor: |num_decks| Box::new(ZenCount::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "Halves",
+        description: "Balanced count with half point tags",
+        aliases: &[],
+        balanced: true,
+        constructor: |num_decks| Box::new(Halves::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "KISS",
+        description: "Unbalanced level one count, Keep It Simple Stupid",
+        aliases: &[],
+        balanced: false,
+        constructor: |num_decks| Box::new(KISS::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "KISSII",
+        description: "Unbalanced level one count, the second revision of KISS",
+        aliases: &["KISS II"],
+        balanced: false,
+        constructor: |num_decks| Box::new(KISSII::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "KISSIII",
+        description: "Unbalanced level one count, the third revision of KISS",
+        aliases: &["KISS III"],
+        balanced: false,
+        constructor: |num_decks| Box::new(KISSIII::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "JNoir",
+        description: "Unbalanced count where every face card counts -1",
+        aliases: &["J. Noir"],
+        balanced: false,
+        constructor: |num_decks| Box::new(JNoir::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "Silver Fox",
+        description: "Balanced level one count that also counts sevens and nines",
+        aliases: &[],
+        balanced: true,
+        constructor: |num_decks| Box::new(SilverFox::new(num_decks)),
+    },
+    CountingStrategyDescriptor {
+        name: "Unbalanced Zen 2",
+        description: "Unbalanced version of the Zen Count",
+        aliases: &[],
+        balanced: false,
+        constructor: |num_decks| Box::new(UnbalancedZen2::new(num_decks)),
+    },
+];
+
+/// Every decision strategy that can be created at runtime.
+pub static DECISION_STRATEGIES: &[DecisionStrategyDescriptor] = &[
+    DecisionStrategyDescriptor {
+        name: "Basic Strategy",
+        description: "Plays strict basic strategy, ignoring the count",
+        aliases: &["basic"],
+        soft_seventeen: None,
+        constructor: || Box::new(BasicStrategy::new()),
+    },
+    DecisionStrategyDescriptor {
+        name: "S17 Deviations",
+        description: "Basic strategy with the count based playing deviations for a dealer that stands on soft seventeen",
+        aliases: &["s17"],
+        soft_seventeen: Some(false),
+        constructor: || Box::new(S17DeviationStrategy::new()),
+    },
+    DecisionStrategyDescriptor {
+        name: "H17 Deviations",
+        description: "Basic strategy with the count based playing deviations for a dealer that hits soft seventeen",
+        aliases: &["h17"],
+        soft_seventeen: Some(true),
+        constructor: || Box::new(H17DeviationStrategy::new()),
+    },
+];
+
+/// Every betting strategy that can be created at runtime.
+pub static BETTING_STRATEGIES: &[BettingStrategyDescriptor] = &[
+    BettingStrategyDescriptor {
+        name: "Margin",
+        description: "Bets the minimum bet times the margin times the rounded up true count",
+        aliases: &[],
+        needs_margin: true,
+        constructor: |params| {
+            Ok(Box::new(MarginBettingStrategy::new(
+                params.margin,
+                params.min_bet,
+            )))
+        },
+    },
+    BettingStrategyDescriptor {
+        name: "Flat",
+        description: "Bets the same amount every hand regardless of the count",
+        aliases: &[],
+        needs_margin: false,
+        constructor: |params| {
+            let amount = params.flat_amount.unwrap_or(params.min_bet);
+            Ok(Box::new(FlatBettingStrategy::new(amount)?))
+        },
+    },
+    BettingStrategyDescriptor {
+        name: "Ramp",
+        description: "Bets the units of the highest step of a bet ramp reached by the true count",
+        aliases: &[],
+        needs_margin: false,
+        constructor: |params| {
+            let ramp = params.ramp.clone().ok_or(ConfigError::new(
+                "ramp",
+                "is required by the Ramp betting strategy",
+            ))?;
+            let unit = params.unit.unwrap_or(params.min_bet);
+            Ok(Box::new(RampBettingStrategy::new(ramp, unit)?))
+        },
+    },
+    BettingStrategyDescriptor {
+        name: "Kelly",
+        description:
+            "Bets a fraction of the Kelly bet for the advantage estimated from the true count",
+        aliases: &[],
+        needs_margin: false,
+        constructor: |params| {
+            let fraction = params.kelly_fraction.unwrap_or(DEFAULT_KELLY_FRACTION);
+            Ok(Box::new(KellyBettingStrategy::new(
+                fraction,
+                params.min_bet,
+            )?))
+        },
+    },
+];
+
+/// Returns the counting strategy registered under `name`, either its canonical name or one of its aliases.
+pub fn counting_strategy(name: &str) -> Option<&'static CountingStrategyDescriptor> {
+    COUNTING_STRATEGIES
+        .iter()
+        .find(|descriptor| descriptor.name == name || descriptor.aliases.contains(&name))
+}
+
+/// Helper function that reduces a strategy name to its lowercase letters and digits, so "zencount" and "Zen Count" compare equal.
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Returns the counting strategy whose canonical name or one of its aliases matches `name`, ignoring case, spaces and punctuation.
+pub fn find_counting_strategy(name: &str) -> Option<&'static CountingStrategyDescriptor> {
+    let name = normalize_name(name);
+    COUNTING_STRATEGIES.iter().find(|descriptor| {
+        normalize_name(descriptor.name) == name
+            || descriptor
+                .aliases
+                .iter()
+                .any(|&alias| normalize_name(alias) == name)
+    })
+}
+
+/// Parses a comma separated list of counting strategy names, each name is looked up with `find_counting_strategy()`.
+/// The strategies are returned in the order they are listed, a strategy listed twice is only returned once.
+/// Returns an error naming every unknown strategy and listing the valid names if any name is not registered.
+pub fn parse_counting_strategies(
+    list: &str,
+) -> Result<Vec<&'static CountingStrategyDescriptor>, String> {
+    let mut strategies: Vec<&'static CountingStrategyDescriptor> = vec![];
+    let mut unknown = vec![];
+    for name in list
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        match find_counting_strategy(name) {
+            Some(descriptor) => {
+                if !strategies.iter().any(|d| d.name == descriptor.name) {
+                    strategies.push(descriptor);
+                }
+            }
+            None => unknown.push(name),
+        }
+    }
+
+    let valid_names = || {
+        COUNTING_STRATEGIES
+            .iter()
+            .map(|descriptor| descriptor.name)
+            .collect::<Vec<&str>>()
+            .join(", ")
+    };
+    if !unknown.is_empty() {
+        return Err(format!(
+            "unknown counting strategies: {}, valid strategies are: {}",
+            unknown.join(", "),
+            valid_names()
+        ));
+    }
+    if strategies.is_empty() {
+        return Err(format!(
+            "no counting strategies given, valid strategies are: {}",
+            valid_names()
+        ));
+    }
+    Ok(strategies)
+}
+
+/// Returns the decision strategy registered under `name`.
+pub fn decision_strategy(name: &str) -> Option<&'static DecisionStrategyDescriptor> {
+    DECISION_STRATEGIES
+        .iter()
+        .find(|descriptor| descriptor.name == name)
+}
+
+/// Returns the decision strategy whose canonical name or one of its aliases matches `name`, ignoring case, spaces and punctuation.
+pub fn find_decision_strategy(name: &str) -> Option<&'static DecisionStrategyDescriptor> {
+    let name = normalize_name(name);
+    DECISION_STRATEGIES.iter().find(|descriptor| {
+        normalize_name(descriptor.name) == name
+            || descriptor
+                .aliases
+                .iter()
+                .any(|&alias| normalize_name(alias) == name)
+    })
+}
+
+/// Returns the betting strategy registered under `name`.
+pub fn betting_strategy(name: &str) -> Option<&'static BettingStrategyDescriptor> {
+    BETTING_STRATEGIES
+        .iter()
+        .find(|descriptor| descriptor.name == name)
+}
+
+/// Returns the betting strategy whose canonical name or one of its aliases matches `name`, ignoring case, spaces and punctuation.
+pub fn find_betting_strategy(name: &str) -> Option<&'static BettingStrategyDescriptor> {
+    let name = normalize_name(name);
+    BETTING_STRATEGIES.iter().find(|descriptor| {
+        normalize_name(descriptor.name) == name
+            || descriptor
+                .aliases
+                .iter()
+                .any(|&alias| normalize_name(alias) == name)
+    })
+}
+
+/// Formats every registered strategy as a list of its name and description, grouped by the kind of strategy.
+pub fn format_strategy_list() -> String {
+    let sections: [(&str, Vec<(&str, &str)>); 3] = [
+        (
+            "counting strategies",
+            COUNTING_STRATEGIES
+                .iter()
+                .map(|descriptor| (descriptor.name, descriptor.description))
+                .collect(),
+        ),
+        (
+            "decision strategies",
+            DECISION_STRATEGIES
+                .iter()
+                .map(|descriptor| (descriptor.name, descriptor.description))
+                .collect(),
+        ),
+        (
+            "betting strategies",
+            BETTING_STRATEGIES
+                .iter()
+                .map(|descriptor| (descriptor.name, descriptor.description))
+                .collect(),
+        ),
+    ];
+    let name_width = sections
+        .iter()
+        .flat_map(|(_, strategies)| strategies.iter().map(|(name, _)| name.len()))
+        .max()
+        .unwrap_or(0);
+    let mut list = String::new();
+    for (heading, strategies) in sections {
+        if !list.is_empty() {
+            list.push('\n');
+        }
+        list.push_str(&format!("{}:\n", heading));
+        for (name, description) in strategies {
+            list.push_str(&format!("  {:<name_width$}  {}\n", name, description));
+        }
+    }
+    list
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn registered_names_are_unique() {
+        let mut names = HashSet::new();
+        for descriptor in COUNTING_STRATEGIES {
+            assert!(
+                names.insert(descriptor.name),
+                "{} registered twice",
+                descriptor.name
+            );
+            for &alias in descriptor.aliases {
+                assert!(names.insert(alias), "{} registered twice", alias);
+            }
+        }
+        let decision_names = DECISION_STRATEGIES
+            .iter()
+            .flat_map(|descriptor| {
+                std::iter::once(descriptor.name).chain(descriptor.aliases.iter().copied())
+            })
+            .collect::<HashSet<&str>>();
+        assert_eq!(
+            decision_names.len(),
+            DECISION_STRATEGIES
+                .iter()
+                .map(|descriptor| 1 + descriptor.aliases.len())
+                .sum::<usize>()
+        );
+        let betting_names = BETTING_STRATEGIES
+            .iter()
+            .map(|descriptor| descriptor.name)
+            .collect::<HashSet<&str>>();
+        assert_eq!(betting_names.len(), BETTING_STRATEGIES.len());
+    }
+
+    #[test]
+    fn betting_strategies_are_created_from_their_parameters() {
+        let mut params = BettingParameters::new(2.0, 5);
+        for descriptor in BETTING_STRATEGIES {
+            if descriptor.name == "Ramp" {
+                assert!((descriptor.constructor)(&params).is_err());
+                continue;
+            }
+            let strategy = (descriptor.constructor)(&params).unwrap();
+            assert_eq!(strategy.name(), descriptor.name);
+        }
+        params.ramp = Some(vec![RampStep {
+            true_count: 0.0,
+            units: 1,
+        }]);
+        params.kelly_fraction = Some(2.0);
+        let ramp = betting_strategy("Ramp").unwrap();
+        assert_eq!((ramp.constructor)(&params).unwrap().name(), "Ramp");
+        let kelly = betting_strategy("Kelly").unwrap();
+        assert!((kelly.constructor)(&params).is_err());
+    }
+
+    #[test]
+    fn counting_strategy_lists_are_parsed_ignoring_case_and_spaces() {
+        let strategies = parse_counting_strategies("HiLo, ko,ZenCount,hilo").unwrap();
+        let names = strategies
+            .iter()
+            .map(|descriptor| descriptor.name)
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["HiLo", "KO", "Zen Count"]);
+        assert_eq!(
+            parse_counting_strategies("kiss ii,j. noir").unwrap()[1].name,
+            "JNoir"
+        );
+    }
+
+    #[test]
+    fn decision_strategies_are_found_by_their_short_names() {
+        for (name, expected) in [
+            ("basic", "Basic Strategy"),
+            ("S17", "S17 Deviations"),
+            ("h17", "H17 Deviations"),
+            ("h17 deviations", "H17 Deviations"),
+        ] {
+            assert_eq!(find_decision_strategy(name).unwrap().name, expected);
+        }
+        assert!(find_decision_strategy("single-deck").is_none());
+        assert_eq!(
+            find_decision_strategy("s17").unwrap().soft_seventeen,
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn unknown_counting_strategies_are_rejected() {
+        let error = parse_counting_strategies("HiLo,Foo,Bar").unwrap_err();
+        assert!(error.starts_with("unknown counting strategies: Foo, Bar,"));
+        assert!(error.contains("Wong Halves"));
+        assert!(parse_counting_strategies(" , ").is_err());
+    }
+
+    #[test]
+    fn strategy_list_has_every_strategy() {
+        let list = format_strategy_list();
+        let lines = list.lines().collect::<Vec<&str>>();
+        assert_eq!(lines[0], "counting strategies:");
+        assert!(lines.contains(&"decision strategies:"));
+        assert!(lines.contains(&"betting strategies:"));
+        let names = COUNTING_STRATEGIES
+            .iter()
+            .map(|descriptor| (descriptor.name, descriptor.description))
+            .chain(
+                DECISION_STRATEGIES
+                    .iter()
+                    .map(|descriptor| (descriptor.name, descriptor.description)),
+            )
+            .chain(
+                BETTING_STRATEGIES
+                    .iter()
+                    .map(|descriptor| (descriptor.name, descriptor.description)),
+            );
+        for (name, description) in names {
+            assert!(
+                lines
+                    .iter()
+                    .any(|line| line.trim_start().starts_with(name) && line.ends_with(description)),
+                "{} is not listed",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn every_built_in_strategy_is_registered_once() {
+        let counting: Vec<Box<dyn CountingStrategy + Send>> = vec![
+            Box::new(HiLo::new(6)),
+            Box::new(WongHalves::new(6)),
+            Box::new(KO::new(6)),
+            Box::new(HiOptI::new(6)),
+            Box::new(HiOptII::new(6)),
+            Box::new(RedSeven::new(6)),
+            Box::new(OmegaII::new(6)),
+            Box::new(AceFive::new(6)),
+            Box::new(ZenCount::new(6)),
+            Box::new(Halves::new(6)),
+            Box::new(KISS::new(6)),
+            Box::new(KISSII::new(6)),
+            Box::new(KISSIII::new(6)),
+            Box::new(JNoir::new(6)),
+            Box::new(SilverFox::new(6)),
+            Box::new(UnbalancedZen2::new(6)),
+        ];
+        assert_eq!(counting.len(), COUNTING_STRATEGIES.len());
+        for strategy in counting {
+            let name = strategy.name();
+            let registered = COUNTING_STRATEGIES
+                .iter()
+                .filter(|descriptor| (descriptor.constructor)(6).name() == name)
+                .count();
+            assert_eq!(registered, 1, "{} is registered {} times", name, registered);
+            let descriptor = find_counting_strategy(&name).unwrap();
+            assert_eq!(
+                find_counting_strategy(descriptor.name).unwrap().name,
+                descriptor.name
+            );
+        }
+
+        let decision: Vec<Box<dyn DecisionStrategy + Send>> = vec![
+            Box::new(BasicStrategy::new()),
+            Box::new(S17DeviationStrategy::new()),
+            Box::new(H17DeviationStrategy::new()),
+        ];
+        assert_eq!(decision.len(), DECISION_STRATEGIES.len());
+        for strategy in decision {
+            let descriptor = find_decision_strategy(&strategy.name()).unwrap();
+            assert_eq!(descriptor.name, strategy.name());
+            assert_eq!((descriptor.constructor)().name(), strategy.name());
+        }
+
+        let mut params = BettingParameters::new(2.0, 5);
+        params.ramp = Some(vec![RampStep {
+            true_count: 0.0,
+            units: 1,
+        }]);
+        for descriptor in BETTING_STRATEGIES {
+            let strategy = (descriptor.constructor)(&params).unwrap();
+            let found = find_betting_strategy(&strategy.name()).unwrap();
+            assert_eq!(found.name, descriptor.name);
+        }
+        for name in ["Margin", "Flat", "Ramp", "Kelly"] {
+            assert!(
+                find_betting_strategy(name).is_some(),
+                "{} is not registered",
+                name
+            );
+        }
+        assert_eq!(BETTING_STRATEGIES.len(), 4);
+    }
+
+    #[test]
+    fn counting_strategies_are_found_by_their_label() {
+        for descriptor in COUNTING_STRATEGIES {
+            let label = (descriptor.constructor)(6).name();
+            let found = counting_strategy(&label)
+                .unwrap_or_else(|| panic!("{} is not registered under its label", label));
+            assert_eq!(found.name, descriptor.name);
+        }
+    }
+}
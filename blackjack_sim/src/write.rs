@@ -1,9 +1,12 @@
+use crate::money::Money;
 use crate::SimulationSummary;
+use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::io::Write;
 use std::iter::FromIterator;
-use std::sync::mpsc::Receiver;
+use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError};
+use std::thread::{self, JoinHandle};
 
 fn format_summaries(summaries: HashMap<usize, SimulationSummary>) -> HashMap<usize, String> {
     const width: usize = 80;
@@ -19,40 +22,850 @@ fn format_summaries(summaries: HashMap<usize, SimulationSummary>) -> HashMap<usi
         .collect::<HashMap<usize, String>>()
 }
 
-/// A public function to take in data i.e. `summary` a `SimulationSummary` object and write it to a writer
-pub fn write_summaries(
+/// An event emitted by `collect_summaries()` while the summaries of a run are being collected.
+pub enum CollectEvent<'a> {
+    /// A single simulation of the strategy with the given id has finished, holds the summary accumulated so far
+    Simulation {
+        id: usize,
+        summary: &'a SimulationSummary,
+    },
+    /// Every simulation of the strategy with the given id has finished, holds its summary unless no simulation sent one
+    Finished {
+        id: usize,
+        summary: Option<&'a SimulationSummary>,
+    },
+}
+
+/// Receives summaries until every simulation in `ids` has finished, accumulating the summaries of each simulation and calling `on_event`
+/// whenever a simulation or a strategy finishes. This is the one place the summaries of a run are aggregated, every writer formats its output from it.
+/// If every sender hangs up first the summaries received so far are returned, along with the ids of the simulations that never finished.
+pub fn collect_summaries(
     receiver: Receiver<(Option<SimulationSummary>, usize)>,
     mut ids: HashSet<usize>,
-    writer: impl Write,
-) -> std::io::Result<()> {
-    let mut writer = std::io::BufWriter::new(writer);
+    mut on_event: impl FnMut(CollectEvent),
+) -> (HashMap<usize, SimulationSummary>, HashSet<usize>) {
     let mut summaries: HashMap<usize, SimulationSummary> = HashMap::new();
-    loop {
-        let (cur_summary, id) = receiver.recv().unwrap();
+    while !ids.is_empty() {
+        // Every sender hung up before all simulations finished, i.e. the run was cancelled or a simulation thread died
+        let Ok((cur_summary, id)) = receiver.recv() else {
+            break;
+        };
         if let Some(cur_sum) = cur_summary {
-            if let Some(summary) = summaries.get_mut(&id) {
-                summary.wins += cur_sum.wins;
-                summary.pushes += cur_sum.pushes;
-                summary.losses += cur_sum.losses;
-                summary.winnings += cur_sum.winnings;
-                summary.player_blackjacks += cur_sum.player_blackjacks;
-                summary.early_endings += cur_sum.early_endings;
-            } else {
-                summaries.insert(id, cur_sum);
-            }
+            let summary = match summaries.entry(id) {
+                Entry::Occupied(entry) => {
+                    let summary = entry.into_mut();
+                    *summary += &cur_sum;
+                    summary
+                }
+                Entry::Vacant(entry) => entry.insert(cur_sum),
+            };
+            on_event(CollectEvent::Simulation { id, summary });
         } else {
             ids.remove(&id);
-            if ids.is_empty() {
-                // We have no more stats to process
-                break;
+            on_event(CollectEvent::Finished {
+                id,
+                summary: summaries.get(&id),
+            });
+        }
+    }
+    (summaries, ids)
+}
+
+/// Helper function that collects the summaries of a run and writes them to `writer` formatted by `format`.
+/// If some simulations never finish, the summaries collected so far are still written and an error naming the unfinished ids is returned.
+fn write_formatted(
+    receiver: Receiver<(Option<SimulationSummary>, usize)>,
+    ids: HashSet<usize>,
+    writer: impl Write,
+    format: impl FnOnce(HashMap<usize, SimulationSummary>) -> String,
+) -> std::io::Result<()> {
+    let mut writer = std::io::BufWriter::new(writer);
+    let (summaries, unfinished) = collect_summaries(receiver, ids, |_| {});
+    writer.write_all(format(summaries).as_bytes())?;
+    writer.flush()?;
+    all_finished(unfinished)
+}
+
+/// A public function to take in data i.e. `summary` a `SimulationSummary` object and write it to a writer.
+/// If some simulations never finish, the summaries collected so far are still written and an error naming the unfinished ids is returned.
+pub fn write_summaries(
+    receiver: Receiver<(Option<SimulationSummary>, usize)>,
+    ids: HashSet<usize>,
+    writer: impl Write,
+) -> std::io::Result<()> {
+    let mut registered = ids.iter().copied().collect::<Vec<usize>>();
+    registered.sort_unstable();
+    write_formatted(receiver, ids, writer, |summaries| {
+        // Get summaries into nicely formatted strings ordered by id. The ids may have gaps if only some of the strategies of a run
+        // are written, simulations that sent no summaries are marked as failed instead
+        let mut formatted_summaries = format_summaries(summaries);
+        registered
+            .into_iter()
+            .map(|id| match formatted_summaries.remove(&id) {
+                Some(formatted) => formatted,
+                None => format!("simulation #{}: no data (failed)\n", id),
+            })
+            .collect()
+    })
+}
+
+/// Formats `summaries` as the blocks of text written by `write_summaries()`, one per simulation ordered by id.
+pub fn format_summaries_as_text(summaries: &HashMap<usize, SimulationSummary>) -> String {
+    let mut formatted_summaries = format_summaries(summaries.clone())
+        .into_iter()
+        .collect::<Vec<(usize, String)>>();
+    formatted_summaries.sort_unstable_by_key(|(id, _)| *id);
+    formatted_summaries
+        .into_iter()
+        .map(|(_, formatted)| formatted)
+        .collect()
+}
+
+/// Helper function that formats the rates of `summary` as cells with four decimals, the cells are empty for a simulation without any hands.
+fn format_hand_rates(summary: &SimulationSummary) -> [String; 4] {
+    match summary.hand_rates() {
+        Some(rates) => [
+            rates.win_pct,
+            rates.push_pct,
+            rates.loss_pct,
+            rates.avg_winnings_per_hand,
+        ]
+        .map(|rate| format!("{:.4}", rate)),
+        None => Default::default(),
+    }
+}
+
+/// Helper function that returns an error naming the simulations in `unfinished`, if any did not finish.
+fn all_finished(unfinished: HashSet<usize>) -> std::io::Result<()> {
+    if unfinished.is_empty() {
+        return Ok(());
+    }
+    let mut unfinished = unfinished.into_iter().collect::<Vec<usize>>();
+    unfinished.sort_unstable();
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Interrupted,
+        format!(
+            "simulations {:?} stopped before all of their results were received",
+            unfinished
+        ),
+    ))
+}
+
+/// The csv header matching the rows produced by `format_summaries_as_csv()`
+pub const SUMMARY_CSV_HEADER: &str = "id,strategy,wins,pushes,losses,early_endings,winnings,player_blackjacks,win_pct,push_pct,loss_pct,avg_winnings_per_hand\n";
+
+/// Formats `summaries` as csv, one row per simulation ordered by id and preceded by `SUMMARY_CSV_HEADER`.
+pub fn format_summaries_as_csv(summaries: &HashMap<usize, SimulationSummary>) -> String {
+    let mut ids = summaries.keys().copied().collect::<Vec<usize>>();
+    ids.sort_unstable();
+    let mut csv = String::from(SUMMARY_CSV_HEADER);
+    for id in ids {
+        let summary = &summaries[&id];
+        let [win_pct, push_pct, loss_pct, avg_winnings_per_hand] = format_hand_rates(summary);
+        // Quote the label, since labels may contain commas
+        csv.push_str(&format!(
+            "{},\"{}\",{},{},{},{},{},{},{},{},{},{}\n",
+            id,
+            summary.label.replace('"', "\"\""),
+            summary.wins,
+            summary.pushes,
+            summary.losses,
+            summary.early_endings,
+            summary.winnings,
+            summary.player_blackjacks,
+            win_pct,
+            push_pct,
+            loss_pct,
+            avg_winnings_per_hand
+        ));
+    }
+    csv
+}
+
+/// A public function that writes the summaries it receives to a writer as csv, see `format_summaries_as_csv()`.
+pub fn write_summaries_csv(
+    receiver: Receiver<(Option<SimulationSummary>, usize)>,
+    ids: HashSet<usize>,
+    writer: impl Write,
+) -> std::io::Result<()> {
+    write_formatted(receiver, ids, writer, |summaries| {
+        format_summaries_as_csv(&summaries)
+    })
+}
+
+/// Formats the summary of the simulation with the given id as a json object. The object has the fields of a row produced by
+/// `format_summaries_as_csv()`, along with the `full_strategy` label, the number of hands and the statistics computed by `SimulationSummary`.
+/// Statistics that are undefined, e.g. the rates of a simulation without any hands, are `null`.
+pub fn format_summary_as_json(id: usize, summary: &SimulationSummary) -> serde_json::Value {
+    let rates = summary.hand_rates();
+    serde_json::json!({
+        "id": id,
+        "strategy": &*summary.label,
+        "full_strategy": &*summary.strategy,
+        "wins": summary.wins,
+        "pushes": summary.pushes,
+        "losses": summary.losses,
+        "early_endings": summary.early_endings,
+        "winnings": summary.winnings.to_f64(),
+        "num_hands": summary.num_hands,
+        "player_blackjacks": summary.player_blackjacks,
+        "insurance_bets": summary.insurance_bets,
+        "insurance_winnings": summary.insurance_winnings.to_f64(),
+        "total_wagered": summary.total_wagered.to_f64(),
+        "win_pct": rates.map(|rates| rates.win_pct),
+        "push_pct": rates.map(|rates| rates.push_pct),
+        "loss_pct": rates.map(|rates| rates.loss_pct),
+        "avg_winnings_per_hand": rates.map(|rates| rates.avg_winnings_per_hand),
+        "edge": summary.edge(),
+        "std_dev_per_round": summary.std_dev(),
+        "max_drawdown": summary.max_drawdown.to_f64(),
+        "n0": summary.n0(),
+        "min_rounds_to_ruin": summary.min_rounds_to_ruin,
+        "mean_rounds_to_ruin": summary.mean_rounds_to_ruin(),
+        "max_rounds_to_ruin": summary.max_rounds_to_ruin,
+    })
+}
+
+/// Formats `summaries` as a json array of objects, one per simulation ordered by id, see `format_summary_as_json()`.
+pub fn format_summaries_as_json(summaries: &HashMap<usize, SimulationSummary>) -> String {
+    let mut ids = summaries.keys().copied().collect::<Vec<usize>>();
+    ids.sort_unstable();
+    let rows = ids
+        .into_iter()
+        .map(|id| format_summary_as_json(id, &summaries[&id]))
+        .collect::<Vec<serde_json::Value>>();
+    format!("{}\n", serde_json::Value::Array(rows))
+}
+
+/// A public function that writes the summaries it receives to a writer as json, see `format_summaries_as_json()`.
+pub fn write_summaries_json(
+    receiver: Receiver<(Option<SimulationSummary>, usize)>,
+    ids: HashSet<usize>,
+    writer: impl Write,
+) -> std::io::Result<()> {
+    write_formatted(receiver, ids, writer, |summaries| {
+        format_summaries_as_json(&summaries)
+    })
+}
+
+/// Formats `summaries` as a markdown table with the columns of `SUMMARY_CSV_HEADER`, one row per simulation ordered by id.
+pub fn format_summaries_as_markdown(summaries: &HashMap<usize, SimulationSummary>) -> String {
+    let columns = SUMMARY_CSV_HEADER
+        .trim_end()
+        .split(',')
+        .collect::<Vec<&str>>();
+    let mut ids = summaries.keys().copied().collect::<Vec<usize>>();
+    ids.sort_unstable();
+    let mut markdown = format!(
+        "| {} |\n|{}\n",
+        columns.join(" | "),
+        " --- |".repeat(columns.len())
+    );
+    for id in ids {
+        let summary = &summaries[&id];
+        let [win_pct, push_pct, loss_pct, avg_winnings_per_hand] = format_hand_rates(summary);
+        // Escape pipes, since they would end the cell
+        markdown.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} |\n",
+            id,
+            summary.label.replace('|', "\\|"),
+            summary.wins,
+            summary.pushes,
+            summary.losses,
+            summary.early_endings,
+            summary.winnings,
+            summary.player_blackjacks,
+            win_pct,
+            push_pct,
+            loss_pct,
+            avg_winnings_per_hand
+        ));
+    }
+    markdown
+}
+
+/// A public function that writes the summaries it receives to a writer as a markdown table, see `format_summaries_as_markdown()`.
+pub fn write_summaries_markdown(
+    receiver: Receiver<(Option<SimulationSummary>, usize)>,
+    ids: HashSet<usize>,
+    writer: impl Write,
+) -> std::io::Result<()> {
+    write_formatted(receiver, ids, writer, |summaries| {
+        format_summaries_as_markdown(&summaries)
+    })
+}
+
+/// Formats a table ranking the strategies of `summaries` by their average winnings per round, best first. Each row holds the average
+/// winnings per round, the edge, the standard deviation of the winnings per round and the probability of ruin, i.e. the fraction of the
+/// `num_simulations` simulations of the strategy that ended early because the player could no longer bet. Strategies whose winnings per
+/// round are unknown are ranked last, ties are ranked by id.
+pub fn format_comparison(
+    summaries: &HashMap<usize, SimulationSummary>,
+    num_simulations: u32,
+) -> String {
+    const width: usize = 80;
+    const rank_width: usize = 6;
+    const label_width: usize = 30;
+    const num_width: usize = 11;
+    let optional = |value: Option<f32>, precision: usize| {
+        value.map_or(String::from("n/a"), |v| format!("{:.*}", precision, v))
+    };
+    let ev_per_round = |summary: &SimulationSummary| {
+        if summary.rounds == 0 {
+            None
+        } else {
+            Some(summary.winnings.to_f32() / summary.rounds as f32)
+        }
+    };
+
+    let mut ids = summaries.keys().copied().collect::<Vec<usize>>();
+    ids.sort_unstable_by(|a, b| {
+        let (ev_a, ev_b) = (ev_per_round(&summaries[a]), ev_per_round(&summaries[b]));
+        match (ev_a, ev_b) {
+            (Some(ev_a), Some(ev_b)) => ev_b.total_cmp(&ev_a),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+        .then(a.cmp(b))
+    });
+
+    let mut comparison = format!(
+        "{:-^width$}\n{:<rank_width$}{:<label_width$}{:>num_width$}{:>num_width$}{:>num_width$}{:>num_width$}\n",
+        "strategy comparison", "rank", "strategy", "EV/hand", "edge", "SD/hand", "ruin"
+    );
+    for (rank, id) in ids.into_iter().enumerate() {
+        let summary = &summaries[&id];
+        // Cut long labels short, so the columns stay aligned
+        let label = summary
+            .label
+            .chars()
+            .take(label_width - 1)
+            .collect::<String>();
+        let ruin = if num_simulations == 0 {
+            None
+        } else {
+            Some(summary.early_endings as f32 / num_simulations as f32)
+        };
+        comparison.push_str(&format!(
+            "{:<rank_width$}{:<label_width$}{:>num_width$}{:>num_width$}{:>num_width$}{:>num_width$}\n",
+            rank + 1,
+            label,
+            optional(ev_per_round(summary), 4),
+            optional(summary.edge(), 4),
+            optional(summary.std_dev(), 2),
+            optional(ruin, 4)
+        ));
+    }
+    comparison.push_str(&format!("{}\n", "-".repeat(width)));
+    comparison
+}
+
+/// A write function that can be passed to `MulStrategyBlackjackSimulator::run()`.
+pub type SummaryWriteFn = fn(
+    Receiver<(Option<SimulationSummary>, usize)>,
+    HashSet<usize>,
+    Box<dyn Write + Send + 'static>,
+) -> std::io::Result<()>;
+
+/// The formats the summaries of a run can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryFormat {
+    /// A block of text per simulation, see `write_summaries()`
+    Text,
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl SummaryFormat {
+    /// Returns the write function that writes summaries in this format.
+    pub fn write_fn(self) -> SummaryWriteFn {
+        match self {
+            SummaryFormat::Text => write_summaries,
+            SummaryFormat::Csv => write_summaries_csv,
+            SummaryFormat::Json => write_summaries_json,
+            SummaryFormat::Markdown => write_summaries_markdown,
+        }
+    }
+
+    /// Formats `summaries` in this format, i.e. the output the write function of this format writes for them.
+    pub fn format(self, summaries: &HashMap<usize, SimulationSummary>) -> String {
+        match self {
+            SummaryFormat::Text => format_summaries_as_text(summaries),
+            SummaryFormat::Csv => format_summaries_as_csv(summaries),
+            SummaryFormat::Json => format_summaries_as_json(summaries),
+            SummaryFormat::Markdown => format_summaries_as_markdown(summaries),
+        }
+    }
+}
+
+/// The record of a single hand played during a simulation, written as one line of csv by a `HandLogWriter`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandRecord {
+    /// The id of the strategy that played the hand
+    pub strategy_id: usize,
+    /// The index of the simulation (i.e. game) the hand was played in
+    pub simulation: u64,
+    /// The index of the hand within its simulation
+    pub hand: u32,
+    pub bet: u32,
+    pub wins: i32,
+    pub pushes: i32,
+    pub losses: i32,
+    pub winnings: Money,
+    /// The balance of the player after the hand was settled
+    pub balance: Money,
+}
+
+impl HandRecord {
+    /// The csv header matching the lines produced by `HandRecord::to_csv_line()`
+    pub const CSV_HEADER: &'static str =
+        "strategy_id,simulation,hand,bet,wins,pushes,losses,winnings,balance\n";
+
+    /// Formats the record as a single line of csv, including the trailing newline.
+    pub fn to_csv_line(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            self.strategy_id,
+            self.simulation,
+            self.hand,
+            self.bet,
+            self.wins,
+            self.pushes,
+            self.losses,
+            self.winnings,
+            self.balance
+        )
+    }
+}
+
+/// The sending half of a hand log, cheap to clone so every simulation thread can own one.
+/// Records are queued in a bounded buffer, once the buffer is full `log()` blocks until the writing thread catches up,
+/// so a slow writer slows down the game loop instead of letting the queue grow without bound.
+#[derive(Clone)]
+pub struct HandLogger {
+    sender: SyncSender<HandRecord>,
+}
+
+impl HandLogger {
+    /// Queues `record` for writing, blocking while the buffer is full. Returns an error if the writing thread has stopped.
+    pub fn log(&self, record: HandRecord) -> std::io::Result<()> {
+        self.sender.send(record).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "hand log writer stopped before all hands were logged",
+            )
+        })
+    }
+}
+
+/// The writing half of a hand log, a dedicated thread that streams `HandRecord`s as csv to a writer.
+/// The writer is flushed whenever the buffer runs empty, so memory use is bounded by the buffer size regardless of the length of the run.
+pub struct HandLogWriter {
+    handle: JoinHandle<std::io::Result<()>>,
+}
+
+impl HandLogWriter {
+    /// Spawns the writing thread, returning it together with the `HandLogger` used to send records to it.
+    /// At most `buffer_size` records are queued before `HandLogger::log()` starts blocking.
+    pub fn spawn(
+        writer: impl Write + Send + 'static,
+        buffer_size: usize,
+    ) -> (HandLogWriter, HandLogger) {
+        let (sender, receiver) = mpsc::sync_channel(buffer_size);
+        let handle = thread::spawn(move || write_hand_records(receiver, writer));
+        (HandLogWriter { handle }, HandLogger { sender })
+    }
+
+    /// Waits for every record to be written, this returns once every `HandLogger` has been dropped.
+    pub fn finish(self) -> std::io::Result<()> {
+        match self.handle.join() {
+            Ok(res) => res,
+            Err(_) => Err(std::io::Error::other("hand log writer panicked")),
+        }
+    }
+}
+
+/// The capacity in bytes of the `BufWriter` used by the `HandLogWriter` thread.
+const HAND_LOG_WRITE_BUFFER_BYTES: usize = 8 * 1024;
+
+/// Helper function run by the `HandLogWriter` thread, writes records until every sender has hung up.
+fn write_hand_records(receiver: Receiver<HandRecord>, writer: impl Write) -> std::io::Result<()> {
+    let mut writer = std::io::BufWriter::with_capacity(HAND_LOG_WRITE_BUFFER_BYTES, writer);
+    writer.write_all(HandRecord::CSV_HEADER.as_bytes())?;
+    loop {
+        let record = match receiver.try_recv() {
+            Ok(record) => record,
+            Err(TryRecvError::Empty) => {
+                // Nothing is waiting, flush what has been written so far before blocking for the next record
+                writer.flush()?;
+                match receiver.recv() {
+                    Ok(record) => record,
+                    Err(_) => break,
+                }
             }
+            Err(TryRecvError::Disconnected) => break,
+        };
+        writer.write_all(record.to_csv_line().as_bytes())?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// A writer that blocks every write until the test releases it, and keeps everything written.
+    struct GatedWriter {
+        gate: Arc<Mutex<()>>,
+        written: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl Write for GatedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let _guard = self.gate.lock().unwrap();
+            self.written.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            let _guard = self.gate.lock().unwrap();
+            Ok(())
         }
     }
 
-    // Get summaries into nicely formatted strings, and write to writer
-    let formatted_summaries = format_summaries(summaries);
-    for i in 1..=formatted_summaries.len() {
-        writer.write(formatted_summaries[&i].as_bytes())?;
+    fn summary(
+        label: &str,
+        wins: i32,
+        pushes: i32,
+        losses: i32,
+        winnings: f32,
+    ) -> SimulationSummary {
+        SimulationSummary {
+            wins,
+            pushes,
+            losses,
+            early_endings: 0,
+            winnings: Money::from_f32(winnings),
+            num_hands: (wins + pushes + losses) as u32,
+            player_blackjacks: 1,
+            label: Arc::from(label),
+            strategy: Arc::from(label),
+            common_random_numbers: false,
+            rounds: 0,
+            winnings_m2: 0.0,
+            total_wagered: Money::ZERO,
+            max_drawdown: Money::ZERO,
+            insurance_bets: 0,
+            insurance_winnings: Money::ZERO,
+            min_rounds_to_ruin: None,
+            max_rounds_to_ruin: None,
+            total_rounds_to_ruin: 0,
+        }
+    }
+
+    /// Helper function that builds the summary of playing `rounds`, given as the amount bet and won each round,
+    /// computing the spread of the winnings directly from their mean.
+    fn summary_of_rounds(rounds: &[(f32, f32)]) -> SimulationSummary {
+        let winnings = rounds.iter().map(|&(_, won)| won).sum::<f32>();
+        let mean = winnings as f64 / rounds.len() as f64;
+        let mut summary = summary("HiLo", 0, 0, 0, winnings);
+        summary.rounds = rounds.len() as u32;
+        summary.winnings_m2 = rounds
+            .iter()
+            .map(|&(_, won)| (won as f64 - mean).powi(2))
+            .sum();
+        summary.total_wagered = rounds.iter().map(|&(bet, _)| Money::from_f32(bet)).sum();
+        summary
+    }
+
+    #[test]
+    fn accumulated_statistics_match_a_single_pass() {
+        let rounds = (0..97)
+            .map(|i| {
+                let bet = (5 * (1 + i % 4)) as f32;
+                let won = match i % 7 {
+                    0 | 3 => bet,
+                    1 => 1.5 * bet,
+                    2 | 5 => -bet,
+                    4 => -2.0 * bet,
+                    _ => 0.0,
+                };
+                (bet, won)
+            })
+            .collect::<Vec<(f32, f32)>>();
+        let reference = summary_of_rounds(&rounds);
+
+        let mut chunks = rounds.chunks(13);
+        let mut accumulated = summary_of_rounds(chunks.next().unwrap());
+        for chunk in chunks {
+            accumulated += &summary_of_rounds(chunk);
+        }
+
+        assert_eq!(accumulated.rounds, reference.rounds);
+        assert_eq!(accumulated.winnings, reference.winnings);
+        assert_eq!(accumulated.total_wagered, reference.total_wagered);
+        assert!((accumulated.winnings_m2 - reference.winnings_m2).abs() < 1e-6);
+        assert!((accumulated.std_dev().unwrap() - reference.std_dev().unwrap()).abs() < 1e-4);
+        assert_eq!(accumulated.edge(), reference.edge());
+        assert!(
+            (accumulated.n0().unwrap() - reference.n0().unwrap()).abs() / reference.n0().unwrap()
+                < 1e-4
+        );
+    }
+
+    #[test]
+    fn accumulated_drawdown_is_the_largest_drawdown() {
+        let mut accumulated = summary("HiLo", 0, 0, 0, 0.0);
+        accumulated.max_drawdown = Money::from_dollars(25);
+        let mut other = summary("HiLo", 0, 0, 0, 0.0);
+        other.max_drawdown = Money::from_dollars(40);
+        accumulated += &other;
+        assert_eq!(accumulated.max_drawdown, Money::from_dollars(40));
+        accumulated += &summary("HiLo", 0, 0, 0, 0.0);
+        assert_eq!(accumulated.max_drawdown, Money::from_dollars(40));
+    }
+
+    #[test]
+    fn comparison_ranks_strategies_by_winnings_per_round() {
+        let mut hilo = summary("HiLo (Margin betting)", 0, 0, 0, 50.0);
+        hilo.rounds = 100;
+        hilo.winnings_m2 = 99.0 * 100.0;
+        hilo.total_wagered = Money::from_dollars(1000);
+        hilo.early_endings = 1;
+        let mut ko = summary("KO (Margin betting)", 0, 0, 0, -20.0);
+        ko.rounds = 100;
+        ko.winnings_m2 = 99.0 * 400.0;
+        ko.total_wagered = Money::from_dollars(800);
+        ko.early_endings = 3;
+        let zen = summary(
+            "Zen Count with a label too long for its column",
+            0,
+            0,
+            0,
+            0.0,
+        );
+        let mut summaries = HashMap::new();
+        summaries.insert(1, zen);
+        summaries.insert(2, ko);
+        summaries.insert(3, hilo);
+
+        let expected = "\
+------------------------------strategy comparison-------------------------------
+rank  strategy                          EV/hand       edge    SD/hand       ruin
+1     HiLo (Margin betting)              0.5000     0.0500      10.00     0.1000
+2     KO (Margin betting)               -0.2000    -0.0250      20.00     0.3000
+3     Zen Count with a label too lo         n/a        n/a        n/a     0.0000
+--------------------------------------------------------------------------------
+";
+        assert_eq!(format_comparison(&summaries, 10), expected);
+    }
+
+    #[test]
+    fn summaries_are_formatted_as_csv_in_id_order() {
+        let mut summaries = HashMap::new();
+        summaries.insert(2, summary("KO", 5, 0, 5, -2.5));
+        summaries.insert(1, summary("HiLo", 4, 2, 4, 10.0));
+
+        let csv = format_summaries_as_csv(&summaries);
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines[0], SUMMARY_CSV_HEADER.trim_end());
+        assert_eq!(
+            lines[1],
+            "1,\"HiLo\",4,2,4,0,10.00,1,0.4000,0.2000,0.4000,1.0000"
+        );
+        assert_eq!(
+            lines[2],
+            "2,\"KO\",5,0,5,0,-2.50,1,0.5000,0.0000,0.5000,-0.2500"
+        );
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn rates_of_simulations_without_hands_are_left_empty() {
+        let summaries = HashMap::from([(1, summary("HiLo", 0, 0, 0, 0.0))]);
+
+        let csv = format_summaries_as_csv(&summaries);
+        assert_eq!(csv.lines().nth(1), Some("1,\"HiLo\",0,0,0,0,0.00,1,,,,"));
+        let markdown = format_summaries_as_markdown(&summaries);
+        assert_eq!(
+            markdown.lines().nth(2),
+            Some("| 1 | HiLo | 0 | 0 | 0 | 0 | 0.00 | 1 |  |  |  |  |")
+        );
+        assert!(format!("{}", summaries[&1]).contains("win percentage"));
+        assert!(!format!("{}", summaries[&1]).contains("NaN"));
+    }
+
+    #[test]
+    fn summaries_are_formatted_as_json_in_id_order() {
+        let mut summaries = HashMap::new();
+        summaries.insert(2, summary("KO", 5, 0, 5, -2.5));
+        summaries.insert(1, summary("HiLo", 4, 2, 4, 10.0));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&format_summaries_as_json(&summaries)).unwrap();
+        let rows = json.as_array().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["id"], 1);
+        assert_eq!(rows[0]["strategy"], "HiLo");
+        assert_eq!(rows[0]["wins"], 4);
+        assert_eq!(rows[1]["strategy"], "KO");
+        assert_eq!(rows[1]["winnings"], -2.5);
+    }
+
+    #[test]
+    fn summaries_are_formatted_as_a_markdown_table() {
+        let mut summaries = HashMap::new();
+        summaries.insert(2, summary("KO", 5, 0, 5, -2.5));
+        summaries.insert(1, summary("Hi|Lo", 4, 2, 4, 10.0));
+
+        let markdown = format_summaries_as_markdown(&summaries);
+        let lines = markdown.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("| id | strategy | wins |"));
+        assert_eq!(lines[1], "| --- ".repeat(12).trim_end().to_owned() + " |");
+        assert_eq!(
+            lines[2],
+            "| 1 | Hi\\|Lo | 4 | 2 | 4 | 0 | 10.00 | 1 | 0.4000 | 0.2000 | 0.4000 | 1.0000 |"
+        );
+        assert!(lines[3].starts_with("| 2 | KO |"));
+    }
+
+    #[test]
+    fn collecting_reports_every_simulation_and_strategy() {
+        let (sender, receiver) = mpsc::channel();
+        sender
+            .send((Some(summary("HiLo", 4, 2, 4, 10.0)), 1))
+            .unwrap();
+        sender
+            .send((Some(summary("HiLo", 1, 0, 1, 5.0)), 1))
+            .unwrap();
+        sender.send((None, 1)).unwrap();
+        sender.send((None, 2)).unwrap();
+
+        let mut events = Vec::new();
+        let (summaries, unfinished) =
+            collect_summaries(receiver, HashSet::from([1, 2]), |event| match event {
+                CollectEvent::Simulation { id, summary } => events.push((id, Some(summary.wins))),
+                CollectEvent::Finished { id, summary } => {
+                    events.push((id, summary.map(|summary| -summary.wins)))
+                }
+            });
+        assert!(unfinished.is_empty());
+        assert_eq!(summaries[&1].wins, 5);
+        assert_eq!(summaries[&1].winnings, Money::from_dollars(15));
+        assert_eq!(
+            events,
+            vec![(1, Some(4)), (1, Some(5)), (1, Some(-5)), (2, None)]
+        );
+    }
+
+    #[test]
+    fn simulations_without_summaries_are_marked_as_failed() {
+        let (sender, receiver) = mpsc::channel();
+        sender
+            .send((Some(summary("HiLo", 4, 2, 4, 10.0)), 1))
+            .unwrap();
+        sender.send((None, 1)).unwrap();
+        sender
+            .send((Some(summary("KO", 5, 0, 5, -2.5)), 3))
+            .unwrap();
+        sender.send((None, 3)).unwrap();
+        // The strategy with id 2 errored before sending any summaries
+        sender.send((None, 2)).unwrap();
+
+        let mut written = Vec::new();
+        write_summaries(receiver, HashSet::from([1, 2, 3]), &mut written).unwrap();
+        let text = String::from_utf8(written).unwrap();
+        let first = text.find("simulation #1").unwrap();
+        let failed = text.find("simulation #2: no data (failed)").unwrap();
+        let third = text.find("simulation #3").unwrap();
+        assert!(first < failed && failed < third);
+        assert!(text.contains("KO"));
+    }
+
+    #[test]
+    fn summaries_collected_before_a_simulation_dies_are_still_written() {
+        let (sender, receiver) = mpsc::channel();
+        sender
+            .send((Some(summary("HiLo", 4, 2, 4, 10.0)), 1))
+            .unwrap();
+        sender.send((None, 1)).unwrap();
+        sender
+            .send((Some(summary("KO", 5, 0, 5, -2.5)), 2))
+            .unwrap();
+        // The simulation thread of the third strategy dies without sending its sentinel
+        drop(sender);
+
+        let mut written = Vec::new();
+        let error =
+            write_summaries_csv(receiver, HashSet::from([1, 2, 3]), &mut written).unwrap_err();
+        assert!(error.to_string().contains("[2, 3]"));
+        let csv = String::from_utf8(written).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.lines().nth(2).unwrap().starts_with("2,\"KO\","));
+    }
+
+    fn record(hand: u32) -> HandRecord {
+        HandRecord {
+            strategy_id: 1,
+            simulation: 0,
+            hand,
+            bet: 5,
+            wins: 1,
+            pushes: 0,
+            losses: 0,
+            winnings: Money::from_dollars(5),
+            balance: Money::from_dollars(505),
+        }
+    }
+
+    #[test]
+    fn slow_hand_log_writer_blocks_the_logger() {
+        const BUFFER_SIZE: usize = 8;
+        const NUM_RECORDS: usize = 5000;
+        let gate = Arc::new(Mutex::new(()));
+        let written = Arc::new(Mutex::new(vec![]));
+        let writer = GatedWriter {
+            gate: Arc::clone(&gate),
+            written: Arc::clone(&written),
+        };
+
+        // Hold the gate so the writing thread stalls as soon as it writes to the underlying writer
+        let gate_guard = gate.lock().unwrap();
+        let (log_writer, logger) = HandLogWriter::spawn(writer, BUFFER_SIZE);
+        let logged = Arc::new(AtomicUsize::new(0));
+        let logged_clone = Arc::clone(&logged);
+        let producer = thread::spawn(move || {
+            for hand in 0..NUM_RECORDS {
+                logger.log(record(hand as u32)).unwrap();
+                logged_clone.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        thread::sleep(Duration::from_millis(200));
+        // Only the channel buffer, the record held by the stalled writing thread and the lines sitting in its `BufWriter` can be queued
+        let max_queued =
+            BUFFER_SIZE + 1 + HAND_LOG_WRITE_BUFFER_BYTES / record(0).to_csv_line().len();
+        let queued = logged.load(Ordering::SeqCst);
+        assert!(
+            queued <= max_queued,
+            "logger queued {} records, expected at most {}",
+            queued,
+            max_queued
+        );
+
+        drop(gate_guard);
+        producer.join().unwrap();
+        log_writer.finish().unwrap();
+
+        let written = String::from_utf8(written.lock().unwrap().clone()).unwrap();
+        let mut lines = written.lines();
+        assert_eq!(lines.next(), Some(HandRecord::CSV_HEADER.trim_end()));
+        assert_eq!(lines.count(), NUM_RECORDS);
     }
-    Ok(())
 }